@@ -14,6 +14,11 @@ pub struct BlendCfg {
     pub src_color_factor: BlendFactor,
     pub dst_color_factor: BlendFactor,
     pub func: BlendFunc,
+    /// A blend mode computed by a shader from the colors of the layer and of
+    /// the layers below it. The GL blending then applies its opacity
+    #[serde(default)]
+    #[wasm_bindgen(skip)]
+    pub mode: Option<BlendMode>,
 }
 
 impl Default for BlendCfg {
@@ -22,6 +27,7 @@ impl Default for BlendCfg {
             src_color_factor: BlendFactor::SrcAlpha,
             dst_color_factor: BlendFactor::OneMinusSrcAlpha,
             func: BlendFunc::FuncAdd,
+            mode: None,
         }
     }
 }
@@ -56,19 +62,41 @@ pub enum BlendFunc {
     FuncAdd,
     FuncSubstract,
     FuncReverseSubstract,
+    // The blend factors are ignored by these equations
+    #[cfg(feature = "webgl2")]
+    Min,
+    #[cfg(feature = "webgl2")]
+    Max,
 }
 
-/*impl BlendFunc {
-    fn gl(&self) -> u32 {
+/// Blend modes implemented in a shader
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum BlendMode {
+    /// 1 - (1 - src) * (1 - dst)
+    Screen,
+    /// src * dst
+    Multiply,
+    /// Multiply where dst is dark, screen where it is bright
+    Overlay,
+    /// max(src, dst)
+    Lighten,
+    /// min(src, dst)
+    Darken,
+}
+
+impl BlendMode {
+    /// The index of the mode in the shaders
+    pub fn id(&self) -> i32 {
         match self {
-            BlendFunc::FuncAdd => WebGlRenderingCtx::FUNC_ADD,
-            BlendFunc::FuncSubstract => WebGlRenderingCtx::FUNC_SUBTRACT,
-            BlendFunc::FuncReverseSubstract => WebGlRenderingCtx::FUNC_REVERSE_SUBTRACT,
-            //BlendFunc::Min => WebGlRenderingCtx::MIN,
-            //BlendFunc::Max => WebGlRenderingCtx::MAX,
+            BlendMode::Screen => 0,
+            BlendMode::Multiply => 1,
+            BlendMode::Overlay => 2,
+            BlendMode::Lighten => 3,
+            BlendMode::Darken => 4,
         }
     }
-}*/
+}
 
 use std::fmt;
 impl fmt::Display for BlendFactor {
@@ -108,11 +136,60 @@ impl fmt::Display for BlendFunc {
             BlendFunc::FuncAdd => "Add",
             BlendFunc::FuncSubstract => "Subtract",
             BlendFunc::FuncReverseSubstract => "Reverse Subtract",
-            /*#[cfg(feature = "webgl2")]
+            #[cfg(feature = "webgl2")]
             BlendFunc::Min => "Min",
             #[cfg(feature = "webgl2")]
-            BlendFunc::Max => "Max",*/
+            BlendFunc::Max => "Max",
         };
         write!(f, "{}", str)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blend_cfg(func: &str, mode: serde_json::Value) -> serde_json::Result<BlendCfg> {
+        serde_json::from_value(serde_json::json!({
+            "srcColorFactor": "SrcAlpha",
+            "dstColorFactor": "One",
+            "func": func,
+            "mode": mode,
+        }))
+    }
+
+    #[test]
+    fn blend_cfg_parsing() {
+        let cfg = blend_cfg("FuncAdd", serde_json::Value::Null).unwrap();
+        assert_eq!(cfg.src_color_factor, BlendFactor::SrcAlpha);
+        assert_eq!(cfg.dst_color_factor, BlendFactor::One);
+        assert_eq!(cfg.func, BlendFunc::FuncAdd);
+        assert_eq!(cfg.mode, None);
+
+        #[cfg(feature = "webgl2")]
+        {
+            assert_eq!(
+                blend_cfg("Min", serde_json::Value::Null).unwrap().func,
+                BlendFunc::Min
+            );
+            assert_eq!(
+                blend_cfg("Max", serde_json::Value::Null).unwrap().func,
+                BlendFunc::Max
+            );
+        }
+        assert!(blend_cfg("FuncMultiply", serde_json::Value::Null).is_err());
+
+        for (mode, expected) in [
+            ("screen", BlendMode::Screen),
+            ("multiply", BlendMode::Multiply),
+            ("overlay", BlendMode::Overlay),
+            ("lighten", BlendMode::Lighten),
+            ("darken", BlendMode::Darken),
+        ] {
+            let cfg = blend_cfg("FuncAdd", serde_json::json!(mode)).unwrap();
+            assert_eq!(cfg.mode, Some(expected));
+        }
+        assert!(blend_cfg("FuncAdd", serde_json::json!("difference")).is_err());
+        assert!(blend_cfg("FuncAdd", serde_json::json!("Screen")).is_err());
+    }
+}
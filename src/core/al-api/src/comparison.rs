@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+/// Compares two layers of the stack
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonCfg {
+    /// The layers A and B
+    pub layers: [String; 2],
+    /// How they are compared
+    pub mode: ComparisonMode,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ComparisonMode {
+    /// A and B are shown alternately, each one during half of the period given in seconds
+    Blink {
+        #[serde(default = "default_period")]
+        period: f32,
+    },
+    /// The upper layer is faded, 0 showing A and 1 showing B
+    CrossFade {
+        #[serde(default = "default_ratio")]
+        ratio: f32,
+    },
+    /// A is shown on the left of a vertical line and B on its right. The position
+    /// of the line is given as a fraction of the width of the view
+    Swipe {
+        #[serde(default = "default_ratio")]
+        position: f32,
+    },
+}
+
+fn default_period() -> f32 {
+    1.0
+}
+
+fn default_ratio() -> f32 {
+    0.5
+}
@@ -0,0 +1,173 @@
+use serde::Deserialize;
+
+use crate::hips::ImageMetadata;
+
+/// A layer composing three single-band layers into a true-color rendering
+///
+/// Each channel layer is rendered in grayscale with its own cuts, stretch and
+/// tonal corrections. The channel layers are only rendered through the composite
+/// and are removed along with it.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RGBCompositeCfg {
+    /// Layer name
+    pub layer: String,
+
+    /// The layers of the red, green and blue channels
+    pub channels: [String; 3],
+    /// The factors applied to the red, green and blue channels
+    #[serde(default = "default_weights")]
+    pub weights: [f32; 3],
+    /// Compose the channels following Lupton et al. (2004) if given.
+    /// The channels are then rendered with a linear stretch between their cuts
+    #[serde(default)]
+    pub lupton: Option<LuptonCfg>,
+
+    /// Its opacity, blending and tonal corrections
+    pub meta: ImageMetadata,
+}
+
+/// Parameters of the asinh composite of Lupton et al. (2004)
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LuptonCfg {
+    /// The asinh softening parameter, the greater the more faint features are enhanced
+    #[serde(default = "default_q")]
+    pub q: f32,
+}
+
+fn default_weights() -> [f32; 3] {
+    [1.0; 3]
+}
+
+fn default_q() -> f32 {
+    8.0
+}
+
+/// A layer computing a per-pixel expression between two layers
+///
+/// The operands are resampled on the screen so they are matched pixel to pixel
+/// for HiPS of the same order and frame. The result is rendered with the cuts,
+/// stretch and colormap of the derived layer. The operand layers are only
+/// rendered through the derived layer and are removed along with it.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerArithmeticCfg {
+    /// Layer name
+    pub layer: String,
+
+    /// The layers of the operands A and B
+    pub operands: [String; 2],
+    /// The expression computed between the operands
+    pub operation: LayerOperation,
+
+    /// Its color config, opacity and blending
+    pub meta: ImageMetadata,
+}
+
+/// The expressions between the operands A and B of a derived layer
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LayerOperation {
+    /// A - B
+    Difference,
+    /// A / B, left transparent where B is 0
+    Ratio,
+    /// A × B, left transparent where the mask B is 0
+    Mask,
+}
+
+impl LayerOperation {
+    /// The index of the operation in the shader
+    pub fn id(&self) -> i32 {
+        match self {
+            LayerOperation::Difference => 0,
+            LayerOperation::Ratio => 1,
+            LayerOperation::Mask => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hips::fixtures;
+
+    fn rgb_composite(
+        channels: &[&str],
+        lupton: serde_json::Value,
+    ) -> serde_json::Result<RGBCompositeCfg> {
+        serde_json::from_value(serde_json::json!({
+            "layer": "rgb",
+            "channels": channels,
+            "lupton": lupton,
+            "meta": fixtures::meta_json(),
+        }))
+    }
+
+    #[test]
+    fn rgb_composite_cfg() {
+        let cfg = rgb_composite(&["i", "r", "g"], serde_json::Value::Null).unwrap();
+        assert_eq!(cfg.channels, ["i", "r", "g"]);
+        assert_eq!(cfg.weights, [1.0; 3]);
+        assert_eq!(cfg.lupton, None);
+
+        let cfg = rgb_composite(&["i", "r", "g"], serde_json::json!({})).unwrap();
+        assert_eq!(cfg.lupton, Some(LuptonCfg { q: 8.0 }));
+        let cfg = rgb_composite(&["i", "r", "g"], serde_json::json!({ "q": 3.0 })).unwrap();
+        assert_eq!(cfg.lupton, Some(LuptonCfg { q: 3.0 }));
+
+        let mut cfg = serde_json::json!({
+            "layer": "rgb",
+            "channels": ["i", "r", "g"],
+            "weights": [0.5, 1.0, 2.0],
+            "meta": fixtures::meta_json(),
+        });
+        let weights = serde_json::from_value::<RGBCompositeCfg>(cfg.clone())
+            .unwrap()
+            .weights;
+        assert_eq!(weights, [0.5, 1.0, 2.0]);
+
+        // One weight per channel
+        cfg["weights"] = serde_json::json!([0.5, 1.0]);
+        assert!(serde_json::from_value::<RGBCompositeCfg>(cfg).is_err());
+    }
+
+    #[test]
+    fn rgb_composite_of_three_channels() {
+        assert!(rgb_composite(&["i", "r"], serde_json::Value::Null).is_err());
+        assert!(rgb_composite(&["z", "i", "r", "g"], serde_json::Value::Null).is_err());
+        assert!(rgb_composite(&[], serde_json::Value::Null).is_err());
+    }
+
+    fn layer_arithmetic(
+        operands: &[&str],
+        operation: &str,
+    ) -> serde_json::Result<LayerArithmeticCfg> {
+        serde_json::from_value(serde_json::json!({
+            "layer": "diff",
+            "operands": operands,
+            "operation": operation,
+            "meta": fixtures::meta_json(),
+        }))
+    }
+
+    #[test]
+    fn layer_arithmetic_cfg() {
+        for (operation, expected) in [
+            ("difference", LayerOperation::Difference),
+            ("ratio", LayerOperation::Ratio),
+            ("mask", LayerOperation::Mask),
+        ] {
+            let cfg = layer_arithmetic(&["a", "b"], operation).unwrap();
+            assert_eq!(cfg.operands, ["a", "b"]);
+            assert_eq!(cfg.operation, expected);
+        }
+
+        assert!(layer_arithmetic(&["a", "b"], "sum").is_err());
+        assert!(layer_arithmetic(&["a", "b"], "Difference").is_err());
+        // Two operands
+        assert!(layer_arithmetic(&["a"], "ratio").is_err());
+        assert!(layer_arithmetic(&["a", "b", "c"], "ratio").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use super::color::ColorRGB;
+
+/// Levels at which the contours of a layer are drawn
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(tag = "method", rename_all = "camelCase")]
+pub enum ContourLevels {
+    /// The levels given
+    Values { values: Vec<f64> },
+    /// Levels spaced linearly between the minimum and the maximum values
+    Linear {
+        #[serde(default = "default_num_levels")]
+        num: usize,
+    },
+    /// Levels spaced logarithmically between the minimum positive value and the maximum one
+    Log {
+        #[serde(default = "default_num_levels")]
+        num: usize,
+    },
+    /// Levels at several standard deviations above the background, the background being the
+    /// median of the values and the standard deviation being estimated from their median
+    /// absolute deviation
+    Sigma {
+        #[serde(default = "default_sigmas")]
+        sigmas: Vec<f64>,
+    },
+}
+
+fn default_num_levels() -> usize {
+    5
+}
+
+fn default_sigmas() -> Vec<f64> {
+    vec![3.0, 5.0, 10.0, 20.0]
+}
+
+/// Style of the contours drawn
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContourStyle {
+    pub color: ColorRGB,
+    pub opacity: f32,
+    pub thickness: f32,
+    pub show: bool,
+}
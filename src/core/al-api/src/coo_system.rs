@@ -1,6 +1,6 @@
 use cgmath::Matrix4;
 
-const GAL2ICRS: &'static Matrix4<f64> = &Matrix4::new(
+const GAL2ICRS: &Matrix4<f64> = &Matrix4::new(
     -0.44482972122205372312012370920248,
     0.74698218398450941835110635824212,
     0.49410943719710765017955928850141,
@@ -19,7 +19,7 @@ const GAL2ICRS: &'static Matrix4<f64> = &Matrix4::new(
     1.0,
 );
 
-const ICRS2GAL: &'static Matrix4<f64> = &Matrix4::new(
+const ICRS2GAL: &Matrix4<f64> = &Matrix4::new(
     -0.44482972122205372312012370920248,
     -0.19807633727507056817237662907031,
     -0.87343705195577915249273984034980,
@@ -38,10 +38,104 @@ const ICRS2GAL: &'static Matrix4<f64> = &Matrix4::new(
     1.0,
 );
 
-const ID: &'static Matrix4<f64> = &Matrix4::new(
+// Frame bias between FK5 (J2000) and ICRS (IERS conventions, Hipparcos vol. 1)
+const FK5J20002ICRS: &Matrix4<f64> = &Matrix4::new(
+    0.9999999999999892,
+    9.647792009628368e-08,
+    1.1102233509835495e-07,
+    0.0,
+    -9.647792498531089e-08,
+    0.9999999999999943,
+    4.411803963527301e-08,
+    0.0,
+    -1.1102233084981164e-07,
+    -4.4118050326662195e-08,
+    0.9999999999999929,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    1.0,
+);
+
+// FK4 (B1950) to ICRS. Rotation part of the Standish (1982) matrix chained with
+// the FK5 frame bias. E-terms of aberration are handled apart, see `coosys`.
+const FK4B19502ICRS: &Matrix4<f64> = &Matrix4::new(
+    0.9999374796440669,
+    -2.705043478112313e-05,
+    -0.011181950085803805,
+    0.0,
+    -2.727243744583976e-05,
+    0.9999881999116955,
+    -0.004857903585498135,
+    0.0,
+    0.011181949517235676,
+    0.004857904864050567,
+    0.9999256796557745,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    1.0,
+);
+
+// Mean ecliptic and equinox of J2000 (IAU 2006 obliquity: 84381.406")
+const ECLJ20002ICRS: &Matrix4<f64> = &Matrix4::new(
+    0.9174821430652418,
+    0.397776969112606,
+    0.0,
+    0.0,
+    -0.397776969112606,
+    0.9174821430652418,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    1.0,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    1.0,
+);
+
+// Supergalactic frame of de Vaucouleurs (SGP at l=47.37°, b=6.32°, origin at l=137.37°, b=0°)
+// chained with GAL2ICRS
+const SUPERGAL2ICRS: &Matrix4<f64> = &Matrix4::new(
+    -0.09572710025099691,
+    0.4287851600069994,
+    -0.8983204377254855,
+    0.0,
+    -0.9350456902643366,
+    0.27075049949149177,
+    0.22887490937889637,
+    0.0,
+    0.3413588718572082,
+    0.8618801851666389,
+    0.3750155557060192,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    1.0,
+);
+
+const ID: &Matrix4<f64> = &Matrix4::new(
     1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
 );
 
+const ICRS2FK5J2000: &Matrix4<f64> = &transpose(FK5J20002ICRS);
+const ICRS2FK4B1950: &Matrix4<f64> = &transpose(FK4B19502ICRS);
+const ICRS2ECLJ2000: &Matrix4<f64> = &transpose(ECLJ20002ICRS);
+const ICRS2SUPERGAL: &Matrix4<f64> = &transpose(SUPERGAL2ICRS);
+
+// The frame matrices are rotations so their inverse is their transpose
+const fn transpose(m: &Matrix4<f64>) -> Matrix4<f64> {
+    Matrix4::new(
+        m.x.x, m.y.x, m.z.x, m.w.x, m.x.y, m.y.y, m.z.y, m.w.y, m.x.z, m.y.z, m.z.z, m.w.z, m.x.w,
+        m.y.w, m.z.w, m.w.w,
+    )
+}
 
 use serde::Deserialize;
 use wasm_bindgen::prelude::*;
@@ -50,17 +144,64 @@ use wasm_bindgen::prelude::*;
 pub enum CooSystem {
     ICRS,
     GAL,
+    FK5J2000,
+    FK4B1950,
+    ECLJ2000,
+    SUPERGAL,
+    /// Topocentric horizontal frame (Alt-Az) of an observer.
+    ///
+    /// The latitude is the altitude and the longitude is 360° minus the azimuth
+    /// (counted from the north toward the east) so that the frame is direct.
+    HORIZONTAL,
 }
 
-pub const NUM_COOSYSTEM: usize = 2;
+pub const NUM_COOSYSTEM: usize = 7;
 
 impl CooSystem {
+    /// Rotation matrix transforming a position from `self` to `coo_system`
+    ///
+    /// Every frame is linked to ICRS so the transformation between two
+    /// non ICRS frames goes through ICRS. The horizontal frame depends on the
+    /// observer and on the date so its rotation from ICRS, `icrs2hor`, is given
+    /// by the caller. It is not used for the other frames.
     #[inline]
-    pub fn to(&self, coo_system: Self) -> &Matrix4<f64> {
-        match (self, coo_system) {
-            (CooSystem::GAL, CooSystem::ICRS) => GAL2ICRS,
-            (CooSystem::ICRS, CooSystem::GAL) => ICRS2GAL,
-            (_, _) => ID,
+    pub fn to(&self, coo_system: Self, icrs2hor: &Matrix4<f64>) -> Matrix4<f64> {
+        if *self == coo_system {
+            *ID
+        } else {
+            coo_system.icrs_to_mat(icrs2hor) * self.mat_to_icrs(icrs2hor)
         }
     }
+
+    #[inline]
+    fn mat_to_icrs(self, icrs2hor: &Matrix4<f64>) -> Matrix4<f64> {
+        match self {
+            CooSystem::ICRS => *ID,
+            CooSystem::GAL => *GAL2ICRS,
+            CooSystem::FK5J2000 => *FK5J20002ICRS,
+            CooSystem::FK4B1950 => *FK4B19502ICRS,
+            CooSystem::ECLJ2000 => *ECLJ20002ICRS,
+            CooSystem::SUPERGAL => *SUPERGAL2ICRS,
+            CooSystem::HORIZONTAL => transpose(icrs2hor),
+        }
+    }
+
+    #[inline]
+    fn icrs_to_mat(self, icrs2hor: &Matrix4<f64>) -> Matrix4<f64> {
+        match self {
+            CooSystem::ICRS => *ID,
+            CooSystem::GAL => *ICRS2GAL,
+            CooSystem::FK5J2000 => *ICRS2FK5J2000,
+            CooSystem::FK4B1950 => *ICRS2FK4B1950,
+            CooSystem::ECLJ2000 => *ICRS2ECLJ2000,
+            CooSystem::SUPERGAL => *ICRS2SUPERGAL,
+            CooSystem::HORIZONTAL => *icrs2hor,
+        }
+    }
+
+    /// Whether positions given in that frame include the E-terms of aberration
+    #[inline]
+    pub fn has_eterms(&self) -> bool {
+        *self == CooSystem::FK4B1950
+    }
 }
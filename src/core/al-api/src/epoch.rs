@@ -0,0 +1,22 @@
+use serde::Deserialize;
+
+/// Astrometric parameters of sources to propagate to another epoch
+///
+/// The arrays must have the same length as the positions they go along with.
+/// Missing parallaxes and radial velocities are taken as zero.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProperMotionCfg {
+    /// Proper motions in right ascension (multiplied by cos(dec)) in mas/yr
+    pub pmra: Vec<f64>,
+    /// Proper motions in declination in mas/yr
+    pub pmdec: Vec<f64>,
+    /// Parallaxes in mas
+    pub parallax: Option<Vec<f64>>,
+    /// Radial velocities in km/s
+    pub rv: Option<Vec<f64>>,
+    /// Epoch of the positions given, in Julian years (e.g. 2016.0)
+    pub from_epoch: f64,
+    /// Epoch to propagate the positions to, in Julian years
+    pub to_epoch: f64,
+}
@@ -25,6 +25,40 @@ impl HiPSCfg {
     }
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HEALPixMapCfg {
+    /// Layer name
+    pub layer: String,
+
+    /// The identifier of the HiPS rendering the map
+    pub creator_did: String,
+    /// The name of the column to display, the first one by default
+    #[serde(default)]
+    pub column: Option<String>,
+    /// Its color
+    pub meta: ImageMetadata,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedHiPSCfg {
+    /// Layer name
+    pub layer: String,
+
+    /// The identifier of the generated HiPS
+    pub creator_did: String,
+    /// The title of the generated HiPS
+    pub name: String,
+    /// The layer of the FITS image from which the HiPS is generated
+    pub image: String,
+    /// The max order of the HiPS, the one reaching the resolution of the image by default
+    #[serde(default)]
+    pub max_order: Option<u8>,
+    /// Its color
+    pub meta: ImageMetadata,
+}
+
 use crate::coo_system::CooSystem;
 
 #[derive(Deserialize, Debug, Clone)]
@@ -60,6 +94,34 @@ pub struct HiPSProperties {
 }
 
 impl HiPSProperties {
+    /// The properties of a HiPS whose FITS tiles are generated in memory, e.g. from a HEALPix map
+    pub fn new_in_memory(
+        creator_did: String,
+        coo_frame: CooSystem,
+        max_order: u8,
+        tile_size: i32,
+        sky_fraction: f32,
+    ) -> Self {
+        Self {
+            url: String::new(),
+            max_order,
+            coo_frame,
+            tile_size,
+            formats: vec![ImageExt::Fits],
+            is_planetary_body: None,
+            bitpix: Some(-32),
+            sky_fraction: Some(sky_fraction),
+            min_order: None,
+            hips_initial_fov: None,
+            hips_initial_ra: None,
+            hips_initial_dec: None,
+            hips_cube_depth: None,
+            min_cutout: None,
+            max_cutout: None,
+            creator_did,
+        }
+    }
+
     #[inline(always)]
     pub fn get_url(&self) -> &str {
         &self.url
@@ -151,27 +213,52 @@ use serde::Serialize;
 use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 #[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", try_from = "String")]
 pub enum TransferFunction {
     Linear,
     Sqrt,
     Log,
     Asinh,
     Pow2,
+    Sinh,
+    /// Power of a user defined exponent
+    Pow,
+    /// Histogram equalization of the pixels in view
+    Histogram,
 }
 
 impl TransferFunction {
-    pub fn new(id: &str) -> Self {
-        if id.contains("linear") {
-            TransferFunction::Linear
-        } else if id.contains("pow2") {
-            TransferFunction::Pow2
-        } else if id.contains("log") {
-            TransferFunction::Log
-        } else if id.contains("sqrt") {
-            TransferFunction::Sqrt
-        } else {
-            TransferFunction::Asinh
+    /// Default value of the parameter of the function (if any)
+    ///
+    /// * `Asinh` and `Sinh` - the softening parameter
+    /// * `Pow` - the exponent
+    pub fn default_param(&self) -> f32 {
+        match self {
+            TransferFunction::Asinh => 0.1,
+            TransferFunction::Sinh => 1.0 / 3.0,
+            TransferFunction::Pow => 2.0,
+            _ => 0.0,
+        }
+    }
+}
+
+impl std::str::FromStr for TransferFunction {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        match id.to_lowercase().as_str() {
+            "linear" => Ok(TransferFunction::Linear),
+            "sqrt" => Ok(TransferFunction::Sqrt),
+            "log" => Ok(TransferFunction::Log),
+            "asinh" => Ok(TransferFunction::Asinh),
+            "pow2" | "square" => Ok(TransferFunction::Pow2),
+            "sinh" => Ok(TransferFunction::Sinh),
+            "pow" | "power" => Ok(TransferFunction::Pow),
+            "histogram" | "histeq" | "equalization" => Ok(TransferFunction::Histogram),
+            _ => Err(format!(
+                "{} is not a valid stretch. Valid ones are: linear, sqrt, log, asinh, pow2 (or square), sinh, pow and histogram",
+                id
+            )),
         }
     }
 }
@@ -182,13 +269,64 @@ impl Default for TransferFunction {
     }
 }
 
-impl From<String> for TransferFunction {
-    fn from(id: String) -> Self {
-        TransferFunction::new(&id)
+impl std::convert::TryFrom<String> for TransferFunction {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        id.parse()
     }
 }
 
+/// Number of entries of the histogram equalization lookup table
+///
+/// It must match the size of the `equalization` uniform array of the shaders
+pub const EQUALIZATION_TABLE_SIZE: usize = 32;
+
+fn default_equalization() -> Vec<f32> {
+    (0..EQUALIZATION_TABLE_SIZE)
+        .map(|i| (i as f32) / ((EQUALIZATION_TABLE_SIZE - 1) as f32))
+        .collect()
+}
+
+/// Algorithm computing the cuts from the pixels in the view
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(tag = "method", rename_all = "camelCase")]
+pub enum CutsAlgorithm {
+    /// The minimum and maximum values
+    MinMax,
+    /// The values at the given percentiles, between 0 and 100
+    Percentile { low: f32, high: f32 },
+    /// The IRAF zscale algorithm
+    #[serde(rename = "zscale")]
+    ZScale {
+        #[serde(default = "default_zscale_contrast")]
+        contrast: f32,
+    },
+    /// The extremal values remaining after an iterative clipping of the values
+    /// farther than `sigma` standard deviations from the median
+    #[serde(rename_all = "camelCase")]
+    SigmaClip {
+        #[serde(default = "default_sigma")]
+        sigma: f32,
+        #[serde(default = "default_sigma_clip_iterations")]
+        max_iterations: u32,
+    },
+}
+
+fn default_zscale_contrast() -> f32 {
+    0.25
+}
+
+fn default_sigma() -> f32 {
+    3.0
+}
+
+fn default_sigma_clip_iterations() -> u32 {
+    5
+}
+
 use crate::colormap::CmapLabel;
+use crate::moc::MOCClipCfg;
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct HiPSColor {
@@ -207,6 +345,44 @@ pub struct HiPSColor {
     pub k_saturation: f32,
     pub k_contrast: f32,
     pub k_brightness: f32,
+    /// The algorithm computing the cuts from the pixels in view,
+    /// `min_cut` and `max_cut` being updated by the core
+    #[serde(default)]
+    pub auto_cuts: Option<CutsAlgorithm>,
+    /// The parameter of the stretch, i.e. the exponent of `Pow` or
+    /// the softening parameter of `Asinh` and `Sinh`
+    #[serde(default, deserialize_with = "deserialize_stretch_param")]
+    pub stretch_param: Option<f32>,
+    /// The lookup table of the histogram equalization, sampling regularly
+    /// the cumulative distribution of the pixels in view between the cuts.
+    /// It is computed by the core when `stretch` is `Histogram`
+    #[serde(skip, default = "default_equalization")]
+    pub equalization: Vec<f32>,
+    /// Output the physical pixel values instead of their colors. It is set
+    /// by the core when rendering the operands of a derived layer
+    #[serde(skip)]
+    pub raw_output: bool,
+}
+
+// The softening parameters of asinh and sinh divide the pixel values
+fn deserialize_stretch_param<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Option::<f32>::deserialize(deserializer)? {
+        Some(param) if param.is_nan() || param <= 0.0 => Err(serde::de::Error::custom(format!(
+            "The stretch parameter must be positive, got {}",
+            param
+        ))),
+        param => Ok(param),
+    }
+}
+
+impl HiPSColor {
+    pub fn get_stretch_param(&self) -> f32 {
+        self.stretch_param
+            .unwrap_or_else(|| self.stretch.default_param())
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -227,6 +403,10 @@ pub struct ImageMetadata {
     pub longitude_reversed: bool,
     /// the current format chosen
     pub img_format: ImageExt,
+    /// Restrict the rendering to the inside or the outside of a MOC
+    #[serde(default)]
+    #[wasm_bindgen(skip)]
+    pub clip: Option<MOCClipCfg>,
 }
 
 fn default_longitude_reversed() -> bool {
@@ -261,6 +441,9 @@ impl ImageMetadata {
             k_saturation,
             k_brightness,
             k_contrast,
+            auto_cuts,
+            stretch_param,
+            ..
         } = &self.color;
 
         js_sys::Reflect::set(
@@ -269,6 +452,12 @@ impl ImageMetadata {
             &serde_wasm_bindgen::to_value(&stretch).unwrap_abort(),
         )
         .unwrap_abort();
+        js_sys::Reflect::set(
+            &js_color_obj,
+            &"stretchParam".into(),
+            &serde_wasm_bindgen::to_value(&stretch_param).unwrap_abort(),
+        )
+        .unwrap_abort();
         js_sys::Reflect::set(
             &js_color_obj,
             &"minCut".into(),
@@ -281,6 +470,12 @@ impl ImageMetadata {
             &serde_wasm_bindgen::to_value(&max_cut).unwrap_abort(),
         )
         .unwrap_abort();
+        js_sys::Reflect::set(
+            &js_color_obj,
+            &"autoCuts".into(),
+            &serde_wasm_bindgen::to_value(&auto_cuts).unwrap_abort(),
+        )
+        .unwrap_abort();
         js_sys::Reflect::set(
             &js_color_obj,
             &"kGamma".into(),
@@ -327,3 +522,79 @@ impl ImageMetadata {
         self.opacity > 0.0
     }
 }
+
+/// Layer configs shared by the tests
+#[cfg(any(test, feature = "test-utils"))]
+pub mod fixtures {
+    use super::ImageMetadata;
+
+    /// The config of a FITS layer with linear cuts from 0 to 1
+    pub fn meta_json() -> serde_json::Value {
+        serde_json::json!({
+            "color": {
+                "stretch": "linear",
+                "minCut": 0.0,
+                "maxCut": 1.0,
+                "reversed": false,
+                "cmapName": "native",
+                "kGamma": 1.0,
+                "kSaturation": 0.0,
+                "kContrast": 0.0,
+                "kBrightness": 0.0,
+            },
+            "imgFormat": "fits",
+        })
+    }
+
+    pub fn meta() -> ImageMetadata {
+        serde_json::from_value(meta_json()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(stretch: &str, stretch_param: Option<f32>) -> serde_json::Result<HiPSColor> {
+        serde_json::from_value(serde_json::json!({
+            "stretch": stretch,
+            "stretchParam": stretch_param,
+            "reversed": false,
+            "cmapName": "native",
+            "kGamma": 1.0,
+            "kSaturation": 0.0,
+            "kContrast": 0.0,
+            "kBrightness": 0.0,
+        }))
+    }
+
+    #[test]
+    fn transfer_function_names() {
+        assert_eq!("Linear".parse(), Ok(TransferFunction::Linear));
+        assert_eq!("asinh".parse(), Ok(TransferFunction::Asinh));
+        // Aliases
+        assert_eq!("square".parse(), Ok(TransferFunction::Pow2));
+        assert_eq!("power".parse(), Ok(TransferFunction::Pow));
+        assert_eq!("histeq".parse(), Ok(TransferFunction::Histogram));
+        assert_eq!("equalization".parse(), Ok(TransferFunction::Histogram));
+
+        assert!("cubic".parse::<TransferFunction>().is_err());
+        assert!("".parse::<TransferFunction>().is_err());
+        assert!(color("cubic", None).is_err());
+        assert_eq!(
+            color("HistEq", None).unwrap().stretch,
+            TransferFunction::Histogram
+        );
+    }
+
+    #[test]
+    fn stretch_params() {
+        let cfg = color("asinh", None).unwrap();
+        assert_eq!(cfg.get_stretch_param(), 0.1);
+        let cfg = color("pow", Some(3.0)).unwrap();
+        assert_eq!(cfg.get_stretch_param(), 3.0);
+
+        assert!(color("asinh", Some(0.0)).is_err());
+        assert!(color("sinh", Some(-1.0)).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use super::color::ColorRGB;
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HorizonCfg {
+    pub color: Option<ColorRGB>,
+    pub thickness: Option<f32>,
+    pub opacity: Option<f32>,
+    pub show_labels: Option<bool>,
+    pub label_size: Option<f32>,
+    pub enabled: Option<bool>,
+    /// Airmasses for which an iso-line is drawn
+    pub airmass: Option<Vec<f64>>,
+}
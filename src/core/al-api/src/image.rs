@@ -10,3 +10,26 @@ pub struct ImageParams {
     pub min_cut: f32,
     pub max_cut: f32,
 }
+
+/// Value of a pixel read from an image
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum PixelValue {
+    /// Physical value of a FITS pixel, i.e. BZERO + BSCALE * stored value
+    Physical(f64),
+    /// Components of a RGB(A) pixel
+    Color(Vec<u8>),
+}
+
+// This struct is intended to be returned
+// to the javascript when probing an image layer
+#[derive(Serialize, Clone, Debug)]
+pub struct ImagePixel {
+    /// The value of the pixel, none for BLANK (or NaN) pixels
+    pub value: Option<PixelValue>,
+
+    /// The pixel in the image starting from 0, i.e. (0, 0) is the first pixel
+    /// stored in the FITS file
+    pub x: u64,
+    pub y: u64,
+}
@@ -4,16 +4,23 @@
 */
 pub mod blend;
 pub mod color;
+pub mod comparison;
+pub mod composite;
+pub mod contour;
 pub mod colormap;
 pub mod coo_system;
+pub mod epoch;
 pub mod grid;
+pub mod horizon;
 pub mod hips;
 pub mod moc;
+pub mod projection;
 pub mod resources;
 pub mod cell;
 pub mod fov;
 pub mod image;
 pub mod angle;
+pub mod stats;
 
 pub trait Abort {
     type Item;
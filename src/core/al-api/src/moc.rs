@@ -1,3 +1,4 @@
+use serde::Deserialize;
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use super::color::{Color, ColorRGBA};
@@ -88,3 +89,137 @@ impl Default for MOCOptions {
         }
     }
 }
+
+/// Restricts the rendering of a layer to the inside or the outside of a loaded MOC
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MOCClipCfg {
+    /// The uuid of the MOC
+    pub uuid: String,
+    /// Keep the outside of the MOC instead of its inside
+    #[serde(default)]
+    pub outside: bool,
+    /// The opacity factor applied to the clipped part, 0 hiding it
+    #[serde(default)]
+    pub clipped_opacity: f32,
+}
+
+/// Sky region from which a MOC is built
+///
+/// Positions and angles are given in degrees in ICRS
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MOCRegion {
+    Cone {
+        ra: f64,
+        dec: f64,
+        radius: f64,
+    },
+    Ellipse {
+        ra: f64,
+        dec: f64,
+        /// Semi-major axis
+        a: f64,
+        /// Semi-minor axis
+        b: f64,
+        /// Position angle of the semi-major axis, from the north towards the east
+        #[serde(default)]
+        pa: f64,
+    },
+    Box {
+        ra: f64,
+        dec: f64,
+        width: f64,
+        height: f64,
+        /// Position angle of the box, from the north towards the east
+        #[serde(default)]
+        angle: f64,
+    },
+    /// Zone delimited by two meridians and two parallels, crossing the
+    /// 0 meridian when `ra_min > ra_max`
+    #[serde(rename_all = "camelCase")]
+    Zone {
+        ra_min: f64,
+        dec_min: f64,
+        ra_max: f64,
+        dec_max: f64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Ring {
+        ra: f64,
+        dec: f64,
+        inner_radius: f64,
+        outer_radius: f64,
+    },
+    Polygon {
+        vertices: Vec<[f64; 2]>,
+        /// A position inside the polygon, the polygon delimits its smallest area if not given
+        #[serde(default)]
+        inside: Option<[f64; 2]>,
+    },
+    /// An STC-S string, e.g. `Circle ICRS 10 20 0.5`
+    Stcs {
+        stcs: String,
+    },
+}
+
+/// Set operation combining MOCs
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MOCOperation {
+    Union,
+    Intersection,
+    /// Removes all the other MOCs from the first one
+    Difference,
+    /// Keeps the cells covered by an odd number of MOCs
+    SymmetricDifference,
+    /// Takes only one MOC
+    Complement,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moc_clip_cfg() {
+        let clip: MOCClipCfg =
+            serde_json::from_value(serde_json::json!({ "uuid": "moc" })).unwrap();
+        assert_eq!(
+            clip,
+            MOCClipCfg {
+                uuid: "moc".to_string(),
+                outside: false,
+                clipped_opacity: 0.0,
+            }
+        );
+
+        let clip: MOCClipCfg = serde_json::from_value(serde_json::json!({
+            "uuid": "moc",
+            "outside": true,
+            "clippedOpacity": 0.2,
+        }))
+        .unwrap();
+        assert!(clip.outside);
+        assert_eq!(clip.clipped_opacity, 0.2);
+
+        // The MOC must be given
+        assert!(
+            serde_json::from_value::<MOCClipCfg>(serde_json::json!({ "outside": true })).is_err()
+        );
+        assert!(serde_json::from_value::<MOCClipCfg>(serde_json::json!({ "uuid": null })).is_err());
+    }
+
+    #[test]
+    fn moc_operations() {
+        let op = |name: &str| serde_json::from_value::<MOCOperation>(serde_json::json!(name));
+        assert_eq!(op("union").unwrap(), MOCOperation::Union);
+        assert_eq!(
+            op("symmetricDifference").unwrap(),
+            MOCOperation::SymmetricDifference
+        );
+        assert_eq!(op("complement").unwrap(), MOCOperation::Complement);
+        assert!(op("Union").is_err());
+        assert!(op("merge").is_err());
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// Projection of the view with its parameters
+///
+/// Parameters not relevant for the projection chosen are rejected.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ProjectionCfg {
+    /// WCS code of the projection, e.g. "TAN", "COD", "ZPN"
+    pub name: String,
+    /// Standard parallels in degrees of the conic projections (COD).
+    /// A single parallel defines a cone tangent to the sphere.
+    pub standard_parallels: Option<Vec<f64>>,
+    /// Polynomial coefficients of the zenithal polynomial projection (ZPN),
+    /// i.e. the values of the PV2_i WCS keywords
+    pub pv: Option<Vec<f64>>,
+    /// Distance of the point of projection from the center of the sphere,
+    /// in sphere radius units, of the zenithal perspective projection (AZP)
+    pub mu: Option<f64>,
+}
+
+impl ProjectionCfg {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
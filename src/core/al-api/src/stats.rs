@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// Sky region in which the pixels of a layer are gathered
+///
+/// Angles are given in degrees in the frame given along with the region,
+/// except for MOCs which are always expressed in ICRS
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Region {
+    Circle {
+        ra: f64,
+        dec: f64,
+        radius: f64,
+    },
+    Box {
+        ra: f64,
+        dec: f64,
+        width: f64,
+        height: f64,
+        /// Position angle of the box, from the north towards the east
+        #[serde(default)]
+        angle: f64,
+    },
+    Polygon {
+        vertices: Vec<[f64; 2]>,
+    },
+    Moc {
+        /// The uuid of a MOC already added to the view
+        uuid: String,
+    },
+}
+
+/// Histogram of pixel values
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Histogram {
+    /// Edges of the bins, there is one more edge than bins
+    pub edges: Vec<f64>,
+    pub counts: Vec<u32>,
+}
+
+// This struct is intended to be returned
+// to the javascript when computing the statistics of a region
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// Number of pixels taken into account, blank pixels are excluded
+    pub count: usize,
+
+    pub sum: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// Standard deviation of the population
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+
+    pub histogram: Histogram,
+}
+
+// This struct is intended to be returned
+// to the javascript when sampling a layer along a great-circle segment
+#[derive(Serialize, Clone, Debug)]
+pub struct LineProfile {
+    /// Positions of the samples in degrees, in the frame of the segment
+    pub lon: Vec<f64>,
+    pub lat: Vec<f64>,
+    /// Angular distance of the samples from the start of the segment in degrees
+    pub dist: Vec<f64>,
+    /// Values of the samples, none for blank pixels or positions out of the layer
+    pub values: Vec<Option<f64>>,
+}
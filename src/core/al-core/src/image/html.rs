@@ -15,6 +15,10 @@ where
             format: std::marker::PhantomData,
         }
     }
+
+    pub fn get_image(&self) -> &web_sys::HtmlImageElement {
+        &self.image
+    }
 }
 
 use crate::image::format::ImageFormat;
@@ -2,48 +2,38 @@ use {wasm_bindgen::prelude::*, web_sys::WebGlFramebuffer};
 
 use crate::webgl_ctx::WebGlRenderingCtx;
 // Internal format used for the framebuffer final texture
-use crate::image::format::RGBA8U;
+use crate::image::format::{ChannelType, ImageFormat, RGBA8U};
 
 pub struct FrameBufferObject {
     gl: WebGlContext,
     fbo: WebGlFramebuffer,
     pub texture: Texture2D,
+    // Recreates the texture when the framebuffer is resized
+    create_texture: fn(&WebGlContext, i32, i32) -> Result<Texture2D, JsValue>,
 }
 use crate::texture::Texture2D;
 use crate::webgl_ctx::WebGlContext;
 
 impl FrameBufferObject {
     pub fn new(gl: &WebGlContext, width: usize, height: usize) -> Result<Self, JsValue> {
+        Self::new_with_format::<RGBA8U>(gl, width, height)
+    }
+
+    /// Create a framebuffer rendering into a texture of a given format
+    ///
+    /// Float formats are sampled with the nearest filter as linear filtering
+    /// them is not guaranteed to be supported
+    pub fn new_with_format<F: ImageFormat>(
+        gl: &WebGlContext,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, JsValue> {
         let fbo = gl
             .create_framebuffer()
             .ok_or("failed to create framebuffer")?;
         gl.bind_framebuffer(WebGlRenderingCtx::FRAMEBUFFER, Some(&fbo));
 
-        let texture = Texture2D::create_empty_with_format::<RGBA8U>(
-            gl,
-            width as i32,
-            height as i32,
-            &[
-                (
-                    WebGlRenderingCtx::TEXTURE_MIN_FILTER,
-                    WebGlRenderingCtx::LINEAR,
-                ),
-                (
-                    WebGlRenderingCtx::TEXTURE_MAG_FILTER,
-                    WebGlRenderingCtx::LINEAR,
-                ),
-                // Prevents s-coordinate wrapping (repeating)
-                (
-                    WebGlRenderingCtx::TEXTURE_WRAP_S,
-                    WebGlRenderingCtx::CLAMP_TO_EDGE,
-                ),
-                // Prevents t-coordinate wrapping (repeating)
-                (
-                    WebGlRenderingCtx::TEXTURE_WRAP_T,
-                    WebGlRenderingCtx::CLAMP_TO_EDGE,
-                ),
-            ],
-        )?;
+        let texture = create_texture::<F>(gl, width as i32, height as i32)?;
         texture.attach_to_framebuffer();
 
         gl.bind_framebuffer(WebGlRenderingCtx::FRAMEBUFFER, None);
@@ -52,6 +42,7 @@ impl FrameBufferObject {
             gl: gl.clone(),
             texture,
             fbo,
+            create_texture: create_texture::<F>,
         })
     }
 
@@ -66,32 +57,7 @@ impl FrameBufferObject {
 
             self.gl
                 .bind_framebuffer(WebGlRenderingCtx::FRAMEBUFFER, Some(&self.fbo));
-            self.texture = Texture2D::create_empty_with_format::<RGBA8U>(
-                &self.gl,
-                width as i32,
-                height as i32,
-                &[
-                    (
-                        WebGlRenderingCtx::TEXTURE_MIN_FILTER,
-                        WebGlRenderingCtx::LINEAR,
-                    ),
-                    (
-                        WebGlRenderingCtx::TEXTURE_MAG_FILTER,
-                        WebGlRenderingCtx::LINEAR,
-                    ),
-                    // Prevents s-coordinate wrapping (repeating)
-                    (
-                        WebGlRenderingCtx::TEXTURE_WRAP_S,
-                        WebGlRenderingCtx::CLAMP_TO_EDGE,
-                    ),
-                    // Prevents t-coordinate wrapping (repeating)
-                    (
-                        WebGlRenderingCtx::TEXTURE_WRAP_T,
-                        WebGlRenderingCtx::CLAMP_TO_EDGE,
-                    ),
-                ],
-            )
-            .unwrap();
+            self.texture = (self.create_texture)(&self.gl, width as i32, height as i32).unwrap();
             self.texture.attach_to_framebuffer();
             self.gl
                 .bind_framebuffer(WebGlRenderingCtx::FRAMEBUFFER, None);
@@ -140,3 +106,56 @@ impl Drop for FrameBufferObject {
         self.gl.delete_framebuffer(Some(&self.fbo));
     }
 }
+
+const TEX_PARAMS_LINEAR: &[(u32, u32)] = &[
+    (
+        WebGlRenderingCtx::TEXTURE_MIN_FILTER,
+        WebGlRenderingCtx::LINEAR,
+    ),
+    (
+        WebGlRenderingCtx::TEXTURE_MAG_FILTER,
+        WebGlRenderingCtx::LINEAR,
+    ),
+    // Prevents s-coordinate wrapping (repeating)
+    (
+        WebGlRenderingCtx::TEXTURE_WRAP_S,
+        WebGlRenderingCtx::CLAMP_TO_EDGE,
+    ),
+    // Prevents t-coordinate wrapping (repeating)
+    (
+        WebGlRenderingCtx::TEXTURE_WRAP_T,
+        WebGlRenderingCtx::CLAMP_TO_EDGE,
+    ),
+];
+
+const TEX_PARAMS_NEAREST: &[(u32, u32)] = &[
+    (
+        WebGlRenderingCtx::TEXTURE_MIN_FILTER,
+        WebGlRenderingCtx::NEAREST,
+    ),
+    (
+        WebGlRenderingCtx::TEXTURE_MAG_FILTER,
+        WebGlRenderingCtx::NEAREST,
+    ),
+    (
+        WebGlRenderingCtx::TEXTURE_WRAP_S,
+        WebGlRenderingCtx::CLAMP_TO_EDGE,
+    ),
+    (
+        WebGlRenderingCtx::TEXTURE_WRAP_T,
+        WebGlRenderingCtx::CLAMP_TO_EDGE,
+    ),
+];
+
+fn create_texture<F: ImageFormat>(
+    gl: &WebGlContext,
+    width: i32,
+    height: i32,
+) -> Result<Texture2D, JsValue> {
+    let tex_params = match F::CHANNEL_TYPE {
+        ChannelType::RGBA32F | ChannelType::RGB32F => TEX_PARAMS_NEAREST,
+        _ => TEX_PARAMS_LINEAR,
+    };
+
+    Texture2D::create_empty_with_format::<F>(gl, width, height, tex_params)
+}
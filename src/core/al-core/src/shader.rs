@@ -308,13 +308,17 @@ impl SendUniforms for HiPSColor {
 
         shader
             .attach_uniform("H", &self.stretch)
+            .attach_uniform("stretch_param", &self.get_stretch_param())
+            // Array uniforms are located by their first element
+            .attach_uniform("equalization[0]", &self.equalization.as_slice())
             .attach_uniform("min_value", &self.min_cut.unwrap_or(0.0))
             .attach_uniform("max_value", &self.max_cut.unwrap_or(1.0))
             .attach_uniform("k_gamma", &self.k_gamma)
             .attach_uniform("k_saturation", &self.k_saturation)
             .attach_uniform("k_brightness", &self.k_brightness)
             .attach_uniform("k_contrast", &self.k_contrast)
-            .attach_uniform("reversed", &reversed);
+            .attach_uniform("reversed", &reversed)
+            .attach_uniform("raw_output", &(self.raw_output as i32));
 
         shader
     }
@@ -332,13 +336,16 @@ impl SendUniformsWithParams<Colormaps> for HiPSColor {
         shader
             .attach_uniforms_with_params_from(cmap, cmaps)
             .attach_uniform("H", &self.stretch)
+            .attach_uniform("stretch_param", &self.get_stretch_param())
+            .attach_uniform("equalization[0]", &self.equalization.as_slice())
             .attach_uniform("min_value", &self.min_cut.unwrap_or(0.0))
             .attach_uniform("max_value", &self.max_cut.unwrap_or(1.0))
             .attach_uniform("k_gamma", &self.k_gamma)
             .attach_uniform("k_saturation", &self.k_saturation)
             .attach_uniform("k_brightness", &self.k_brightness)
             .attach_uniform("k_contrast", &self.k_contrast)
-            .attach_uniform("reversed", &reversed);
+            .attach_uniform("reversed", &reversed)
+            .attach_uniform("raw_output", &(self.raw_output as i32));
 
         shader
     }
@@ -298,6 +298,104 @@ impl Texture2D {
     }
 
     pub fn read_pixel(&self, x: i32, y: i32) -> Result<JsValue, JsValue> {
+        self.read_from_framebuffer(|metadata| {
+            #[cfg(feature = "webgl2")]
+            let value = match (metadata.format, metadata.type_) {
+                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::UNSIGNED_BYTE) => {
+                    let p = <[u8; 1]>::read_pixel(&self.gl, x, y)?;
+                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
+                }
+                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::SHORT) => {
+                    let p = <[i16; 1]>::read_pixel(&self.gl, x, y)?;
+                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
+                }
+                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::INT) => {
+                    let p = <[i32; 1]>::read_pixel(&self.gl, x, y)?;
+                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
+                }
+                (WebGlRenderingCtx::RED, WebGlRenderingCtx::FLOAT) => {
+                    let p = <[f32; 1]>::read_pixel(&self.gl, x, y)?;
+                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
+                }
+                (WebGlRenderingCtx::RGB, WebGlRenderingCtx::UNSIGNED_BYTE) => {
+                    let p = <[u8; 3]>::read_pixel(&self.gl, x, y)?;
+                    Ok(serde_wasm_bindgen::to_value(&p)?)
+                }
+                (WebGlRenderingCtx::RGBA, WebGlRenderingCtx::UNSIGNED_BYTE) => {
+                    let p = <[u8; 4]>::read_pixel(&self.gl, x, y)?;
+                    Ok(serde_wasm_bindgen::to_value(&p)?)
+                }
+                _ => Err(JsValue::from_str(
+                    "Pixel retrieval not implemented for that texture format.",
+                )),
+            };
+
+            value
+        })
+    }
+
+    /// Read all the pixels of a single channel texture
+    ///
+    /// The values are given row by row, starting from the first row of the texture
+    pub fn read_values(&self) -> Result<Vec<f32>, JsValue> {
+        self.read_from_framebuffer(|metadata| {
+            let (w, h) = (metadata.width as i32, metadata.height as i32);
+            let len = metadata.width * metadata.height;
+
+            macro_rules! read_values {
+                ($array:ty, $format:expr, $type_:expr) => {{
+                    let pixels = <$array>::new_with_length(len);
+                    self.gl.read_pixels_with_opt_array_buffer_view(
+                        0,
+                        0,
+                        w,
+                        h,
+                        $format,
+                        $type_,
+                        Some(&pixels),
+                    )?;
+
+                    Ok(pixels.to_vec().into_iter().map(|v| v as f32).collect())
+                }};
+            }
+
+            match (metadata.format, metadata.type_) {
+                #[cfg(feature = "webgl2")]
+                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::UNSIGNED_BYTE) => read_values!(
+                    js_sys::Uint8Array,
+                    WebGlRenderingCtx::RED_INTEGER,
+                    WebGlRenderingCtx::UNSIGNED_BYTE
+                ),
+                #[cfg(feature = "webgl2")]
+                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::SHORT) => read_values!(
+                    js_sys::Int16Array,
+                    WebGlRenderingCtx::RED_INTEGER,
+                    WebGlRenderingCtx::SHORT
+                ),
+                #[cfg(feature = "webgl2")]
+                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::INT) => read_values!(
+                    js_sys::Int32Array,
+                    WebGlRenderingCtx::RED_INTEGER,
+                    WebGlRenderingCtx::INT
+                ),
+                #[cfg(feature = "webgl2")]
+                (WebGlRenderingCtx::RED, WebGlRenderingCtx::FLOAT) => read_values!(
+                    js_sys::Float32Array,
+                    WebGlRenderingCtx::RED,
+                    WebGlRenderingCtx::FLOAT
+                ),
+                _ => Err(JsValue::from_str(
+                    "Only the textures of one channel can be read entirely",
+                )),
+            }
+        })
+    }
+
+    // Attach the texture to a framebuffer for reading its pixels
+    fn read_from_framebuffer<T>(
+        &self,
+        read: impl FnOnce(&Texture2DMeta) -> Result<T, JsValue>,
+    ) -> Result<T, JsValue> {
         // Create and bind the framebuffer
         let reader = self.gl.create_framebuffer();
         self.gl
@@ -330,36 +428,7 @@ impl Texture2D {
             self.gl
                 .viewport(0, 0, metadata.width as i32, metadata.height as i32);
 
-            #[cfg(feature = "webgl2")]
-            let value = match (metadata.format, metadata.type_) {
-                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::UNSIGNED_BYTE) => {
-                    let p = <[u8; 1]>::read_pixel(&self.gl, x, y)?;
-                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
-                }
-                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::SHORT) => {
-                    let p = <[i16; 1]>::read_pixel(&self.gl, x, y)?;
-                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
-                }
-                (WebGlRenderingCtx::RED_INTEGER, WebGlRenderingCtx::INT) => {
-                    let p = <[i32; 1]>::read_pixel(&self.gl, x, y)?;
-                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
-                }
-                (WebGlRenderingCtx::RED, WebGlRenderingCtx::FLOAT) => {
-                    let p = <[f32; 1]>::read_pixel(&self.gl, x, y)?;
-                    Ok(serde_wasm_bindgen::to_value(&p[0])?)
-                }
-                (WebGlRenderingCtx::RGB, WebGlRenderingCtx::UNSIGNED_BYTE) => {
-                    let p = <[u8; 3]>::read_pixel(&self.gl, x, y)?;
-                    Ok(serde_wasm_bindgen::to_value(&p)?)
-                }
-                (WebGlRenderingCtx::RGBA, WebGlRenderingCtx::UNSIGNED_BYTE) => {
-                    let p = <[u8; 4]>::read_pixel(&self.gl, x, y)?;
-                    Ok(serde_wasm_bindgen::to_value(&p)?)
-                }
-                _ => Err(JsValue::from_str(
-                    "Pixel retrieval not implemented for that texture format.",
-                )),
-            };
+            let value = read(&metadata);
 
             // Unbind the framebuffer
             self.gl
@@ -400,6 +469,20 @@ pub struct Texture2DBound<'a> {
 }
 
 impl<'a> Texture2DBound<'a> {
+    /// Copy a region of the framebuffer bound for reading into the texture
+    pub fn copy_tex_sub_image_2d(&self, dx: i32, dy: i32, x: i32, y: i32, width: i32, height: i32) {
+        self.texture_2d.gl.copy_tex_sub_image_2d(
+            WebGlRenderingCtx::TEXTURE_2D,
+            0,
+            dx,
+            dy,
+            x,
+            y,
+            width,
+            height,
+        );
+    }
+
     pub fn tex_sub_image_2d_with_u32_and_u32_and_html_image_element(
         &self,
         dx: i32,
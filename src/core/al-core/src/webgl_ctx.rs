@@ -60,6 +60,10 @@ impl WebGlContext {
             {
                 let _ = r;
             }
+            // Blending onto float framebuffers, e.g. the operands of derived layers
+            if let Ok(r) = get_extension::<js_sys::Object>(&gl, "EXT_float_blend") {
+                let _ = r;
+            }
 
             let ctx = WebGlContext { inner: gl };
             Ok(ctx)
@@ -112,51 +116,71 @@ pub trait GlWrapper {
 }
 
 use al_api::blend::{BlendCfg, BlendFactor};
+
+// The GL blend factor of a blend config
+fn blend_factor(f: &BlendFactor) -> u32 {
+    match f {
+        BlendFactor::ConstantAlpha => WebGlRenderingCtx::CONSTANT_ALPHA,
+        BlendFactor::ConstantColor => WebGlRenderingCtx::CONSTANT_COLOR,
+        BlendFactor::Zero => WebGlRenderingCtx::ZERO,
+        BlendFactor::One => WebGlRenderingCtx::ONE,
+        BlendFactor::DstAlpha => WebGlRenderingCtx::DST_ALPHA,
+        BlendFactor::DstColor => WebGlRenderingCtx::DST_COLOR,
+        BlendFactor::OneMinusConstantAlpha => WebGlRenderingCtx::ONE_MINUS_CONSTANT_ALPHA,
+        BlendFactor::OneMinusDstColor => WebGlRenderingCtx::ONE_MINUS_DST_COLOR,
+        BlendFactor::OneMinusDstAlpha => WebGlRenderingCtx::ONE_MINUS_DST_ALPHA,
+        BlendFactor::SrcAlpha => WebGlRenderingCtx::SRC_ALPHA,
+        BlendFactor::SrcColor => WebGlRenderingCtx::SRC_COLOR,
+        BlendFactor::OneMinusSrcColor => WebGlRenderingCtx::ONE_MINUS_SRC_COLOR,
+        BlendFactor::OneMinusSrcAlpha => WebGlRenderingCtx::ONE_MINUS_SRC_ALPHA,
+        BlendFactor::OneMinusConstantColor => WebGlRenderingCtx::ONE_MINUS_CONSTANT_COLOR,
+    }
+}
+
+// The GL blend equation of a blend config
+fn blend_equation(f: &BlendFunc) -> u32 {
+    match f {
+        BlendFunc::FuncAdd => WebGlRenderingCtx::FUNC_ADD,
+        BlendFunc::FuncReverseSubstract => WebGlRenderingCtx::FUNC_REVERSE_SUBTRACT,
+        BlendFunc::FuncSubstract => WebGlRenderingCtx::FUNC_SUBTRACT,
+        #[cfg(feature = "webgl2")]
+        BlendFunc::Min => WebGlRenderingCtx::MIN,
+        #[cfg(feature = "webgl2")]
+        BlendFunc::Max => WebGlRenderingCtx::MAX,
+    }
+}
+
 impl GlWrapper for BlendCfg {
     fn enable(
         &self,
         gl: &WebGlContext,
         f: impl FnOnce() -> Result<(), JsValue>,
     ) -> Result<(), JsValue> {
-        let blend_factor_f = |f: &BlendFactor| -> u32 {
-            match f {
-                BlendFactor::ConstantAlpha => WebGlRenderingCtx::CONSTANT_ALPHA,
-                BlendFactor::ConstantColor => WebGlRenderingCtx::CONSTANT_COLOR,
-                BlendFactor::Zero => WebGlRenderingCtx::ZERO,
-                BlendFactor::One => WebGlRenderingCtx::ONE,
-                BlendFactor::DstAlpha => WebGlRenderingCtx::DST_ALPHA,
-                BlendFactor::DstColor => WebGlRenderingCtx::DST_COLOR,
-                BlendFactor::OneMinusConstantAlpha => WebGlRenderingCtx::ONE_MINUS_CONSTANT_ALPHA,
-                BlendFactor::OneMinusDstColor => WebGlRenderingCtx::ONE_MINUS_DST_COLOR,
-                BlendFactor::OneMinusDstAlpha => WebGlRenderingCtx::ONE_MINUS_DST_ALPHA,
-                BlendFactor::SrcAlpha => WebGlRenderingCtx::SRC_ALPHA,
-                BlendFactor::SrcColor => WebGlRenderingCtx::SRC_COLOR,
-                BlendFactor::OneMinusSrcColor => WebGlRenderingCtx::ONE_MINUS_SRC_COLOR,
-                BlendFactor::OneMinusSrcAlpha => WebGlRenderingCtx::ONE_MINUS_SRC_ALPHA,
-                BlendFactor::OneMinusConstantColor => WebGlRenderingCtx::ONE_MINUS_CONSTANT_ALPHA,
-            }
-        };
-
-        let blend_func_f = |f: &BlendFunc| -> u32 {
-            match f {
-                BlendFunc::FuncAdd => WebGlRenderingCtx::FUNC_ADD,
-                BlendFunc::FuncReverseSubstract => WebGlRenderingCtx::FUNC_REVERSE_SUBTRACT,
-                BlendFunc::FuncSubstract => WebGlRenderingCtx::FUNC_SUBTRACT,
-            }
-        };
-
-        gl.blend_equation(blend_func_f(&self.func));
+        gl.blend_equation(blend_equation(&self.func));
         gl.blend_func_separate(
-            blend_factor_f(&self.src_color_factor),
-            blend_factor_f(&self.dst_color_factor),
+            blend_factor(&self.src_color_factor),
+            blend_factor(&self.dst_color_factor),
             WebGlRenderingCtx::ONE,
             WebGlRenderingCtx::ONE,
         );
 
         f()?;
 
-        gl.blend_equation(blend_func_f(&BlendFunc::FuncAdd));
+        gl.blend_equation(blend_equation(&BlendFunc::FuncAdd));
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_minus_constant_color_factor() {
+        assert_eq!(
+            blend_factor(&BlendFactor::OneMinusConstantColor),
+            WebGlRenderingCtx::ONE_MINUS_CONSTANT_COLOR
+        );
+    }
+}
@@ -1,19 +1,26 @@
 use crate::renderable::ImageLayer;
-use crate::tile_fetcher::HiPSLocalFiles;
+use crate::tile_fetcher::{HiPSLocalFiles, InMemoryHiPS};
+use crate::healpix::map::HEALPixMap;
 
+use crate::renderable::hips::sampling::{FITSCutout, TilePixelSamples, TileSampling, TileValues};
+use crate::renderable::image::fits;
 use crate::renderable::hips::HiPS;
 use crate::{
     //async_task::{BuildCatalogIndex, ParseTableTask, TaskExecutor, TaskResult, TaskType},
-    camera::CameraViewPort,
+    camera::{wcs::ImageWcs, CameraViewPort},
     downloader::Downloader,
     healpix::coverage::HEALPixCoverage,
+    healpix::time_coverage::{SpaceTimeCoverage, TimeCoverage},
     inertia::Inertia,
     math::{
         self,
         angle::{Angle, ArcDeg},
+        horizontal::Observer,
         lonlat::{LonLat, LonLatT},
     },
     renderable::grid::ProjetedGrid,
+    renderable::contour::{self, ContourRenderer, PendingContours},
+    renderable::horizon::Horizon,
     renderable::Layers,
     renderable::{catalog::Manager, moc::MOCRenderer},
     shader::ShaderManager,
@@ -25,17 +32,23 @@ use crate::math::angle::ToAngle;
 use wcs::WCS;
 
 use wasm_bindgen::prelude::*;
+use serde::Serialize;
 
 use al_core::colormap::{Colormap, Colormaps};
 use al_core::WebGlContext;
 
 use super::coosys;
 use al_api::{
+    comparison::ComparisonCfg,
+    composite::{LayerArithmeticCfg, RGBCompositeCfg},
+    contour::{ContourLevels, ContourStyle},
     coo_system::CooSystem,
     grid::GridCfg,
-    hips::{HiPSCfg, ImageMetadata},
+    horizon::HorizonCfg,
+    hips::{GeneratedHiPSCfg, HEALPixMapCfg, HiPSCfg, HiPSProperties, ImageMetadata},
+    stats::{LineProfile, Region},
 };
-use cgmath::Vector4;
+use cgmath::{Matrix4, Vector4};
 use fitsrs::{fits::AsyncFits, hdu::extension::AsyncXtensionHDU};
 
 use web_sys::{HtmlElement, WebGl2RenderingContext};
@@ -43,7 +56,7 @@ use web_sys::{HtmlElement, WebGl2RenderingContext};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::renderable::final_pass::RenderPass;
 use al_core::FrameBufferObject;
@@ -60,6 +73,13 @@ pub struct App {
     downloader: Rc<RefCell<Downloader>>,
     tile_fetcher: TileFetcherQueue,
     layers: Layers,
+    // Samplings of HiPS layers waiting for their tiles
+    tile_samplings: Vec<TileSampling>,
+    fits_cutouts: Vec<FITSCutout>,
+    // Pixels sampled in the FITS tiles received for computing the cuts of the view
+    tile_pixel_samples: TilePixelSamples,
+    // Whether the cuts computed from the pixels in view must be updated
+    auto_cuts_outdated: bool,
 
     time_start_blending: Time,
     request_redraw: bool,
@@ -67,8 +87,18 @@ pub struct App {
 
     // The grid renderable
     grid: ProjetedGrid,
+    // The horizon of the observer
+    horizon: Horizon,
+    // The observer of the horizontal frame
+    observer: Option<Observer>,
+    // The contours of the layers
+    contours: ContourRenderer,
+    // Contours of HiPS waiting for their tiles
+    pending_contours: Vec<PendingContours>,
     // The moc renderable
     moc: MOCRenderer,
+    // Space-time MOCs displayed through their spatial coverage during chosen times
+    space_time_mocs: HashMap<String, SpaceTimeCoverage>,
     // Catalog manager
     manager: Manager,
 
@@ -112,6 +142,14 @@ use futures::io::BufReader; // for `next`
 
 use crate::math::projection::*;
 pub const BLENDING_ANIM_DURATION: DeltaTime = DeltaTime::from_millis(200.0); // in ms
+// Maximum number of pixels read for computing statistics and line profiles
+const MAX_NUM_SAMPLES: u64 = 1 << 18;
+// Maximum number of pixels of the images reprojected from a HiPS
+const MAX_NUM_CUTOUT_PIXELS: usize = 1 << 22;
+// Maximum number of HiPS tiles on which contours are traced
+const MAX_NUM_CONTOUR_TILES: usize = 64;
+// Depth of the cells tested against the viewport when the view goes beyond the projection domain
+const VIEW_MOC_CLIPPING_DEPTH: u8 = 6;
                                                                              //use crate::buffer::Tile;
 use crate::time::Time;
 use cgmath::InnerSpace;
@@ -164,6 +202,8 @@ impl App {
 
         // Grid definition
         let grid = ProjetedGrid::new(gl.clone(), aladin_div)?;
+        let horizon = Horizon::new(&gl, aladin_div)?;
+        let contours = ContourRenderer::new(&gl)?;
 
         // Variable storing the location to move to
         let inertia = None;
@@ -217,8 +257,13 @@ impl App {
             request_redraw,
             // The grid renderable
             grid,
+            horizon,
+            observer: None,
+            contours,
+            pending_contours: vec![],
             // MOCs renderable
             moc,
+            space_time_mocs: HashMap::new(),
             // The catalog renderable
             manager,
             //exec,
@@ -244,6 +289,10 @@ impl App {
             catalog_loaded,
 
             tile_fetcher,
+            tile_samplings: vec![],
+            fits_cutouts: vec![],
+            tile_pixel_samples: TilePixelSamples::default(),
+            auto_cuts_outdated: false,
 
             colormaps,
             projection,
@@ -260,6 +309,27 @@ impl App {
         self.tile_fetcher.clear();
         // Loop over the hipss
         for hips in self.layers.get_mut_hipses() {
+            // The tiles of a HEALPix map or of a generated HiPS are computed in memory
+            // instead of being fetched
+            let hips_cdid = hips.get_config().get_creator_did().to_string();
+            if let Some(source) = self.tile_fetcher.get_in_memory_hips(&hips_cdid) {
+                if let HiPS::D2(hips) = hips {
+                    let cells = hips
+                        .look_for_new_tiles(&self.camera, &self.projection)
+                        .map(|tiles| tiles.collect::<Vec<_>>())
+                        .unwrap_or_default();
+
+                    for cell in &cells {
+                        self.auto_cuts_outdated |=
+                            add_in_memory_tile(hips, source, cell, &mut self.tile_pixel_samples)?;
+                    }
+
+                    self.request_redraw |= !cells.is_empty();
+                }
+
+                continue;
+            }
+
             if self.camera.get_texture_depth() == 0 {
                 let allsky_query = match hips {
                     HiPS::D2(h) => query::Allsky::new(h.get_config(), None),
@@ -390,18 +460,114 @@ impl App {
 }
 
 use crate::downloader::request::Resource;
+use crate::renderable::hips::d2::HiPS2D;
+use al_core::image::format::R32F;
+use al_core::image::raw::ImageBuffer;
+use std::ops::Range;
+
 use al_api::cell::HEALPixCellProjeted;
 
-use crate::healpix::cell::HEALPixCell;
+use crate::healpix::cell::{HEALPixCell, MAX_HPX_DEPTH};
+use wasm_bindgen_futures::JsFuture;
 
 use al_api::color::ColorRGB;
 
+// Generate a tile in memory and push it to its HiPS
+//
+// Returns whether new values have been sampled for the cuts
+fn add_in_memory_tile(
+    hips: &mut HiPS2D,
+    source: &InMemoryHiPS,
+    cell: &HEALPixCell,
+    tile_pixel_samples: &mut TilePixelSamples,
+) -> Result<bool, JsValue> {
+    let size = source.get_tile_size();
+    let tile = source.get_tile(cell);
+
+    let sampled = tile_pixel_samples.add_tile_values(
+        hips.get_config().get_creator_did(),
+        cell,
+        &tile,
+        size as usize,
+    );
+    // The float values are sent as bytes like the ones of the FITS tiles
+    let bytes = tile.iter().flat_map(|v| v.to_ne_bytes()).collect();
+    hips.add_tile(cell, ImageBuffer::<R32F>::new(bytes, size, size), Time::now())?;
+
+    Ok(sampled)
+}
+
 impl App {
     pub(crate) fn set_background_color(&mut self, color: ColorRGB) {
         self.layers.set_background_color(color);
         self.request_redraw = true;
     }
 
+    /// Coverage of the view in ICRS
+    ///
+    /// The view is delimited by a polygon when it is inside the projection domain.
+    /// Otherwise the coverage is restricted to the cells having their center or one
+    /// of their vertices in the viewport, tested at a bounded depth.
+    pub(crate) fn get_view_moc(&self, depth: u8) -> HEALPixCoverage {
+        let coverage = crate::camera::build_fov_coverage(
+            depth,
+            self.camera.get_field_of_view(),
+            self.camera.get_center(),
+            self.camera.get_coo_system(),
+            self.camera.get_icrs_to_horizontal(),
+            CooSystem::ICRS,
+            &self.projection,
+        );
+
+        if self.camera.get_field_of_view().get_vertices().is_some() {
+            return coverage;
+        }
+
+        let in_view = |(lon, lat): (f64, f64)| {
+            let xyzw = crate::math::lonlat::radec_to_xyzw(lon.to_angle(), lat.to_angle());
+            self.projection
+                .icrs_celestial_to_normalized_device_space(&xyzw, &self.camera)
+                .is_some_and(|ndc| ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0)
+        };
+
+        crate::camera::clip_coverage_to_view(&coverage, VIEW_MOC_CLIPPING_DEPTH, in_view)
+    }
+
+    /// Coverage of the tiles of a HiPS layer in the view, at the order of the tiles displayed
+    ///
+    /// The tiles are restricted to the ones in the MOC of the HiPS when it is known
+    pub(crate) fn get_layer_view_moc(&self, layer: &str) -> Result<HEALPixCoverage, JsValue> {
+        let hips = self
+            .layers
+            .get_hips_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str(&format!("HiPS layer {} not found", layer)))?;
+        let cfg = hips.get_config();
+        if cfg.get_frame() != CooSystem::ICRS {
+            return Err(JsValue::from_str(
+                "The tiles of a HiPS are only given as a MOC for HiPS in ICRS",
+            ));
+        }
+
+        let depth = (self.camera.get_texture_depth() + cfg.delta_depth())
+            .clamp(cfg.get_min_depth_tile(), cfg.get_max_depth_tile());
+        let view_moc = self.get_view_moc(depth);
+
+        if let Some(moc) = hips.get_moc() {
+            let tiles = view_moc
+                .flatten_to_fixed_depth_cells()
+                .filter(|&idx| moc.intersects_cell(&HEALPixCell(depth, idx)))
+                .collect::<Vec<_>>();
+
+            Ok(HEALPixCoverage::from_fixed_hpx_cells(
+                depth,
+                tiles.into_iter(),
+                None,
+            ))
+        } else {
+            Ok(view_moc)
+        }
+    }
+
     pub(crate) fn get_visible_cells(&self, depth: u8) -> Box<[HEALPixCellProjeted]> {
         // Convert the camera frame vertices to ICRS before doing the moc
         let coverage = crate::camera::build_fov_coverage(
@@ -409,6 +575,7 @@ impl App {
             self.camera.get_field_of_view(),
             self.camera.get_center(),
             self.camera.get_coo_system(),
+            self.camera.get_icrs_to_horizontal(),
             CooSystem::ICRS,
             &self.projection,
         );
@@ -427,6 +594,7 @@ impl App {
                     let xyzw = crate::coosys::apply_coo_system(
                         CooSystem::ICRS,
                         self.camera.get_coo_system(),
+                        self.camera.get_icrs_to_horizontal(),
                         &xyzw,
                     );
 
@@ -493,10 +661,49 @@ impl App {
         Ok(())
     }
 
+    /// Add a space-time MOC displayed through its spatial coverage at all times
+    pub(crate) fn add_space_time_moc(
+        &mut self,
+        stmoc: SpaceTimeCoverage,
+        options: MOCOptions,
+    ) -> Result<(), JsValue> {
+        let moc = stmoc.spatial_coverage(&TimeCoverage::from_jd_range(0.0, f64::INFINITY));
+        self.space_time_mocs
+            .insert(options.get_uuid().clone(), stmoc);
+
+        self.add_moc(moc, options)
+    }
+
+    /// Display the sky areas covered by a space-time MOC during the given times
+    pub(crate) fn set_moc_times(
+        &mut self,
+        moc_uuid: &str,
+        times: &TimeCoverage,
+    ) -> Result<(), JsValue> {
+        let moc = self
+            .space_time_mocs
+            .get(moc_uuid)
+            .ok_or_else(|| JsValue::from_str("Space-time MOC not found"))?
+            .spatial_coverage(times);
+
+        self.moc.set_hpx_coverage(moc_uuid, moc);
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
+    /// The first and last times covered by a space-time MOC, in julian days
+    pub(crate) fn get_moc_jd_range(&self, moc_uuid: &str) -> Option<[f64; 2]> {
+        self.space_time_mocs
+            .get(moc_uuid)
+            .and_then(|stmoc| stmoc.jd_range())
+    }
+
     pub(crate) fn remove_moc(&mut self, moc_uuid: &str) -> Result<(), JsValue> {
         self.moc
             .remove(moc_uuid, &mut self.camera, &self.projection)
             .ok_or_else(|| JsValue::from_str("MOC not found"))?;
+        self.space_time_mocs.remove(moc_uuid);
 
         self.request_redraw = true;
 
@@ -549,6 +756,7 @@ impl App {
                 &mut self.tile_fetcher,
             )?;
             self.request_redraw = true;
+            self.auto_cuts_outdated = true;
 
             // Send the ack to the js promise so that she finished
             let ack_img_send = self.ack_img_send.clone();
@@ -601,6 +809,17 @@ impl App {
         for rsc in rscs_received {
             match rsc {
                 Resource::Tile(tile) => {
+                    for sampling in &mut self.tile_samplings {
+                        sampling.add_tile(&tile);
+                    }
+                    for cutout in &mut self.fits_cutouts {
+                        cutout.add_tile(&tile);
+                    }
+                    for pending in &mut self.pending_contours {
+                        pending.tiles.add_tile(&tile);
+                    }
+                    self.auto_cuts_outdated |= self.tile_pixel_samples.add_tile(&tile);
+
                     //if !_has_camera_zoomed {
                     if let Some(hips) = self.layers.get_mut_hips_from_cdid(&tile.get_hips_cdid()) {
                         let cfg = hips.get_config_mut();
@@ -773,6 +992,65 @@ impl App {
             }
         }
 
+        // The tiles whose queries have been dropped will never be received
+        for query in self.tile_fetcher.take_dropped_base_tiles() {
+            for sampling in &mut self.tile_samplings {
+                sampling.drop_tile(&query);
+            }
+            for cutout in &mut self.fits_cutouts {
+                cutout.drop_tile(&query);
+            }
+        }
+
+        // Send the values of the samplings whose tiles have all been received
+        if self.tile_samplings.iter().any(|sampling| sampling.is_complete()) {
+            let (complete, pending): (Vec<_>, Vec<_>) = self
+                .tile_samplings
+                .drain(..)
+                .partition(|sampling| sampling.is_complete());
+            self.tile_samplings = pending;
+
+            for sampling in complete {
+                sampling.resolve()?;
+            }
+        }
+        if self.fits_cutouts.iter().any(|cutout| cutout.is_complete()) {
+            let (complete, pending): (Vec<_>, Vec<_>) = self
+                .fits_cutouts
+                .drain(..)
+                .partition(|cutout| cutout.is_complete());
+            self.fits_cutouts = pending;
+
+            for cutout in complete {
+                cutout.resolve()?;
+            }
+        }
+        if self.pending_contours.iter().any(|pending| pending.is_complete()) {
+            let (complete, pending): (Vec<_>, Vec<_>) = self
+                .pending_contours
+                .drain(..)
+                .partition(|pending| pending.is_complete());
+            self.pending_contours = pending;
+
+            for pending in complete {
+                pending.trace(&mut self.contours)?;
+            }
+            self.request_redraw = true;
+        }
+
+        // Cuts computed from the pixels in view are updated once the view stops moving
+        self.auto_cuts_outdated |= has_camera_moved;
+        if self.auto_cuts_outdated && self.inertia.is_none() && !self.dragging {
+            if self
+                .layers
+                .update_auto_cuts(&self.camera, &self.tile_pixel_samples)
+            {
+                self.request_redraw = true;
+            }
+
+            self.auto_cuts_outdated = false;
+        }
+
         // - there is at least one tile in its blending phase
         let blending_anim_occuring =
             (Time::now() - self.time_start_blending) < BLENDING_ANIM_DURATION;
@@ -791,22 +1069,466 @@ impl App {
 
     pub(crate) fn read_pixel(&self, pos: &Vector2<f64>, layer: &str) -> Result<JsValue, JsValue> {
         if let Some(lonlat) = self.screen_to_world(pos) {
-            if let Some(hips) = self.layers.get_hips_from_layer(layer) {
-                hips.read_pixel(&lonlat, &self.camera)
-            } else if let Some(_image) = self.layers.get_image_from_layer(layer) {
-                Err(JsValue::from_str("TODO: read pixel value"))
+            self.read_pixel_from_lonlat(&lonlat, layer)
+        } else {
+            Err(JsValue::from_str(&"position is out of projection"))
+        }
+    }
+
+    // The position is given in the frame of the view
+    pub(crate) fn read_pixel_from_lonlat(
+        &self,
+        lonlat: &LonLatT<f64>,
+        layer: &str,
+    ) -> Result<JsValue, JsValue> {
+        if let Some(hips) = self.layers.get_hips_from_layer(layer) {
+            hips.read_pixel(lonlat, &self.camera)
+        } else if let Some(images) = self.layers.get_image_from_layer(layer) {
+            for image in images {
+                if let Some(pixel) = image.read_pixel(lonlat, &self.camera)? {
+                    let serializer =
+                        serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+                    return Ok(pixel.serialize(&serializer)?);
+                }
+            }
+
+            Err(JsValue::from_str("position is out of the image"))
+        } else {
+            Err(JsValue::from_str("Survey not found"))
+        }
+    }
+
+    /// Read the values of a layer at several sky positions
+    ///
+    /// # Arguments
+    ///
+    /// * `positions` - The sky positions
+    /// * `frame` - The frame in which the positions are given
+    /// * `layer` - The name of the layer
+    /// * `depth` - The order of the HiPS tiles to sample, the deepest one if not given
+    ///
+    /// Returns a promise resolved once all the tiles needed have been received
+    pub(crate) fn read_pixels(
+        &mut self,
+        positions: &[Vector4<f64>],
+        frame: CooSystem,
+        layer: &str,
+        depth: Option<u8>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        if let Some(hips) = self.layers.get_hips_from_layer(layer) {
+            let cfg = hips.get_config();
+            let depth = depth
+                .unwrap_or(cfg.get_max_depth_tile())
+                .clamp(cfg.get_min_depth_tile(), cfg.get_max_depth_tile());
+
+            let hips_frame = cfg.get_frame();
+            let positions = positions
+                .iter()
+                .map(|pos| {
+                    coosys::apply_coo_system(frame, hips_frame, self.get_icrs_to_horizontal(), pos)
+                        .lonlat()
+                })
+                .collect::<Vec<_>>();
+
+            let mut settle = None;
+            let promise = js_sys::Promise::new(&mut |resolve_fn, reject_fn| {
+                settle = Some((resolve_fn, reject_fn))
+            });
+            let (resolve, reject) =
+                settle.ok_or_else(|| JsValue::from_str("Promise not created"))?;
+
+            let (sampling, queries) = TileSampling::new(hips, &positions, depth, resolve, reject);
+            if sampling.is_complete() {
+                // No position given
+                sampling.resolve()?;
             } else {
-                Err(JsValue::from_str("Survey not found"))
+                for query in queries {
+                    self.tile_fetcher.append_base_tile(query);
+                }
+                self.tile_samplings.push(sampling);
             }
+
+            Ok(promise)
+        } else if self.layers.get_image_from_layer(layer).is_some() {
+            let camera_frame = self.camera.get_coo_system();
+            let values = positions
+                .iter()
+                .map(|pos| {
+                    let lonlat = coosys::apply_coo_system(
+                        frame,
+                        camera_frame,
+                        self.get_icrs_to_horizontal(),
+                        pos,
+                    )
+                    .lonlat();
+
+                    self.read_pixel_from_lonlat(&lonlat, layer)
+                        .unwrap_or(JsValue::NULL)
+                })
+                .collect::<js_sys::Array>();
+
+            Ok(js_sys::Promise::resolve(&JsValue::from(values)))
         } else {
-            Err(JsValue::from_str(&"position is out of projection"))
+            Err(JsValue::from_str("Survey not found"))
+        }
+    }
+
+    /// Reproject a HiPS layer into a FITS image
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the layer
+    /// * `params` - The WCS of the image
+    /// * `depth` - The order of the HiPS tiles to sample, the one whose pixels are as small as
+    ///   the ones of the image if not given
+    ///
+    /// Returns a promise resolved with the bytes of the FITS file
+    pub(crate) fn get_fits_cutout(
+        &mut self,
+        layer: &str,
+        params: &wcs::WCSParams,
+        depth: Option<u8>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let hips = self
+            .layers
+            .get_hips_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("HiPS layer not found"))?;
+        let cfg = hips.get_config();
+        if cfg.is_colored() {
+            return Err(JsValue::from_str("Only the FITS HiPS can be reprojected"));
+        }
+
+        let wcs = ImageWcs::new(params).map_err(|e| JsValue::from_str(&e))?;
+        let (width, height) = wcs.img_dimensions();
+        if width * height > MAX_NUM_CUTOUT_PIXELS {
+            return Err(JsValue::from_str(&format!(
+                "The image has {}x{} pixels, more than the {} allowed",
+                width, height, MAX_NUM_CUTOUT_PIXELS
+            )));
+        }
+
+        let depth = depth
+            .unwrap_or_else(|| {
+                // Tiles whose pixels are at least as small as the ones of the image
+                let pixel_depth = ((std::f64::consts::PI / 3.0).sqrt() / wcs.pixel_size())
+                    .log2()
+                    .ceil()
+                    .max(0.0);
+
+                (pixel_depth as u8).saturating_sub(cfg.delta_depth())
+            })
+            .clamp(cfg.get_min_depth_tile(), cfg.get_max_depth_tile());
+
+        // The centers of the pixels in the frame of the HiPS
+        let (wcs_frame, hips_frame) = (wcs.frame(), cfg.get_frame());
+        let icrs2hor = self.get_icrs_to_horizontal();
+        let positions = wcs
+            .pixel_centers()
+            .map(|pos| {
+                pos.map(|pos| {
+                    coosys::apply_coo_system(wcs_frame, hips_frame, icrs2hor, &pos).lonlat()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut settle = None;
+        let promise = js_sys::Promise::new(&mut |resolve_fn, reject_fn| {
+            settle = Some((resolve_fn, reject_fn))
+        });
+        let (resolve, reject) = settle.ok_or_else(|| JsValue::from_str("Promise not created"))?;
+
+        let (mut cutout, queries) = FITSCutout::new(
+            hips,
+            &positions,
+            width,
+            depth,
+            fits::wcs_cards(params),
+            resolve,
+            reject,
+        );
+
+        // The tiles of a HEALPix map or of a generated HiPS are computed in memory
+        if let Some(source) = self
+            .tile_fetcher
+            .get_in_memory_hips(cfg.get_creator_did())
+        {
+            let size = source.get_tile_size() as usize;
+            for query in &queries {
+                cutout.add_tile_values(&query.cell, &source.get_tile(&query.cell), size);
+            }
+        } else {
+            for query in queries {
+                self.tile_fetcher.append_base_tile(query);
+            }
+        }
+
+        if cutout.is_complete() {
+            cutout.resolve()?;
+        } else {
+            self.fits_cutouts.push(cutout);
+        }
+
+        Ok(promise)
+    }
+
+    // Depth of the HEALPix cells having the size of the pixels of a layer
+    // along with the difference between that depth and the order of the tiles to sample
+    fn sampling_depth(&self, layer: &str, depth: Option<u8>) -> Result<(u8, u8), JsValue> {
+        if let Some(hips) = self.layers.get_hips_from_layer(layer) {
+            let cfg = hips.get_config();
+            let depth = depth
+                .unwrap_or(cfg.get_max_depth_tile())
+                .clamp(cfg.get_min_depth_tile(), cfg.get_max_depth_tile());
+            let delta_depth = (cfg.get_tile_size() as u32).trailing_zeros() as u8;
+
+            Ok(((depth + delta_depth).min(MAX_HPX_DEPTH), delta_depth))
+        } else if let Some(images) = self.layers.get_image_from_layer(layer) {
+            let pixel_scale = images
+                .iter()
+                .map(|image| image.get_pixel_scale())
+                .fold(f64::INFINITY, f64::min);
+
+            // Oversample the image so that every pixel is reached
+            let depth = crate::healpix::utils::MEAN_HPX_CELL_RES
+                .iter()
+                .position(|&res| res <= 0.5 * pixel_scale)
+                .unwrap_or(MAX_HPX_DEPTH as usize) as u8;
+
+            Ok((depth, 0))
+        } else {
+            Err(JsValue::from_str("Survey not found"))
+        }
+    }
+
+    /// Compute the statistics of the pixels of a layer lying in a region
+    ///
+    /// The region is sampled with HEALPix cells having the size of the pixels of the layer.
+    /// For big regions, the resolution is decreased so that at most `MAX_NUM_SAMPLES` pixels are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region
+    /// * `frame` - The frame in which the region is given, MOCs being always in ICRS
+    /// * `layer` - The name of the layer
+    /// * `depth` - The order of the HiPS tiles to sample, the deepest one if not given
+    /// * `num_bins` - The number of bins of the histogram
+    ///
+    /// Returns a promise resolved with the statistics
+    pub(crate) fn get_region_statistics(
+        &mut self,
+        region: Region,
+        frame: CooSystem,
+        layer: &str,
+        depth: Option<u8>,
+        num_bins: usize,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let (mut cell_depth, delta_depth) = self.sampling_depth(layer, depth)?;
+
+        let to_lonlat = |lon: f64, lat: f64| {
+            LonLatT::new(lon.to_radians().to_angle(), lat.to_radians().to_angle())
+        };
+        let (coverage, frame) = match region {
+            Region::Circle { ra, dec, radius } => (
+                HEALPixCoverage::from_cone(&to_lonlat(ra, dec), radius.to_radians(), cell_depth),
+                frame,
+            ),
+            Region::Box {
+                ra,
+                dec,
+                width,
+                height,
+                angle,
+            } => {
+                if width <= 0.0 || height <= 0.0 || width.max(height) > 180.0 {
+                    return Err(JsValue::from_str(
+                        "The sides of the box must be in ]0, 180] degrees",
+                    ));
+                }
+
+                (
+                    HEALPixCoverage::from_box(
+                        &to_lonlat(ra, dec),
+                        width.to_radians(),
+                        height.to_radians(),
+                        angle.to_radians(),
+                        cell_depth,
+                    ),
+                    frame,
+                )
+            }
+            Region::Polygon { vertices } => {
+                if vertices.len() < 3 {
+                    return Err(JsValue::from_str("A polygon must have at least 3 vertices"));
+                }
+
+                let vertices = vertices
+                    .iter()
+                    .map(|&[lon, lat]| to_lonlat(lon, lat))
+                    .collect::<Vec<_>>();
+                (
+                    HEALPixCoverage::from_polygon(&vertices, cell_depth),
+                    frame,
+                )
+            }
+            Region::Moc { uuid } => {
+                let moc = self
+                    .get_moc(&uuid)
+                    .ok_or_else(|| JsValue::from_str("MOC not found"))?;
+
+                (moc.clone(), CooSystem::ICRS)
+            }
+        };
+
+        // Decrease the resolution of the sampling for big regions
+        let num_cells = |depth: u8| {
+            coverage.degraded(depth).range_sum() >> (2 * (MAX_HPX_DEPTH - depth))
+        };
+        while cell_depth > 0 && num_cells(cell_depth) > MAX_NUM_SAMPLES {
+            cell_depth -= 1;
+        }
+
+        let shift = 2 * (MAX_HPX_DEPTH - cell_depth);
+        let positions = coverage
+            .degraded(cell_depth)
+            .moc_ranges()
+            .iter()
+            .flat_map(|range| (range.start >> shift)..(range.end >> shift))
+            .map(|idx| {
+                let (lon, lat) = HEALPixCell(cell_depth, idx).center();
+                LonLatT::new(lon.to_angle(), lat.to_angle()).vector()
+            })
+            .collect::<Vec<_>>();
+
+        let is_image = self.layers.get_image_from_layer(layer).is_some();
+        let tile_depth = cell_depth.saturating_sub(delta_depth);
+        let samples = self.read_pixels(&positions, frame, layer, Some(tile_depth))?;
+
+        Ok(wasm_bindgen_futures::future_to_promise(async move {
+            let samples = js_sys::Array::from(&JsFuture::from(samples).await?);
+
+            let mut values = if is_image {
+                // An image pixel can be sampled several times
+                let mut pixels = HashSet::new();
+                samples
+                    .iter()
+                    .filter_map(|sample| {
+                        let x = js_sys::Reflect::get(&sample, &"x".into()).ok()?.as_f64()?;
+                        let y = js_sys::Reflect::get(&sample, &"y".into()).ok()?.as_f64()?;
+
+                        if pixels.insert((x as u64, y as u64)) {
+                            sample_value(&js_sys::Reflect::get(&sample, &"value".into()).ok()?)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                samples
+                    .iter()
+                    .filter_map(|sample| sample_value(&sample))
+                    .collect::<Vec<_>>()
+            };
+
+            let stats = crate::math::statistics::compute(&mut values, num_bins);
+            Ok(serde_wasm_bindgen::to_value(&stats)?)
+        }))
+    }
+
+    /// Sample the pixels of a layer along a great-circle segment
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The start of the segment
+    /// * `end` - The end of the segment
+    /// * `frame` - The frame in which the segment is given
+    /// * `layer` - The name of the layer
+    /// * `depth` - The order of the HiPS tiles to sample, the deepest one if not given
+    /// * `num_samples` - The number of samples. By default, the segment is sampled at the
+    ///   resolution of the layer
+    ///
+    /// Returns a promise resolved with the profile
+    pub(crate) fn get_line_profile(
+        &mut self,
+        start: &LonLatT<f64>,
+        end: &LonLatT<f64>,
+        frame: CooSystem,
+        layer: &str,
+        depth: Option<u8>,
+        num_samples: Option<usize>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let (cell_depth, delta_depth) = self.sampling_depth(layer, depth)?;
+
+        let start: Vector3<f64> = start.vector();
+        let end: Vector3<f64> = end.vector();
+        if (start + end).magnitude2() < 1e-20 {
+            return Err(JsValue::from_str("The segment must not join antipodal points"));
         }
+
+        let num_samples = num_samples
+            .unwrap_or_else(|| {
+                let length = crate::math::vector::angle3(&start, &end).to_radians();
+                (length / crate::healpix::utils::MEAN_HPX_CELL_RES[cell_depth as usize]) as usize + 1
+            })
+            .clamp(2, MAX_NUM_SAMPLES as usize);
+
+        let samples = crate::math::statistics::sample_great_circle_arc(&start, &end, num_samples);
+        let positions = samples
+            .iter()
+            .map(|(p, _)| p.extend(1.0))
+            .collect::<Vec<_>>();
+
+        let mut lon = Vec::with_capacity(num_samples);
+        let mut lat = Vec::with_capacity(num_samples);
+        for p in &positions {
+            let lonlat = p.lonlat();
+            lon.push(lonlat.lon().to_degrees());
+            lat.push(lonlat.lat().to_degrees());
+        }
+        let dist = samples
+            .iter()
+            .map(|(_, d)| d.to_degrees())
+            .collect::<Vec<_>>();
+
+        let samples = self.read_pixels(
+            &positions,
+            frame,
+            layer,
+            Some(cell_depth.saturating_sub(delta_depth)),
+        )?;
+
+        Ok(wasm_bindgen_futures::future_to_promise(async move {
+            let samples = js_sys::Array::from(&JsFuture::from(samples).await?);
+            let values = samples
+                .iter()
+                .map(|sample| {
+                    if sample.is_object() && !js_sys::Array::is_array(&sample) {
+                        // Image pixel
+                        sample_value(&js_sys::Reflect::get(&sample, &"value".into()).ok()?)
+                    } else {
+                        sample_value(&sample)
+                    }
+                })
+                .collect();
+
+            let profile = LineProfile {
+                lon,
+                lat,
+                dist,
+                values,
+            };
+            let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+            Ok(profile.serialize(&serializer)?)
+        }))
     }
 
     pub(crate) fn draw_grid_labels(&mut self) -> Result<(), JsValue> {
         self.grid.draw_labels()
     }
 
+    pub(crate) fn draw_horizon_labels(&mut self) -> Result<(), JsValue> {
+        self.horizon.draw_labels()
+    }
+
     pub(crate) fn draw(&mut self) -> Result<(), JsValue> {
         /*let scene_redraw = self.rendering | force_render;
         let mut ui = self.ui.lock();
@@ -881,7 +1603,9 @@ impl App {
             let camera = &mut self.camera;
 
             let grid = &mut self.grid;
+            let horizon = &mut self.horizon;
             let moc = &mut self.moc;
+            let contours = &mut self.contours;
             let projection = &self.projection;
 
             let layers = &mut self.layers;
@@ -896,7 +1620,7 @@ impl App {
             // Clear all the screen first (only the region set by the scissor)
             gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
             // set the blending options
-            layers.draw(camera, shaders, colormaps, projection)?;
+            layers.draw(camera, shaders, colormaps, projection, moc)?;
 
             // Draw the catalog
             //let fbo_view = &self.fbo_view;
@@ -909,6 +1633,7 @@ impl App {
                 WebGl2RenderingContext::ONE,
             );*/
             moc.draw(camera, projection, shaders)?;
+            contours.draw(camera, projection, shaders)?;
 
             gl.blend_func_separate(
                 WebGl2RenderingContext::SRC_ALPHA,
@@ -917,6 +1642,7 @@ impl App {
                 WebGl2RenderingContext::ONE,
             );
             grid.draw(camera, projection, shaders)?;
+            horizon.draw(camera, projection, shaders)?;
 
             //        Ok(())
             //    },
@@ -987,6 +1713,157 @@ impl App {
         Ok(())
     }
 
+    pub(crate) fn add_healpix_map(
+        &mut self,
+        cfg: HEALPixMapCfg,
+        map: HEALPixMap,
+    ) -> Result<Range<f32>, JsValue> {
+        let HEALPixMapCfg {
+            layer,
+            creator_did,
+            meta,
+            ..
+        } = cfg;
+
+        self.add_in_memory_hips(layer, creator_did, meta, InMemoryHiPS::Map(map))
+    }
+
+    /// Generate a HiPS from a FITS image layer and add it as a new layer
+    pub(crate) fn add_generated_hips(
+        &mut self,
+        cfg: GeneratedHiPSCfg,
+    ) -> Result<Range<f32>, JsValue> {
+        let GeneratedHiPSCfg {
+            layer,
+            creator_did,
+            name,
+            image,
+            max_order,
+            meta,
+        } = cfg;
+
+        let images = self
+            .layers
+            .get_image_from_layer(&image)
+            .ok_or_else(|| JsValue::from_str(&format!("{} is not a FITS image layer", image)))?;
+        // Multi-extension FITS files are converted from their first image
+        let hips = images[0].to_hips(max_order, creator_did.clone(), name)?;
+
+        self.add_in_memory_hips(layer, creator_did, meta, InMemoryHiPS::Generated(hips))
+    }
+
+    /// The paths of the files of a generated HiPS, relative to its root
+    pub(crate) fn get_generated_hips_file_paths(
+        &self,
+        creator_did: &str,
+    ) -> Result<Vec<String>, JsValue> {
+        match self.tile_fetcher.get_in_memory_hips(creator_did) {
+            Some(InMemoryHiPS::Generated(hips)) => Ok(hips.get_file_paths()),
+            _ => Err(JsValue::from_str(&format!("{} is not a generated HiPS", creator_did))),
+        }
+    }
+
+    /// The content of a file of a generated HiPS
+    pub(crate) fn get_generated_hips_file(
+        &self,
+        creator_did: &str,
+        path: &str,
+    ) -> Result<Vec<u8>, JsValue> {
+        match self.tile_fetcher.get_in_memory_hips(creator_did) {
+            Some(InMemoryHiPS::Generated(hips)) => hips.get_file(path),
+            _ => Err(JsValue::from_str(&format!("{} is not a generated HiPS", creator_did))),
+        }
+    }
+
+    // Add a HiPS whose tiles are computed in memory
+    //
+    // Returns the cuts of its values
+    fn add_in_memory_hips(
+        &mut self,
+        layer: String,
+        creator_did: String,
+        meta: ImageMetadata,
+        source: InMemoryHiPS,
+    ) -> Result<Range<f32>, JsValue> {
+        let properties = HiPSProperties::new_in_memory(
+            creator_did.clone(),
+            source.get_frame(),
+            source.get_max_order(),
+            source.get_tile_size(),
+            source.get_sky_fraction(),
+        );
+
+        self.layers.add_hips(
+            &self.gl,
+            HiPSCfg {
+                layer,
+                properties,
+                meta,
+            },
+            &mut self.camera,
+            &self.projection,
+            &mut self.tile_fetcher,
+        )?;
+
+        if let Some(HiPS::D2(hips)) = self.layers.get_mut_hips_from_cdid(&creator_did) {
+            // The values are stored as they are
+            hips.get_config_mut().set_fits_metadata(1.0, 0.0, f32::NAN);
+
+            // The base tiles are always in the buffer
+            let delta_depth = hips.get_config().delta_depth();
+            for texture_cell in crate::healpix::cell::ALLSKY_HPX_CELLS_D0 {
+                for cell in texture_cell.get_tile_cells(delta_depth) {
+                    add_in_memory_tile(hips, &source, &cell, &mut self.tile_pixel_samples)?;
+                }
+            }
+        }
+
+        let cuts = source.get_cuts();
+        self.tile_fetcher.insert_in_memory_hips(creator_did, source);
+
+        // Once its added, generate the tiles in the view
+        self.request_for_new_tiles = true;
+        self.request_redraw = true;
+
+        Ok(cuts)
+    }
+
+    pub(crate) fn add_rgb_composite(&mut self, cfg: RGBCompositeCfg) -> Result<(), JsValue> {
+        self.layers.add_rgb_composite(
+            cfg,
+            &mut self.camera,
+            &self.projection,
+            &mut self.tile_fetcher,
+        )?;
+
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
+    pub(crate) fn add_layer_arithmetic(&mut self, cfg: LayerArithmeticCfg) -> Result<(), JsValue> {
+        self.layers.add_layer_arithmetic(
+            cfg,
+            &mut self.camera,
+            &self.projection,
+            &mut self.tile_fetcher,
+        )?;
+
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_layer_comparison(
+        &mut self,
+        cfg: Option<ComparisonCfg>,
+    ) -> Result<(), JsValue> {
+        self.layers.set_comparison(cfg)?;
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
     pub(crate) fn add_image_from_blob_and_wcs(
         &mut self,
         layer: String,
@@ -1004,7 +1881,6 @@ impl App {
         let disable_inertia = self.disable_inertia.clone();
         *(disable_inertia.borrow_mut()) = true;
 
-        let camera_coo_sys = self.camera.get_coo_system();
 
         let fut = async move {
             use crate::renderable::image::Image;
@@ -1030,16 +1906,8 @@ impl App {
                 ),
             };
             use al_core::image::format::RGBA8U;
-            match Image::from_reader_and_wcs::<_, RGBA8U>(
-                &gl,
-                bytes_reader,
-                wcs,
-                None,
-                None,
-                None,
-                camera_coo_sys,
-            )
-            .await
+            match Image::from_reader_and_wcs::<_, RGBA8U>(&gl, bytes_reader, wcs, None, None, None)
+                .await
             {
                 Ok(image) => {
                     let img = ImageLayer {
@@ -1096,7 +1964,6 @@ impl App {
         let disable_inertia = self.disable_inertia.clone();
         *(disable_inertia.borrow_mut()) = true;
 
-        let camera_coo_sys = self.camera.get_coo_system();
 
         let fut = async move {
             use crate::renderable::image::Image;
@@ -1132,7 +1999,7 @@ impl App {
             let mut hdu_ext_idx = 0;
             let mut images = vec![];
 
-            match Image::from_fits_hdu_async(&gl, &mut hdu.0, camera_coo_sys).await {
+            match Image::from_fits_hdu_async(&gl, &mut hdu.0).await {
                 Ok(image) => {
                     images.push(image);
 
@@ -1142,9 +2009,7 @@ impl App {
                     while let Ok(Some(mut xhdu)) = hdu_ext {
                         match &mut xhdu {
                             AsyncXtensionHDU::Image(xhdu_img) => {
-                                match Image::from_fits_hdu_async(&gl, xhdu_img, camera_coo_sys)
-                                    .await
-                                {
+                                match Image::from_fits_hdu_async(&gl, xhdu_img).await {
                                     Ok(image) => {
                                         images.push(image);
                                     }
@@ -1177,9 +2042,7 @@ impl App {
                     while let Ok(Some(mut xhdu)) = hdu_ext {
                         match &mut xhdu {
                             AsyncXtensionHDU::Image(xhdu_img) => {
-                                match Image::from_fits_hdu_async(&gl, xhdu_img, camera_coo_sys)
-                                    .await
-                                {
+                                match Image::from_fits_hdu_async(&gl, xhdu_img).await {
                                     Ok(image) => {
                                         images.push(image);
                                     }
@@ -1279,6 +2142,7 @@ impl App {
         let new_img_ext = meta.img_format;
         self.layers
             .set_layer_cfg(layer.clone(), meta)?;
+        self.auto_cuts_outdated = true;
 
         if old_meta.img_format != new_img_ext {
             // The image format has been changed
@@ -1412,6 +2276,171 @@ impl App {
         Ok(())
     }
 
+    /// Trace the contours of a layer
+    ///
+    /// The contours of an image are traced on its pixels whereas the ones of a HiPS are traced
+    /// on its tiles in the view, fetched if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the layer
+    /// * `uuid` - The identifier of the contours, replacing the ones having the same
+    /// * `levels` - How the levels are chosen
+    /// * `style` - How the contours are drawn
+    /// * `depth` - The order of the HiPS tiles, the one of the tiles in the view if not given
+    ///
+    /// Returns a promise resolved with the levels once the contours are traced
+    pub(crate) fn add_contours(
+        &mut self,
+        layer: &str,
+        uuid: String,
+        levels: ContourLevels,
+        style: ContourStyle,
+        depth: Option<u8>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        if let Some(images) = self.layers.get_image_from_layer(layer) {
+            let grids = images
+                .iter()
+                .map(|image| image.get_contour_grid())
+                .collect::<Result<Vec<_>, _>>()?;
+            let (levels, lines) = contour::trace(&grids, &levels);
+
+            self.contours.push(uuid, lines, style);
+            self.request_redraw = true;
+
+            Ok(js_sys::Promise::resolve(&serde_wasm_bindgen::to_value(
+                &levels,
+            )?))
+        } else if let Some(hips) = self.layers.get_hips_from_layer(layer) {
+            let cfg = hips.get_config();
+            if cfg.is_colored() {
+                return Err(JsValue::from_str(
+                    "Contours can only be traced on the FITS HiPS",
+                ));
+            }
+
+            let (min_depth, max_depth) = (cfg.get_min_depth_tile(), cfg.get_max_depth_tile());
+            let mut depth = depth
+                .unwrap_or(self.camera.get_texture_depth() + cfg.delta_depth())
+                .clamp(min_depth, max_depth);
+            let mut cells = self.camera.get_hpx_cells(depth, cfg.get_frame());
+            while cells.len() > MAX_NUM_CONTOUR_TILES && depth > min_depth {
+                depth -= 1;
+                cells = self.camera.get_hpx_cells(depth, cfg.get_frame());
+            }
+
+            let mut resolve = None;
+            let promise = js_sys::Promise::new(&mut |resolve_fn, _| resolve = Some(resolve_fn));
+            let resolve = resolve.ok_or_else(|| JsValue::from_str("Promise not created"))?;
+
+            let (tiles, queries) = TileValues::new(hips, &cells);
+            let mut pending = PendingContours {
+                tiles,
+                uuid,
+                levels,
+                style,
+                frame: cfg.get_frame(),
+                resolve,
+            };
+
+            // The tiles of a HEALPix map or of a generated HiPS are computed in memory
+            if let Some(source) = self
+                .tile_fetcher
+                .get_in_memory_hips(cfg.get_creator_did())
+            {
+                for query in &queries {
+                    pending
+                        .tiles
+                        .add_tile_values(&query.cell, source.get_tile(&query.cell));
+                }
+            } else {
+                for query in queries {
+                    self.tile_fetcher.append_base_tile(query);
+                }
+            }
+
+            if pending.is_complete() {
+                pending.trace(&mut self.contours)?;
+                self.request_redraw = true;
+            } else {
+                self.pending_contours.push(pending);
+            }
+
+            Ok(promise)
+        } else {
+            Err(JsValue::from_str("Layer not found"))
+        }
+    }
+
+    pub(crate) fn remove_contours(&mut self, uuid: &str) -> Result<(), JsValue> {
+        let num_pending = self.pending_contours.len();
+        self.pending_contours.retain(|pending| pending.uuid != uuid);
+
+        if self.contours.remove(uuid).is_none() && num_pending == self.pending_contours.len() {
+            return Err(JsValue::from_str("Contours not found"));
+        }
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_contours_style(
+        &mut self,
+        uuid: &str,
+        style: ContourStyle,
+    ) -> Result<(), JsValue> {
+        if let Some(pending) = self.pending_contours.iter_mut().find(|p| p.uuid == uuid) {
+            pending.style = style;
+        } else {
+            self.contours
+                .set_style(uuid, style)
+                .ok_or_else(|| JsValue::from_str("Contours not found"))?;
+            self.request_redraw = true;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn set_horizon_cfg(&mut self, cfg: HorizonCfg) -> Result<(), JsValue> {
+        self.horizon.set_cfg(cfg)?;
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
+    /// Set the observer and the UTC date (in ms since the Unix epoch) defining the horizontal frame
+    pub(crate) fn set_observer(&mut self, observer: Observer, utc_ms: f64) {
+        self.observer = Some(observer);
+        self.horizon.set_observer(observer);
+
+        self.set_icrs_to_horizontal(observer.icrs_to_horizontal(utc_ms));
+    }
+
+    /// Set the UTC date (in ms since the Unix epoch) of the horizontal frame
+    ///
+    /// The sky turns in the horizontal frame so the date must be updated for the
+    /// frame, the horizon and the airmass lines to follow the time.
+    pub(crate) fn set_observer_time(&mut self, utc_ms: f64) -> Result<(), JsValue> {
+        let observer = self
+            .observer
+            .ok_or_else(|| JsValue::from_str("No observer has been set"))?;
+
+        self.set_icrs_to_horizontal(observer.icrs_to_horizontal(utc_ms));
+
+        Ok(())
+    }
+
+    fn set_icrs_to_horizontal(&mut self, icrs2hor: Matrix4<f64>) {
+        self.camera
+            .set_icrs_to_horizontal(icrs2hor, &self.projection);
+
+        if self.camera.get_coo_system() == CooSystem::HORIZONTAL {
+            // The sky has moved in the view
+            self.request_for_new_tiles = true;
+        }
+        self.request_redraw = true;
+    }
+
     pub(crate) fn set_coo_system(&mut self, coo_system: CooSystem) {
         self.camera.set_coo_system(coo_system, &self.projection);
         self.request_for_new_tiles = true;
@@ -1443,18 +2472,40 @@ impl App {
         self.camera.get_coo_system()
     }
 
+    /// Rotation from ICRS to the horizontal frame of the observer
+    pub(crate) fn get_icrs_to_horizontal(&self) -> &Matrix4<f64> {
+        self.camera.get_icrs_to_horizontal()
+    }
+
     pub(crate) fn view_to_icrs_coosys(&self, lonlat: &LonLatT<f64>) -> LonLatT<f64> {
         let celestial_pos: Vector4<_> = lonlat.vector();
         let view_system = self.camera.get_coo_system();
         let (ra, dec) = math::lonlat::xyzw_to_radec(&coosys::apply_coo_system(
             view_system,
             CooSystem::ICRS,
+            self.get_icrs_to_horizontal(),
             &celestial_pos,
         ));
 
         LonLatT::new(ra, dec)
     }
 
+    pub(crate) fn convert_coosys(
+        &self,
+        lonlat: &LonLatT<f64>,
+        from: CooSystem,
+        to: CooSystem,
+    ) -> LonLatT<f64> {
+        let (lon, lat) = math::lonlat::xyzw_to_radec(&coosys::apply_coo_system(
+            from,
+            to,
+            self.get_icrs_to_horizontal(),
+            &lonlat.vector(),
+        ));
+
+        LonLatT::new(lon, lat)
+    }
+
     /// lonlat must be given in icrs frame
     pub(crate) fn set_center(&mut self, lonlat: &LonLatT<f64>) {
         self.prev_cam_position = self.camera.get_center().truncate();
@@ -1625,10 +2676,19 @@ impl App {
         self.camera.get_texture_depth() as i32
     }
 
+    /// Default depth of the MOCs built in the view, about the resolution of the screen pixels
+    pub(crate) fn get_moc_depth(&self) -> u8 {
+        (self.camera.get_texture_depth() + 9 - 1).min(MAX_HPX_DEPTH)
+    }
+
     pub(crate) fn get_clip_zoom_factor(&self) -> f64 {
         self.camera.get_clip_zoom_factor()
     }
 
+    pub(crate) fn get_view_wcs(&self) -> Result<wcs::WCSParams, JsValue> {
+        self.camera.get_wcs(&self.projection)
+    }
+
     pub(crate) fn get_fov(&self) -> f64 {
         let deg: ArcDeg<f64> = self.camera.get_aperture().into();
         deg.0
@@ -1646,3 +2706,24 @@ impl App {
         self.rendering
     }
 }
+
+// Numeric value of a pixel sampled by `App::read_pixels`, the mean of the
+// red, green and blue components being taken for color pixels
+fn sample_value(sample: &JsValue) -> Option<f64> {
+    if let Some(value) = sample.as_f64() {
+        Some(value)
+    } else if js_sys::Array::is_array(sample) {
+        let components = js_sys::Array::from(sample);
+        let num_components = components.length().min(3);
+        if num_components == 0 {
+            return None;
+        }
+
+        let sum = (0..num_components)
+            .filter_map(|i| components.get(i).as_f64())
+            .sum::<f64>();
+        Some(sum / (num_components as f64))
+    } else {
+        None
+    }
+}
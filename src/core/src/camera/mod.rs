@@ -6,16 +6,20 @@ pub use viewport::CameraViewPort;
 pub mod fov;
 pub use fov::FieldOfView;
 pub mod view_hpx_cells;
+pub mod wcs;
 
+use crate::healpix::cell::HEALPixCell;
 use crate::CooSystem;
 use crate::HEALPixCoverage;
 use crate::ProjectionType;
+use cgmath::Matrix4;
 
 pub fn build_fov_coverage(
     depth: u8,
     fov: &FieldOfView,
     camera_center: &XYZWModel<f64>,
     camera_frame: CooSystem,
+    icrs2hor: &Matrix4<f64>,
     frame: CooSystem,
     proj: &ProjectionType,
 ) -> HEALPixCoverage {
@@ -24,7 +28,7 @@ pub fn build_fov_coverage(
         // but cdshealpix accepts them to be given in ICRS coo sys
         let vertices_iter = vertices
             .iter()
-            .map(|v| crate::coosys::apply_coo_system(camera_frame, frame, v));
+            .map(|v| crate::coosys::apply_coo_system(camera_frame, frame, icrs2hor, v));
 
         // Check if the polygon is too small with respect to the angular size
         // of a cell at depth order
@@ -47,7 +51,8 @@ pub fn build_fov_coverage(
             HEALPixCoverage::from_fixed_hpx_cells(depth, hpx_idxs_iter, Some(vertices.len()))
         } else {
             // The polygon is not too small for the depth asked
-            let inside_vertex = crate::coosys::apply_coo_system(camera_frame, frame, camera_center);
+            let inside_vertex =
+                crate::coosys::apply_coo_system(camera_frame, frame, icrs2hor, camera_center);
 
             // Prefer to query from_polygon with depth >= 2
             let moc = HEALPixCoverage::from_3d_coos(depth, vertices_iter, &inside_vertex);
@@ -55,10 +60,116 @@ pub fn build_fov_coverage(
             moc
         }
     } else {
-        let center_xyzw = crate::coosys::apply_coo_system(camera_frame, frame, camera_center);
+        let center_xyzw =
+            crate::coosys::apply_coo_system(camera_frame, frame, icrs2hor, camera_center);
 
         let biggest_fov_rad = proj.aperture_start().to_radians();
         let lonlat = center_xyzw.lonlat();
         HEALPixCoverage::from_cone(&lonlat, biggest_fov_rad * 0.5, depth)
     }
 }
+
+/// Restrict a coverage to the cells having their center or one of their vertices in the view
+///
+/// The cells are tested at `clipping_depth` at most, `in_view` telling whether a position
+/// (lon, lat) in radians, given in the frame of the coverage, lands inside the viewport.
+pub fn clip_coverage_to_view(
+    coverage: &HEALPixCoverage,
+    clipping_depth: u8,
+    in_view: impl Fn((f64, f64)) -> bool,
+) -> HEALPixCoverage {
+    let clipping_depth = coverage.depth().min(clipping_depth);
+    let visible_cells = coverage
+        .degraded(clipping_depth)
+        .flatten_to_fixed_depth_cells()
+        .filter(|&idx| {
+            let cell = HEALPixCell(clipping_depth, idx);
+            in_view(cell.center()) || cell.vertices().iter().any(|&v| in_view(v))
+        })
+        .collect::<Vec<_>>();
+    let visible =
+        HEALPixCoverage::from_fixed_hpx_cells(clipping_depth, visible_cells.into_iter(), None);
+
+    coverage.intersection(&visible)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::lonlat::radec_to_xyzw;
+    use crate::math::projection::Projection;
+    use crate::math::{angle::ToAngle, rotation::Rotation};
+    use cgmath::{InnerSpace, SquareMatrix, Vector2};
+
+    #[test]
+    fn view_coverage_inside_the_projection_domain() {
+        // A SIN view twice as wide as high, going beyond the projection domain
+        // horizontally but not vertically
+        let proj = ProjectionType::Sin(mapproj::zenithal::sin::Sin);
+        let ndc_to_clip = Vector2::new(1.0, 0.5);
+        let clip_zoom_factor = 1.6;
+        let center = radec_to_xyzw(
+            30_f64.to_radians().to_angle(),
+            40_f64.to_radians().to_angle(),
+        );
+        let w2m: Matrix4<f64> = (&Rotation::from_sky_position(&center)).into();
+        let m2w = w2m.invert().unwrap();
+
+        let fov = FieldOfView::new(&ndc_to_clip, clip_zoom_factor, &w2m, &proj);
+        assert!(fov.get_vertices().is_none());
+
+        let depth = 7;
+        let coverage = build_fov_coverage(
+            depth,
+            &fov,
+            &center,
+            CooSystem::ICRS,
+            &Matrix4::identity(),
+            CooSystem::ICRS,
+            &proj,
+        );
+        let in_view = |(lon, lat): (f64, f64)| {
+            let model = radec_to_xyzw(lon.to_angle(), lat.to_angle());
+            proj.world_to_clip_space(&(m2w * model))
+                .map(|clip| {
+                    (clip.x / (ndc_to_clip.x * clip_zoom_factor)).abs() <= 1.0
+                        && (clip.y / (ndc_to_clip.y * clip_zoom_factor)).abs() <= 1.0
+                })
+                .unwrap_or(false)
+        };
+        let view = clip_coverage_to_view(&coverage, 6, in_view);
+        assert_eq!(view.depth(), depth);
+
+        // The position seen at a position of the screen given in clip space
+        let seen = |x: f64, y: f64| {
+            let world = proj.clip_to_world_space(&Vector2::new(x, y)).unwrap();
+            let model = w2m * world;
+            assert!(coverage.contains_coo(&model));
+
+            view.contains_coo(&model)
+        };
+        // Inside the viewport and the projection domain
+        assert!(seen(0.0, 0.0));
+        assert!(seen(0.97, 0.0));
+        assert!(seen(-0.97, 0.0));
+        assert!(seen(0.0, 0.78));
+        assert!(seen(0.5, -0.78));
+        // Inside the projection domain but above or below the viewport
+        assert!(!seen(0.0, 0.95));
+        assert!(!seen(0.0, -0.95));
+        assert!(!seen(0.3, 0.92));
+
+        // The hidden hemisphere of SIN cannot be seen
+        for idx in view.flatten_to_fixed_depth_cells() {
+            let (lon, lat) = HEALPixCell(depth, idx).center();
+            let pos = radec_to_xyzw(lon.to_angle(), lat.to_angle());
+            assert!(pos.dot(center) > -0.05);
+        }
+        let antipode = cgmath::Vector4::new(-center.x, -center.y, -center.z, 1.0);
+        assert!(!view.contains_coo(&antipode));
+        // The area of a slice of the sphere is proportional to its height, the view
+        // being the visible hemisphere between the heights -0.8 and 0.8
+        assert!((coverage.sky_fraction() - 0.5).abs() < 0.01);
+        assert!((view.sky_fraction() - 0.4).abs() < 0.01);
+    }
+}
@@ -18,6 +18,11 @@ impl ViewHpxCells {
         let hpx_cells = [
             HpxCells::new(CooSystem::ICRS),
             HpxCells::new(CooSystem::GAL),
+            HpxCells::new(CooSystem::FK5J2000),
+            HpxCells::new(CooSystem::FK4B1950),
+            HpxCells::new(CooSystem::ECLJ2000),
+            HpxCells::new(CooSystem::SUPERGAL),
+            HpxCells::new(CooSystem::HORIZONTAL),
         ];
 
         Self {
@@ -26,42 +31,34 @@ impl ViewHpxCells {
         }
     }
 
+    /// Register a frame in which the cells in the view are needed
+    ///
+    /// Returns true if the frame was not registered yet, i.e. its cells must be computed
     pub(super) fn register_frame(
         &mut self,
-        camera_depth: u8,
-        fov: &FieldOfView,
-        center: &XYZWModel<f64>,
-        camera_frame: CooSystem,
-        proj: &ProjectionType,
         // survey frame
         frame: CooSystem,
-    ) {
+    ) -> bool {
         self.reg_frames[frame as usize] += 1;
 
-        if self.reg_frames[frame as usize] == 1 {
-            // a new frame has been added
-            self.update(camera_depth, fov, center, camera_frame, proj);
-        }
+        // a new frame has been added
+        self.reg_frames[frame as usize] == 1
     }
 
+    /// Unregister a frame
+    ///
+    /// Returns true if the frame is not needed anymore
     pub(super) fn unregister_frame(
         &mut self,
-        camera_depth: u8,
-        fov: &FieldOfView,
-        center: &XYZWModel<f64>,
-        camera_frame: CooSystem,
-        proj: &ProjectionType,
         // survey frame
         frame: CooSystem,
-    ) {
+    ) -> bool {
         if self.reg_frames[frame as usize] > 0 {
             self.reg_frames[frame as usize] -= 1;
         }
 
-        if self.reg_frames[frame as usize] == 0 {
-            // a frame has been deleted
-            self.update(camera_depth, fov, center, camera_frame, proj);
-        }
+        // a frame has been deleted
+        self.reg_frames[frame as usize] == 0
     }
 
     pub(super) fn update(
@@ -70,12 +67,20 @@ impl ViewHpxCells {
         fov: &FieldOfView,
         center: &XYZWModel<f64>,
         camera_frame: CooSystem,
+        icrs2hor: &Matrix4<f64>,
         proj: &ProjectionType,
     ) {
         for (frame, num_req) in self.reg_frames.iter().enumerate() {
             // if there are surveys/camera requesting the coverage
             if *num_req > 0 {
-                self.hpx_cells[frame].update(camera_depth, fov, center, camera_frame, proj);
+                self.hpx_cells[frame].update(
+                    camera_depth,
+                    fov,
+                    center,
+                    camera_frame,
+                    icrs2hor,
+                    proj,
+                );
             }
         }
     }
@@ -121,6 +126,7 @@ impl Default for HpxCells {
 }
 
 use al_api::coo_system::{CooSystem, NUM_COOSYSTEM};
+use cgmath::Matrix4;
 use moclib::moc::RangeMOCIntoIterator;
 
 use super::FieldOfView;
@@ -151,11 +157,19 @@ impl HpxCells {
         fov: &FieldOfView,
         center: &XYZWModel<f64>,
         camera_frame: CooSystem,
+        icrs2hor: &Matrix4<f64>,
         proj: &ProjectionType,
     ) {
         // Compute the new coverage for that frame
-        self.cov =
-            super::build_fov_coverage(camera_depth, fov, center, camera_frame, self.frame, proj);
+        self.cov = super::build_fov_coverage(
+            camera_depth,
+            fov,
+            center,
+            camera_frame,
+            icrs2hor,
+            self.frame,
+            proj,
+        );
 
         // Clear the old cells
         /*let r = self.idx_rng[camera_depth as usize]
@@ -70,6 +70,8 @@ pub struct CameraViewPort {
     // A reference to the WebGL2 context
     gl: WebGlContext,
     coo_sys: CooSystem,
+    // Rotation from ICRS to the horizontal frame of the observer
+    icrs2hor: Matrix4<f64>,
     reversed_longitude: bool,
 }
 use al_api::coo_system::CooSystem;
@@ -175,31 +177,37 @@ impl CameraViewPort {
             gl,
             // coo system
             coo_sys,
+            // no observer has been set yet
+            icrs2hor: Matrix4::identity(),
             // a flag telling if the viewport has a reversed longitude axis
             reversed_longitude,
         }
     }
 
     pub fn register_view_frame(&mut self, frame: CooSystem, proj: &ProjectionType) {
-        self.view_hpx_cells.register_frame(
-            self.texture_depth,
-            &self.fov,
-            &self.center,
-            self.coo_sys,
-            proj,
-            frame,
-        );
+        if self.view_hpx_cells.register_frame(frame) {
+            self.view_hpx_cells.update(
+                self.texture_depth,
+                &self.fov,
+                &self.center,
+                self.coo_sys,
+                &self.icrs2hor,
+                proj,
+            );
+        }
     }
 
     pub fn unregister_view_frame(&mut self, frame: CooSystem, proj: &ProjectionType) {
-        self.view_hpx_cells.unregister_frame(
-            self.texture_depth,
-            &self.fov,
-            &self.center,
-            self.coo_sys,
-            proj,
-            frame,
-        );
+        if self.view_hpx_cells.unregister_frame(frame) {
+            self.view_hpx_cells.update(
+                self.texture_depth,
+                &self.fov,
+                &self.center,
+                self.coo_sys,
+                &self.icrs2hor,
+                proj,
+            );
+        }
     }
 
     /*pub fn has_new_hpx_cells(&mut self) -> bool {
@@ -230,6 +238,22 @@ impl CameraViewPort {
             ProjectionType::Ait(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
             ProjectionType::Mol(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
             ProjectionType::Zea(_) => self.aperture >= 140.0_f64.to_radians().to_angle(),
+            ProjectionType::Arc(_) => self.aperture >= 140.0_f64.to_radians().to_angle(),
+            ProjectionType::Zpn(_) => self.aperture >= 140.0_f64.to_radians().to_angle(),
+            ProjectionType::Air(_) => self.aperture >= 200.0_f64.to_radians().to_angle(),
+            ProjectionType::Azp(azp) => {
+                if azp.is_bounded() {
+                    self.aperture >= 140.0_f64.to_radians().to_angle()
+                } else {
+                    self.aperture >= 100.0_f64.to_radians().to_angle()
+                }
+            }
+            ProjectionType::Car(_) => self.aperture >= 120.0_f64.to_radians().to_angle(),
+            ProjectionType::Cea(_) => self.aperture >= 120.0_f64.to_radians().to_angle(),
+            ProjectionType::Sfl(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
+            ProjectionType::Par(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
+            ProjectionType::Cod(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
+            ProjectionType::Hpx(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
         }
     }
 
@@ -270,6 +294,13 @@ impl CameraViewPort {
         );
     }
 
+    /// Restore the viewport and the scissor of the screen, e.g. after rendering into a framebuffer
+    pub fn reset_viewport(&self) {
+        self.gl
+            .viewport(0, 0, self.width as i32, self.height as i32);
+        self.recompute_scissor();
+    }
+
     pub fn set_screen_size(&mut self, width: f32, height: f32, projection: &ProjectionType) {
         self.width = (width as f32) * self.dpi;
         self.height = (height as f32) * self.dpi;
@@ -340,12 +371,13 @@ impl CameraViewPort {
         let can_unzoom_more = match proj {
             ProjectionType::Tan(_)
             | ProjectionType::Mer(_)
-            //| ProjectionType::Air(_)
-            | ProjectionType::Stg(_) => false,
-            //| ProjectionType::Car(_)
-            //| ProjectionType::Cea(_)
+            | ProjectionType::Air(_)
+            | ProjectionType::Stg(_)
+            | ProjectionType::Car(_)
+            | ProjectionType::Cea(_)
             //| ProjectionType::Cyp(_)
-            //| ProjectionType::Hpx(_) => false,
+            | ProjectionType::Hpx(_) => false,
+            ProjectionType::Azp(azp) => azp.is_bounded(),
             _ => true,
         };
 
@@ -417,6 +449,7 @@ impl CameraViewPort {
             &self.fov,
             &self.center,
             self.get_coo_system(),
+            &self.icrs2hor,
             proj,
         );
     }
@@ -488,7 +521,7 @@ impl CameraViewPort {
     pub fn set_center(&mut self, lonlat: &LonLatT<f64>, proj: &ProjectionType) {
         let icrs_pos: Vector4<_> = lonlat.vector();
 
-        let view_pos = CooSystem::ICRS.to(self.get_coo_system()) * icrs_pos;
+        let view_pos = CooSystem::ICRS.to(self.get_coo_system(), &self.icrs2hor) * icrs_pos;
         let rot_to_center = Rotation::from_sky_position(&view_pos);
 
         let phi = self.get_center_pos_angle();
@@ -519,13 +552,42 @@ impl CameraViewPort {
         &self.fov
     }
 
+    /// Set the rotation from ICRS to the horizontal frame, i.e. of a new observer or date
+    ///
+    /// A view in the horizontal frame keeps its altitude and azimuth, the sky turning in it.
+    pub fn set_icrs_to_horizontal(&mut self, icrs2hor: Matrix4<f64>, proj: &ProjectionType) {
+        self.icrs2hor = icrs2hor;
+
+        if self.coo_sys == CooSystem::HORIZONTAL {
+            self.set_coo_system(CooSystem::HORIZONTAL, proj);
+        }
+    }
+
+    /// Rotation from ICRS to the horizontal frame of the observer
+    ///
+    /// This is the identity as long as no observer has been set.
+    pub fn get_icrs_to_horizontal(&self) -> &Matrix4<f64> {
+        &self.icrs2hor
+    }
+
+    /// Change the frame of the view
+    ///
+    /// Setting the current frame again keeps the view as it is but refreshes
+    /// the sky it covers. This is needed when the horizontal frame changes, i.e.
+    /// with the date or the location of the observer.
     pub fn set_coo_system(&mut self, new_coo_sys: CooSystem, proj: &ProjectionType) {
-        // Compute the center position according to the new coordinate frame system
-        let new_center = coosys::apply_coo_system(self.coo_sys, new_coo_sys, &self.center);
-        // Create a rotation object from that position
-        let new_rotation = Rotation::from_sky_position(&new_center);
-        // Apply it to the center of the view
-        self.set_rotation(&new_rotation, proj);
+        if self.coo_sys != new_coo_sys {
+            // Compute the center position according to the new coordinate frame system
+            let new_center =
+                coosys::apply_coo_system(self.coo_sys, new_coo_sys, &self.icrs2hor, &self.center);
+            // Create a rotation object from that position
+            let new_rotation = Rotation::from_sky_position(&new_center);
+            // Apply it to the center of the view
+            self.set_rotation(&new_rotation, proj);
+        } else {
+            self.moved = true;
+            self.time_last_move = Time::now();
+        }
 
         // unregister the coo sys
         //self.view_hpx_cells.unregister_frame(self.coo_sys);
@@ -537,6 +599,7 @@ impl CameraViewPort {
             &self.fov,
             &self.center,
             new_coo_sys,
+            &self.icrs2hor,
             proj,
         );
 
@@ -677,6 +740,7 @@ impl CameraViewPort {
             &self.fov,
             &self.center,
             self.get_coo_system(),
+            &self.icrs2hor,
             proj,
         );
     }
@@ -0,0 +1,1055 @@
+//! FITS World Coordinate System of the view
+//!
+//! The pixels of the WCS are the ones of the canvas following the FITS convention,
+//! i.e. the center of the bottom left pixel of the canvas is located at (1, 1).
+//!
+//! The celestial WCS of FITS headers are read back with the projections of mapproj
+//! following the WCS paper II (Calabretta & Greisen 2002), see [`ImageWcs`].
+use al_api::coo_system::CooSystem;
+use cgmath::{Matrix, Matrix2, Matrix4, Rad, SquareMatrix, Vector2, Vector4};
+use mapproj::{CanonicalProjection, ProjXY, XYZ};
+use serde_json::json;
+use wasm_bindgen::JsValue;
+use wcs::WCSParams;
+
+use super::CameraViewPort;
+use crate::math::angle::ToAngle;
+use crate::math::lonlat::{radec_to_xyzw, LonLat};
+use crate::math::projection::proj_plane_half_size;
+use crate::math::{HALF_PI, PI, TWICE_PI};
+use crate::ProjectionType;
+
+// Position of the fiducial point, i.e. the center of the view, in the native frame
+enum Native {
+    // At the native pole
+    Pole,
+    // On the native equator
+    Equator,
+    // At the native latitude theta_a (conic projections)
+    Parallel(f64),
+}
+
+impl Native {
+    // Native latitude of the fiducial point
+    fn theta_0(&self) -> f64 {
+        match self {
+            Native::Pole => HALF_PI,
+            Native::Equator => 0.0,
+            Native::Parallel(theta_a) => *theta_a,
+        }
+    }
+}
+
+// WCS code, native frame, PV2_i keywords and half size of the projection plane in radians
+fn proj_keywords(proj: &ProjectionType) -> (&'static str, Native, Vec<(usize, f64)>, Vector2<f64>) {
+    match proj {
+        // Zenithal projections
+        ProjectionType::Tan(p) => ("TAN", Native::Pole, vec![], proj_plane_half_size(p)),
+        ProjectionType::Stg(p) => ("STG", Native::Pole, vec![], proj_plane_half_size(p)),
+        ProjectionType::Sin(p) => ("SIN", Native::Pole, vec![], proj_plane_half_size(p)),
+        ProjectionType::Zea(p) => ("ZEA", Native::Pole, vec![], proj_plane_half_size(p)),
+        // The default AIR minimizes the error up to 90° from the center, i.e. theta_b = 0
+        ProjectionType::Air(p) => ("AIR", Native::Pole, vec![(1, 0.0)], proj_plane_half_size(p)),
+        ProjectionType::Azp(azp) => (
+            "AZP",
+            Native::Pole,
+            vec![(1, azp.mu())],
+            proj_plane_half_size(azp.canonical()),
+        ),
+        ProjectionType::Arc(p) => ("ARC", Native::Pole, vec![], proj_plane_half_size(p)),
+        ProjectionType::Zpn(zpn) => (
+            "ZPN",
+            Native::Pole,
+            zpn.coeffs().iter().copied().enumerate().collect(),
+            proj_plane_half_size(zpn.canonical()),
+        ),
+        // Pseudo-cylindrical projections
+        ProjectionType::Ait(p) => ("AIT", Native::Equator, vec![], proj_plane_half_size(p)),
+        ProjectionType::Mol(p) => ("MOL", Native::Equator, vec![], proj_plane_half_size(p)),
+        ProjectionType::Par(p) => ("PAR", Native::Equator, vec![], proj_plane_half_size(p)),
+        ProjectionType::Sfl(p) => ("SFL", Native::Equator, vec![], proj_plane_half_size(p)),
+        // Cylindrical projections
+        ProjectionType::Mer(p) => ("MER", Native::Equator, vec![], proj_plane_half_size(p)),
+        ProjectionType::Car(p) => ("CAR", Native::Equator, vec![], proj_plane_half_size(p)),
+        ProjectionType::Cea(p) => ("CEA", Native::Equator, vec![], proj_plane_half_size(p)),
+        // Conic projections
+        ProjectionType::Cod(cod) => (
+            "COD",
+            Native::Parallel(cod.theta_a()),
+            vec![(1, cod.theta_a().to_degrees()), (2, cod.nu().to_degrees())],
+            // Southern cones are drawn as the northern one rotated by 180°, which
+            // gives the same projection plane as the one of the WCS
+            proj_plane_half_size(cod.canonical()),
+        ),
+        // Hybrid projection
+        ProjectionType::Hpx(p) => ("HPX", Native::Equator, vec![], proj_plane_half_size(p)),
+    }
+}
+
+// Native longitude and latitude in radians of a direction given in the world space
+//
+// The axes of the world space given to mapproj form an indirect frame so that the east
+// is toward the left. The native frame of the WCS being direct, these are mirrored back
+// unless the view rotation is already indirect (`parity` of -1), i.e. the longitudes
+// are reversed.
+fn native_lonlat(native: &Native, v: &Vector4<f64>, parity: f64) -> (f64, f64) {
+    let v = if let Native::Parallel(theta_a) = native {
+        Matrix4::from_angle_x(Rad(-theta_a)) * v
+    } else {
+        *v
+    };
+    // Axes of mapproj, the x axis pointing toward the fiducial point
+    let (x, y, z) = (v.z, parity * v.x, v.y);
+
+    match native {
+        Native::Pole => (y.atan2(-z), x.clamp(-1.0, 1.0).asin()),
+        _ => (y.atan2(x), z.clamp(-1.0, 1.0).asin()),
+    }
+}
+
+/// Compute the WCS of a view
+///
+/// # Arguments
+///
+/// * `proj` - The projection of the view
+/// * `w2m` - The rotation from the world space to the frame of the view
+/// * `frame` - The frame of the view
+/// * `size` - The size of the canvas in pixels
+/// * `clip_scale` - The size of the canvas in the clipping space divided by 2
+///
+/// Returns `None` if the frame has no equivalent in the FITS standard
+pub fn view_wcs(
+    proj: &ProjectionType,
+    w2m: &Matrix4<f64>,
+    frame: CooSystem,
+    size: Vector2<f64>,
+    clip_scale: Vector2<f64>,
+) -> Option<WCSParams> {
+    let (lon, lat, radesys, equinox) = match frame {
+        CooSystem::ICRS => ("RA--", "DEC-", Some("ICRS"), None),
+        CooSystem::FK5J2000 => ("RA--", "DEC-", Some("FK5"), Some(2000.0)),
+        CooSystem::FK4B1950 => ("RA--", "DEC-", Some("FK4"), Some(1950.0)),
+        CooSystem::GAL => ("GLON", "GLAT", None, None),
+        CooSystem::ECLJ2000 => ("ELON", "ELAT", None, Some(2000.0)),
+        CooSystem::SUPERGAL => ("SLON", "SLAT", None, None),
+        CooSystem::HORIZONTAL => return None,
+    };
+    let (code, native, pv, half_size) = proj_keywords(proj);
+
+    let m2w = w2m.transpose();
+    let parity = w2m.determinant().signum();
+
+    let center = w2m.z.lonlat();
+    // Native longitude of the celestial north pole
+    let (lonpole, _) = native_lonlat(&native, &m2w.y, parity);
+    // Celestial latitude of the native north pole
+    let native_pole = match native {
+        Native::Pole => w2m.z,
+        Native::Equator => w2m.y,
+        Native::Parallel(theta_a) => w2m * Matrix4::from_angle_x(Rad(theta_a)) * Vector4::unit_y(),
+    };
+    let latpole = native_pole.lonlat().lat();
+
+    // Pixels to the projection plane, the x axis of the WCS pointing toward the east
+    let cd1_1 = (-parity * 2.0 * clip_scale.x * half_size.x / size.x).to_degrees();
+    let cd2_2 = (2.0 * clip_scale.y * half_size.y / size.y).to_degrees();
+
+    let mut header = json!({
+        "NAXIS": 2,
+        "NAXIS1": size.x.round() as i64,
+        "NAXIS2": size.y.round() as i64,
+        "CTYPE1": format!("{}-{}", lon, code),
+        "CTYPE2": format!("{}-{}", lat, code),
+        "CRPIX1": size.x * 0.5 + 0.5,
+        "CRPIX2": size.y * 0.5 + 0.5,
+        "CRVAL1": center.lon().to_degrees().rem_euclid(360.0),
+        "CRVAL2": center.lat().to_degrees(),
+        "CD1_1": cd1_1,
+        "CD1_2": 0.0,
+        "CD2_1": 0.0,
+        "CD2_2": cd2_2,
+        "LONPOLE": lonpole.to_degrees(),
+        "LATPOLE": latpole.to_degrees(),
+    });
+    if let Some(radesys) = radesys {
+        header["RADESYS"] = json!(radesys);
+    }
+    if let Some(equinox) = equinox {
+        header["EQUINOX"] = json!(equinox);
+    }
+    for (i, value) in pv {
+        header[format!("PV2_{}", i)] = json!(value);
+    }
+
+    serde_json::from_value(header).ok()
+}
+
+impl CameraViewPort {
+    /// FITS WCS of the view
+    pub fn get_wcs(&self, proj: &ProjectionType) -> Result<WCSParams, JsValue> {
+        let clip_scale = self.get_ndc_to_clip() * self.get_clip_zoom_factor();
+
+        view_wcs(
+            proj,
+            self.get_w2m(),
+            self.get_coo_system(),
+            // Pixels of the canvas as seen in the page, i.e. not the ones of the device
+            Vector2::new(self.get_width() as f64, self.get_height() as f64) / (self.get_dpi() as f64),
+            clip_scale,
+        )
+        .ok_or_else(|| JsValue::from_str("The horizontal frame has no FITS WCS equivalent"))
+    }
+}
+
+// Celestial position of the native pole along with the native longitude of the celestial
+// pole, in radians, following the section 2.4 of the WCS paper II
+//
+// The fiducial point of native latitude `theta_0` is assumed to lie at the native longitude 0
+fn celestial_pole(
+    crval: (f64, f64),
+    lonpole: Option<f64>,
+    latpole: Option<f64>,
+    theta_0: f64,
+) -> Option<(f64, f64, f64)> {
+    let (a0, d0) = crval;
+    let phi_p = lonpole.unwrap_or(if d0 >= theta_0 { 0.0 } else { PI });
+    let theta_p = latpole.unwrap_or(HALF_PI);
+
+    if theta_0 == HALF_PI {
+        // Zenithal projections, the fiducial point is the native pole
+        return Some((a0, d0, phi_p));
+    }
+
+    let (s_phi_p, c_phi_p) = phi_p.sin_cos();
+    let (s_t0, c_t0) = theta_0.sin_cos();
+
+    let a = (c_t0 * s_phi_p).powi(2);
+    let delta_p = if a >= 1.0 {
+        if d0 == 0.0 {
+            theta_p
+        } else {
+            return None;
+        }
+    } else {
+        let b = s_t0.atan2(c_t0 * c_phi_p);
+        let c = d0.sin() / (1.0 - a).sqrt();
+        if c.abs() > 1.0 + 1e-12 {
+            return None;
+        }
+        let c = c.clamp(-1.0, 1.0).acos();
+
+        // The solution the closest to LATPOLE
+        [b - c, b + c]
+            .iter()
+            .map(|d| (d + PI).rem_euclid(TWICE_PI) - PI)
+            .filter(|d| d.abs() <= HALF_PI + 1e-12)
+            .min_by(|d1, d2| (d1 - theta_p).abs().total_cmp(&(d2 - theta_p).abs()))?
+            .clamp(-HALF_PI, HALF_PI)
+    };
+
+    let alpha_p = if d0.abs() == HALF_PI {
+        a0
+    } else if delta_p == HALF_PI {
+        a0 + phi_p - PI
+    } else if delta_p == -HALF_PI {
+        a0 - phi_p
+    } else {
+        let (s_dp, c_dp) = delta_p.sin_cos();
+        a0 - (s_phi_p * c_t0 / d0.cos()).atan2((s_t0 - s_dp * d0.sin()) / (c_dp * d0.cos()))
+    };
+
+    Some((alpha_p, delta_p, phi_p))
+}
+
+// Frame of the celestial axes of a header
+fn header_frame(params: &WCSParams) -> Result<CooSystem, String> {
+    let lon = params.ctype1.get(..4).unwrap_or_default();
+    let lat = params
+        .ctype2
+        .as_deref()
+        .and_then(|ctype2| ctype2.get(..4))
+        .unwrap_or_default();
+    let equinox = params.equinox.or(params.epoch);
+
+    match (lon, lat) {
+        ("RA--", "DEC-") => match (params.radesys.as_deref().map(str::trim), equinox) {
+            (Some("ICRS"), _) | (None, None) => Ok(CooSystem::ICRS),
+            (Some("FK5"), None | Some(2000.0)) => Ok(CooSystem::FK5J2000),
+            (Some("FK4"), None | Some(1950.0)) => Ok(CooSystem::FK4B1950),
+            (None, Some(2000.0)) => Ok(CooSystem::FK5J2000),
+            (None, Some(1950.0)) => Ok(CooSystem::FK4B1950),
+            (radesys, equinox) => Err(format!(
+                "The equatorial frame of RADESYS {:?} and EQUINOX {:?} is not supported",
+                radesys, equinox
+            )),
+        },
+        ("GLON", "GLAT") => Ok(CooSystem::GAL),
+        ("SLON", "SLAT") => Ok(CooSystem::SUPERGAL),
+        ("ELON", "ELAT") => match equinox {
+            None | Some(2000.0) => Ok(CooSystem::ECLJ2000),
+            Some(equinox) => Err(format!(
+                "The ecliptic frame of equinox {} is not supported",
+                equinox
+            )),
+        },
+        _ => Err(format!(
+            "The celestial axes {} and {:?} are not supported",
+            params.ctype1, params.ctype2
+        )),
+    }
+}
+
+// Pixels to the projection plane in degrees, from the CDi_j, the PCi_j and CDELTi
+// or the CDELTi and CROTA2 keywords
+fn header_cd(params: &WCSParams) -> Matrix2<f64> {
+    let cd = [params.cd1_1, params.cd1_2, params.cd2_1, params.cd2_2];
+    let pc = [params.pc1_1, params.pc1_2, params.pc2_1, params.pc2_2];
+    let (cdelt1, cdelt2) = (params.cdelt1.unwrap_or(1.0), params.cdelt2.unwrap_or(1.0));
+
+    // The matrices are given by columns
+    if cd.iter().any(Option::is_some) {
+        let [cd11, cd12, cd21, cd22] = cd.map(|v| v.unwrap_or(0.0));
+        Matrix2::new(cd11, cd21, cd12, cd22)
+    } else if pc.iter().any(Option::is_some) {
+        let [pc11, pc12, pc21, pc22] = [
+            pc[0].unwrap_or(1.0),
+            pc[1].unwrap_or(0.0),
+            pc[2].unwrap_or(0.0),
+            pc[3].unwrap_or(1.0),
+        ];
+        Matrix2::new(cdelt1 * pc11, cdelt2 * pc21, cdelt1 * pc12, cdelt2 * pc22)
+    } else {
+        let (s, c) = params.crota2.unwrap_or(0.0).to_radians().sin_cos();
+        Matrix2::new(cdelt1 * c, cdelt1 * s, -cdelt2 * s, cdelt2 * c)
+    }
+}
+
+type Unproj = Box<dyn Fn(&ProjXY) -> Option<XYZ>>;
+
+fn unproj<P: CanonicalProjection + 'static>(proj: P) -> Unproj {
+    Box::new(move |xy| proj.unproj(xy))
+}
+
+// Projection of a header with the position of its fiducial point in the native frame
+fn header_projection(params: &WCSParams) -> Result<(Unproj, Native), String> {
+    let code = match (
+        params.ctype1.get(4..),
+        params.ctype2.as_deref().and_then(|c| c.get(4..)),
+    ) {
+        (Some(code1), Some(code2)) if code1 == code2 && code1.len() == 4 => &code1[1..],
+        _ => {
+            return Err(format!(
+                "The projections of {} and {:?} are not supported",
+                params.ctype1, params.ctype2
+            ))
+        }
+    };
+    if params.pv1_0.unwrap_or(0.0) != 0.0 || params.pv1_1.is_some() || params.pv1_2.is_some() {
+        return Err("A fiducial point given with PV1_i is not supported".to_string());
+    }
+
+    let pv = [
+        params.pv2_0,
+        params.pv2_1,
+        params.pv2_2,
+        params.pv2_3,
+        params.pv2_4,
+        params.pv2_5,
+        params.pv2_6,
+        params.pv2_7,
+        params.pv2_8,
+        params.pv2_9,
+        params.pv2_10,
+        params.pv2_11,
+        params.pv2_12,
+        params.pv2_13,
+        params.pv2_14,
+        params.pv2_15,
+        params.pv2_16,
+        params.pv2_17,
+        params.pv2_18,
+        params.pv2_19,
+        params.pv2_20,
+    ];
+    let no_pv = || pv.iter().all(|v| v.unwrap_or(0.0) == 0.0);
+    let unsupported = || {
+        format!(
+            "The {} projection with parameters {:?} is not supported",
+            code, pv
+        )
+    };
+
+    let proj = match code {
+        "TAN" if no_pv() => (unproj(mapproj::zenithal::tan::Tan), Native::Pole),
+        "STG" if no_pv() => (unproj(mapproj::zenithal::stg::Stg), Native::Pole),
+        "SIN" if no_pv() => (unproj(mapproj::zenithal::sin::Sin), Native::Pole),
+        "ZEA" if no_pv() => (unproj(mapproj::zenithal::zea::Zea), Native::Pole),
+        "ARC" if no_pv() => (unproj(mapproj::zenithal::arc::Arc), Native::Pole),
+        "AIR" => {
+            // mapproj takes the angular distance to the center where the error is minimized
+            let theta_b = pv[1].unwrap_or(90.0);
+            if theta_b.is_nan() || theta_b.abs() >= 90.0 {
+                return Err(unsupported());
+            }
+            (
+                unproj(mapproj::zenithal::air::Air::from_param(
+                    (90.0 - theta_b).to_radians(),
+                )),
+                Native::Pole,
+            )
+        }
+        "AZP" => {
+            let (mu, gamma) = (pv[1].unwrap_or(0.0), pv[2].unwrap_or(0.0));
+            if !mu.is_finite() || !gamma.is_finite() {
+                return Err(unsupported());
+            }
+            (
+                unproj(mapproj::zenithal::azp::Azp::from_params(
+                    mu,
+                    gamma.to_radians(),
+                )),
+                Native::Pole,
+            )
+        }
+        "ZPN" => {
+            // Coefficients up to the last one given
+            let num_coeffs = pv.iter().rposition(Option::is_some).map_or(0, |i| i + 1);
+            let coeffs = pv[..num_coeffs]
+                .iter()
+                .map(|v| v.unwrap_or(0.0))
+                .collect::<Vec<_>>();
+            let zpn = mapproj::zenithal::zpn::Zpn::from_params(coeffs).ok_or_else(unsupported)?;
+
+            (unproj(zpn), Native::Pole)
+        }
+        "AIT" if no_pv() => (unproj(mapproj::pseudocyl::ait::Ait), Native::Equator),
+        "MOL" if no_pv() => (unproj(mapproj::pseudocyl::mol::Mol::new()), Native::Equator),
+        "PAR" if no_pv() => (unproj(mapproj::pseudocyl::par::Par), Native::Equator),
+        "SFL" if no_pv() => (unproj(mapproj::pseudocyl::sfl::Sfl), Native::Equator),
+        "MER" if no_pv() => (unproj(mapproj::cylindrical::mer::Mer), Native::Equator),
+        "CAR" if no_pv() => (unproj(mapproj::cylindrical::car::Car), Native::Equator),
+        "CEA" => {
+            let lambda = pv[1].unwrap_or(1.0);
+            if lambda.is_nan() || lambda <= 0.0 || lambda > 1.0 {
+                return Err(unsupported());
+            }
+            (
+                unproj(mapproj::cylindrical::cea::Cea::from_param(lambda)),
+                Native::Equator,
+            )
+        }
+        "HPX" if pv[1].unwrap_or(4.0) == 4.0 && pv[2].unwrap_or(3.0) == 3.0 => {
+            (unproj(mapproj::hybrid::hpx::Hpx), Native::Equator)
+        }
+        "COD" => {
+            let (theta_a, eta) = match (pv[1], pv[2].unwrap_or(0.0)) {
+                (Some(theta_a), eta) if theta_a != 0.0 && theta_a.abs() < 90.0 && eta >= 0.0 => {
+                    (theta_a.to_radians(), eta.to_radians())
+                }
+                _ => return Err(unsupported()),
+            };
+            let cod = mapproj::conic::cod::Cod::from_params(theta_a.abs(), eta);
+
+            (unproj(cod), Native::Parallel(theta_a))
+        }
+        _ => return Err(unsupported()),
+    };
+
+    Ok(proj)
+}
+
+/// Celestial WCS of a FITS image read with the projections of the core
+///
+/// Unlike the wcs crate, all the celestial frames of the core are supported and the
+/// projection parameters follow the WCS paper II. Distortions are not supported.
+pub struct ImageWcs {
+    naxis: (usize, usize),
+    frame: CooSystem,
+
+    crpix: Vector2<f64>,
+    // Pixels to the projection plane in radians
+    cd: Matrix2<f64>,
+
+    unproj: Unproj,
+    native: Native,
+    // Celestial longitude and latitude of the native pole and native longitude
+    // of the celestial pole
+    pole: (f64, f64, f64),
+}
+
+impl ImageWcs {
+    pub fn new(params: &WCSParams) -> Result<Self, String> {
+        if params.a_order.is_some() || params.b_order.is_some() || params.ctype1.len() != 8 {
+            return Err(format!("The CTYPE1 {} is not supported", params.ctype1));
+        }
+        if params.naxis1 <= 0 || params.naxis2 <= 0 {
+            return Err(format!(
+                "Invalid image size {}x{}",
+                params.naxis1, params.naxis2
+            ));
+        }
+        let naxis = (params.naxis1 as usize, params.naxis2 as usize);
+        let frame = header_frame(params)?;
+        let (unproj, native) = header_projection(params)?;
+
+        let crpix = Vector2::new(params.crpix1.unwrap_or(0.0), params.crpix2.unwrap_or(0.0));
+        let cd = header_cd(params) * 1.0_f64.to_radians();
+        if !cd.determinant().is_normal() {
+            return Err("The CD matrix is not invertible".to_string());
+        }
+
+        let crval = (
+            params.crval1.unwrap_or(0.0).to_radians(),
+            params.crval2.unwrap_or(0.0).to_radians(),
+        );
+        let pole = celestial_pole(
+            crval,
+            params.lonpole.map(f64::to_radians),
+            params.latpole.map(f64::to_radians),
+            native.theta_0(),
+        )
+        .ok_or_else(|| "The celestial pole cannot be computed".to_string())?;
+
+        Ok(Self {
+            naxis,
+            frame,
+            crpix,
+            cd,
+            unproj,
+            native,
+            pole,
+        })
+    }
+
+    /// Number of pixels along the two axes
+    pub fn img_dimensions(&self) -> (usize, usize) {
+        self.naxis
+    }
+
+    /// Frame of the celestial axes
+    pub fn frame(&self) -> CooSystem {
+        self.frame
+    }
+
+    /// Mean size of the pixels in radians
+    pub fn pixel_size(&self) -> f64 {
+        self.cd.determinant().abs().sqrt()
+    }
+
+    /// Position in the frame of the WCS of a FITS pixel, i.e. the center of the
+    /// first pixel being located at (1, 1)
+    pub fn unproj(&self, xy: &Vector2<f64>) -> Option<Vector4<f64>> {
+        let xy = self.cd * (xy - self.crpix);
+
+        // Native longitude and latitude
+        let (phi, theta) = match self.native {
+            Native::Pole => {
+                let xyz = (self.unproj)(&ProjXY::new(xy.x, xy.y))?;
+                (xyz.y().atan2(-xyz.z()), xyz.x().clamp(-1.0, 1.0).asin())
+            }
+            Native::Equator => {
+                let xyz = (self.unproj)(&ProjXY::new(xy.x, xy.y))?;
+                (xyz.y().atan2(xyz.x()), xyz.z().clamp(-1.0, 1.0).asin())
+            }
+            // Southern cones are the northern ones mirrored about the equator
+            Native::Parallel(theta_a) => {
+                let south = if theta_a < 0.0 { -1.0 } else { 1.0 };
+                let xyz = (self.unproj)(&ProjXY::new(xy.x, south * xy.y))?;
+                (
+                    xyz.y().atan2(xyz.x()),
+                    south * xyz.z().clamp(-1.0, 1.0).asin(),
+                )
+            }
+        };
+
+        // Native to celestial rotation
+        let (alpha_p, delta_p, phi_p) = self.pole;
+        let (s_t, c_t) = theta.sin_cos();
+        let (s_dp, c_dp) = delta_p.sin_cos();
+        let (s_dphi, c_dphi) = (phi - phi_p).sin_cos();
+
+        let alpha = alpha_p + (-c_t * s_dphi).atan2(s_t * c_dp - c_t * s_dp * c_dphi);
+        let delta = (s_t * s_dp + c_t * c_dp * c_dphi).clamp(-1.0, 1.0).asin();
+
+        Some(radec_to_xyzw(alpha.to_angle(), delta.to_angle()))
+    }
+
+    /// Positions in the frame of the WCS of the centers of the pixels row by row,
+    /// `None` for the pixels that cannot be unprojected
+    pub fn pixel_centers(&self) -> impl Iterator<Item = Option<Vector4<f64>>> + '_ {
+        let (width, height) = self.naxis;
+
+        (0..height)
+            .flat_map(move |y| (0..width).map(move |x| (x, y)))
+            .map(move |(x, y)| self.unproj(&Vector2::new(x as f64 + 1.0, y as f64 + 1.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::lonlat::xyzw_to_radec;
+    use crate::math::projection::{azp, cod, zpn, Projection};
+    use crate::math::rotation::Rotation;
+    use cgmath::InnerSpace;
+
+    // Center, position angle and reversed longitude flag of the views tested
+    const VIEWS: [(f64, f64, f64, bool); 5] = [
+        (83.63, 22.01, 0.0, false),
+        (266.4, -29.0, 30.0, false),
+        (10.0, -60.0, -50.0, false),
+        (200.0, 45.0, 120.0, true),
+        (150.0, -70.0, 0.0, true),
+    ];
+
+    fn w2m(lon: f64, lat: f64, pos_angle: f64, reversed: bool) -> Matrix4<f64> {
+        let pos = radec_to_xyzw(lon.to_radians().to_angle(), lat.to_radians().to_angle());
+        let rot = Rotation::from_axis_angle(&pos.truncate(), pos_angle.to_radians().to_angle())
+            * Rotation::from_sky_position(&pos);
+
+        let w2m: Matrix4<f64> = (&rot).into();
+        if reversed {
+            w2m * Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0)
+        } else {
+            w2m
+        }
+    }
+
+    fn size() -> Vector2<f64> {
+        Vector2::new(800.0, 600.0)
+    }
+
+    fn clip_scale(proj: &ProjectionType) -> Vector2<f64> {
+        Vector2::new(1.0, size().y / size().x * proj.bounds_size_ratio()) * 0.3
+    }
+
+    // FITS pixels of the canvas with their position in the frame of the view
+    fn sample_canvas(
+        proj: &ProjectionType,
+        w2m: &Matrix4<f64>,
+        params: &WCSParams,
+    ) -> Vec<(f64, f64, Vector4<f64>)> {
+        let (crpix1, crpix2) = (params.crpix1.unwrap(), params.crpix2.unwrap());
+        let clip_scale = clip_scale(proj);
+
+        (0..=8)
+            .flat_map(|i| (0..=8).map(move |j| (i, j)))
+            .filter_map(|(i, j)| {
+                let (x, y) = (size().x * (i as f64) / 8.0, size().y * (j as f64) / 8.0);
+                // Same as the screen to clipping space conversion
+                let clip = Vector2::new(
+                    2.0 * (x - crpix1) / size().x * clip_scale.x,
+                    2.0 * (y - crpix2) / size().y * clip_scale.y,
+                );
+
+                proj.clip_to_world_space(&clip)
+                    .map(|world| (x, y, w2m * world))
+            })
+            .collect()
+    }
+
+    // Celestial to native spherical rotation of the WCS paper II (Calabretta & Greisen 2002)
+    // for a fiducial point located at (0, theta_0)
+    fn fits_native(params: &WCSParams, theta_0: f64, pos: &Vector4<f64>) -> (f64, f64) {
+        let (alpha_p, delta_p, phi_p) = celestial_pole(
+            (
+                params.crval1.unwrap().to_radians(),
+                params.crval2.unwrap().to_radians(),
+            ),
+            params.lonpole.map(f64::to_radians),
+            params.latpole.map(f64::to_radians),
+            theta_0,
+        )
+        .unwrap();
+
+        let (a, d) = xyzw_to_radec(pos);
+        let (a, d) = (a.to_radians() - alpha_p, d.to_radians());
+        let phi = phi_p
+            + (-d.cos() * a.sin())
+                .atan2(d.sin() * delta_p.cos() - d.cos() * delta_p.sin() * a.cos());
+        let theta = (d.sin() * delta_p.sin() + d.cos() * delta_p.cos() * a.cos()).asin();
+
+        (phi, theta)
+    }
+
+    #[test]
+    fn north_up_east_left() {
+        let proj = ProjectionType::Tan(mapproj::zenithal::tan::Tan);
+        let params = view_wcs(
+            &proj,
+            &w2m(83.63, 22.01, 0.0, false),
+            CooSystem::ICRS,
+            size(),
+            clip_scale(&proj),
+        )
+        .unwrap();
+
+        assert_eq!(params.ctype1, "RA---TAN");
+        assert_eq!(params.ctype2.as_deref(), Some("DEC--TAN"));
+        assert!((params.crval1.unwrap() - 83.63).abs() < 1e-9);
+        assert!((params.crval2.unwrap() - 22.01).abs() < 1e-9);
+        assert!(params.cd1_1.unwrap() < 0.0 && params.cd2_2.unwrap() > 0.0);
+        assert!((params.lonpole.unwrap() - 180.0).abs() < 1e-9);
+    }
+
+    fn projections() -> Vec<ProjectionType> {
+        vec![
+            ProjectionType::Tan(mapproj::zenithal::tan::Tan),
+            ProjectionType::Stg(mapproj::zenithal::stg::Stg),
+            ProjectionType::Sin(mapproj::zenithal::sin::Sin),
+            ProjectionType::Zea(mapproj::zenithal::zea::Zea),
+            ProjectionType::Air(mapproj::zenithal::air::Air::new()),
+            ProjectionType::Arc(mapproj::zenithal::arc::Arc),
+            ProjectionType::Zpn(zpn::Zpn::new()),
+            ProjectionType::Azp(azp::Azp::from_param(2.0).unwrap()),
+            ProjectionType::Azp(azp::Azp::from_param(0.5).unwrap()),
+            ProjectionType::Ait(mapproj::pseudocyl::ait::Ait),
+            ProjectionType::Mol(mapproj::pseudocyl::mol::Mol::new()),
+            ProjectionType::Par(mapproj::pseudocyl::par::Par),
+            ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl),
+            ProjectionType::Mer(mapproj::cylindrical::mer::Mer),
+            ProjectionType::Car(mapproj::cylindrical::car::Car),
+            ProjectionType::Cea(mapproj::cylindrical::cea::Cea::new()),
+            ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx),
+            ProjectionType::Cod(cod::Cod::new()),
+            ProjectionType::Cod(
+                cod::Cod::from_standard_parallels(20.0_f64.to_radians(), 40.0_f64.to_radians())
+                    .unwrap(),
+            ),
+            ProjectionType::Cod(
+                cod::Cod::from_standard_parallels(
+                    (-20.0_f64).to_radians(),
+                    (-50.0_f64).to_radians(),
+                )
+                .unwrap(),
+            ),
+        ]
+    }
+
+    const FRAMES: [CooSystem; 6] = [
+        CooSystem::ICRS,
+        CooSystem::GAL,
+        CooSystem::FK5J2000,
+        CooSystem::FK4B1950,
+        CooSystem::ECLJ2000,
+        CooSystem::SUPERGAL,
+    ];
+
+    // The header of a WCS as it is read by the wcs crate, which takes the PV2_1 of AIR
+    // as the angular distance to the center rho_b = 90° - theta_b
+    fn wcs_crate_params(params: &WCSParams) -> WCSParams {
+        let mut header = serde_json::to_value(params).unwrap();
+        if params.ctype1.ends_with("AIR") {
+            header["PV2_1"] = json!(90.0 - params.pv2_1.unwrap());
+        }
+
+        serde_json::from_value(header).unwrap()
+    }
+
+    #[test]
+    fn round_trip_through_wcs() {
+        // COD is not tested as the wcs crate centers the cone on CRVAL, the native
+        // latitude of its fiducial point being different from 90° and 0°. It is checked
+        // against the WCS paper II in `native_frame_of_rotated_views`
+        let projections = projections()
+            .into_iter()
+            .filter(|proj| !matches!(proj, ProjectionType::Cod(_)))
+            .collect::<Vec<_>>();
+
+        for proj in &projections {
+            for frame in FRAMES {
+                for view in VIEWS {
+                    let (lon, lat, pos_angle, reversed) = view;
+                    // For the non zenithal projections, the wcs crate loses the
+                    // rotation of the view
+                    if pos_angle != 0.0 && !matches!(proj_keywords(proj).1, Native::Pole) {
+                        continue;
+                    }
+
+                    let w2m = w2m(lon, lat, pos_angle, reversed);
+                    let params = view_wcs(proj, &w2m, frame, size(), clip_scale(proj)).unwrap();
+                    let wcs = wcs::WCS::new(&wcs_crate_params(&params)).unwrap();
+                    // The ICRS to galactic matrices of the wcs crate and of the core differ
+                    // by about 0.1 arcsec
+                    let tol = if frame == CooSystem::GAL {
+                        0.1 / (3600.0 * params.cd2_2.unwrap())
+                    } else {
+                        1e-4
+                    };
+
+                    for (x, y, mut pos) in sample_canvas(proj, &w2m, &params) {
+                        // The wcs crate only converts ICRS positions to the galactic frame,
+                        // positions of the other frames are given in the frame of the view
+                        if frame == CooSystem::GAL {
+                            pos = CooSystem::GAL.to(CooSystem::ICRS, &Matrix4::identity()) * pos;
+                        }
+                        let (lon, lat) = xyzw_to_radec(&pos);
+
+                        let xy = wcs
+                            .proj(&wcs::LonLat::new(lon.to_radians(), lat.to_radians()))
+                            .unwrap();
+                        assert!(
+                            (xy.x() - x).abs() < tol && (xy.y() - y).abs() < tol,
+                            "{}, {:?}, {:?}: ({}, {}) != ({}, {})",
+                            params.ctype1,
+                            frame,
+                            view,
+                            xy.x(),
+                            xy.y(),
+                            x,
+                            y
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn read_back_with_image_wcs() {
+        for proj in &projections() {
+            for frame in FRAMES {
+                for view in VIEWS {
+                    let (lon, lat, pos_angle, reversed) = view;
+                    let w2m = w2m(lon, lat, pos_angle, reversed);
+                    let params = view_wcs(proj, &w2m, frame, size(), clip_scale(proj)).unwrap();
+                    let wcs = ImageWcs::new(&params).unwrap();
+                    assert_eq!(wcs.frame(), frame);
+                    assert_eq!(wcs.img_dimensions(), (800, 600));
+
+                    // A thousandth of a pixel
+                    let tol = 1e-3 * wcs.pixel_size();
+                    for (x, y, pos) in sample_canvas(proj, &w2m, &params) {
+                        let read = wcs.unproj(&Vector2::new(x, y)).unwrap();
+                        let dist = pos.truncate().angle(read.truncate()).0;
+                        assert!(
+                            dist < tol,
+                            "{}, {:?}, {:?}: ({}, {}) is {} pixels away",
+                            params.ctype1,
+                            frame,
+                            view,
+                            x,
+                            y,
+                            dist / wcs.pixel_size()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_headers() {
+        let header = |cards: serde_json::Value| {
+            let mut header = json!({
+                "NAXIS": 2,
+                "NAXIS1": 100,
+                "NAXIS2": 100,
+                "CTYPE1": "RA---TAN",
+                "CTYPE2": "DEC--TAN",
+                "CRPIX1": 50.5,
+                "CRPIX2": 50.5,
+                "CRVAL1": 10.0,
+                "CRVAL2": 20.0,
+                "CDELT1": -0.01,
+                "CDELT2": 0.01,
+            });
+            header
+                .as_object_mut()
+                .unwrap()
+                .extend(cards.as_object().unwrap().clone());
+
+            ImageWcs::new(&serde_json::from_value(header).unwrap())
+        };
+
+        assert!(header(json!({})).is_ok());
+        assert_eq!(header(json!({})).unwrap().frame(), CooSystem::ICRS);
+        assert_eq!(
+            header(json!({"EQUINOX": 1950.0})).unwrap().frame(),
+            CooSystem::FK4B1950
+        );
+        // Frames without equivalent in the core
+        assert!(header(json!({"RADESYS": "FK5", "EQUINOX": 1975.0})).is_err());
+        assert!(header(json!({"RADESYS": "GAPPT"})).is_err());
+        assert!(
+            header(json!({"CTYPE1": "ELON-TAN", "CTYPE2": "ELAT-TAN", "EQUINOX": 1950.0})).is_err()
+        );
+        assert!(header(json!({"CTYPE1": "HLON-TAN", "CTYPE2": "HLAT-TAN"})).is_err());
+        // Projections
+        assert!(header(json!({"CTYPE1": "RA---TAN-SIP", "CTYPE2": "DEC--TAN-SIP"})).is_err());
+        assert!(header(json!({"CTYPE1": "RA---TAN", "CTYPE2": "DEC--SIN"})).is_err());
+        assert!(header(json!({"CTYPE1": "RA---TSC", "CTYPE2": "DEC--TSC"})).is_err());
+        assert!(header(json!({"CTYPE1": "RA---SIN", "CTYPE2": "DEC--SIN", "PV2_1": 0.1})).is_err());
+        assert!(header(json!({"CTYPE1": "RA---AIR", "CTYPE2": "DEC--AIR"})).is_err());
+        assert!(header(json!({"CTYPE1": "RA---COD", "CTYPE2": "DEC--COD"})).is_err());
+        assert!(header(json!({"PV1_2": 45.0})).is_err());
+        // Linear transformation
+        assert!(header(json!({"CDELT1": 0.0})).is_err());
+        assert!(header(json!({"NAXIS1": 0})).is_err());
+    }
+
+    #[test]
+    fn native_frame_of_rotated_views() {
+        let projections = [
+            ProjectionType::Ait(mapproj::pseudocyl::ait::Ait),
+            ProjectionType::Mol(mapproj::pseudocyl::mol::Mol::new()),
+            ProjectionType::Par(mapproj::pseudocyl::par::Par),
+            ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl),
+            ProjectionType::Mer(mapproj::cylindrical::mer::Mer),
+            ProjectionType::Car(mapproj::cylindrical::car::Car),
+            ProjectionType::Cea(mapproj::cylindrical::cea::Cea::new()),
+            ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx),
+            ProjectionType::Cod(cod::Cod::new()),
+            ProjectionType::Cod(
+                cod::Cod::from_standard_parallels(20.0_f64.to_radians(), 40.0_f64.to_radians())
+                    .unwrap(),
+            ),
+            ProjectionType::Cod(
+                cod::Cod::from_standard_parallels(
+                    (-20.0_f64).to_radians(),
+                    (-50.0_f64).to_radians(),
+                )
+                .unwrap(),
+            ),
+        ];
+
+        for proj in &projections {
+            for view in VIEWS {
+                let (lon, lat, pos_angle, reversed) = view;
+                let w2m = w2m(lon, lat, pos_angle, reversed);
+                let params =
+                    view_wcs(proj, &w2m, CooSystem::ICRS, size(), clip_scale(proj)).unwrap();
+
+                for (x, y, pos) in sample_canvas(proj, &w2m, &params) {
+                    let (phi, theta) = fits_native(&params, proj_keywords(proj).1.theta_0(), &pos);
+                    let xyz = mapproj::XYZ::new(
+                        theta.cos() * phi.cos(),
+                        theta.cos() * phi.sin(),
+                        theta.sin(),
+                    );
+
+                    let xy = match proj {
+                        ProjectionType::Ait(p) => p.proj(&xyz),
+                        ProjectionType::Mol(p) => p.proj(&xyz),
+                        ProjectionType::Par(p) => p.proj(&xyz),
+                        ProjectionType::Sfl(p) => p.proj(&xyz),
+                        ProjectionType::Mer(p) => p.proj(&xyz),
+                        ProjectionType::Car(p) => p.proj(&xyz),
+                        ProjectionType::Cea(p) => p.proj(&xyz),
+                        ProjectionType::Hpx(p) => p.proj(&xyz),
+                        ProjectionType::Cod(cod) => {
+                            mapproj::conic::cod::Cod::from_params(cod.theta_a(), cod.nu())
+                                .proj(&xyz)
+                        }
+                        _ => unreachable!(),
+                    }
+                    .unwrap();
+
+                    let (px, py) = (
+                        params.crpix1.unwrap() + xy.x().to_degrees() / params.cd1_1.unwrap(),
+                        params.crpix2.unwrap() + xy.y().to_degrees() / params.cd2_2.unwrap(),
+                    );
+                    assert!(
+                        (px - x).abs() < 1e-4 && (py - y).abs() < 1e-4,
+                        "{}, {:?}: ({}, {}) != ({}, {})",
+                        params.ctype1,
+                        view,
+                        px,
+                        py,
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_centers_of_non_equatorial_headers() {
+        use crate::coosys;
+
+        // Frame, center of the image in that frame and its ICRS position in degrees
+        let headers = [
+            // The summer solstice
+            (
+                "ELON-CAR",
+                "ELAT-CAR",
+                None,
+                (90.0, 0.0),
+                (90.0, 23.439_291),
+            ),
+            // The B1950 equinox
+            (
+                "RA---SIN",
+                "DEC--SIN",
+                Some("FK4"),
+                (0.0, 0.0),
+                (0.640_691, 0.278_369),
+            ),
+            // The origin of the supergalactic frame
+            (
+                "SLON-TAN",
+                "SLAT-TAN",
+                None,
+                (0.0, 0.0),
+                (42.310_3, 59.528_3),
+            ),
+        ];
+
+        for (ctype1, ctype2, radesys, crval, icrs) in headers {
+            let mut header = json!({
+                "NAXIS": 2,
+                "NAXIS1": 3,
+                "NAXIS2": 3,
+                "CTYPE1": ctype1,
+                "CTYPE2": ctype2,
+                "CRPIX1": 2.0,
+                "CRPIX2": 2.0,
+                "CRVAL1": crval.0,
+                "CRVAL2": crval.1,
+                "CDELT1": -1.0,
+                "CDELT2": 1.0,
+            });
+            if let Some(radesys) = radesys {
+                header["RADESYS"] = json!(radesys);
+            }
+            let wcs = ImageWcs::new(&serde_json::from_value(header).unwrap()).unwrap();
+            let icrs2hor = Matrix4::identity();
+            let centers = wcs
+                .pixel_centers()
+                .map(|pos| {
+                    let pos = pos.unwrap();
+                    let (lon, lat) = xyzw_to_radec(&coosys::apply_coo_system(
+                        wcs.frame(),
+                        CooSystem::ICRS,
+                        &icrs2hor,
+                        &pos,
+                    ));
+                    (lon.to_degrees().rem_euclid(360.0), lat.to_degrees())
+                })
+                .collect::<Vec<_>>();
+
+            // The central pixel is at CRPIX
+            assert_eq!(centers.len(), 9);
+            let (lon, lat) = centers[4];
+            assert!(
+                (lon - icrs.0).abs() < 1e-3 && (lat - icrs.1).abs() < 1e-3,
+                "{}: ({}, {}) != {:?}",
+                ctype1,
+                lon,
+                lat,
+                icrs
+            );
+
+            // The first pixel is one pixel away along both axes, east to the left
+            let first = wcs.pixel_centers().next().unwrap().unwrap();
+            let center = wcs.pixel_centers().nth(4).unwrap().unwrap();
+            let (lon_first, lat_first) = xyzw_to_radec(&first);
+            let (lon_center, lat_center) = xyzw_to_radec(&center);
+            assert!((lat_center - lat_first).to_degrees() > 0.9);
+            assert!(
+                ((lon_first - lon_center).to_degrees() + 540.0).rem_euclid(360.0) - 180.0 > 0.9
+            );
+        }
+    }
+}
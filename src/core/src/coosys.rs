@@ -1,14 +1,61 @@
-use cgmath::Vector4;
 use al_api::coo_system::CooSystem;
+use cgmath::{InnerSpace, Matrix4, Vector3, Vector4};
 
 /// This is conversion method returning a transformation
 /// matrix when the system requested by the user is not
 /// ICRS.
 /// The core projections are always performed in ICRS
+///
+/// FK4 positions contain the E-terms of aberration. They are removed
+/// (resp. added) when converting from (resp. to) FK4 so that the position
+/// is exact on the CPU side. The GPU only applies the rotation part
+/// (the E-terms being below the arcsec).
+///
+/// `icrs2hor` is the rotation from ICRS to the horizontal frame of the observer,
+/// see `CameraViewPort::get_icrs_to_horizontal`.
 #[inline]
-pub fn apply_coo_system(c1: CooSystem, c2: CooSystem, v: &Vector4<f64>) -> Vector4<f64> {
-    let c1_2_c2_mat = c1.to(c2);
-    c1_2_c2_mat * (*v)
+pub fn apply_coo_system(
+    c1: CooSystem,
+    c2: CooSystem,
+    icrs2hor: &Matrix4<f64>,
+    v: &Vector4<f64>,
+) -> Vector4<f64> {
+    if c1 == c2 {
+        return *v;
+    }
+
+    let v = if c1.has_eterms() {
+        remove_eterms(v)
+    } else {
+        *v
+    };
+
+    let c1_2_c2_mat = c1.to(c2, icrs2hor);
+    let v = c1_2_c2_mat * v;
+
+    if c2.has_eterms() {
+        add_eterms(&v)
+    } else {
+        v
+    }
+}
+
+// E-terms of aberration vector for the B1950 epoch (in radians), expressed
+// in the (y, z, x) axis convention used by the core
+const ETERMS: Vector3<f64> = Vector3::new(-0.31919e-6, -0.13843e-6, -1.62557e-6);
+
+fn remove_eterms(v: &Vector4<f64>) -> Vector4<f64> {
+    let p = v.truncate();
+    let p0 = (p - ETERMS + p.dot(ETERMS) * p).normalize();
+
+    p0.extend(1.0)
+}
+
+fn add_eterms(v: &Vector4<f64>) -> Vector4<f64> {
+    let p0 = v.truncate();
+    let p = (p0 + ETERMS - p0.dot(ETERMS) * p0).normalize();
+
+    p.extend(1.0)
 }
 
 mod tests {
@@ -27,10 +74,16 @@ mod tests {
         use crate::math::lonlat::LonLat;
         use crate::ArcDeg;
         use crate::LonLatT;
+        use cgmath::{Matrix4, SquareMatrix};
 
         let lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(0.0).into());
-        let gal_lonlat =
-            super::apply_coo_system(CooSystem::ICRS, CooSystem::GAL, &lonlat.vector()).lonlat();
+        let gal_lonlat = super::apply_coo_system(
+            CooSystem::ICRS,
+            CooSystem::GAL,
+            &Matrix4::identity(),
+            &lonlat.vector(),
+        )
+        .lonlat();
 
         let gal_lon_deg = gal_lonlat.lon().to_degrees();
         let gal_lat_deg = gal_lonlat.lat().to_degrees();
@@ -45,10 +98,16 @@ mod tests {
         use crate::math::lonlat::LonLat;
         use crate::ArcDeg;
         use crate::LonLatT;
+        use cgmath::{Matrix4, SquareMatrix};
 
         let lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(0.0).into());
-        let icrs_lonlat =
-            super::apply_coo_system(CooSystem::GAL, CooSystem::ICRS, &lonlat.vector()).lonlat();
+        let icrs_lonlat = super::apply_coo_system(
+            CooSystem::GAL,
+            CooSystem::ICRS,
+            &Matrix4::identity(),
+            &lonlat.vector(),
+        )
+        .lonlat();
         let icrs_lon_deg = icrs_lonlat.lon().to_degrees();
         let icrs_lat_deg = icrs_lonlat.lat().to_degrees();
 
@@ -62,13 +121,23 @@ mod tests {
         use crate::math::lonlat::LonLat;
         use crate::ArcDeg;
         use crate::LonLatT;
+        use cgmath::{Matrix4, SquareMatrix};
 
         let gal_lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(0.0).into());
 
-        let icrs_pos =
-            super::apply_coo_system(CooSystem::GAL, CooSystem::ICRS, &gal_lonlat.vector());
+        let icrs_pos = super::apply_coo_system(
+            CooSystem::GAL,
+            CooSystem::ICRS,
+            &Matrix4::identity(),
+            &gal_lonlat.vector(),
+        );
 
-        let gal_lonlat = super::apply_coo_system(CooSystem::ICRS, CooSystem::GAL, &icrs_pos);
+        let gal_lonlat = super::apply_coo_system(
+            CooSystem::ICRS,
+            CooSystem::GAL,
+            &Matrix4::identity(),
+            &icrs_pos,
+        );
 
         let gal_lon_deg = gal_lonlat.lon().to_degrees();
         let gal_lat_deg = gal_lonlat.lat().to_degrees();
@@ -76,4 +145,111 @@ mod tests {
         assert_delta!(gal_lon_deg, 0.0, 1e-3);
         assert_delta!(gal_lat_deg, 0.0, 1e-3);
     }
+
+    #[test]
+    fn ecliptic_pole_to_icrs() {
+        use super::CooSystem;
+        use crate::math::lonlat::LonLat;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+        use cgmath::{Matrix4, SquareMatrix};
+
+        let ecl_pole: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(90.0).into());
+        let icrs_lonlat = super::apply_coo_system(
+            CooSystem::ECLJ2000,
+            CooSystem::ICRS,
+            &Matrix4::identity(),
+            &ecl_pole.vector(),
+        )
+        .lonlat();
+
+        assert!((icrs_lonlat.lon().to_degrees() - 270.0).abs() < 1e-6);
+        assert!((icrs_lonlat.lat().to_degrees() - 66.56072056).abs() < 1e-6);
+    }
+
+    #[test]
+    fn supergal_origin_to_gal() {
+        use super::CooSystem;
+        use crate::math::lonlat::LonLat;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+        use cgmath::{Matrix4, SquareMatrix};
+
+        let origin: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(0.0).into());
+        let gal_lonlat = super::apply_coo_system(
+            CooSystem::SUPERGAL,
+            CooSystem::GAL,
+            &Matrix4::identity(),
+            &origin.vector(),
+        )
+        .lonlat();
+
+        assert!((gal_lonlat.lon().to_degrees() - 137.37).abs() < 1e-6);
+        assert!(gal_lonlat.lat().to_degrees().abs() < 1e-6);
+    }
+
+    #[test]
+    fn fk4_to_fk5_roundtrip() {
+        use super::CooSystem;
+        use crate::math::lonlat::LonLat;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+        use cgmath::{Matrix4, SquareMatrix};
+
+        let fk4_lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(10.0).into(), ArcDeg(20.0).into());
+        let fk5_pos = super::apply_coo_system(
+            CooSystem::FK4B1950,
+            CooSystem::FK5J2000,
+            &Matrix4::identity(),
+            &fk4_lonlat.vector(),
+        );
+        // B1950 to J2000 precession shifts the position of ~0.6 deg
+        let fk5_lonlat = fk5_pos.lonlat();
+        assert!((fk5_lonlat.lon().to_degrees() - 10.6688).abs() < 1e-2);
+        assert!((fk5_lonlat.lat().to_degrees() - 20.2671).abs() < 1e-2);
+
+        let fk4_lonlat = super::apply_coo_system(
+            CooSystem::FK5J2000,
+            CooSystem::FK4B1950,
+            &Matrix4::identity(),
+            &fk5_pos,
+        )
+        .lonlat();
+        assert!((fk4_lonlat.lon().to_degrees() - 10.0).abs() < 1e-7);
+        assert!((fk4_lonlat.lat().to_degrees() - 20.0).abs() < 1e-7);
+    }
+
+    #[test]
+    fn horizontal_frame_of_the_observer() {
+        use super::CooSystem;
+        use crate::math::horizontal::Observer;
+        use crate::math::lonlat::LonLat;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+
+        // 2025 March 20, 0h UTC
+        let utc_ms = 1_742_428_800_000.0;
+        let zenith: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(90.0).into());
+
+        // Each view has its own observer, the zenith being at the declination
+        // of its latitude (up to the precession since J2000)
+        for lat in [48.85, -33.87] {
+            let observer = Observer {
+                lon: 2.35,
+                lat,
+                height: 0.0,
+            };
+            let icrs2hor = observer.icrs_to_horizontal(utc_ms);
+
+            let icrs_zenith = super::apply_coo_system(
+                CooSystem::HORIZONTAL,
+                CooSystem::ICRS,
+                &icrs2hor,
+                &zenith.vector(),
+            )
+            .lonlat();
+
+            assert!((icrs_zenith.lat().to_degrees() - lat).abs() < 0.5);
+        }
+    }
 }
@@ -1,16 +1,21 @@
+use crate::math::angle::ToAngle;
 use crate::math::lonlat::LonLatT;
-use crate::math::PI;
+use crate::math::{PI, TWICE_PI};
+use al_api::moc::{MOCOperation, MOCRegion};
 use crate::math::{self, lonlat::LonLat};
 
 use cgmath::{Vector4};
 use moclib::{
-    moc::range::{CellSelection, RangeMOC},
+    moc::{
+        range::{CellSelection, RangeMOC},
+        CellMOCIterator, CellOrCellRangeMOCIterator, RangeMOCIntoIterator, RangeMOCIterator,
+    },
     qty::Hpx,
     ranges::SNORanges,
 };
 pub type Smoc = RangeMOC<u64, Hpx<u64>>;
 
-use crate::healpix::cell::HEALPixCell;
+use crate::healpix::cell::{HEALPixCell, MAX_HPX_DEPTH};
 #[derive(Clone, Debug)]
 pub struct HEALPixCoverage(pub Smoc);
 
@@ -76,6 +81,258 @@ impl HEALPixCoverage {
         }
     }
 
+    /// Coverage of a box
+    ///
+    /// # Arguments
+    ///
+    /// * `lonlat` - The center of the box
+    /// * `width` - The extent of the box along the east-west direction when `angle` is 0, in radians
+    /// * `height` - The extent of the box along the north-south direction when `angle` is 0, in radians
+    /// * `angle` - The position angle of the box, from the north towards the east, in radians
+    /// * `depth` - The depth of the coverage
+    pub fn from_box(
+        lonlat: &LonLatT<f64>,
+        width: f64,
+        height: f64,
+        angle: f64,
+        depth: u8,
+    ) -> Self {
+        // moclib expects the semi-major axis first along with its position angle
+        let (a, b, pa) = if width >= height {
+            (0.5 * width, 0.5 * height, angle + 0.5 * PI)
+        } else {
+            (0.5 * height, 0.5 * width, angle)
+        };
+
+        HEALPixCoverage(RangeMOC::from_box(
+            lonlat.lon().to_radians(),
+            lonlat.lat().to_radians(),
+            a,
+            b,
+            pa.rem_euclid(PI),
+            depth,
+            CellSelection::All,
+        ))
+    }
+
+    /// Coverage of an ellipse
+    ///
+    /// # Arguments
+    ///
+    /// * `lonlat` - The center of the ellipse
+    /// * `a` - The semi-major axis, in radians
+    /// * `b` - The semi-minor axis, in radians
+    /// * `pa` - The position angle of the semi-major axis, from the north towards the east, in radians
+    /// * `depth` - The depth of the coverage
+    pub fn from_ellipse(lonlat: &LonLatT<f64>, a: f64, b: f64, pa: f64, depth: u8) -> Self {
+        HEALPixCoverage(RangeMOC::from_elliptical_cone(
+            lonlat.lon().to_radians(),
+            lonlat.lat().to_radians(),
+            a,
+            b,
+            pa,
+            depth,
+            2,
+            CellSelection::All,
+        ))
+    }
+
+    /// Coverage of a zone delimited by two meridians and two parallels, in radians
+    ///
+    /// The zone crosses the 0 meridian if the minimum longitude is greater than the maximum one
+    pub fn from_zone(lon_min: f64, lat_min: f64, lon_max: f64, lat_max: f64, depth: u8) -> Self {
+        HEALPixCoverage(RangeMOC::from_zone(
+            lon_min,
+            lat_min,
+            lon_max,
+            lat_max,
+            depth,
+            CellSelection::All,
+        ))
+    }
+
+    /// Coverage of a ring between two radii given in radians
+    pub fn from_ring(lonlat: &LonLatT<f64>, inner_rad: f64, outer_rad: f64, depth: u8) -> Self {
+        HEALPixCoverage(RangeMOC::from_ring(
+            lonlat.lon().to_radians(),
+            lonlat.lat().to_radians(),
+            inner_rad,
+            outer_rad,
+            depth,
+            2.min(MAX_HPX_DEPTH - depth),
+            CellSelection::All,
+        ))
+    }
+
+    /// Coverage of an STC-S region, e.g. `Circle ICRS 10 20 0.5`
+    ///
+    /// Only the ICRS frame and degrees are supported
+    pub fn from_stcs(stcs: &str, depth: u8) -> Result<Self, String> {
+        moclib::deser::stcs::stcs2moc(depth, Some(2.min(MAX_HPX_DEPTH - depth)), stcs)
+            .map(HEALPixCoverage)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Coverage of the area delimited by a polygon containing a given position
+    pub fn from_polygon_with_inside(
+        vertices: &[LonLatT<f64>],
+        inside: &LonLatT<f64>,
+        depth: u8,
+    ) -> Self {
+        let vertices = vertices
+            .iter()
+            .map(|lonlat| (lonlat.lon().to_radians(), lonlat.lat().to_radians()))
+            .collect::<Vec<_>>();
+
+        HEALPixCoverage(RangeMOC::from_polygon_with_control_point(
+            &vertices,
+            (inside.lon().to_radians(), inside.lat().to_radians()),
+            depth,
+            CellSelection::All,
+        ))
+    }
+
+    /// Coverage of a region given in degrees
+    pub fn from_region(region: &MOCRegion, depth: u8) -> Result<Self, String> {
+        let to_lonlat = |lon: f64, lat: f64| -> Result<LonLatT<f64>, String> {
+            if !(-90.0..=90.0).contains(&lat) || !lon.is_finite() {
+                return Err(format!("Invalid position ({}, {})", lon, lat));
+            }
+
+            Ok(LonLatT::new(
+                lon.to_radians().to_angle(),
+                lat.to_radians().to_angle(),
+            ))
+        };
+
+        let moc = match region {
+            MOCRegion::Cone { ra, dec, radius } => {
+                if *radius <= 0.0 {
+                    return Err("The radius of the cone must be positive".to_string());
+                }
+
+                Self::from_cone(&to_lonlat(*ra, *dec)?, radius.to_radians(), depth)
+            }
+            MOCRegion::Ellipse { ra, dec, a, b, pa } => {
+                if *b <= 0.0 || a < b || *a > 90.0 {
+                    return Err(
+                        "The semi-axes of the ellipse must verify 0 < b <= a <= 90 degrees"
+                            .to_string(),
+                    );
+                }
+
+                Self::from_ellipse(
+                    &to_lonlat(*ra, *dec)?,
+                    a.to_radians(),
+                    b.to_radians(),
+                    pa.to_radians(),
+                    depth,
+                )
+            }
+            MOCRegion::Box {
+                ra,
+                dec,
+                width,
+                height,
+                angle,
+            } => {
+                if *width <= 0.0 || *height <= 0.0 || width.max(*height) > 180.0 {
+                    return Err("The sides of the box must be in ]0, 180] degrees".to_string());
+                }
+
+                Self::from_box(
+                    &to_lonlat(*ra, *dec)?,
+                    width.to_radians(),
+                    height.to_radians(),
+                    angle.to_radians(),
+                    depth,
+                )
+            }
+            MOCRegion::Zone {
+                ra_min,
+                dec_min,
+                ra_max,
+                dec_max,
+            } => {
+                if !(-90.0 <= *dec_min && dec_min < dec_max && *dec_max <= 90.0) {
+                    return Err("The declinations of the zone must verify \
+                                -90 <= min < max <= 90 degrees"
+                        .to_string());
+                }
+
+                // The minimum longitude is in [0, 2pi[ and the maximum one in ]0, 2pi]
+                let (lon_min, lon_max) = if ra_max - ra_min >= 360.0 {
+                    (0.0, TWICE_PI)
+                } else {
+                    let lon_min = ra_min.to_radians().rem_euclid(TWICE_PI);
+                    let lon_max = ra_max.to_radians().rem_euclid(TWICE_PI);
+                    (
+                        if lon_min < TWICE_PI { lon_min } else { 0.0 },
+                        if lon_max > 0.0 { lon_max } else { TWICE_PI },
+                    )
+                };
+
+                Self::from_zone(
+                    lon_min,
+                    dec_min.to_radians(),
+                    lon_max,
+                    dec_max.to_radians(),
+                    depth,
+                )
+            }
+            MOCRegion::Ring {
+                ra,
+                dec,
+                inner_radius,
+                outer_radius,
+            } => {
+                if *inner_radius < 0.0 || inner_radius >= outer_radius {
+                    return Err("The radii of the ring must verify 0 <= inner < outer".to_string());
+                }
+
+                Self::from_ring(
+                    &to_lonlat(*ra, *dec)?,
+                    inner_radius.to_radians(),
+                    outer_radius.to_radians(),
+                    depth,
+                )
+            }
+            MOCRegion::Polygon { vertices, inside } => {
+                if vertices.len() < 3 {
+                    return Err("A polygon must have at least 3 vertices".to_string());
+                }
+
+                let vertices = vertices
+                    .iter()
+                    .map(|&[lon, lat]| to_lonlat(lon, lat))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Some([lon, lat]) = inside {
+                    Self::from_polygon_with_inside(&vertices, &to_lonlat(*lon, *lat)?, depth)
+                } else {
+                    Self::from_polygon(&vertices, depth)
+                }
+            }
+            MOCRegion::Stcs { stcs } => Self::from_stcs(stcs, depth)?,
+        };
+
+        Ok(moc)
+    }
+
+    /// Coverage of the smallest area delimited by a polygon
+    pub fn from_polygon(vertices: &[LonLatT<f64>], depth: u8) -> Self {
+        let vertices = vertices
+            .iter()
+            .map(|lonlat| (lonlat.lon().to_radians(), lonlat.lat().to_radians()))
+            .collect::<Vec<_>>();
+
+        HEALPixCoverage(RangeMOC::from_polygon(
+            &vertices,
+            false,
+            depth,
+            CellSelection::All,
+        ))
+    }
+
     pub fn allsky(depth_max: u8) -> Self {
         let moc = RangeMOC::new_full_domain(depth_max);
         HEALPixCoverage(moc)
@@ -116,6 +373,83 @@ impl HEALPixCoverage {
     pub fn empty(depth: u8) -> Self {
         HEALPixCoverage(RangeMOC::new_empty(depth))
     }
+
+    pub fn union(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.union(&other.0))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.intersection(&other.0))
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.minus(&other.0))
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.xor(&other.0))
+    }
+
+    /// Combine coverages with a set operation
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The set operation. The difference removes all the other coverages from
+    ///   the first one and the symmetric difference keeps the cells covered by an odd number of coverages.
+    /// * `mocs` - The coverages to combine, two or more except for the complement that takes only one
+    pub fn from_operation(operation: MOCOperation, mocs: &[&Self]) -> Result<Self, String> {
+        let op: fn(&Self, &Self) -> Self = match (operation, mocs.len()) {
+            (MOCOperation::Complement, 1) => return Ok(mocs[0].not()),
+            (MOCOperation::Complement, _) => {
+                return Err("The complement takes exactly one MOC".to_string())
+            }
+            (_, n) if n < 2 => return Err(format!("The {:?} takes at least two MOCs", operation)),
+            (MOCOperation::Union, _) => Self::union,
+            (MOCOperation::Intersection, _) => Self::intersection,
+            (MOCOperation::Difference, _) => Self::difference,
+            (MOCOperation::SymmetricDifference, _) => Self::symmetric_difference,
+        };
+
+        Ok(mocs[1..]
+            .iter()
+            .fold(mocs[0].clone(), |result, moc| op(&result, moc)))
+    }
+
+    /// Serialize the coverage into an IVOA MOC 2.0 FITS file
+    pub fn to_fits(&self) -> Result<Vec<u8>, String> {
+        let mut buf = Vec::new();
+        (&self.0)
+            .into_range_moc_iter()
+            .to_fits_ivoa(None, None, &mut buf)
+            .map_err(|err| format!("{:?}", err))?;
+
+        Ok(buf)
+    }
+
+    /// Serialize the coverage into the IVOA ASCII format, e.g. `3/1-4 8/`
+    pub fn to_ascii(&self) -> Result<String, String> {
+        let mut buf = Vec::new();
+        (&self.0)
+            .into_range_moc_iter()
+            .cells()
+            .cellranges()
+            .to_ascii_ivoa(None, false, &mut buf)
+            .map_err(|err| format!("{:?}", err))?;
+
+        String::from_utf8(buf).map_err(|err| format!("{:?}", err))
+    }
+
+    /// Serialize the coverage into the Aladin JSON format, e.g. `{"3":[1,2,3,4],"8":[]}`
+    pub fn to_json(&self) -> Result<String, String> {
+        let mut buf = Vec::new();
+        (&self.0)
+            .into_range_moc_iter()
+            .cells()
+            .to_json_aladin(None, &mut buf)
+            .map_err(|err| format!("{:?}", err))?;
+
+        String::from_utf8(buf).map_err(|err| format!("{:?}", err))
+    }
 }
 
 use core::ops::Deref;
@@ -126,3 +460,238 @@ impl Deref for HEALPixCoverage {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::HEALPixCoverage;
+    use crate::math::angle::ArcDeg;
+    use crate::math::lonlat::LonLatT;
+    use al_api::moc::{MOCOperation, MOCRegion};
+    use moclib::deser::{ascii::from_ascii_ivoa, fits, json::from_json_aladin};
+    use moclib::moc::{
+        CellMOCIntoIterator, CellMOCIterator, CellOrCellRangeMOCIntoIterator,
+        CellOrCellRangeMOCIterator, RangeMOCIterator,
+    };
+    use moclib::qty::Hpx;
+
+    fn coverage() -> HEALPixCoverage {
+        HEALPixCoverage::from_cone(
+            &LonLatT::new(ArcDeg(10.0).into(), ArcDeg(20.0).into()),
+            0.1,
+            8,
+        )
+    }
+
+    #[test]
+    fn set_operations() {
+        let cone = |lon: f64| {
+            HEALPixCoverage::from_cone(
+                &LonLatT::new(ArcDeg(lon).into(), ArcDeg(0.0).into()),
+                0.1,
+                8,
+            )
+        };
+        let (a, b, c) = (cone(10.0), cone(10.1), cone(10.2));
+
+        let union = HEALPixCoverage::from_operation(MOCOperation::Union, &[&a, &b, &c]).unwrap();
+        let inter =
+            HEALPixCoverage::from_operation(MOCOperation::Intersection, &[&a, &b, &c]).unwrap();
+        assert!(inter.sky_fraction() > 0.0);
+        assert!(inter.sky_fraction() < a.sky_fraction());
+        assert!(union.sky_fraction() > a.sky_fraction());
+        assert_eq!(union.intersection(&a).0, a.0);
+
+        // a - b - c is outside of the two other cones
+        let diff =
+            HEALPixCoverage::from_operation(MOCOperation::Difference, &[&a, &b, &c]).unwrap();
+        assert!(diff.sky_fraction() > 0.0);
+        assert!(!diff.is_intersecting(&b));
+        assert!(!diff.is_intersecting(&c));
+
+        // The cells covered by the three cones are covered an odd number of times
+        let xor = HEALPixCoverage::from_operation(MOCOperation::SymmetricDifference, &[&a, &b, &c])
+            .unwrap();
+        assert_eq!(xor.intersection(&inter).0, inter.0);
+        assert!(xor.is_intersecting(&diff));
+        assert_eq!(
+            HEALPixCoverage::from_operation(MOCOperation::SymmetricDifference, &[&a, &a])
+                .unwrap()
+                .sky_fraction(),
+            0.0
+        );
+
+        let complement = HEALPixCoverage::from_operation(MOCOperation::Complement, &[&a]).unwrap();
+        assert!(!complement.is_intersecting(&a));
+        assert_eq!(complement.union(&a).sky_fraction(), 1.0);
+
+        assert!(HEALPixCoverage::from_operation(MOCOperation::Union, &[&a]).is_err());
+        assert!(HEALPixCoverage::from_operation(MOCOperation::Complement, &[&a, &b]).is_err());
+    }
+
+    #[test]
+    fn regions() {
+        let depth = 8;
+        let from_region = |region: MOCRegion| HEALPixCoverage::from_region(&region, depth);
+        let lonlat = |lon: f64, lat: f64| LonLatT::new(ArcDeg(lon).into(), ArcDeg(lat).into());
+
+        let cone = from_region(MOCRegion::Cone {
+            ra: 10.0,
+            dec: 20.0,
+            radius: 1.0,
+        })
+        .unwrap();
+        assert!(cone.contains_lonlat(&lonlat(10.0, 20.0)));
+
+        let ellipse = from_region(MOCRegion::Ellipse {
+            ra: 10.0,
+            dec: 20.0,
+            a: 2.0,
+            b: 0.5,
+            pa: 0.0,
+        })
+        .unwrap();
+        assert!(ellipse.contains_lonlat(&lonlat(10.0, 21.5)));
+        assert!(!ellipse.contains_lonlat(&lonlat(11.5, 20.0)));
+
+        let ring = from_region(MOCRegion::Ring {
+            ra: 10.0,
+            dec: 20.0,
+            inner_radius: 1.0,
+            outer_radius: 2.0,
+        })
+        .unwrap();
+        assert!(!ring.contains_lonlat(&lonlat(10.0, 20.0)));
+        assert!(ring.contains_lonlat(&lonlat(10.0, 21.5)));
+
+        // A zone crossing the 0 meridian
+        let zone = from_region(MOCRegion::Zone {
+            ra_min: -10.0,
+            dec_min: -5.0,
+            ra_max: 10.0,
+            dec_max: 5.0,
+        })
+        .unwrap();
+        assert!(zone.contains_lonlat(&lonlat(355.0, 0.0)));
+        assert!(zone.contains_lonlat(&lonlat(5.0, 0.0)));
+        assert!(!zone.contains_lonlat(&lonlat(180.0, 0.0)));
+        let band = from_region(MOCRegion::Zone {
+            ra_min: 0.0,
+            dec_min: -5.0,
+            ra_max: 360.0,
+            dec_max: 5.0,
+        })
+        .unwrap();
+        assert!(band.contains_lonlat(&lonlat(180.0, 0.0)));
+
+        // The smallest area of a polygon unless a position inside is given
+        let vertices = vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+        let polygon = from_region(MOCRegion::Polygon {
+            vertices: vertices.clone(),
+            inside: None,
+        })
+        .unwrap();
+        assert!(polygon.contains_lonlat(&lonlat(5.0, 5.0)));
+        let outside = from_region(MOCRegion::Polygon {
+            vertices,
+            inside: Some([180.0, 0.0]),
+        })
+        .unwrap();
+        assert!(!outside.contains_lonlat(&lonlat(5.0, 5.0)));
+        assert!(outside.contains_lonlat(&lonlat(180.0, 0.0)));
+
+        let stcs = from_region(MOCRegion::Stcs {
+            stcs: "Circle ICRS 10 20 1".to_string(),
+        })
+        .unwrap();
+        assert!(stcs.contains_lonlat(&lonlat(10.0, 20.9)));
+        assert!(!stcs.contains_lonlat(&lonlat(10.0, 21.5)));
+        assert!((stcs.sky_fraction() / cone.sky_fraction() - 1.0).abs() < 0.05);
+
+        for region in &[
+            MOCRegion::Cone {
+                ra: 10.0,
+                dec: 95.0,
+                radius: 1.0,
+            },
+            MOCRegion::Ellipse {
+                ra: 10.0,
+                dec: 20.0,
+                a: 0.5,
+                b: 2.0,
+                pa: 0.0,
+            },
+            MOCRegion::Ring {
+                ra: 10.0,
+                dec: 20.0,
+                inner_radius: 2.0,
+                outer_radius: 1.0,
+            },
+            MOCRegion::Zone {
+                ra_min: 0.0,
+                dec_min: 5.0,
+                ra_max: 10.0,
+                dec_max: -5.0,
+            },
+            MOCRegion::Stcs {
+                stcs: "Circle GALACTIC 10 20 1".to_string(),
+            },
+        ] {
+            assert!(HEALPixCoverage::from_region(region, depth).is_err());
+        }
+    }
+
+    #[test]
+    fn serialize_to_fits() {
+        let moc = coverage();
+        let buf = moc.to_fits().unwrap();
+        assert_eq!(buf.len() % 2880, 0);
+
+        // The keywords of the binary table extension following the empty primary HDU
+        let cards = buf[2880..5760]
+            .chunks(80)
+            .map(|card| String::from_utf8_lossy(card).into_owned())
+            .collect::<Vec<_>>();
+        let value = |keyword: &str| {
+            cards
+                .iter()
+                .find(|card| card[..8].trim_end() == keyword)
+                .map(|card| card[10..].trim().to_string())
+        };
+        assert_eq!(value("MOCVERS").as_deref(), Some("'2.0'"));
+        assert_eq!(value("MOCORD_S").as_deref(), Some("8"));
+        assert!(value("MOCTOOL").is_some());
+
+        let ranges = match fits::from_fits_ivoa(&buf[..]).unwrap() {
+            fits::MocIdxType::U64(fits::MocQtyType::Hpx(moc)) => moc.collect(),
+            _ => panic!("expected a 64 bits HEALPix MOC"),
+        };
+        assert_eq!(ranges, moc.0);
+    }
+
+    #[test]
+    fn serialize_to_ascii() {
+        let moc = coverage();
+        let ascii = moc.to_ascii().unwrap();
+        assert!(ascii.contains("8/"));
+
+        let parsed = from_ascii_ivoa::<u64, Hpx<u64>>(&ascii)
+            .unwrap()
+            .into_cellcellrange_moc_iter()
+            .ranges()
+            .into_range_moc();
+        assert_eq!(parsed, moc.0);
+    }
+
+    #[test]
+    fn serialize_to_json() {
+        let moc = coverage();
+        let json = moc.to_json().unwrap();
+
+        let parsed = from_json_aladin::<u64, Hpx<u64>>(&json)
+            .unwrap()
+            .into_cell_moc_iter()
+            .ranges()
+            .into_range_moc();
+        assert_eq!(parsed, moc.0);
+    }
+}
@@ -0,0 +1,435 @@
+//! HiPS generated in memory
+//!
+//! The tiles of the deepest order are sampled at the center of their pixels from a function
+//! giving the value of the sky at a position (e.g. a FITS image reprojected with its WCS).
+//! As hipsgen does, the tiles of the lower orders are built by averaging 2x2 pixels of their
+//! children. The files of the HiPS (tiles, Allsky, properties and MOC) can be exported for
+//! publishing it.
+use std::collections::HashMap;
+use std::ops::Range;
+
+use al_api::fov::CenteredFoV;
+use moclib::moc::{RangeMOCIntoIterator, RangeMOCIterator};
+use wasm_bindgen::JsValue;
+
+use crate::healpix::cell::HEALPixCell;
+use crate::healpix::coverage::HEALPixCoverage;
+use crate::healpix::map::xy2z;
+use crate::renderable::image::cuts;
+use crate::renderable::image::fits;
+
+// Depth of the pixels of a tile inside it, i.e. tiles of 512x512 pixels
+pub const TILE_DEPTH: u8 = 9;
+// The Allsky file gathers the tiles of that order, downsampled to 64x64 pixels
+const ALLSKY_ORDER: u8 = 3;
+const ALLSKY_TILE_DEPTH: u8 = 6;
+const ALLSKY_NUM_COLS: usize = 27;
+// Maximum number of values read for computing the cuts
+const MAX_NUM_CUTS_SAMPLES: usize = 1 << 18;
+
+pub struct GeneratedHiPS {
+    max_order: u8,
+    // The tiles of every order, their pixels being stored in the NESTED scheme
+    tiles: HashMap<HEALPixCell, Box<[f32]>>,
+    // The coverage of the HiPS at its max order
+    moc: HEALPixCoverage,
+    cuts: Range<f32>,
+
+    creator_did: String,
+    title: String,
+    centered_fov: CenteredFoV,
+}
+
+impl GeneratedHiPS {
+    /// Generate the tiles of a HiPS in the ICRS frame
+    ///
+    /// # Arguments
+    ///
+    /// * `moc` - The coverage of the HiPS, its depth being the max order of the HiPS
+    /// * `sample` - The value at a position given in radians, NaN if blank
+    /// * `creator_did` - The identifier of the HiPS
+    /// * `title` - The title of the HiPS
+    /// * `centered_fov` - The initial position and field of view of the HiPS
+    pub fn new<F>(
+        moc: HEALPixCoverage,
+        sample: F,
+        creator_did: String,
+        title: String,
+        centered_fov: CenteredFoV,
+    ) -> Self
+    where
+        F: Fn(f64, f64) -> f32,
+    {
+        let max_order = moc.depth();
+        let num_pixels = 1_usize << (2 * TILE_DEPTH);
+
+        let mut tiles = HashMap::new();
+        for idx in moc.0.flatten_to_fixed_depth_cells() {
+            let first_pixel = idx << (2 * TILE_DEPTH);
+            let tile = (0..num_pixels)
+                .map(|z| {
+                    let (lon, lat) =
+                        healpix::nested::center(max_order + TILE_DEPTH, first_pixel + z as u64);
+                    sample(lon, lat)
+                })
+                .collect::<Box<[f32]>>();
+
+            tiles.insert(HEALPixCell(max_order, idx), tile);
+        }
+
+        let cuts = {
+            let num_values = tiles.len() * num_pixels;
+            let step = (num_values / MAX_NUM_CUTS_SAMPLES).max(1);
+            let mut samples = tiles
+                .values()
+                .flat_map(|tile| tile.iter())
+                .step_by(step)
+                .copied()
+                .filter(|v| v.is_finite())
+                .collect::<Vec<_>>();
+
+            if samples.is_empty() {
+                0.0..1.0
+            } else {
+                cuts::percentile(&mut samples, 0.5, 99.5)
+            }
+        };
+
+        // Build the tiles of the lower orders from their children
+        for order in (0..max_order).rev() {
+            let mut parents = tiles
+                .keys()
+                .filter(|cell| cell.depth() == order + 1)
+                .map(|cell| cell.idx() >> 2)
+                .collect::<Vec<_>>();
+            parents.sort_unstable();
+            parents.dedup();
+
+            for idx in parents {
+                let children = (0..4)
+                    .map(|c| tiles.get(&HEALPixCell(order + 1, (idx << 2) | c)))
+                    .collect::<Vec<_>>();
+
+                // The 4 sub-pixels of a pixel z are the pixels 4z..4z+3 of the concatenated children
+                let tile = (0..num_pixels)
+                    .map(|z| {
+                        let child = z >> (2 * TILE_DEPTH - 2);
+                        let first = (z << 2) & (num_pixels - 1);
+
+                        children[child].map_or(f32::NAN, |tile| mean(&tile[first..(first + 4)]))
+                    })
+                    .collect::<Box<[f32]>>();
+
+                tiles.insert(HEALPixCell(order, idx), tile);
+            }
+        }
+
+        Self {
+            max_order,
+            tiles,
+            moc,
+            cuts,
+            creator_did,
+            title,
+            centered_fov,
+        }
+    }
+
+    #[inline]
+    pub fn get_max_order(&self) -> u8 {
+        self.max_order
+    }
+
+    #[inline]
+    pub fn get_tile_size(&self) -> i32 {
+        1 << TILE_DEPTH
+    }
+
+    #[inline]
+    pub fn get_sky_fraction(&self) -> f32 {
+        self.moc.sky_fraction() as f32
+    }
+
+    #[inline]
+    pub fn get_moc(&self) -> &HEALPixCoverage {
+        &self.moc
+    }
+
+    /// The 0.5% and 99.5% percentiles of the pixels of the max order
+    #[inline]
+    pub fn get_cuts(&self) -> Range<f32> {
+        self.cuts.clone()
+    }
+
+    /// The pixels of a tile, blank outside the coverage of the HiPS
+    ///
+    /// The rows are stored upside down as in the FITS tiles of a HiPS
+    /// (see `TileReader::read`)
+    pub fn get_tile(&self, cell: &HEALPixCell) -> Vec<f32> {
+        to_tile_layout(&self.get_pixels(cell, TILE_DEPTH), TILE_DEPTH)
+    }
+
+    /// The paths of the files of the HiPS, relative to its root
+    pub fn get_file_paths(&self) -> Vec<String> {
+        let mut cells = self.tiles.keys().collect::<Vec<_>>();
+        cells.sort_unstable();
+
+        ["properties", "Moc.fits", "Norder3/Allsky.fits"]
+            .iter()
+            .map(|path| path.to_string())
+            .chain(cells.into_iter().map(|HEALPixCell(order, idx)| {
+                format!(
+                    "Norder{}/Dir{}/Npix{}.fits",
+                    order,
+                    (idx / 10000) * 10000,
+                    idx
+                )
+            }))
+            .collect()
+    }
+
+    /// The content of a file of the HiPS
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file relative to the root of the HiPS (see `get_file_paths`)
+    pub fn get_file(&self, path: &str) -> Result<Vec<u8>, JsValue> {
+        match path {
+            "properties" => Ok(self.get_properties().into_bytes()),
+            "Moc.fits" => {
+                let mut buf = vec![];
+                (&self.moc.0)
+                    .into_range_moc_iter()
+                    .to_fits_ivoa(None, None, &mut buf)
+                    .map_err(|err| JsValue::from_str(&format!("{:?}", err)))?;
+
+                Ok(buf)
+            }
+            "Norder3/Allsky.fits" => Ok(self.get_allsky()),
+            _ => {
+                let cell = parse_tile_path(path)
+                    .filter(|cell| self.tiles.contains_key(cell))
+                    .ok_or_else(|| {
+                        JsValue::from_str(&format!("{} is not a file of the HiPS", path))
+                    })?;
+                let size = self.get_tile_size() as usize;
+
+                let tile = fits::ImageData::F32(self.get_tile(&cell));
+
+                Ok(fits::write_image(size, size, &tile, vec![]))
+            }
+        }
+    }
+
+    fn get_properties(&self) -> String {
+        let CenteredFoV { ra, dec, fov } = self.centered_fov;
+
+        [
+            ("creator_did", self.creator_did.clone()),
+            ("obs_title", self.title.clone()),
+            ("dataproduct_type", "image".to_string()),
+            ("hips_version", "1.4".to_string()),
+            ("hips_builder", "Aladin Lite".to_string()),
+            ("hips_frame", "equatorial".to_string()),
+            ("hips_order", self.max_order.to_string()),
+            ("hips_order_min", "0".to_string()),
+            ("hips_tile_width", self.get_tile_size().to_string()),
+            ("hips_tile_format", "fits".to_string()),
+            ("hips_pixel_bitpix", "-32".to_string()),
+            (
+                "hips_pixel_cut",
+                format!("{} {}", self.cuts.start, self.cuts.end),
+            ),
+            ("hips_initial_ra", ra.to_string()),
+            ("hips_initial_dec", dec.to_string()),
+            ("hips_initial_fov", fov.to_string()),
+            ("moc_sky_fraction", self.moc.sky_fraction().to_string()),
+        ]
+        .iter()
+        .map(|(key, value)| format!("{:<20} = {}\n", key, value))
+        .collect()
+    }
+
+    // The mosaic of the tiles of order 3, 27 tiles per row
+    fn get_allsky(&self) -> Vec<u8> {
+        let tile_size = 1 << ALLSKY_TILE_DEPTH;
+        let num_tiles: usize = 12 << (2 * ALLSKY_ORDER);
+        let num_rows = num_tiles.div_ceil(ALLSKY_NUM_COLS);
+        let (width, height) = (ALLSKY_NUM_COLS * tile_size, num_rows * tile_size);
+
+        // The tiles are placed from the top of the image whereas they are stored upside down
+        let mut allsky = vec![f32::NAN; width * height];
+        for idx in 0..num_tiles {
+            let cell = HEALPixCell(ALLSKY_ORDER, idx as u64);
+            let tile = to_tile_layout(
+                &self.get_pixels(&cell, ALLSKY_TILE_DEPTH),
+                ALLSKY_TILE_DEPTH,
+            );
+
+            let (x0, y0) = (
+                (idx % ALLSKY_NUM_COLS) * tile_size,
+                (idx / ALLSKY_NUM_COLS) * tile_size,
+            );
+            for (i, row) in tile.chunks(tile_size).rev().enumerate() {
+                let start = (height - 1 - (y0 + i)) * width + x0;
+                allsky[start..(start + tile_size)].copy_from_slice(row);
+            }
+        }
+
+        fits::write_image(width, height, &fits::ImageData::F32(allsky), vec![])
+    }
+
+    // The pixels of depth `cell.depth() + depth` inside a cell in the NESTED scheme,
+    // computed from the tile containing it
+    fn get_pixels(&self, cell: &HEALPixCell, depth: u8) -> Vec<f32> {
+        let num_pixels = 1_usize << (2 * depth);
+
+        let order = cell.depth().min(self.max_order);
+        let tile_idx = cell.idx() >> (2 * (cell.depth() - order));
+
+        if let Some(tile) = self.tiles.get(&HEALPixCell(order, tile_idx)) {
+            // Number of pixels of the tile inside a pixel
+            debug_assert!(cell.depth() + depth <= order + TILE_DEPTH);
+            let num_sub_pixels = 1_usize << (2 * (order + TILE_DEPTH - cell.depth() - depth));
+            let first_pixel =
+                ((cell.idx() - (tile_idx << (2 * (cell.depth() - order)))) as usize) << (2 * depth);
+
+            (0..num_pixels)
+                .map(|z| {
+                    let start = (first_pixel + z) * num_sub_pixels;
+                    mean(&tile[start..(start + num_sub_pixels)])
+                })
+                .collect()
+        } else {
+            vec![f32::NAN; num_pixels]
+        }
+    }
+}
+
+// Mean of the values, the blank ones excepted
+fn mean(values: &[f32]) -> f32 {
+    let (sum, num) = values
+        .iter()
+        .filter(|v| !v.is_nan())
+        .fold((0.0_f64, 0_usize), |(sum, num), &v| {
+            (sum + v as f64, num + 1)
+        });
+
+    if num > 0 {
+        (sum / num as f64) as f32
+    } else {
+        f32::NAN
+    }
+}
+
+// Convert pixels stored in the NESTED scheme to the layout of a FITS tile
+fn to_tile_layout(pixels: &[f32], depth: u8) -> Vec<f32> {
+    let size = 1_usize << depth;
+
+    let mut tile = vec![f32::NAN; size * size];
+    for x in 0..size {
+        for y in 0..size {
+            tile[(size - 1 - x) * size + y] = pixels[xy2z(x, y)];
+        }
+    }
+
+    tile
+}
+
+// Parse a path of the form NorderK/DirD/NpixN.fits
+fn parse_tile_path(path: &str) -> Option<HEALPixCell> {
+    let mut parts = path.split('/');
+
+    let order = parts.next()?.strip_prefix("Norder")?.parse().ok()?;
+    let _dir = parts.next()?.strip_prefix("Dir")?;
+    let idx = parts
+        .next()?
+        .strip_prefix("Npix")?
+        .strip_suffix(".fits")?
+        .parse()
+        .ok()?;
+
+    Some(HEALPixCell(order, idx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hips(depth: u8, sample: impl Fn(f64, f64) -> f32) -> GeneratedHiPS {
+        let moc =
+            HEALPixCoverage::from_fixed_hpx_cells(depth, [0, 1, 2, 3, 6].iter().copied(), None);
+        let centered_fov = CenteredFoV {
+            ra: 0.0,
+            dec: 0.0,
+            fov: 1.0,
+        };
+
+        GeneratedHiPS::new(
+            moc,
+            sample,
+            String::from("ivo://test"),
+            String::from("test"),
+            centered_fov,
+        )
+    }
+
+    #[test]
+    fn lower_orders_average_their_children() {
+        let hips = hips(1, |lon, _| lon as f32);
+
+        let parent = &hips.tiles[&HEALPixCell(0, 0)];
+        let children = (0..4)
+            .map(|c| &hips.tiles[&HEALPixCell(1, c)])
+            .collect::<Vec<_>>();
+
+        for z in [0, 1, 1000, (1 << 18) - 1] {
+            let child = children[z >> 16];
+            let first = (z << 2) & ((1 << 18) - 1);
+            let expected = child[first..(first + 4)].iter().sum::<f32>() / 4.0;
+            assert!((parent[z] - expected).abs() < 1e-5);
+        }
+
+        // Only the third child of the base cell 1 is in the coverage
+        let parent = &hips.tiles[&HEALPixCell(0, 1)];
+        assert!(parent[0].is_nan());
+        assert!(!parent[2 << 16].is_nan());
+        assert!(parent[3 << 16].is_nan());
+    }
+
+    #[test]
+    fn tile_values_are_located_at_their_pixel() {
+        let hips = hips(1, |lon, lat| (lon + 10.0 * lat) as f32);
+        let size = 1 << TILE_DEPTH;
+
+        let cell = HEALPixCell(1, 2);
+        let tile = hips.get_tile(&cell);
+        let layer = healpix::nested::get(1);
+
+        // Pixel of the tile at the row 100 and column 300
+        let (row, col) = (100, 300);
+        let z = xy2z(size - 1 - row, col);
+        let (lon, lat) = healpix::nested::center(1 + TILE_DEPTH, (cell.idx() << 18) + z as u64);
+        assert_eq!(layer.hash(lon, lat), cell.idx());
+        assert!((tile[row * size + col] - (lon + 10.0 * lat) as f32).abs() < 1e-5);
+    }
+
+    #[test]
+    fn file_paths_can_be_read() {
+        let hips = hips(1, |_, _| 1.0);
+        let paths = hips.get_file_paths();
+
+        assert!(paths.contains(&String::from("Norder1/Dir0/Npix6.fits")));
+        assert!(paths.contains(&String::from("Norder0/Dir0/Npix1.fits")));
+        assert_eq!(
+            parse_tile_path("Norder1/Dir0/Npix6.fits"),
+            Some(HEALPixCell(1, 6))
+        );
+        assert_eq!(
+            hips.get_file("Norder3/Allsky.fits")
+                .ok()
+                .map(|f| f.len() % 2880),
+            Some(0)
+        );
+    }
+}
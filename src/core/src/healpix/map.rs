@@ -0,0 +1,639 @@
+//! HEALPix maps stored in FITS binary tables
+//!
+//! The maps follow the HEALPix FITS convention (e.g. the Planck maps): the pixels are given
+//! in the RING or NESTED scheme (ORDERING) at a resolution NSIDE, either implicitly by the row
+//! order or explicitly by a PIXEL column for partial maps. They are converted into the NESTED
+//! scheme so that the tiles of the HiPS rendering them can be generated in memory.
+use std::collections::HashMap;
+use std::ops::Range;
+
+use al_api::coo_system::CooSystem;
+
+use crate::healpix::cell::HEALPixCell;
+use crate::renderable::image::cuts;
+use crate::renderable::image::fits::{self, CardValue};
+
+type Header = HashMap<String, CardValue>;
+
+// Value of the pixels not observed in the HEALPix convention
+const UNSEEN: f32 = -1.6375e30;
+// Beyond this depth, the map converted to the NESTED scheme would not fit in memory
+const MAX_DEPTH: u8 = 12;
+// Depth of the pixels of a tile inside it, i.e. tiles of 64x64 pixels
+const TILE_DEPTH: u8 = 6;
+// Maximum number of values read for computing the cuts
+const MAX_NUM_CUTS_SAMPLES: usize = 1 << 18;
+
+pub struct HEALPixMap {
+    depth: u8,
+    // The values in the NESTED scheme, the blank pixels being NaN
+    values: Box<[f32]>,
+    frame: CooSystem,
+}
+
+impl HEALPixMap {
+    /// Parse the map stored in the first binary table of a FITS file
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The FITS file
+    /// * `column` - The name (TTYPE) of the column to read, the first one storing values by default
+    pub fn from_fits(bytes: &[u8], column: Option<&str>) -> Result<Self, String> {
+        // The HDUs are skipped until the first binary table
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let (header, header_size) = fits::read_header(&bytes[offset..])?;
+            let data_offset = offset + header_size;
+
+            if get_string(&header, "XTENSION").as_deref() == Some("BINTABLE") {
+                let table_size = get_number(&header, "NAXIS1").unwrap_or(0.0) as usize
+                    * get_number(&header, "NAXIS2").unwrap_or(0.0) as usize;
+                let table = bytes
+                    .get(data_offset..(data_offset + table_size))
+                    .ok_or_else(|| "The binary table is truncated".to_string())?;
+
+                return Self::from_bintable(&header, table, column);
+            }
+
+            offset = data_offset + data_size(&header).div_ceil(2880) * 2880;
+        }
+
+        Err("No binary table storing a HEALPix map has been found".to_string())
+    }
+
+    fn from_bintable(header: &Header, table: &[u8], column: Option<&str>) -> Result<Self, String> {
+        if let Some(pixtype) = get_string(header, "PIXTYPE") {
+            if pixtype != "HEALPIX" {
+                return Err(format!("PIXTYPE {} is not HEALPIX", pixtype));
+            }
+        }
+
+        let nside = get_number(header, "NSIDE")
+            .ok_or_else(|| "NSIDE keyword not found".to_string())? as u64;
+        if !nside.is_power_of_two() {
+            return Err("NSIDE must be a power of two".to_string());
+        }
+        let depth = nside.trailing_zeros() as u8;
+        if depth > MAX_DEPTH {
+            return Err(format!(
+                "NSIDE {} is too large, it cannot exceed {}",
+                nside,
+                1 << MAX_DEPTH
+            ));
+        }
+
+        let ring = match get_string(header, "ORDERING").as_deref() {
+            Some("RING") => Ok(true),
+            Some("NESTED") | Some("NEST") => Ok(false),
+            Some(ordering) => Err(format!(
+                "ORDERING {} not supported. Must be RING or NESTED",
+                ordering
+            )),
+            None => Err("ORDERING keyword not found".to_string()),
+        }?;
+
+        let frame = match get_string(header, "COORDSYS").as_ref().and_then(|c| c.chars().next()) {
+            Some('G') => Ok(CooSystem::GAL),
+            Some('C') | Some('Q') | None => Ok(CooSystem::ICRS),
+            Some('E') => Ok(CooSystem::ECLJ2000),
+            Some(_) => Err("COORDSYS must be G, C or E".to_string()),
+        }?;
+
+        let columns = Column::parse_all(header)?;
+        let pixel_column = columns.iter().position(|c| c.name == "PIXEL");
+        // The column giving the index of the pixels of a partial map
+        let explicit_column = match get_string(header, "INDXSCHM").as_deref() {
+            Some("EXPLICIT") => Some(
+                pixel_column
+                    .ok_or_else(|| "Explicit indexing without a PIXEL column".to_string())?,
+            ),
+            Some(_) => None,
+            None => pixel_column,
+        };
+
+        let value_column = if let Some(name) = column {
+            columns
+                .iter()
+                .position(|c| c.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    format!(
+                        "Column {} not found. Available columns are: {}",
+                        name,
+                        columns
+                            .iter()
+                            .map(|c| c.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?
+        } else {
+            (0..columns.len())
+                .find(|&idx| Some(idx) != pixel_column)
+                .ok_or_else(|| "No column storing the values of the map".to_string())?
+        };
+        let value_column = &columns[value_column];
+        // A row holds as many pixel indices as values
+        if let Some(explicit_column) = explicit_column {
+            if columns[explicit_column].repeat != value_column.repeat {
+                return Err(format!(
+                    "The PIXEL column and the {} column must have the same repeat count",
+                    value_column.name
+                ));
+            }
+        }
+
+        let row_size = get_number(header, "NAXIS1").unwrap_or(0.0) as usize;
+        if row_size == 0 {
+            return Err("The binary table is empty".to_string());
+        }
+        if columns.iter().map(|c| c.offset + c.size).max() > Some(row_size) {
+            return Err("The columns are larger than NAXIS1".to_string());
+        }
+
+        let layer = healpix::nested::get(depth);
+        let num_pixels = layer.n_hash();
+        let mut values = vec![f32::NAN; num_pixels as usize].into_boxed_slice();
+
+        let first_pixel = get_number(header, "FIRSTPIX").unwrap_or(0.0) as u64;
+        for (idx_row, row) in table.chunks_exact(row_size).enumerate() {
+            for k in 0..value_column.repeat {
+                let pixel = if let Some(explicit_column) = explicit_column {
+                    let pixel = columns[explicit_column].read(row, k)?;
+                    // Null index
+                    if pixel.is_nan() || pixel < 0.0 {
+                        continue;
+                    }
+
+                    pixel as u64
+                } else {
+                    first_pixel + (idx_row * value_column.repeat + k) as u64
+                };
+
+                if pixel >= num_pixels {
+                    continue;
+                }
+
+                let pixel = if ring { layer.from_ring(pixel) } else { pixel };
+                let value = value_column.read(row, k)? as f32;
+                values[pixel as usize] = if value == UNSEEN { f32::NAN } else { value };
+            }
+        }
+
+        Ok(Self {
+            depth,
+            values,
+            frame,
+        })
+    }
+
+    /// The frame in which the map is given
+    pub fn get_frame(&self) -> CooSystem {
+        self.frame
+    }
+
+    /// The size in pixels of the tiles of the HiPS rendering the map
+    pub fn get_tile_size(&self) -> i32 {
+        1 << self.depth.min(TILE_DEPTH)
+    }
+
+    /// The order of the deepest tiles of the HiPS rendering the map
+    pub fn get_max_order(&self) -> u8 {
+        self.depth - self.depth.min(TILE_DEPTH)
+    }
+
+    /// The fraction of the sky covered by the map
+    pub fn get_sky_fraction(&self) -> f32 {
+        let num_observed = self.values.iter().filter(|v| !v.is_nan()).count();
+
+        num_observed as f32 / self.values.len() as f32
+    }
+
+    /// The 0.5% and 99.5% percentiles of the values
+    pub fn get_cuts(&self) -> Range<f32> {
+        let step = (self.values.len() / MAX_NUM_CUTS_SAMPLES).max(1);
+        let mut samples = self
+            .values
+            .iter()
+            .step_by(step)
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect::<Vec<_>>();
+
+        if samples.is_empty() {
+            0.0..1.0
+        } else {
+            cuts::percentile(&mut samples, 0.5, 99.5)
+        }
+    }
+
+    /// The pixels of a tile, a pixel being the mean of the values of the map it covers
+    ///
+    /// The rows are stored upside down as in the FITS tiles of a HiPS
+    /// (see `TileReader::read`)
+    pub fn get_tile(&self, cell: &HEALPixCell) -> Vec<f32> {
+        let tile_size = self.get_tile_size() as usize;
+        let tile_depth = self.depth.min(TILE_DEPTH);
+        debug_assert!(cell.depth() + tile_depth <= self.depth);
+
+        // Number of pixels of the map inside a pixel of the tile
+        let num_sub_pixels = 1_usize << (2 * (self.depth - cell.depth() - tile_depth));
+        let first_pixel = (cell.idx() as usize) << (2 * tile_depth);
+
+        let mut tile = vec![f32::NAN; tile_size * tile_size];
+        for x in 0..tile_size {
+            for y in 0..tile_size {
+                let start = (first_pixel + xy2z(x, y)) * num_sub_pixels;
+                let (sum, num) = self.values[start..(start + num_sub_pixels)]
+                    .iter()
+                    .filter(|v| !v.is_nan())
+                    .fold((0.0_f64, 0_usize), |(sum, num), &v| (sum + v as f64, num + 1));
+
+                if num > 0 {
+                    tile[(tile_size - 1 - x) * tile_size + y] = (sum / num as f64) as f32;
+                }
+            }
+        }
+
+        tile
+    }
+}
+
+// Index in the NESTED scheme of the pixel at (x, y) inside a cell, x being along its dx axis
+pub(crate) fn xy2z(x: usize, y: usize) -> usize {
+    (0..(usize::BITS as usize / 2)).fold(0, |z, b| {
+        z | (((x >> b) & 1) << (2 * b)) | (((y >> b) & 1) << (2 * b + 1))
+    })
+}
+
+// A column of the binary table
+struct Column {
+    name: String,
+    // Position of the column in a row in bytes
+    offset: usize,
+    // Size of the column in a row in bytes
+    size: usize,
+    repeat: usize,
+    tform: char,
+    scale: f64,
+    zero: f64,
+    null: Option<f64>,
+}
+
+impl Column {
+    // The columns of the table, a TFORM without repeat count having one element
+    fn parse_all(header: &Header) -> Result<Vec<Self>, String> {
+        let num_columns = get_number(header, "TFIELDS")
+            .ok_or_else(|| "TFIELDS keyword not found".to_string())?
+            as usize;
+
+        let mut offset = 0;
+        (1..=num_columns)
+            .map(|idx| {
+                let tform = get_string(header, &format!("TFORM{}", idx))
+                    .ok_or_else(|| format!("TFORM{} keyword not found", idx))?;
+                let num_digits = tform.chars().take_while(|c| c.is_ascii_digit()).count();
+                let repeat = tform[..num_digits].parse::<usize>().unwrap_or(1);
+                let tform = tform[num_digits..].chars().next().unwrap_or('A');
+
+                let num_bytes = match tform {
+                    'L' | 'B' | 'A' => repeat,
+                    'X' => repeat.div_ceil(8),
+                    'I' => 2 * repeat,
+                    'J' | 'E' => 4 * repeat,
+                    'K' | 'D' | 'C' | 'P' => 8 * repeat,
+                    'M' | 'Q' => 16 * repeat,
+                    _ => return Err(format!("TFORM{} not valid", idx)),
+                };
+
+                let column = Column {
+                    name: get_string(header, &format!("TTYPE{}", idx))
+                        .unwrap_or_else(|| format!("COLUMN{}", idx)),
+                    offset,
+                    size: num_bytes,
+                    repeat,
+                    tform,
+                    scale: get_number(header, &format!("TSCAL{}", idx)).unwrap_or(1.0),
+                    zero: get_number(header, &format!("TZERO{}", idx)).unwrap_or(0.0),
+                    null: get_number(header, &format!("TNULL{}", idx)),
+                };
+                offset += num_bytes;
+
+                Ok(column)
+            })
+            .collect()
+    }
+
+    // Read the k-th element of the column in a row, NaN for the null values
+    fn read(&self, row: &[u8], k: usize) -> Result<f64, String> {
+        macro_rules! read_be {
+            ($t:ty) => {{
+                const N: usize = std::mem::size_of::<$t>();
+                let start = self.offset + k * N;
+                let mut bytes = [0_u8; N];
+                bytes.copy_from_slice(&row[start..(start + N)]);
+
+                <$t>::from_be_bytes(bytes) as f64
+            }};
+        }
+
+        let raw = match self.tform {
+            'B' => row[self.offset + k] as f64,
+            'I' => read_be!(i16),
+            'J' => read_be!(i32),
+            'K' => read_be!(i64),
+            'E' => read_be!(f32),
+            'D' => read_be!(f64),
+            _ => {
+                return Err(format!(
+                    "Column {} of type {} cannot be read as a HEALPix map",
+                    self.name, self.tform
+                ))
+            }
+        };
+
+        if self.null == Some(raw) {
+            Ok(f64::NAN)
+        } else {
+            Ok(raw * self.scale + self.zero)
+        }
+    }
+}
+
+// Size in bytes of the data of a HDU, padding excluded
+fn data_size(header: &Header) -> usize {
+    let num_axes = get_number(header, "NAXIS").unwrap_or(0.0) as usize;
+    if num_axes == 0 {
+        return 0;
+    }
+
+    let num_bytes = get_number(header, "BITPIX").unwrap_or(8.0).abs() as usize / 8;
+    let num_elements = (1..=num_axes)
+        .map(|idx| get_number(header, &format!("NAXIS{}", idx)).unwrap_or(0.0) as usize)
+        .product::<usize>();
+    let heap_size = get_number(header, "PCOUNT").unwrap_or(0.0) as usize;
+    let num_groups = get_number(header, "GCOUNT").unwrap_or(1.0) as usize;
+
+    num_bytes * num_groups * (heap_size + num_elements)
+}
+
+fn get_string(header: &Header, name: &str) -> Option<String> {
+    match header.get(name) {
+        Some(CardValue::String(s)) => Some(s.trim().to_uppercase()),
+        _ => None,
+    }
+}
+
+fn get_number(header: &Header, name: &str) -> Option<f64> {
+    match header.get(name) {
+        Some(CardValue::Float(v)) => Some(*v),
+        Some(CardValue::Integer(v)) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{xy2z, HEALPixMap, UNSEEN};
+    use crate::healpix::cell::HEALPixCell;
+    use al_api::coo_system::CooSystem;
+
+    // A FITS file from its header cards and the data of its binary table
+    fn fits(cards: &[&str], table: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut header = |cards: &[&str]| {
+            for card in cards.iter().chain(std::iter::once(&"END")) {
+                bytes.extend(format!("{:<80}", card).bytes());
+            }
+            bytes.resize(bytes.len().div_ceil(2880) * 2880, b' ');
+        };
+
+        header(&["SIMPLE  = T", "BITPIX  = 8", "NAXIS   = 0", "EXTEND  = T"]);
+        header(cards);
+        bytes.extend_from_slice(table);
+        bytes.resize(bytes.len().div_ceil(2880) * 2880, 0);
+
+        bytes
+    }
+
+    // A binary table of `row_size` bytes per row, `cards` following the ones of its columns
+    fn bintable(
+        columns: &[(&str, &str)],
+        row_size: usize,
+        table: &[u8],
+        cards: &[&str],
+    ) -> Vec<u8> {
+        let mut header = vec![
+            "XTENSION= 'BINTABLE'".to_string(),
+            "BITPIX  = 8".to_string(),
+            "NAXIS   = 2".to_string(),
+            format!("NAXIS1  = {}", row_size),
+            format!("NAXIS2  = {}", table.len() / row_size),
+            "PCOUNT  = 0".to_string(),
+            "GCOUNT  = 1".to_string(),
+            format!("TFIELDS = {}", columns.len()),
+        ];
+        for (idx, (ttype, tform)) in columns.iter().enumerate() {
+            header.push(format!("TTYPE{:<3}= '{}'", idx + 1, ttype));
+            header.push(format!("TFORM{:<3}= '{}'", idx + 1, tform));
+        }
+        header.extend(cards.iter().map(|card| card.to_string()));
+
+        fits(
+            &header.iter().map(String::as_str).collect::<Vec<_>>(),
+            table,
+        )
+    }
+
+    // The observed pixels of a map with their values
+    fn observed(map: &HEALPixMap) -> Vec<(usize, f32)> {
+        map.values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !v.is_nan())
+            .map(|(pixel, v)| (pixel, *v))
+            .collect()
+    }
+
+    #[test]
+    fn xy2z_follows_the_tile_layout() {
+        let (depth, idx, tile_depth) = (1, 5, 3);
+        let tile_size = 1 << tile_depth;
+        let layer = healpix::nested::get(depth);
+
+        for x in 0..tile_size {
+            for y in 0..tile_size {
+                let pixel = (idx << (2 * tile_depth)) + xy2z(x, y) as u64;
+                let (lon, lat) = healpix::nested::center(depth + tile_depth, pixel);
+                let (hash, dx, dy) = layer.hash_with_dxdy(lon, lat);
+
+                assert_eq!(hash, idx);
+                assert_eq!((dx * tile_size as f64) as usize, x);
+                assert_eq!((dy * tile_size as f64) as usize, y);
+            }
+        }
+    }
+
+    #[test]
+    fn ring_map_to_nested_tiles() {
+        // One value per row given with a TFORM without repeat count, the RING index
+        let num_pixels = 12 * 4;
+        let table = (0..num_pixels)
+            .flat_map(|ring| (ring as f32).to_be_bytes())
+            .collect::<Vec<_>>();
+        let bytes = fits(
+            &[
+                "XTENSION= 'BINTABLE'",
+                "BITPIX  = 8",
+                "NAXIS   = 2",
+                "NAXIS1  = 4",
+                &format!("NAXIS2  = {}", num_pixels),
+                "PCOUNT  = 0",
+                "GCOUNT  = 1",
+                "TFIELDS = 1",
+                "TTYPE1  = 'I_STOKES'",
+                "TFORM1  = 'E       '",
+                "PIXTYPE = 'HEALPIX '",
+                "ORDERING= 'RING    '",
+                "NSIDE   = 2",
+                "COORDSYS= 'GALACTIC'",
+            ],
+            &table,
+        );
+
+        let map = HEALPixMap::from_fits(&bytes, None).unwrap();
+        assert_eq!(map.get_tile_size(), 2);
+        assert_eq!(map.get_max_order(), 0);
+        assert_eq!(map.get_sky_fraction(), 1.0);
+
+        let layer = healpix::nested::get(1);
+        let tile = map.get_tile(&HEALPixCell(0, 4));
+        for x in 0..2 {
+            for y in 0..2 {
+                let nested = (4 << 2) + xy2z(x, y) as u64;
+                assert_eq!(tile[(1 - x) * 2 + y], layer.to_ring(nested) as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn partial_map_with_explicit_indices() {
+        // The null index and the pixel beyond the 48 ones of the map are skipped
+        let rows: [(i32, f32); 5] = [(3, 1.5), (17, UNSEEN), (40, 2.5), (100, 7.0), (-1, 9.0)];
+        let table = rows
+            .iter()
+            .flat_map(|(pixel, value)| [pixel.to_be_bytes(), value.to_be_bytes()].concat())
+            .collect::<Vec<_>>();
+        let columns = [("PIXEL", "J"), ("SIGNAL", "E")];
+        let cards = [
+            "PIXTYPE = 'HEALPIX '",
+            "ORDERING= 'NESTED  '",
+            "NSIDE   = 2",
+            "TNULL1  = -1",
+            "OBJECT  = 'PARTIAL '",
+        ];
+
+        for indxschm in ["INDXSCHM= 'EXPLICIT'", "COMMENT no INDXSCHM"] {
+            let bytes = bintable(&columns, 8, &table, &[&cards[..], &[indxschm]].concat());
+            let map = HEALPixMap::from_fits(&bytes, None).unwrap();
+            assert_eq!(map.get_frame(), CooSystem::ICRS);
+            assert_eq!(observed(&map), vec![(3, 1.5), (40, 2.5)]);
+            assert_eq!(map.get_sky_fraction(), 2.0 / 48.0);
+        }
+
+        // The rows give the pixels in order when the indexing is implicit
+        let bytes = bintable(
+            &columns,
+            8,
+            &table,
+            &[&cards[..], &["INDXSCHM= 'IMPLICIT'"]].concat(),
+        );
+        let map = HEALPixMap::from_fits(&bytes, Some("signal")).unwrap();
+        assert_eq!(observed(&map), vec![(0, 1.5), (2, 2.5), (3, 7.0), (4, 9.0)]);
+
+        // A row holds as many indices as values
+        let bytes = bintable(
+            &[("PIXEL", "1J"), ("SIGNAL", "2E")],
+            12,
+            &[0; 24],
+            &[&cards[..], &["INDXSCHM= 'EXPLICIT'"]].concat(),
+        );
+        assert!(
+            matches!(HEALPixMap::from_fits(&bytes, None), Err(e) if e.contains("repeat count"))
+        );
+    }
+
+    #[test]
+    fn nested_map_with_several_values_per_row() {
+        // The 12 pixels of NSIDE 1 in 3 rows of 4 values, the raw value of pixel 5 being TNULL
+        let raw = (0..12_i16)
+            .map(|pixel| if pixel == 5 { i16::MIN } else { pixel })
+            .collect::<Vec<_>>();
+        let table = raw.iter().flat_map(|v| v.to_be_bytes()).collect::<Vec<_>>();
+        let bytes = bintable(
+            &[("TEMPERATURE", "4I")],
+            8,
+            &table,
+            &[
+                "PIXTYPE = 'HEALPIX '",
+                "ORDERING= 'NESTED  '",
+                "NSIDE   = 1",
+                "COORDSYS= 'ECLIPTIC'",
+                "TSCAL1  = 0.5",
+                "TZERO1  = 10.0",
+                "TNULL1  = -32768",
+            ],
+        );
+
+        let map = HEALPixMap::from_fits(&bytes, None).unwrap();
+        assert_eq!(map.get_frame(), CooSystem::ECLJ2000);
+        let expected = (0..12)
+            .filter(|&pixel| pixel != 5)
+            .map(|pixel| (pixel, pixel as f32 * 0.5 + 10.0))
+            .collect::<Vec<_>>();
+        assert_eq!(observed(&map), expected);
+    }
+
+    #[test]
+    fn invalid_maps() {
+        let table = vec![0; 4 * 12];
+        let map = |columns: &[(&str, &str)], cards: &[&str], column: Option<&str>| {
+            let bytes = bintable(columns, 4, &table, cards);
+            HEALPixMap::from_fits(&bytes, column)
+        };
+        let signal = [("SIGNAL", "E")];
+        let nested = ["ORDERING= 'NESTED  '", "NSIDE   = 1"];
+
+        assert!(map(&signal, &nested, None).is_ok());
+        assert!(map(&signal, &["ORDERING= 'NESTED  '"], None).is_err());
+        assert!(map(&signal, &["ORDERING= 'NESTED  '", "NSIDE   = 3"], None).is_err());
+        assert!(map(&signal, &["ORDERING= 'NESTED  '", "NSIDE   = 8192"], None).is_err());
+        assert!(map(&signal, &["NSIDE   = 1"], None).is_err());
+        assert!(map(&signal, &["ORDERING= 'XY      '", "NSIDE   = 1"], None).is_err());
+        assert!(map(
+            &signal,
+            &[&nested[..], &["PIXTYPE = 'CAR     '"]].concat(),
+            None
+        )
+        .is_err());
+        assert!(map(
+            &signal,
+            &[&nested[..], &["COORDSYS= 'HORIZON '"]].concat(),
+            None
+        )
+        .is_err());
+        assert!(
+            matches!(map(&signal, &nested, Some("Q_POLARISATION")), Err(e) if e.contains("SIGNAL"))
+        );
+        // Columns that cannot be read or that are larger than NAXIS1
+        assert!(map(&[("NAME", "4A")], &nested, None).is_err());
+        assert!(map(&[("SIGNAL", "D")], &nested, None).is_err());
+        assert!(map(&[("SIGNAL", "Y")], &nested, None).is_err());
+
+        // No binary table
+        let bytes = fits(&["XTENSION= 'IMAGE   '", "BITPIX  = 8", "NAXIS   = 0"], &[]);
+        assert!(HEALPixMap::from_fits(&bytes, None).is_err());
+        // Truncated table
+        let bytes = bintable(&signal, 4, &table, &nested);
+        assert!(HEALPixMap::from_fits(&bytes[..(2 * 2880 + 20)], None).is_err());
+    }
+}
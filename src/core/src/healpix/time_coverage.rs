@@ -0,0 +1,256 @@
+use std::io::{BufRead, Cursor};
+
+use moclib::{
+    deser::{
+        fits::{self, MocIdxType, MocQtyType, STMocType},
+        json::{cellmoc2d_from_json_aladin, from_json_aladin},
+    },
+    elemset::range::MocRanges,
+    moc::{
+        range::RangeMOC, CellMOCIntoIterator, CellMOCIterator, RangeMOCIntoIterator,
+        RangeMOCIterator,
+    },
+    moc2d::{
+        range::RangeMOC2, CellMOC2IntoIterator, HasTwoMaxDepth, RangeMOC2ElemIt,
+        RangeMOC2IntoIterator, RangeMOC2Iterator,
+    },
+    qty::{Hpx, MocQty, Time},
+};
+
+use super::coverage::HEALPixCoverage;
+
+pub type Tmoc = RangeMOC<u64, Time<u64>>;
+pub type Stmoc = RangeMOC2<u64, Time<u64>, u64, Hpx<u64>>;
+
+// The indices of time MOCs are microseconds since JD 0
+const MICROSEC_PER_DAY: f64 = 86_400_000_000.0;
+
+fn jd_to_idx(jd: f64) -> u64 {
+    (jd.max(0.0) * MICROSEC_PER_DAY).min(Time::<u64>::n_cells_max() as f64) as u64
+}
+
+fn idx_to_jd(idx: u64) -> f64 {
+    idx as f64 / MICROSEC_PER_DAY
+}
+
+/// Coverage of time ranges
+#[derive(Clone, Debug)]
+pub struct TimeCoverage(pub Tmoc);
+
+impl TimeCoverage {
+    /// Coverage of the times between two julian days
+    pub fn from_jd_range(jd_min: f64, jd_max: f64) -> Self {
+        let range = jd_to_idx(jd_min)..jd_to_idx(jd_max);
+        let ranges = MocRanges::new_from(vec![range]);
+
+        TimeCoverage(RangeMOC::new(Time::<u64>::MAX_DEPTH, ranges))
+    }
+
+    /// Parse an IVOA FITS T-MOC
+    pub fn from_fits(data: &[u8]) -> Result<Self, String> {
+        let tmoc = match fits::from_fits_ivoa_custom(Cursor::new(data), false)
+            .map_err(|err| err.to_string())?
+        {
+            MocIdxType::U16(MocQtyType::Time(moc)) => moc.collect_to_u64::<Time<u64>>(),
+            MocIdxType::U32(MocQtyType::Time(moc)) => moc.collect_to_u64::<Time<u64>>(),
+            MocIdxType::U64(MocQtyType::Time(moc)) => moc.collect(),
+            _ => return Err("MOC not supported. Must be a time MOC".to_string()),
+        };
+
+        Ok(TimeCoverage(tmoc))
+    }
+
+    /// Parse a T-MOC in the Aladin JSON format
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let tmoc = from_json_aladin::<u64, Time<u64>>(json)
+            .map_err(|err| err.to_string())?
+            .into_cell_moc_iter()
+            .ranges()
+            .into_range_moc();
+
+        Ok(TimeCoverage(tmoc))
+    }
+
+    /// The time ranges covered, in julian days
+    pub fn jd_ranges(&self) -> Vec<[f64; 2]> {
+        (&self.0)
+            .into_range_moc_iter()
+            .map(|range| [idx_to_jd(range.start), idx_to_jd(range.end)])
+            .collect()
+    }
+}
+
+/// Space-time coverage, i.e. the sky areas covered during time ranges
+#[derive(Clone, Debug)]
+pub struct SpaceTimeCoverage(pub Stmoc);
+
+impl SpaceTimeCoverage {
+    /// Collect the ranges of a parsed FITS ST-MOC
+    pub fn from_fits_ranges<R: BufRead>(moc2: STMocType<u64, R>) -> Self {
+        let stmoc = match moc2 {
+            STMocType::V2(moc2) => {
+                RangeMOC2::new(moc2.depth_max_1(), moc2.depth_max_2(), moc2.collect())
+            }
+            STMocType::PreV2(moc2) => {
+                RangeMOC2::new(moc2.depth_max_1(), moc2.depth_max_2(), moc2.collect())
+            }
+        };
+
+        SpaceTimeCoverage(stmoc)
+    }
+
+    /// Parse an ST-MOC in the Aladin JSON format, e.g. `[{"t": {...}, "s": {...}}]`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let stmoc = cellmoc2d_from_json_aladin::<u64, Time<u64>, u64, Hpx<u64>>(json)
+            .map_err(|err| err.to_string())?
+            .into_cell_moc2_iter()
+            .into_range_moc2_iter()
+            .into_range_moc2();
+
+        Ok(SpaceTimeCoverage(stmoc))
+    }
+
+    /// The first and last times covered, in julian days
+    pub fn jd_range(&self) -> Option<[f64; 2]> {
+        self.0
+            .global_range_left()
+            .map(|range| [idx_to_jd(range.start), idx_to_jd(range.end)])
+    }
+
+    /// Union of the sky areas covered during the given times
+    pub fn spatial_coverage(&self, times: &TimeCoverage) -> HEALPixCoverage {
+        let depth = self.0.depth_max_2();
+
+        (&self.0)
+            .into_range_moc2_iter()
+            .filter_map(|elem| {
+                let (elem_times, elem_cells) = elem.range_mocs_it();
+                let elem_times = elem_times.into_range_moc();
+
+                if elem_times.intersection(&times.0).is_empty() {
+                    None
+                } else {
+                    Some(elem_cells.into_range_moc())
+                }
+            })
+            .fold(HEALPixCoverage::empty(depth), |coverage, cells| {
+                HEALPixCoverage(coverage.0.union(&cells))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HEALPixCoverage, SpaceTimeCoverage, TimeCoverage};
+    use moclib::deser::fits::{self, MocIdxType, MocQtyType};
+    use moclib::moc::{RangeMOCIntoIterator, RangeMOCIterator};
+    use std::io::Cursor;
+
+    // Two nights observing different parts of the sky, the time cells at order 29 lasting
+    // about 1.2 hour: from JD 2460000.481 to 2460000.879 and from JD 2460001.475 to 2460001.873
+    const STMOC: &str = r#"[
+        {
+            "t": {"29": [
+                49486766, 49486767, 49486768, 49486769, 49486770, 49486771, 49486772, 49486773
+            ]},
+            "s": {"3": [1, 2]}
+        },
+        {
+            "t": {"29": [
+                49486786, 49486787, 49486788, 49486789, 49486790, 49486791, 49486792, 49486793
+            ]},
+            "s": {"3": [100]}
+        }
+    ]"#;
+
+    fn cells(moc: &HEALPixCoverage) -> Vec<u64> {
+        moc.0.flatten_to_fixed_depth_cells().collect()
+    }
+
+    #[test]
+    fn time_ranges() {
+        let tmoc = TimeCoverage::from_jd_range(2_460_000.5, 2_460_001.0);
+        let ranges = tmoc.jd_ranges();
+        assert_eq!(ranges.len(), 1);
+        assert!((ranges[0][0] - 2_460_000.5).abs() < 1e-6);
+        assert!((ranges[0][1] - 2_460_001.0).abs() < 1e-6);
+
+        let tmoc = TimeCoverage::from_json(r#"{"29": [49486766, 49486767]}"#).unwrap();
+        let ranges = tmoc.jd_ranges();
+        assert_eq!(ranges.len(), 1);
+        assert!((ranges[0][0] - 2_460_000.481).abs() < 1e-3);
+    }
+
+    #[test]
+    fn spatial_coverage_during_times() {
+        let stmoc = SpaceTimeCoverage::from_json(STMOC).unwrap();
+
+        let [jd_min, jd_max] = stmoc.jd_range().unwrap();
+        assert!((jd_min - 2_460_000.481).abs() < 1e-3);
+        assert!((jd_max - 2_460_001.873).abs() < 1e-3);
+
+        let during = |jd_min: f64, jd_max: f64| {
+            cells(&stmoc.spatial_coverage(&TimeCoverage::from_jd_range(jd_min, jd_max)))
+        };
+        assert_eq!(during(jd_min, jd_max), vec![1, 2, 100]);
+        assert_eq!(during(2_460_000.5, 2_460_000.6), vec![1, 2]);
+        assert!(during(2_460_001.0, 2_460_001.4).is_empty());
+        assert_eq!(during(2_460_001.5, 2_460_001.6), vec![100]);
+        assert_eq!(
+            stmoc
+                .spatial_coverage(&TimeCoverage::from_jd_range(0.0, 1e8))
+                .depth(),
+            3
+        );
+    }
+
+    #[test]
+    fn tmoc_fits_round_trip() {
+        let tmoc = TimeCoverage::from_json(
+            r#"{"29": [49486766, 49486767, 49486768, 49486786, 49486787]}"#,
+        )
+        .unwrap();
+
+        let mut buf = Vec::new();
+        (&tmoc.0)
+            .into_range_moc_iter()
+            .to_fits_ivoa(None, None, &mut buf)
+            .unwrap();
+        let read = TimeCoverage::from_fits(&buf).unwrap();
+        assert_eq!(read.0, tmoc.0);
+        assert_eq!(read.jd_ranges().len(), 2);
+
+        // A spatial MOC is rejected
+        let moc = HEALPixCoverage::allsky(3);
+        assert!(TimeCoverage::from_fits(&moc.to_fits().unwrap()).is_err());
+    }
+
+    #[test]
+    fn stmoc_fits_round_trip() {
+        let stmoc = SpaceTimeCoverage::from_json(STMOC).unwrap();
+
+        let mut buf = Vec::new();
+        fits::rangemoc2d_to_fits_ivoa(&stmoc.0, None, None, &mut buf).unwrap();
+        let read = match fits::from_fits_ivoa_custom(Cursor::new(&buf[..]), false).unwrap() {
+            MocIdxType::U64(MocQtyType::TimeHpx(moc2)) => SpaceTimeCoverage::from_fits_ranges(moc2),
+            _ => panic!("Not a space-time MOC"),
+        };
+
+        assert_eq!(read.jd_range(), stmoc.jd_range());
+        for [jd_min, jd_max] in [
+            [2_460_000.0, 2_460_002.0],
+            [2_460_000.5, 2_460_000.6],
+            [2_460_001.5, 2_460_001.6],
+        ] {
+            let times = TimeCoverage::from_jd_range(jd_min, jd_max);
+            assert_eq!(
+                cells(&read.spatial_coverage(&times)),
+                cells(&stmoc.spatial_coverage(&times))
+            );
+        }
+        assert_eq!(
+            cells(&read.spatial_coverage(&TimeCoverage::from_jd_range(2_460_000.0, 2_460_002.0))),
+            vec![1, 2, 100]
+        );
+    }
+}
@@ -85,12 +85,13 @@ mod utils;
 
 use math::projection::*;
 
-use moclib::moc::RangeMOCIntoIterator;
 //use votable::votable::VOTableWrapper;
 use crate::tile_fetcher::HiPSLocalFiles;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlElement;
 use al_api::moc::MOCOptions;
+use al_api::projection::ProjectionCfg;
+use serde::Serialize;
 
 use crate::math::angle::ToAngle;
 
@@ -123,7 +124,8 @@ use std::io::Cursor;
 
 use al_api::color::{Color, ColorRGBA};
 use al_api::coo_system::CooSystem;
-use al_api::hips::HiPSProperties;
+use al_api::epoch::ProperMotionCfg;
+use al_api::hips::{HEALPixMapCfg, HiPSProperties};
 
 use al_core::colormap::Colormaps;
 use al_core::Colormap;
@@ -132,7 +134,9 @@ use al_core::WebGlContext;
 use app::App;
 use cgmath::{Vector2, Vector4};
 
-use crate::healpix::cell::HEALPixCell;
+use crate::healpix::cell::{HEALPixCell, MAX_HPX_DEPTH};
+use crate::healpix::map::HEALPixMap;
+use crate::healpix::time_coverage::{SpaceTimeCoverage, TimeCoverage};
 use math::angle::ArcDeg;
 use moclib::{
     moc::{CellMOCIntoIterator, CellMOCIterator, RangeMOCIterator},
@@ -227,80 +231,28 @@ impl WebClient {
     ///
     /// # Arguments
     ///
-    /// * `name` - Can be aitoff, mollweide, arc, sinus, tan or mercator
+    /// * `name` - The WCS code of the projection, e.g. AIT, MOL, SIN, TAN, ZEA, STG, MER, ARC, AIR,
+    ///   AZP, ZPN, CAR, CEA, SFL, PAR, COD or HPX
     #[wasm_bindgen(js_name = setProjection)]
     pub fn set_projection(&mut self, projection: &str) -> Result<(), JsValue> {
-        match projection {
-            // Zenithal
-            "TAN" => self
-                .app
-                .set_projection(ProjectionType::Tan(mapproj::zenithal::tan::Tan::new())), /* Gnomonic projection      */
-            "STG" => self
-                .app
-                .set_projection(ProjectionType::Stg(mapproj::zenithal::stg::Stg::new())), /* Stereographic projection */
-            "SIN" => self
-                .app
-                .set_projection(ProjectionType::Sin(mapproj::zenithal::sin::Sin::new())), /* Orthographic		         */
-            "ZEA" => self
-                .app
-                .set_projection(ProjectionType::Zea(mapproj::zenithal::zea::Zea::new())), /* Equal-area 		         */
-            /*"FEYE" => self
-                .app
-                .set_projection(ProjectionType::Feye(mapproj::zenithal::feye::Feye::new())),
-            "AIR" => {
-                let air_proj = mapproj::zenithal::air::Air::new();
-                //air_proj.set_n_iter(10);
-                //air_proj.set_eps(1e-12);
-                self.app.set_projection(ProjectionType::Air(air_proj))
-            }*/
-            //"AZP",
-            /*"ARC" => self
-                .app
-                .set_projection(ProjectionType::Arc(mapproj::zenithal::arc::Arc::new())),
-            "NCP" => self
-                .app
-                .set_projection(ProjectionType::Ncp(mapproj::zenithal::ncp::Ncp::new())),*/
-            // Cylindrical
-            "MER" => self
-                .app
-                .set_projection(ProjectionType::Mer(mapproj::cylindrical::mer::Mer::new())),
-            /*"CAR" => self
-                .app
-                .set_projection(ProjectionType::Car(mapproj::cylindrical::car::Car::new())),
-            "CEA" => self
-                .app
-                .set_projection(ProjectionType::Cea(mapproj::cylindrical::cea::Cea::new())),
-            "CYP" => self
-                .app
-                .set_projection(ProjectionType::Cyp(mapproj::cylindrical::cyp::Cyp::new())),*/
-            // Pseudo-cylindrical
-            "AIT" => self
-                .app
-                .set_projection(ProjectionType::Ait(mapproj::pseudocyl::ait::Ait::new())),
-            /*"PAR" => self
-                .app
-                .set_projection(ProjectionType::Par(mapproj::pseudocyl::par::Par::new())),
-            "SFL" => self
-                .app
-                .set_projection(ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl::new())),*/
-            "MOL" => {
-                let mut mol_proj = mapproj::pseudocyl::mol::Mol::new();
-                mol_proj.set_n_iter(10);
-                mol_proj.set_epsilon(1e-12);
-
-                self.app.set_projection(ProjectionType::Mol(mol_proj))
-            } // Conic
-            /*"COD" => self
-                .app
-                .set_projection(ProjectionType::Cod(mapproj::conic::cod::Cod::new())),
-            // Hybrid
-            "HPX" => self
-                .app
-                .set_projection(ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx::new())),*/
-            _ => Err(JsValue::from_str(
-                "Not a valid projection name. AIT, ZEA, SIN, STG, TAN, MOL and MER are accepted",
-            )),
-        }
+        let projection = ProjectionType::from_cfg(&ProjectionCfg::new(projection))?;
+
+        self.app.set_projection(projection)
+    }
+
+    /// Set the projection with its parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - An object with the WCS code of the projection in `name` and the optional
+    ///   parameters `standardParallels` (COD, in degrees), `pv` (ZPN polynomial coefficients)
+    ///   and `mu` (AZP distance of the point of projection, in sphere radius units)
+    #[wasm_bindgen(js_name = setProjectionCfg)]
+    pub fn set_projection_cfg(&mut self, cfg: JsValue) -> Result<(), JsValue> {
+        let cfg: ProjectionCfg = serde_wasm_bindgen::from_value(cfg)?;
+        let projection = ProjectionType::from_cfg(&cfg)?;
+
+        self.app.set_projection(projection)
     }
 
     /*
@@ -350,6 +302,112 @@ impl WebClient {
         Ok(())
     }
 
+    /// Add a layer from a HEALPix map stored in a FITS binary table
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The layer name, the identifier of the HiPS rendering the map, the optional
+    ///   name of the column to display and the color metadata of the layer
+    /// * `data` - The bytes of the FITS file
+    ///
+    /// Returns the 0.5% and 99.5% percentiles of the map
+    #[wasm_bindgen(js_name = addHEALPixMap)]
+    pub fn add_healpix_map(&mut self, cfg: JsValue, data: &[u8]) -> Result<Box<[f32]>, JsValue> {
+        let cfg: HEALPixMapCfg = serde_wasm_bindgen::from_value(cfg)?;
+        let map = HEALPixMap::from_fits(data, cfg.column.as_deref())
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        let cuts = self.app.add_healpix_map(cfg, map)?;
+        Ok(Box::new([cuts.start, cuts.end]))
+    }
+
+    /// Generate a HiPS in memory from a FITS image layer and add it as a new layer
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The layer name, the identifier and the title of the HiPS, the layer of the
+    ///   FITS image, an optional max order and the color metadata of the HiPS
+    ///
+    /// Returns the 0.5% and 99.5% percentiles of the pixels of the HiPS
+    #[wasm_bindgen(js_name = addGeneratedHiPS)]
+    pub fn add_generated_hips(&mut self, cfg: JsValue) -> Result<Box<[f32]>, JsValue> {
+        let cfg = serde_wasm_bindgen::from_value(cfg)?;
+
+        let cuts = self.app.add_generated_hips(cfg)?;
+        Ok(Box::new([cuts.start, cuts.end]))
+    }
+
+    /// The paths of the files of a generated HiPS relative to its root
+    /// (properties, MOC, Allsky and tiles of every order)
+    #[wasm_bindgen(js_name = getGeneratedHiPSFilePaths)]
+    pub fn get_generated_hips_file_paths(
+        &self,
+        creator_did: String,
+    ) -> Result<Vec<String>, JsValue> {
+        self.app.get_generated_hips_file_paths(&creator_did)
+    }
+
+    /// The content of a file of a generated HiPS
+    ///
+    /// # Arguments
+    ///
+    /// * `creator_did` - The identifier of the HiPS
+    /// * `path` - The path of the file relative to the root of the HiPS, e.g. `Norder3/Allsky.fits`
+    #[wasm_bindgen(js_name = getGeneratedHiPSFile)]
+    pub fn get_generated_hips_file(
+        &self,
+        creator_did: String,
+        path: String,
+    ) -> Result<Box<[u8]>, JsValue> {
+        self.app
+            .get_generated_hips_file(&creator_did, &path)
+            .map(|file| file.into_boxed_slice())
+    }
+
+    /// Add a layer composing three single-band layers into a true-color rendering
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The layer name, the layers of the red, green and blue channels,
+    ///   their weights, the optional Lupton et al. (2004) asinh composite parameters and the
+    ///   color metadata of the composite. Adding it again under the same name updates it.
+    #[wasm_bindgen(js_name = addRGBComposite)]
+    pub fn add_rgb_composite(&mut self, cfg: JsValue) -> Result<(), JsValue> {
+        let cfg = serde_wasm_bindgen::from_value(cfg)?;
+        self.app.add_rgb_composite(cfg)
+    }
+
+    /// Add a layer computing a per-pixel expression between two layers
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The layer name, the layers of the operands A and B, the operation
+    ///   (`difference` for A - B, `ratio` for A / B or `mask` for A × B) and the color
+    ///   metadata of the result. Adding it again under the same name updates it.
+    #[wasm_bindgen(js_name = addLayerArithmetic)]
+    pub fn add_layer_arithmetic(&mut self, cfg: JsValue) -> Result<(), JsValue> {
+        let cfg = serde_wasm_bindgen::from_value(cfg)?;
+        self.app.add_layer_arithmetic(cfg)
+    }
+
+    /// Compare two layers
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The layers A and B and the comparison mode: `blink` with its period in seconds,
+    ///   `crossFade` with the ratio of B or `swipe` with the position of the line splitting the view.
+    ///   null stops the comparison.
+    #[wasm_bindgen(js_name = setLayerComparison)]
+    pub fn set_layer_comparison(&mut self, cfg: JsValue) -> Result<(), JsValue> {
+        let cfg = if cfg.is_null() || cfg.is_undefined() {
+            None
+        } else {
+            Some(serde_wasm_bindgen::from_value(cfg)?)
+        };
+
+        self.app.set_layer_comparison(cfg)
+    }
+
     #[wasm_bindgen(js_name = addImageFITS)]
     pub fn add_image_fits(
         &mut self,
@@ -449,6 +507,109 @@ impl WebClient {
         self.app.set_grid_cfg(cfg)
     }
 
+    /// Set the horizon overlay options
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - An object with the optional fields `color`, `opacity`, `thickness`,
+    ///   `showLabels`, `labelSize`, `enabled` and `airmass`, the latter being the list
+    ///   of airmasses for which an iso-line is drawn.
+    #[wasm_bindgen(js_name = setHorizonOptions)]
+    pub fn set_horizon_cfg(&mut self, cfg: JsValue) -> Result<(), JsValue> {
+        let cfg = serde_wasm_bindgen::from_value(cfg)?;
+
+        self.app.set_horizon_cfg(cfg)
+    }
+
+    /// Trace the contours of a FITS image or FITS HiPS layer
+    ///
+    /// The contours of a HiPS are traced on its tiles in the view, fetched if needed.
+    ///
+    /// # Returns
+    ///
+    /// A promise resolved with the levels of the contours once they are traced
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the layer
+    /// * `uuid` - The identifier of the contours, replacing the contours having the same
+    /// * `levels` - An object whose `method` field is `values` (with the `values` array),
+    ///   `linear` or `log` (with the number of levels `num`) or `sigma` (with the `sigmas`
+    ///   array giving the levels in standard deviations above the background)
+    /// * `style` - An object with the `color`, `opacity`, `thickness` and `show` fields
+    /// * `depth` - The order of the HiPS tiles, the one of the tiles in the view if not given
+    #[wasm_bindgen(js_name = addContours)]
+    pub fn add_contours(
+        &mut self,
+        layer: String,
+        uuid: String,
+        levels: JsValue,
+        style: JsValue,
+        depth: Option<u8>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let levels = serde_wasm_bindgen::from_value(levels)?;
+        let style = serde_wasm_bindgen::from_value(style)?;
+
+        self.app.add_contours(&layer, uuid, levels, style, depth)
+    }
+
+    #[wasm_bindgen(js_name = removeContours)]
+    pub fn remove_contours(&mut self, uuid: String) -> Result<(), JsValue> {
+        self.app.remove_contours(&uuid)
+    }
+
+    /// Set the color, opacity, thickness and visibility of contours
+    #[wasm_bindgen(js_name = setContoursStyle)]
+    pub fn set_contours_style(&mut self, uuid: String, style: JsValue) -> Result<(), JsValue> {
+        let style = serde_wasm_bindgen::from_value(style)?;
+
+        self.app.set_contours_style(&uuid, style)
+    }
+
+    /// Set the observer defining the horizontal (Alt-Az) frame
+    ///
+    /// In that frame, the latitude is the altitude and the longitude
+    /// is 360° minus the azimuth (counted from the north toward the east).
+    ///
+    /// # Arguments
+    ///
+    /// * `lon` - Geodetic longitude of the observer in degrees, positive toward the east
+    /// * `lat` - Geodetic latitude of the observer in degrees
+    /// * `height` - Height of the observer above the sea level in meters
+    /// * `utc_ms` - UTC date in milliseconds since the Unix epoch (e.g. `Date.now()`)
+    #[wasm_bindgen(js_name = setObserver)]
+    pub fn set_observer(
+        &mut self,
+        lon: f64,
+        lat: f64,
+        height: f64,
+        utc_ms: f64,
+    ) -> Result<(), JsValue> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(JsValue::from_str(
+                "The latitude of the observer must be between -90 and 90 degrees",
+            ));
+        }
+
+        let observer = crate::math::horizontal::Observer { lon, lat, height };
+        self.app.set_observer(observer, utc_ms);
+
+        Ok(())
+    }
+
+    /// Set the UTC date of the horizontal (Alt-Az) frame
+    ///
+    /// The frame is computed for a given date, this moves it to another one
+    /// without changing the observer.
+    ///
+    /// # Arguments
+    ///
+    /// * `utc_ms` - UTC date in milliseconds since the Unix epoch (e.g. `Date.now()`)
+    #[wasm_bindgen(js_name = setObserverTime)]
+    pub fn set_observer_time(&mut self, utc_ms: f64) -> Result<(), JsValue> {
+        self.app.set_observer_time(utc_ms)
+    }
+
     /// Set the coordinate system for the view
     ///
     /// # Arguments
@@ -469,6 +630,27 @@ impl WebClient {
         Ok(fov)
     }
 
+    /// Get the FITS World Coordinate System of the view
+    ///
+    /// This returns a javascript object whose keys are the keywords of a FITS header
+    /// (CTYPE, CRPIX, CRVAL, CD, LONPOLE, LATPOLE, RADESYS, PV...).
+    /// Its pixels are the ones of the canvas, the center of the bottom left pixel being
+    /// located at (1, 1).
+    #[wasm_bindgen(js_name = getViewWCS)]
+    pub fn get_view_wcs(&self) -> Result<JsValue, JsValue> {
+        let params = self.app.get_view_wcs()?;
+
+        let mut header = serde_json::to_value(&params)
+            .map_err(|err| JsValue::from_str(&format!("{:?}", err)))?;
+        // Remove the keywords that are not defined
+        if let Some(keywords) = header.as_object_mut() {
+            keywords.retain(|_, value| !value.is_null());
+        }
+
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        Ok(header.serialize(&serializer)?)
+    }
+
     /// Set the field of view
     ///
     /// # Arguments
@@ -617,6 +799,26 @@ impl WebClient {
         Box::new([lon_deg.0, lat_deg.0])
     }
 
+    /// Coosys conversion between two frames
+    ///
+    /// # Arguments
+    ///
+    /// * `lon` - A longitude in degrees
+    /// * `lat` - A latitude in degrees
+    /// * `from` - The frame in which the coordinates are given
+    /// * `to` - The frame in which the coordinates are returned
+    #[wasm_bindgen(js_name = convertCooSys)]
+    pub fn convert_coosys(&self, lon: f64, lat: f64, from: CooSystem, to: CooSystem) -> Box<[f64]> {
+        let lonlat = LonLatT::new(ArcDeg(lon).into(), ArcDeg(lat).into());
+
+        let res = self.app.convert_coosys(&lonlat, from, to);
+
+        let lon_deg: ArcDeg<f64> = res.lon().into();
+        let lat_deg: ArcDeg<f64> = res.lat().into();
+
+        Box::new([lon_deg.0, lat_deg.0])
+    }
+
     /// World to screen projection
     ///
     /// Coordinates must be given in the ICRS or FK5J2000 coo system
@@ -638,7 +840,8 @@ impl WebClient {
             use crate::math::lonlat::LonLat;
             let xyz =
                 LonLatT::new(lon.to_radians().to_angle(), lat.to_radians().to_angle()).vector();
-            let lonlat = coosys::apply_coo_system(frame, CooSystem::ICRS, &xyz).lonlat();
+            let icrs2hor = self.app.get_icrs_to_horizontal();
+            let lonlat = coosys::apply_coo_system(frame, CooSystem::ICRS, icrs2hor, &xyz).lonlat();
             lon = lonlat.lon().to_degrees();
             lat = lonlat.lat().to_degrees();
         }
@@ -657,6 +860,116 @@ impl WebClient {
         .to_degrees()
     }
 
+    /// Precess equatorial positions from an equinox to another
+    ///
+    /// The IAU 2006 precession model is used. Positions are referred
+    /// to the mean equator and equinox unless `nutation` is set.
+    ///
+    /// # Returns
+    ///
+    /// A flat array of the precessed positions, i.e. [lon0, lat0, lon1, lat1, ...]
+    ///
+    /// # Arguments
+    ///
+    /// * `lon` - The right ascensions in degrees
+    /// * `lat` - The declinations in degrees
+    /// * `from_equinox` - The equinox of the positions given, in Julian years (e.g. 2000.0)
+    /// * `to_equinox` - The equinox to precess the positions to, in Julian years
+    /// * `nutation` - Refer the positions to the true equator and equinox of date
+    #[wasm_bindgen(js_name = precess)]
+    pub fn precess(
+        &self,
+        lon: &[f64],
+        lat: &[f64],
+        from_equinox: f64,
+        to_equinox: f64,
+        nutation: Option<bool>,
+    ) -> Result<Box<[f64]>, JsValue> {
+        if lon.len() != lat.len() {
+            return Err(JsValue::from_str("lon and lat arrays must have the same length"));
+        }
+
+        let m = crate::math::epoch::precession_matrix(
+            from_equinox,
+            to_equinox,
+            nutation.unwrap_or(false),
+        );
+
+        let lonlat = lon
+            .iter()
+            .zip(lat.iter())
+            .flat_map(|(&lon, &lat)| {
+                use crate::math::lonlat::LonLat;
+                let xyzw: Vector4<f64> =
+                    LonLatT::new(lon.to_radians().to_angle(), lat.to_radians().to_angle())
+                        .vector();
+                let lonlat = (m * xyzw).lonlat();
+
+                [
+                    lonlat.lon().to_degrees().rem_euclid(360.0),
+                    lonlat.lat().to_degrees(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        Ok(lonlat.into_boxed_slice())
+    }
+
+    /// Propagate positions to another epoch
+    ///
+    /// Sources are propagated with their proper motions, parallaxes and radial velocities
+    /// along a straight line at constant space velocity. This is typically used to bring
+    /// a catalog (e.g. Gaia at epoch 2016.0) to the epoch of an image before plotting it.
+    ///
+    /// # Returns
+    ///
+    /// A flat array of the propagated positions, i.e. [ra0, dec0, ra1, dec1, ...]
+    ///
+    /// # Arguments
+    ///
+    /// * `ra` - The right ascensions in degrees
+    /// * `dec` - The declinations in degrees
+    /// * `cfg` - The proper motions (`pmra`, `pmdec` in mas/yr), the optional `parallax` (mas)
+    ///   and `rv` (km/s) arrays, and the `fromEpoch` and `toEpoch` in Julian years
+    #[wasm_bindgen(js_name = propagateProperMotion)]
+    pub fn propagate_proper_motion(
+        &self,
+        ra: &[f64],
+        dec: &[f64],
+        cfg: JsValue,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let cfg: ProperMotionCfg = serde_wasm_bindgen::from_value(cfg)?;
+
+        let n = ra.len();
+        let same_len = |a: Option<&Vec<f64>>| a.map(|a| a.len() == n).unwrap_or(true);
+        if dec.len() != n
+            || !(same_len(Some(&cfg.pmra))
+                && same_len(Some(&cfg.pmdec))
+                && same_len(cfg.parallax.as_ref())
+                && same_len(cfg.rv.as_ref()))
+        {
+            return Err(JsValue::from_str("astrometric arrays must have the same length"));
+        }
+
+        let radec = (0..n)
+            .flat_map(|i| {
+                let src = crate::math::epoch::Astrometry {
+                    ra: ra[i],
+                    dec: dec[i],
+                    pmra: cfg.pmra[i],
+                    pmdec: cfg.pmdec[i],
+                    parallax: cfg.parallax.as_ref().map(|p| p[i]).unwrap_or(0.0),
+                    rv: cfg.rv.as_ref().map(|rv| rv[i]).unwrap_or(0.0),
+                }
+                .propagate(cfg.from_epoch, cfg.to_epoch);
+
+                [src.ra, src.dec]
+            })
+            .collect::<Vec<_>>();
+
+        Ok(radec.into_boxed_slice())
+    }
+
     #[wasm_bindgen(js_name = screenToClip)]
     pub fn screen_to_clip(&self, x: f64, y: f64) -> Box<[f64]> {
         let v = self.app.screen_to_clip(&Vector2::new(x, y));
@@ -730,8 +1043,13 @@ impl WebClient {
                 if let Some(frame) = frame {
                     use crate::math::lonlat::LonLat;
                     let xyz = lonlat.vector();
-                    lonlat =
-                        coosys::apply_coo_system(self.app.get_coo_system(), frame, &xyz).lonlat();
+                    lonlat = coosys::apply_coo_system(
+                        self.app.get_coo_system(),
+                        frame,
+                        self.app.get_icrs_to_horizontal(),
+                        &xyz,
+                    )
+                    .lonlat();
                 }
 
                 let lon_deg: ArcDeg<f64> = lonlat.lon().into();
@@ -940,27 +1258,252 @@ impl WebClient {
     ///
     /// # Returns
     ///
+    /// For HiPS layers:
     /// - An array of 3 items (rgb) for JPG tiles
     /// - An array of 4 items (rgba) for PNG tiles
     /// - A single value for FITS tiles
     ///
+    /// For image layers, an object containing the `value` of the pixel (the physical
+    /// value for FITS images, null for BLANK pixels) and its `x`, `y` position in the image
+    ///
     /// # Arguments
     ///
     /// * `x` - The x screen coordinate in pixels
     /// * `y` - The y screen coordinate in pixels
-    /// * `base_url` - The base url of the hips identifying it
+    /// * `layer` - The name of the layer
     #[wasm_bindgen(js_name = readPixel)]
     pub fn read_pixel(&self, x: f64, y: f64, layer: String) -> Result<JsValue, JsValue> {
         let pixel = self.app.read_pixel(&Vector2::new(x, y), layer.as_str())?;
         Ok(pixel)
     }
 
+    /// Read the pixel value at a sky position
+    ///
+    /// Same as `readPixel` but the pixel is given by its sky position.
+    ///
+    /// # Arguments
+    ///
+    /// * `lon` - A longitude in degrees
+    /// * `lat` - A latitude in degrees
+    /// * `layer` - The name of the layer
+    /// * `frame` - The frame of the position, ICRS if not given
+    #[wasm_bindgen(js_name = readPixelFromWorld)]
+    pub fn read_pixel_from_world(
+        &self,
+        lon: f64,
+        lat: f64,
+        layer: String,
+        frame: Option<CooSystem>,
+    ) -> Result<JsValue, JsValue> {
+        use crate::math::lonlat::LonLat;
+        let xyz = LonLatT::new(lon.to_radians().to_angle(), lat.to_radians().to_angle()).vector();
+        // Positions are read in the frame of the view
+        let lonlat = coosys::apply_coo_system(
+            frame.unwrap_or(CooSystem::ICRS),
+            self.app.get_coo_system(),
+            self.app.get_icrs_to_horizontal(),
+            &xyz,
+        )
+        .lonlat();
+
+        self.app.read_pixel_from_lonlat(&lonlat, layer.as_str())
+    }
+
+    /// Read the pixel values of a layer at several sky positions
+    ///
+    /// HiPS tiles containing the positions are fetched if needed.
+    ///
+    /// # Returns
+    ///
+    /// A promise resolved with an array giving, for each position, the value as returned by
+    /// `readPixel` or null if it could not be read (e.g. the tile does not exist or the
+    /// position lies outside the image)
+    ///
+    /// # Arguments
+    ///
+    /// * `lon` - The longitudes in degrees
+    /// * `lat` - The latitudes in degrees
+    /// * `layer` - The name of the layer
+    /// * `frame` - The frame of the positions, ICRS if not given
+    /// * `depth` - The order of the HiPS tiles to sample, the deepest one if not given
+    #[wasm_bindgen(js_name = readPixelsFromWorld)]
+    pub fn read_pixels_from_world(
+        &mut self,
+        lon: &[f64],
+        lat: &[f64],
+        layer: String,
+        frame: Option<CooSystem>,
+        depth: Option<u8>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let positions = lon
+            .iter()
+            .zip(lat.iter())
+            .map(|(&lon, &lat)| {
+                LonLatT::new(lon.to_radians().to_angle(), lat.to_radians().to_angle()).vector()
+            })
+            .collect::<Vec<_>>();
+
+        self.app.read_pixels(
+            &positions,
+            frame.unwrap_or(CooSystem::ICRS),
+            layer.as_str(),
+            depth,
+        )
+    }
+
+    /// Reproject the tiles of a HiPS layer into a FITS image
+    ///
+    /// HiPS tiles covering the image are fetched if needed.
+    ///
+    /// # Returns
+    ///
+    /// A promise resolved with the bytes of the FITS file. Its pixels are stored with the
+    /// BITPIX, BSCALE, BZERO and BLANK of the tiles and its header contains the WCS given.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the HiPS layer
+    /// * `wcs` - The WCS of the image, a javascript object whose keys are FITS keywords
+    ///   (NAXIS1, NAXIS2, CTYPE1, CRVAL1...) as returned by `getViewWCS`. Its frame
+    ///   is given by CTYPEi, RADESYS and EQUINOX, the SIP distortions are not supported
+    /// * `depth` - The order of the HiPS tiles to sample, the one whose pixels are as small
+    ///   as the ones of the image if not given
+    #[wasm_bindgen(js_name = getFITSCutout)]
+    pub fn get_fits_cutout(
+        &mut self,
+        layer: String,
+        wcs: JsValue,
+        depth: Option<u8>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let params: wcs::WCSParams = serde_wasm_bindgen::from_value(wcs)?;
+
+        self.app.get_fits_cutout(&layer, &params, depth)
+    }
+
+    /// Compute the statistics of the pixels of a layer lying in a sky region
+    ///
+    /// # Returns
+    ///
+    /// A promise resolved with the count, sum, mean, median, standard deviation, min and max
+    /// of the pixel values along with their histogram. Blank pixels are not taken into account
+    /// and the mean of the red, green and blue components is used for color pixels.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - A circle `{type: "circle", ra, dec, radius}`, a box
+    ///   `{type: "box", ra, dec, width, height, angle}`, a polygon `{type: "polygon", vertices}`
+    ///   or a MOC `{type: "moc", uuid}`. Angles are given in degrees.
+    /// * `layer` - The name of the layer
+    /// * `frame` - The frame of the region, ICRS if not given
+    /// * `depth` - The order of the HiPS tiles to sample, the deepest one if not given
+    /// * `num_bins` - The number of bins of the histogram, 100 if not given
+    #[wasm_bindgen(js_name = getRegionStatistics)]
+    pub fn get_region_statistics(
+        &mut self,
+        region: JsValue,
+        layer: String,
+        frame: Option<CooSystem>,
+        depth: Option<u8>,
+        num_bins: Option<usize>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let region: al_api::stats::Region = serde_wasm_bindgen::from_value(region)?;
+
+        self.app.get_region_statistics(
+            region,
+            frame.unwrap_or(CooSystem::ICRS),
+            layer.as_str(),
+            depth,
+            num_bins.unwrap_or(100),
+        )
+    }
+
+    /// Sample the pixels of a layer along a great-circle segment
+    ///
+    /// # Returns
+    ///
+    /// A promise resolved with the positions of the samples `lon` and `lat`, their angular
+    /// distance `dist` from the start of the segment (all in degrees) and their `values`,
+    /// null for blank pixels or positions outside the layer
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The (lon, lat) start of the segment in degrees
+    /// * `end` - The (lon, lat) end of the segment in degrees
+    /// * `layer` - The name of the layer
+    /// * `frame` - The frame of the segment, ICRS if not given
+    /// * `depth` - The order of the HiPS tiles to sample, the deepest one if not given
+    /// * `num_samples` - The number of samples, the segment is sampled at the resolution
+    ///   of the layer if not given
+    #[wasm_bindgen(js_name = getLineProfile)]
+    pub fn get_line_profile(
+        &mut self,
+        start: &[f64],
+        end: &[f64],
+        layer: String,
+        frame: Option<CooSystem>,
+        depth: Option<u8>,
+        num_samples: Option<usize>,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let to_lonlat = |lonlat: &[f64]| match lonlat {
+            &[lon, lat] => Ok(LonLatT::new(
+                lon.to_radians().to_angle(),
+                lat.to_radians().to_angle(),
+            )),
+            _ => Err(JsValue::from_str("A position must be given as a [lon, lat] array")),
+        };
+        let start = to_lonlat(start)?;
+        let end = to_lonlat(end)?;
+
+        self.app.get_line_profile(
+            &start,
+            &end,
+            frame.unwrap_or(CooSystem::ICRS),
+            layer.as_str(),
+            depth,
+            num_samples,
+        )
+    }
+
     #[wasm_bindgen(js_name = getVisibleCells)]
     pub fn get_visible_cells(&self, depth: u8) -> Result<JsValue, JsValue> {
         let cells = self.app.get_visible_cells(depth);
         Ok(serde_wasm_bindgen::to_value(&cells)?)
     }
 
+    /// Get the coverage of the view as a MOC serialized in the Aladin JSON format
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The depth of the MOC, 9 levels deeper than the tiles in the view minus one if not given
+    #[wasm_bindgen(js_name = getViewMOC)]
+    pub fn get_view_moc(&self, depth: Option<u8>) -> Result<JsValue, JsValue> {
+        let depth = depth.unwrap_or_else(|| self.app.get_moc_depth());
+        if depth > MAX_HPX_DEPTH {
+            return Err(JsValue::from_str("The depth of a MOC must be lower than 30"));
+        }
+
+        let json = self
+            .app
+            .get_view_moc(depth)
+            .to_json()
+            .map_err(|err| JsValue::from_str(&err))?;
+
+        js_sys::JSON::parse(&json)
+    }
+
+    /// Get the coverage of the tiles of a HiPS layer in the view as a MOC
+    /// serialized in the Aladin JSON format
+    #[wasm_bindgen(js_name = getLayerViewMOC)]
+    pub fn get_layer_view_moc(&self, layer: String) -> Result<JsValue, JsValue> {
+        let json = self
+            .app
+            .get_layer_view_moc(&layer)?
+            .to_json()
+            .map_err(|err| JsValue::from_str(&err))?;
+
+        js_sys::JSON::parse(&json)
+    }
+
     #[wasm_bindgen(js_name = isRendering)]
     pub fn is_rendering(&self) -> bool {
         self.app.is_rendering()
@@ -971,6 +1514,11 @@ impl WebClient {
         self.app.draw_grid_labels()
     }
 
+    #[wasm_bindgen(js_name = drawHorizonLabels)]
+    pub fn draw_horizon_labels(&mut self) -> Result<(), JsValue> {
+        self.app.draw_horizon_labels()
+    }
+
     #[wasm_bindgen(js_name = parseVOTable)]
     pub fn parse_votable(&mut self, _s: &str) -> Result<JsValue, JsValue> {
         /*let votable: VOTableWrapper<votable::impls::mem::InMemTableDataRows> =
@@ -992,6 +1540,13 @@ impl WebClient {
     ) -> Result<(), JsValue> {
         let str: String = js_sys::JSON::stringify(data)?.into();
 
+        // Space-time MOCs are given as an array of time and space MOC couples
+        if js_sys::Array::is_array(data) {
+            let stmoc = SpaceTimeCoverage::from_json(&str)
+                .map_err(|e| JsValue::from(js_sys::Error::new(&e)))?;
+            return self.app.add_space_time_moc(stmoc, options);
+        }
+
         let moc = moclib::deser::json::from_json_aladin::<u64, Hpx<u64>>(&str)
             .map_err(|e| JsValue::from(js_sys::Error::new(&e.to_string())))?
             .into_cell_moc_iter()
@@ -1014,7 +1569,19 @@ impl WebClient {
             }
             MocIdxType::U32(MocQtyType::<u32, _>::Hpx(moc)) => Ok(from_fits_hpx(moc)),
             MocIdxType::U64(MocQtyType::<u64, _>::Hpx(moc)) => Ok(from_fits_hpx(moc)),
-            _ => Err(JsValue::from_str("MOC not supported. Must be a HPX MOC")),
+            MocIdxType::U64(MocQtyType::<u64, _>::TimeHpx(moc2)) => {
+                let stmoc = SpaceTimeCoverage::from_fits_ranges(moc2);
+                return self.app.add_space_time_moc(stmoc, options);
+            }
+            MocIdxType::U16(MocQtyType::Time(_))
+            | MocIdxType::U32(MocQtyType::Time(_))
+            | MocIdxType::U64(MocQtyType::Time(_)) => Err(JsValue::from_str(
+                "A time MOC has no sky coverage. It can be used to filter the times of a \
+                 space-time MOC",
+            )),
+            _ => Err(JsValue::from_str(
+                "MOC not supported. Must be a HPX MOC or a space-time MOC",
+            )),
         }?;
 
         self.app.add_moc(HEALPixCoverage(moc), options)?;
@@ -1022,6 +1589,65 @@ impl WebClient {
         Ok(())
     }
 
+    /// Display the sky areas covered by a space-time MOC between two times
+    ///
+    /// # Arguments
+    ///
+    /// * `moc_uuid` - The uuid of the space-time MOC
+    /// * `jd_min` - The start of the time range, in julian days
+    /// * `jd_max` - The end of the time range, in julian days
+    #[wasm_bindgen(js_name = setMOCTimeRange)]
+    pub fn set_moc_time_range(
+        &mut self,
+        moc_uuid: String,
+        jd_min: f64,
+        jd_max: f64,
+    ) -> Result<(), JsValue> {
+        if jd_min >= jd_max {
+            return Err(JsValue::from_str(
+                "The start of the time range must be before its end",
+            ));
+        }
+
+        self.app
+            .set_moc_times(&moc_uuid, &TimeCoverage::from_jd_range(jd_min, jd_max))
+    }
+
+    /// Display the sky areas covered by a space-time MOC during the times of a time MOC
+    ///
+    /// # Arguments
+    ///
+    /// * `moc_uuid` - The uuid of the space-time MOC
+    /// * `tmoc` - The time MOC, the bytes of a FITS file or a JSON object
+    ///
+    /// Returns the time ranges of the time MOC in julian days, flattened
+    #[wasm_bindgen(js_name = setMOCTimeMOC)]
+    pub fn set_moc_time_moc(
+        &mut self,
+        moc_uuid: String,
+        tmoc: JsValue,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let times = if tmoc.is_instance_of::<js_sys::Uint8Array>() {
+            TimeCoverage::from_fits(&js_sys::Uint8Array::new(&tmoc).to_vec())
+        } else {
+            let json: String = js_sys::JSON::stringify(&tmoc)?.into();
+            TimeCoverage::from_json(&json)
+        }
+        .map_err(|e| JsValue::from(js_sys::Error::new(&e)))?;
+
+        self.app.set_moc_times(&moc_uuid, &times)?;
+
+        Ok(times.jd_ranges().concat().into_boxed_slice())
+    }
+
+    /// Get the first and last times covered by a space-time MOC, in julian days
+    #[wasm_bindgen(js_name = getMOCTimeRange)]
+    pub fn get_moc_time_range(&self, moc_uuid: String) -> Option<Box<[f64]>> {
+        self.app
+            .get_moc_jd_range(&moc_uuid)
+            .map(|range| Box::new(range) as Box<[f64]>)
+    }
+
     #[wasm_bindgen(js_name = addConeMOC)]
     pub fn add_cone_moc(
         &mut self,
@@ -1076,6 +1702,71 @@ impl WebClient {
         Ok(())
     }
 
+    /// Add a MOC built from a sky region
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The display options of the MOC
+    /// * `region` - The region, a cone, an ellipse, a box, a zone, a ring, a polygon or an STC-S string.
+    ///   Positions and angles are given in degrees in ICRS.
+    /// * `depth` - The depth of the MOC, 9 levels deeper than the tiles in the view minus one if not given
+    /// * `complement` - Keep the outside of the region instead of its inside
+    #[wasm_bindgen(js_name = addRegionMOC)]
+    pub fn add_region_moc(
+        &mut self,
+        options: MOCOptions,
+        region: JsValue,
+        depth: Option<u8>,
+        complement: bool,
+    ) -> Result<(), JsValue> {
+        let region: al_api::moc::MOCRegion = serde_wasm_bindgen::from_value(region)?;
+        let depth = depth.unwrap_or_else(|| self.app.get_moc_depth());
+        if depth > MAX_HPX_DEPTH {
+            return Err(JsValue::from_str("The depth of a MOC must be lower than 30"));
+        }
+
+        let mut moc = HEALPixCoverage::from_region(&region, depth)
+            .map_err(|err| JsValue::from(js_sys::Error::new(&err)))?;
+        if complement {
+            moc = moc.not();
+        }
+
+        self.app.add_moc(moc, options)?;
+
+        Ok(())
+    }
+
+    /// Add a MOC resulting from a set operation between loaded MOCs
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The display options of the new MOC
+    /// * `operation` - `union`, `intersection`, `difference`, `symmetricDifference` or `complement`
+    /// * `moc_uuids` - The uuids of the MOCs to combine, in order for the difference
+    #[wasm_bindgen(js_name = addOperationMOC)]
+    pub fn add_operation_moc(
+        &mut self,
+        options: MOCOptions,
+        operation: JsValue,
+        moc_uuids: Vec<String>,
+    ) -> Result<(), JsValue> {
+        let operation: al_api::moc::MOCOperation = serde_wasm_bindgen::from_value(operation)?;
+        let mocs = moc_uuids
+            .iter()
+            .map(|uuid| {
+                self.app
+                    .get_moc(uuid)
+                    .ok_or_else(|| JsValue::from(js_sys::Error::new("MOC not found")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let moc = HEALPixCoverage::from_operation(operation, &mocs)
+            .map_err(|err| JsValue::from(js_sys::Error::new(&err)))?;
+
+        self.app.add_moc(moc, options)?;
+
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = removeMoc)]
     pub fn remove_moc(&mut self, moc_uuid: String) -> Result<(), JsValue> {
         self.app.remove_moc(&moc_uuid)?;
@@ -1106,26 +1797,32 @@ impl WebClient {
         Ok(moc.contains_lonlat(&location))
     }
 
+    /// Serialize a MOC
+    ///
+    /// # Arguments
+    ///
+    /// * `moc_uuid` - The uuid of the MOC
+    /// * `format` - Case insensitive, `fits` for an IVOA MOC 2.0 FITS file given as a `Uint8Array`,
+    ///   `ascii` for the IVOA ASCII serialization or `json` for the Aladin JSON one
     #[wasm_bindgen(js_name = mocSerialize)]
-    pub fn moc_serialize(
-        &mut self,
-        moc_uuid: String,
-        _format: String, // todo support the fits/ascii serialization
-    ) -> Result<JsValue, JsValue> {
+    pub fn moc_serialize(&mut self, moc_uuid: String, format: String) -> Result<JsValue, JsValue> {
         let moc = self
             .app
             .get_moc(&moc_uuid)
             .ok_or_else(|| JsValue::from(js_sys::Error::new("MOC not found")))?;
 
-        let mut buf: Vec<u8> = Default::default();
-        let json = (&moc.0)
-            .into_range_moc_iter()
-            .cells()
-            .to_json_aladin(None, &mut buf)
-            .map(|()| unsafe { String::from_utf8_unchecked(buf) })
-            .map_err(|err| JsValue::from_str(&format!("{:?}", err)))?;
-
-        serde_wasm_bindgen::to_value(&json).map_err(|err| JsValue::from_str(&format!("{:?}", err)))
+        match format.to_lowercase().as_str() {
+            "fits" => moc
+                .to_fits()
+                .map(|bytes| js_sys::Uint8Array::from(&bytes[..]).into()),
+            "ascii" => moc.to_ascii().map(|ascii| JsValue::from_str(&ascii)),
+            "json" => moc.to_json().map(|json| JsValue::from_str(&json)),
+            _ => Err(format!(
+                "Unknown MOC serialization format {:?}, expected fits, ascii or json",
+                format
+            )),
+        }
+        .map_err(|err| JsValue::from_str(&err))
     }
 
     #[wasm_bindgen(js_name = getMOCSkyFraction)]
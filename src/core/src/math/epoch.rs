@@ -0,0 +1,292 @@
+//! Epoch dependant astrometry
+//!
+//! - IAU 2006 precession of the mean equator and equinox (with an optional
+//!   low precision nutation model).
+//! - Propagation of positions with proper motion, parallax and radial velocity.
+//!
+//! Epochs are given in Julian years (TT), e.g. 2000.0 or 2016.0 for Gaia DR3.
+//! The matrices returned are expressed in the axis convention of the core
+//! (see `crate::math::lonlat::radec_to_xyzw`) so that they can be chained with the
+//! matrices of `al_api::coo_system::CooSystem`.
+use cgmath::{InnerSpace, Matrix4, Vector3};
+
+use crate::math::angle::ToAngle;
+use crate::math::lonlat::{radec_to_xyz, xyz_to_radec};
+
+pub(crate) const ARCSEC_TO_RAD: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+const MAS_TO_RAD: f64 = ARCSEC_TO_RAD * 1e-3;
+// Astronomical unit expressed in km.yr/s
+const AU_KM_YR_PER_S: f64 = 4.740470463533348;
+
+pub(crate) type Mat3 = [[f64; 3]; 3];
+
+#[inline]
+pub(crate) fn julian_centuries(epoch: f64) -> f64 {
+    (epoch - 2000.0) / 100.0
+}
+
+// Elementary rotations following the SOFA conventions (x toward the equinox, z toward the pole)
+fn r1(a: f64) -> Mat3 {
+    let (s, c) = a.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]]
+}
+
+fn r2(a: f64) -> Mat3 {
+    let (s, c) = a.sin_cos();
+    [[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]]
+}
+
+pub(crate) fn r3(a: f64) -> Mat3 {
+    let (s, c) = a.sin_cos();
+    [[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]]
+}
+
+pub(crate) fn mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+
+    m
+}
+
+// The core stores (x, y, z) = (y_sofa, z_sofa, x_sofa)
+pub(crate) fn to_core_axes(m: &Mat3) -> Matrix4<f64> {
+    const AXIS: [usize; 3] = [1, 2, 0];
+    let e = |i: usize, j: usize| m[AXIS[i]][AXIS[j]];
+
+    // cgmath matrices are column major
+    Matrix4::new(
+        e(0, 0),
+        e(1, 0),
+        e(2, 0),
+        0.0,
+        e(0, 1),
+        e(1, 1),
+        e(2, 1),
+        0.0,
+        e(0, 2),
+        e(1, 2),
+        e(2, 2),
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+/// Mean obliquity of the ecliptic (IAU 2006) in radians
+pub fn mean_obliquity(epoch: f64) -> f64 {
+    let t = julian_centuries(epoch);
+
+    (84381.406
+        + t * (-46.836769
+            + t * (-0.0001831 + t * (0.00200340 + t * (-0.000000576 - t * 0.0000000434)))))
+        * ARCSEC_TO_RAD
+}
+
+// IAU 2006 (P03) precession matrix from the J2000 mean equator and equinox to the ones of `epoch`
+pub(crate) fn precession_from_j2000(epoch: f64) -> Mat3 {
+    let t = julian_centuries(epoch);
+
+    let zeta = (2.650545
+        + t * (2306.083227
+            + t * (0.2988499 + t * (0.01801828 + t * (-0.000005971 - t * 0.0000003173)))))
+        * ARCSEC_TO_RAD;
+    let z = (-2.650545
+        + t * (2306.077181
+            + t * (1.0927348 + t * (0.01826837 + t * (-0.000028596 - t * 0.0000002904)))))
+        * ARCSEC_TO_RAD;
+    let theta = (t
+        * (2004.191903
+            + t * (-0.4294934 + t * (-0.04182264 + t * (-0.000007089 - t * 0.0000001274)))))
+        * ARCSEC_TO_RAD;
+
+    mul(&r3(-z), &mul(&r2(theta), &r3(-zeta)))
+}
+
+// Nutation in longitude and in obliquity at `epoch` in radians
+//
+// Only the four leading terms of the nutation series are kept, which gives
+// a precision of about 0.5 arcsec.
+pub(crate) fn nutation_angles(epoch: f64) -> (f64, f64) {
+    let t = julian_centuries(epoch);
+
+    // Longitude of the ascending node of the Moon
+    let om = (125.04452 - 1934.136261 * t).to_radians();
+    // Mean longitudes of the Sun and the Moon
+    let l_sun = (280.4665 + 36000.7698 * t).to_radians();
+    let l_moon = (218.3165 + 481267.8813 * t).to_radians();
+
+    let dpsi = (-17.20 * om.sin() - 1.32 * (2.0 * l_sun).sin() - 0.23 * (2.0 * l_moon).sin()
+        + 0.21 * (2.0 * om).sin())
+        * ARCSEC_TO_RAD;
+    let deps = (9.20 * om.cos() + 0.57 * (2.0 * l_sun).cos() + 0.10 * (2.0 * l_moon).cos()
+        - 0.09 * (2.0 * om).cos())
+        * ARCSEC_TO_RAD;
+
+    (dpsi, deps)
+}
+
+// Nutation matrix from the mean to the true equator and equinox of `epoch`
+pub(crate) fn nutation(epoch: f64) -> Mat3 {
+    let (dpsi, deps) = nutation_angles(epoch);
+    let eps = mean_obliquity(epoch);
+
+    mul(&r1(-(eps + deps)), &mul(&r3(-dpsi), &r1(eps)))
+}
+
+fn transpose(m: &Mat3) -> Mat3 {
+    let mut t = [[0.0; 3]; 3];
+    for (i, row) in m.iter().enumerate() {
+        for (j, v) in row.iter().enumerate() {
+            t[j][i] = *v;
+        }
+    }
+
+    t
+}
+
+/// Matrix transforming equatorial positions referred to the equator and equinox of
+/// `from_epoch` to the ones of `to_epoch`
+///
+/// If `with_nutation` is set, positions are referred to the true equator and equinox
+/// of date, otherwise to the mean ones.
+pub fn precession_matrix(from_epoch: f64, to_epoch: f64, with_nutation: bool) -> Matrix4<f64> {
+    let mut from = precession_from_j2000(from_epoch);
+    let mut to = precession_from_j2000(to_epoch);
+
+    if with_nutation {
+        from = mul(&nutation(from_epoch), &from);
+        to = mul(&nutation(to_epoch), &to);
+    }
+
+    to_core_axes(&mul(&to, &transpose(&from)))
+}
+
+/// Astrometric parameters of a source at a given epoch
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Astrometry {
+    /// Right ascension in degrees
+    pub ra: f64,
+    /// Declination in degrees
+    pub dec: f64,
+    /// Proper motion in right ascension multiplied by cos(dec), in mas/yr
+    pub pmra: f64,
+    /// Proper motion in declination in mas/yr
+    pub pmdec: f64,
+    /// Parallax in mas
+    pub parallax: f64,
+    /// Radial velocity in km/s
+    pub rv: f64,
+}
+
+impl Astrometry {
+    /// Propagate the source from `from_epoch` to `to_epoch`
+    ///
+    /// This follows the rigorous model of Butkevich & Lindegren (2014) where the source
+    /// moves along a straight line at constant velocity. When the parallax is unknown
+    /// (i.e. 0), the radial velocity is discarded.
+    pub fn propagate(&self, from_epoch: f64, to_epoch: f64) -> Self {
+        let t = to_epoch - from_epoch;
+
+        let (ra, dec) = (self.ra.to_radians(), self.dec.to_radians());
+        let (sa, ca) = ra.sin_cos();
+        let (sd, cd) = dec.sin_cos();
+
+        let r = radec_to_xyz(ra.to_angle(), dec.to_angle());
+        // Unit vectors toward the east and the north
+        let p = Vector3::new(ca, 0.0, -sa);
+        let q = Vector3::new(-sd * sa, cd, -sd * ca);
+
+        // Proper motions in rad/yr
+        let pm = p * (self.pmra * MAS_TO_RAD) + q * (self.pmdec * MAS_TO_RAD);
+        let pm_r = self.rv * self.parallax * MAS_TO_RAD / AU_KM_YR_PER_S;
+
+        let pm2 = pm.magnitude2();
+        let f2 = 1.0 / (1.0 + 2.0 * pm_r * t + (pm2 + pm_r * pm_r) * t * t);
+        let f = f2.sqrt();
+
+        let u = (r * (1.0 + pm_r * t) + pm * t) * f;
+        let pm = (pm * (1.0 + pm_r * t) - r * (pm2 * t)) * (f2 * f);
+        let pm_r = (pm_r + (pm2 + pm_r * pm_r) * t) * f2;
+
+        let (ra, dec) = xyz_to_radec(&u.normalize());
+        let (ra, dec) = (
+            ra.to_radians().rem_euclid(std::f64::consts::TAU),
+            dec.to_radians(),
+        );
+        let (sa, ca) = ra.sin_cos();
+        let (sd, cd) = dec.sin_cos();
+        let p = Vector3::new(ca, 0.0, -sa);
+        let q = Vector3::new(-sd * sa, cd, -sd * ca);
+
+        let parallax = self.parallax * f;
+        let rv = if self.parallax != 0.0 {
+            pm_r * AU_KM_YR_PER_S / (parallax * MAS_TO_RAD)
+        } else {
+            self.rv
+        };
+
+        Self {
+            ra: ra.to_degrees(),
+            dec: dec.to_degrees(),
+            pmra: pm.dot(p) / MAS_TO_RAD,
+            pmdec: pm.dot(q) / MAS_TO_RAD,
+            parallax,
+            rv,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::lonlat::{radec_to_xyzw, xyzw_to_radec};
+    use cgmath::Matrix;
+
+    #[test]
+    fn precession_theta_persei() {
+        // Meeus, Astronomical Algorithms, example 21.b
+        let to_epoch = 2000.0 + (2462088.69 - 2451545.0) / 365.25;
+        let m = precession_matrix(2000.0, to_epoch, false);
+        let v = radec_to_xyzw(
+            41.054063_f64.to_radians().to_angle(),
+            49.227750_f64.to_radians().to_angle(),
+        );
+        let (ra, dec) = xyzw_to_radec(&(m * v));
+
+        assert!((ra.to_degrees() - 41.547214).abs() < 1e-4);
+        assert!((dec.to_degrees() - 49.348483).abs() < 1e-4);
+
+        let (ra, dec) = xyzw_to_radec(&(m.transpose() * (m * v)));
+        assert!((ra.to_degrees() - 41.054063).abs() < 1e-9);
+        assert!((dec.to_degrees() - 49.227750).abs() < 1e-9);
+    }
+
+    #[test]
+    fn barnard_star_propagation() {
+        // Barnard's star, Gaia DR3 (epoch 2016.0)
+        let src = Astrometry {
+            ra: 269.44850252543836,
+            dec: 4.739420051112412,
+            pmra: -801.551,
+            pmdec: 10362.394,
+            parallax: 546.9759,
+            rv: -110.11,
+        };
+
+        let src_2000 = src.propagate(2016.0, 2000.0);
+        // SIMBAD J2000 position of Barnard's star
+        assert!((src_2000.ra - 269.45207511).abs() < 1e-4);
+        assert!((src_2000.dec - 4.69339088).abs() < 1e-4);
+
+        let src_2016 = src_2000.propagate(2000.0, 2016.0);
+        assert!((src_2016.ra - src.ra).abs() < 1e-9);
+        assert!((src_2016.dec - src.dec).abs() < 1e-9);
+        assert!((src_2016.parallax - src.parallax).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,162 @@
+//! Topocentric horizontal (Alt-Az) frame of an observer
+//!
+//! The rotation from ICRS chains the precession and nutation of `crate::math::epoch`
+//! with the Greenwich apparent sidereal time. Polar motion, aberration and
+//! refraction are neglected and UTC is taken for UT1, which is well below the arcminute
+//! and enough for planning observations.
+//!
+//! In the horizontal frame, the latitude is the altitude and the longitude is 360°
+//! minus the azimuth, the latter being counted from the north toward the east.
+use cgmath::Matrix4;
+
+use crate::math::epoch::{self, Mat3, ARCSEC_TO_RAD};
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+// Julian dates of the Unix epoch and of J2000.0
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+const J2000_JD: f64 = 2451545.0;
+// TT - UTC in seconds (37 leap seconds + 32.184s)
+const TT_MINUS_UTC: f64 = 69.184;
+// Mean radius of the Earth in meters
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+#[inline]
+fn julian_date(utc_ms: f64) -> f64 {
+    UNIX_EPOCH_JD + utc_ms / MS_PER_DAY
+}
+
+/// Julian epoch (TT) of a UTC date given in milliseconds since the Unix epoch
+pub fn julian_epoch(utc_ms: f64) -> f64 {
+    2000.0 + (julian_date(utc_ms) + TT_MINUS_UTC / 86_400.0 - J2000_JD) / 365.25
+}
+
+/// Greenwich apparent sidereal time in radians of a UTC date given in milliseconds
+/// since the Unix epoch
+pub fn sidereal_time(utc_ms: f64) -> f64 {
+    let du = julian_date(utc_ms) - J2000_JD;
+    // Earth rotation angle (IERS conventions 2003)
+    let era =
+        std::f64::consts::TAU * (0.7790572732640 + 1.002_737_811_911_354_5 * du).rem_euclid(1.0);
+
+    let epoch = julian_epoch(utc_ms);
+    let t = epoch::julian_centuries(epoch);
+    // IAU 2006 Greenwich mean sidereal time
+    let gmst = era
+        + (0.014506
+            + t * (4612.156534
+                + t * (1.3915817 + t * (-0.00000044 + t * (-0.000029956 - t * 0.0000000368)))))
+            * ARCSEC_TO_RAD;
+    // Equation of the equinoxes
+    let (dpsi, _) = epoch::nutation_angles(epoch);
+
+    (gmst + dpsi * epoch::mean_obliquity(epoch).cos()).rem_euclid(std::f64::consts::TAU)
+}
+
+/// Altitude in radians above which the airmass is lower than `airmass`
+///
+/// A plane parallel atmosphere is assumed, i.e. the airmass is the secant
+/// of the zenith distance.
+pub fn airmass_to_altitude(airmass: f64) -> Option<f64> {
+    if airmass >= 1.0 {
+        Some((1.0 / airmass).asin())
+    } else {
+        None
+    }
+}
+
+/// Location of an observer on the Earth
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Observer {
+    /// Geodetic longitude in degrees, positive toward the east
+    pub lon: f64,
+    /// Geodetic latitude in degrees
+    pub lat: f64,
+    /// Height above the sea level in meters
+    pub height: f64,
+}
+
+impl Observer {
+    /// Rotation matrix from ICRS to the horizontal frame of the observer at a UTC date
+    /// given in milliseconds since the Unix epoch
+    pub fn icrs_to_horizontal(&self, utc_ms: f64) -> Matrix4<f64> {
+        let epoch = julian_epoch(utc_ms);
+        let lst = sidereal_time(utc_ms) + self.lon.to_radians();
+
+        let (sp, cp) = self.lat.to_radians().sin_cos();
+        // From the local equatorial frame (x toward the upper meridian, z toward the pole)
+        // to the horizontal one (x toward the north, y toward the west, z toward the zenith)
+        let eq2hor: Mat3 = [[-sp, 0.0, cp], [0.0, -1.0, 0.0], [cp, 0.0, sp]];
+
+        let icrs2eq = epoch::mul(
+            &epoch::r3(lst),
+            &epoch::mul(&epoch::nutation(epoch), &epoch::precession_from_j2000(epoch)),
+        );
+
+        epoch::to_core_axes(&epoch::mul(&eq2hor, &icrs2eq))
+    }
+
+    /// Altitude of the apparent horizon in radians
+    ///
+    /// It is negative for an observer above the sea level because of the dip
+    /// of the horizon.
+    pub fn horizon_altitude(&self) -> f64 {
+        -(EARTH_RADIUS / (EARTH_RADIUS + self.height.max(0.0))).acos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::angle::ToAngle;
+    use crate::math::lonlat::{radec_to_xyzw, xyzw_to_radec};
+    use cgmath::Matrix;
+
+    // 1987 April 10, 0h UT
+    const MEEUS_DATE_MS: f64 = (2446895.5 - UNIX_EPOCH_JD) * MS_PER_DAY;
+
+    #[test]
+    fn apparent_sidereal_time() {
+        // Meeus, Astronomical Algorithms, example 12.a: 13h10m46.1351s
+        let gast = (13.0 + 10.0 / 60.0 + 46.1351 / 3600.0) * 15.0;
+
+        assert!((sidereal_time(MEEUS_DATE_MS).to_degrees() - gast).abs() < 1e-4);
+    }
+
+    #[test]
+    fn hour_angle_to_alt_az() {
+        let observer = Observer {
+            lon: 2.0,
+            lat: 45.0,
+            height: 0.0,
+        };
+        let icrs2hor = observer.icrs_to_horizontal(MEEUS_DATE_MS);
+        let lst = sidereal_time(MEEUS_DATE_MS) + observer.lon.to_radians();
+        // Apparent equatorial positions of date to ICRS
+        let epoch = julian_epoch(MEEUS_DATE_MS);
+        let eq2icrs = epoch::to_core_axes(&epoch::mul(
+            &epoch::nutation(epoch),
+            &epoch::precession_from_j2000(epoch),
+        ))
+        .transpose();
+
+        let alt_az = |ha: f64, dec: f64| {
+            let v = radec_to_xyzw((lst - ha).to_angle(), dec.to_radians().to_angle());
+            let (lon, alt) = xyzw_to_radec(&(icrs2hor * eq2icrs * v));
+            (alt.to_degrees(), (360.0 - lon.to_degrees()).rem_euclid(360.0))
+        };
+
+        // Zenith
+        let (alt, _) = alt_az(0.0, observer.lat);
+        assert!((alt - 90.0).abs() < 1e-6);
+
+        // Culmination of the celestial equator, toward the south
+        let (alt, az) = alt_az(0.0, 0.0);
+        assert!((alt - 45.0).abs() < 1e-6);
+        assert!((az - 180.0).abs() < 1e-6);
+
+        // Rising of the celestial equator, toward the east
+        let (alt, az) = alt_az(-std::f64::consts::FRAC_PI_2, 0.0);
+        assert!(alt.abs() < 1e-6);
+        assert!((az - 90.0).abs() < 1e-6);
+    }
+}
@@ -10,9 +10,12 @@ pub const SQRT_TWO: f64 = 1.41421356237;
 pub const ZERO: f64 = 0.0;
 
 pub mod angle;
+pub mod epoch;
+pub mod horizontal;
 pub mod lonlat;
 pub mod projection;
 pub mod rotation;
 pub mod sph_geom;
+pub mod statistics;
 pub mod utils;
 pub mod vector;
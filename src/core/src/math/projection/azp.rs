@@ -0,0 +1,114 @@
+//! Zenithal perspective (AZP) projection
+//!
+//! The sphere is seen from a point located at a distance `mu` (in sphere radius units)
+//! from its center, on the opposite side of the center of projection.
+//! The plane of projection is not tilted.
+use mapproj::CanonicalProjection;
+
+use super::domain::{azp, sdf::ProjDefType};
+use super::Projection;
+use crate::coo_space::{XYClip, XYZWWorld};
+use crate::math::PI;
+
+// Default distance of mapproj
+const DEFAULT_MU: f64 = 1.35;
+
+pub struct Azp {
+    proj: mapproj::zenithal::azp::Azp,
+    mu: f64,
+    // Radius of the limb in the clipping space
+    radius: f64,
+
+    domain: ProjDefType,
+}
+
+impl Default for Azp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Azp {
+    pub fn new() -> Self {
+        Self::from_param(DEFAULT_MU).unwrap()
+    }
+
+    /// Define an AZP projection from the distance of the point of projection
+    ///
+    /// Returns `None` if `mu` is negative, i.e. the point of projection is
+    /// not on the opposite side of the center of projection.
+    pub fn from_param(mu: f64) -> Option<Self> {
+        if !mu.is_finite() || mu < 0.0 {
+            return None;
+        }
+
+        let proj = mapproj::zenithal::azp::Azp::from_params(mu, 0.0);
+
+        // Beyond the unit distance, the projection is a disk bounded by the limb
+        let radius = if mu > 1.0 {
+            mu / (mu * mu - 1.0).sqrt()
+        } else {
+            f64::INFINITY
+        };
+        let domain = ProjDefType::Azp(azp::Azp { radius });
+
+        Some(Self {
+            proj,
+            mu,
+            radius,
+            domain,
+        })
+    }
+
+    pub fn mu(&self) -> f64 {
+        self.mu
+    }
+
+    pub fn canonical(&self) -> &mapproj::zenithal::azp::Azp {
+        &self.proj
+    }
+
+    /// Whether the whole visible part of the sphere fits in a bounded region
+    pub fn is_bounded(&self) -> bool {
+        self.mu > 1.0
+    }
+
+    pub fn get_area(&self) -> &ProjDefType {
+        &self.domain
+    }
+
+    pub fn aperture_start(&self) -> f64 {
+        if self.is_bounded() {
+            // Angular diameter of the limb
+            2.0 * (-1.0 / self.mu).acos()
+        } else {
+            // Positions sent to infinity are at an angular distance of acos(-mu)
+            // from the center. Like the gnomonic projection, stop before.
+            2.0 * (-self.mu).acos() * 5.0 / 6.0
+        }
+    }
+
+    /// Parameters sent to the shaders: mu, the half length of the projection plane
+    /// and the radius of the limb in the clipping space
+    pub fn gpu_params(&self) -> Vec<f32> {
+        let bound = self
+            .proj
+            .bounds()
+            .x_bounds()
+            .as_ref()
+            .map_or(PI, |x| *x.end());
+        let radius = self.radius.min(f32::MAX as f64);
+
+        [self.mu, bound, radius].iter().map(|v| *v as f32).collect()
+    }
+}
+
+impl Projection for Azp {
+    fn clip_to_world_space(&self, xy_clip: &XYClip<f64>) -> Option<XYZWWorld<f64>> {
+        (&self.proj).clip_to_world_space(xy_clip)
+    }
+
+    fn world_to_clip_space(&self, pos_world_space: &XYZWWorld<f64>) -> Option<XYClip<f64>> {
+        (&self.proj).world_to_clip_space(pos_world_space)
+    }
+}
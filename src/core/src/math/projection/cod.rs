@@ -0,0 +1,187 @@
+//! Conic equidistant (COD) projection
+//!
+//! The cone is tangent (or secant) to the sphere around the latitude `theta_a`.
+//! The world is rotated so that this latitude lies at the center of the view.
+//! Cones of the southern hemisphere are handled as their northern counterpart,
+//! rotated by 180° around the center of the view.
+use cgmath::{Matrix, Matrix4, Rad};
+use mapproj::CanonicalProjection;
+
+use super::domain::sdf::ProjDefType;
+use super::Projection;
+use crate::coo_space::{XYClip, XYZWWorld};
+use crate::math::{HALF_PI, PI};
+
+// Below this latitude, the cone degenerates into a cylinder
+const MIN_THETA_A: f64 = 1e-2;
+
+pub struct Cod {
+    proj: mapproj::conic::cod::Cod,
+    theta_a: f64,
+    nu: f64,
+    // Rotation bringing the center of the view to the latitude theta_a
+    rot: Matrix4<f64>,
+
+    c: f64,
+    y0: f64,
+    south: bool,
+
+    domain: ProjDefType,
+}
+
+impl Default for Cod {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cod {
+    /// Cone tangent to the sphere at the latitude 45°
+    pub fn new() -> Self {
+        Self::from_params(HALF_PI * 0.5, 0.0).unwrap()
+    }
+
+    /// Define a cone from its two standard parallels given in radians
+    ///
+    /// Returns `None` if a parallel is not a valid latitude or if the cone
+    /// degenerates into a cylinder, i.e. the parallels are symmetric about the equator,
+    /// or into a plane, i.e. both parallels are at the same pole.
+    pub fn from_standard_parallels(theta1: f64, theta2: f64) -> Option<Self> {
+        if !(-HALF_PI..=HALF_PI).contains(&theta1) || !(-HALF_PI..=HALF_PI).contains(&theta2) {
+            return None;
+        }
+
+        Self::from_params((theta1 + theta2) * 0.5, (theta2 - theta1).abs() * 0.5)
+    }
+
+    /// Define a cone from the WCS parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `theta_a` - The mean of the two standard parallels in radians
+    /// * `nu` - The half distance between the two standard parallels in radians
+    pub fn from_params(theta_a: f64, nu: f64) -> Option<Self> {
+        if !theta_a.is_finite()
+            || !nu.is_finite()
+            || theta_a.abs() < MIN_THETA_A
+            || nu < 0.0
+            || theta_a.abs() + nu > HALF_PI
+            || theta_a.abs() >= HALF_PI
+        {
+            return None;
+        }
+
+        let south = theta_a < 0.0;
+        let ta = theta_a.abs();
+        let proj = mapproj::conic::cod::Cod::from_params(ta, nu);
+
+        // Same constants as the ones computed by mapproj
+        let (c, y0) = if nu == 0.0 {
+            (ta.sin(), 1.0 / ta.tan())
+        } else {
+            (ta.sin() * nu.sin() / nu, nu / (ta.tan() * nu.tan()))
+        };
+
+        let mut rot = Matrix4::from_angle_x(Rad(-theta_a));
+        if south {
+            rot = Matrix4::from_angle_z(Rad(PI)) * rot;
+        }
+
+        let (r_max, y_min, y_max) = bounds(&proj);
+        let r_min = ta + y0 - HALF_PI;
+        let domain = ProjDefType::Cod(super::domain::cod::Cod::new(
+            c, y0, r_min, r_max, y_min, y_max, south,
+        ));
+
+        Some(Self {
+            proj,
+            theta_a,
+            nu,
+            rot,
+            c,
+            y0,
+            south,
+            domain,
+        })
+    }
+
+    /// Mean of the two standard parallels in radians
+    pub fn theta_a(&self) -> f64 {
+        self.theta_a
+    }
+
+    /// Half distance between the two standard parallels in radians
+    pub fn nu(&self) -> f64 {
+        self.nu
+    }
+
+    /// Cone of the northern hemisphere used for the projection, i.e. at `|theta_a|`
+    pub fn canonical(&self) -> &mapproj::conic::cod::Cod {
+        &self.proj
+    }
+
+    pub fn get_area(&self) -> &ProjDefType {
+        &self.domain
+    }
+
+    pub fn bounds_size_ratio(&self) -> f64 {
+        let (r_max, y_min, y_max) = bounds(&self.proj);
+        2.0 * r_max / (y_max - y_min)
+    }
+
+    /// Parameters sent to the shaders
+    pub fn gpu_params(&self) -> Vec<f32> {
+        let (r_max, y_min, y_max) = bounds(&self.proj);
+        let (s, c) = self.theta_a.sin_cos();
+
+        [
+            self.c,
+            self.y0,
+            self.theta_a.abs() + self.y0,
+            s,
+            c,
+            r_max,
+            y_min,
+            y_max,
+            if self.south { -1.0 } else { 1.0 },
+        ]
+        .iter()
+        .map(|v| *v as f32)
+        .collect()
+    }
+
+    // Southern cones are rotated by 180° in the clipping space
+    fn flip(&self, xy: XYClip<f64>) -> XYClip<f64> {
+        if self.south {
+            -xy
+        } else {
+            xy
+        }
+    }
+}
+
+// x bound (the bounds are symmetric) and y bounds of the projection plane
+fn bounds(proj: &mapproj::conic::cod::Cod) -> (f64, f64, f64) {
+    let bounds = proj.bounds();
+    let r_max = bounds.x_bounds().as_ref().map_or(PI, |x| *x.end());
+    let (y_min, y_max) = bounds
+        .y_bounds()
+        .as_ref()
+        .map_or((-PI, PI), |y| (*y.start(), *y.end()));
+
+    (r_max, y_min, y_max)
+}
+
+impl Projection for Cod {
+    fn clip_to_world_space(&self, xy_clip: &XYClip<f64>) -> Option<XYZWWorld<f64>> {
+        (&self.proj)
+            .clip_to_world_space(&self.flip(*xy_clip))
+            .map(|xyzw| self.rot.transpose() * xyzw)
+    }
+
+    fn world_to_clip_space(&self, pos_world_space: &XYZWWorld<f64>) -> Option<XYClip<f64>> {
+        (&self.proj)
+            .world_to_clip_space(&(self.rot * pos_world_space))
+            .map(|xy| self.flip(xy))
+    }
+}
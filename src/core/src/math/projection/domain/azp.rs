@@ -0,0 +1,20 @@
+use crate::math::projection::coo_space::XYClip;
+
+use super::{basic::disk::Disk, full::FullScreen, op::Inter, sdf::ProjDef};
+
+pub struct Azp {
+    // Radius of the limb, infinite if the point of projection is inside the sphere
+    pub radius: f64,
+}
+
+impl ProjDef for Azp {
+    fn sdf(&self, xy: &XYClip<f64>) -> f64 {
+        Inter::new(
+            Disk {
+                radius: self.radius,
+            },
+            FullScreen,
+        )
+        .sdf(xy)
+    }
+}
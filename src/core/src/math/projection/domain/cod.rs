@@ -1,86 +1,106 @@
 use crate::math::projection::coo_space::XYClip;
-use cgmath::Vector2;
+use cgmath::{InnerSpace, Vector2};
 
 use super::{
-    basic::{ellipse::Ellipse, triangle::Triangle},
+    basic::ellipse::Ellipse,
     op::{Diff, Translate},
     sdf::ProjDef,
 };
 use crate::math::angle::PI;
-use crate::math::HALF_PI;
 
 pub struct Cod {
-    pub r_max: f64,
-    pub r_min: f64,
-    pub negative_ta: bool,
-    // Angle is defined by c * PI
-    pub c: f64,
-    pub y0: f64,
-    pub y_min: f64,
-    pub y_max: f64,
-    pub x_min: f64,
-    pub x_max: f64,
+    // Apex of the cone in the clipping space
+    apex: XYClip<f64>,
+    // Semi axes of the frontier ellipse
+    ext: Vector2<f64>,
+    // Semi axes of the small ellipse around the apex where the projection is not defined
+    int: Vector2<f64>,
+    // Half angle of the wedge where the projection is not defined.
+    // That wedge is centered around the y axis, pointing up.
+    beta: f64,
+    // The domain of southern cones is rotated by 180°
+    south: bool,
 }
 
 impl Cod {
-    pub const fn new() -> Self {
-        Self {
-            r_min: 0.2146018366,
-            r_max: 3.35619449019,
-            negative_ta: false,
-            c: 0.7071067811865475,
-            y0: 1.0000000000000002,
+    /// Definition domain of a conic equidistant projection
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - The ratio between the angle of the unrolled cone and 2*PI
+    /// * `y0` - The y coordinate of the apex in the projection plane
+    /// * `r_min`, `r_max` - The radii of the poles in the projection plane
+    /// * `y_min`, `y_max` - The y bounds of the projection plane
+    /// * `south` - Whether the cone is tangent to the southern hemisphere
+    pub fn new(c: f64, y0: f64, r_min: f64, r_max: f64, y_min: f64, y_max: f64, south: bool) -> Self {
+        let y_len = y_max - y_min;
+        let y_mean = (y_min + y_max) * 0.5;
+
+        // The x bounds are [-r_max, r_max]
+        let apex = XYClip::new(0.0, ((y0 + y_mean - y_min) / y_len - 0.5) * 2.0);
+
+        let ext = Vector2::new(1.0, 2.0 * r_max / y_len);
+        let int = Vector2::new(r_min.abs() / r_max, 2.0 * r_min.abs() / y_len);
+
+        // Edge of the domain, i.e. the direction of the longitude PI from the apex
+        let (s, c) = (PI * c).sin_cos();
+        let beta = (s / r_max).atan2(-c * 2.0 / y_len);
 
-            x_min: -3.356194490192345,
-            x_max: 3.356194490192345,
-            y_min: -2.356194490192345,
-            y_max: 3.0328465566001492,
+        Self {
+            apex,
+            ext,
+            int,
+            beta,
+            south,
         }
     }
+}
+
+// Wedge of apex at the origin, centered around the y axis
+struct Wedge {
+    // Half angle
+    beta: f64,
+}
 
-    fn to_clip(&self, xy: &Vector2<f64>) -> XYClip<f64> {
-        let x = (xy.x - self.x_min) / (self.x_max - self.x_min);
-        let y = (xy.y - self.y_min) / (self.y_max - self.y_min);
+impl ProjDef for Wedge {
+    fn sdf(&self, xy: &XYClip<f64>) -> f64 {
+        let q = Vector2::new(xy.x.abs(), xy.y);
+        let (s, c) = self.beta.sin_cos();
 
-        XYClip::new((x - 0.5) * 2.0, (y - 0.5) * 2.0)
+        if q.dot(Vector2::new(s, c)) > 0.0 {
+            // Signed distance to the edge
+            q.x * c - q.y * s
+        } else if q.x.atan2(q.y) < self.beta {
+            -q.magnitude()
+        } else {
+            q.magnitude()
+        }
     }
 }
 
 impl ProjDef for Cod {
     fn sdf(&self, xy: &XYClip<f64>) -> f64 {
-        let y_mean = (self.y_min + self.y_max) * 0.5;
-        let center_ellipse = self.to_clip(&Vector2::new(0.0, self.y0 + y_mean));
+        let xy = if self.south { -*xy } else { *xy };
 
-        // Big frontier ellipse
-        let a = 1.0;
-        let b = 2.0 * (2.356194490192345 + self.y0) / (2.356194490192345 + 3.0328465566001492);
-        let e = b / a;
         let ext_ellipse = Translate {
-            off: center_ellipse,
-            def: Ellipse { a: a, b: b },
+            off: self.apex,
+            def: Ellipse {
+                a: self.ext.x,
+                b: self.ext.y,
+            },
         };
-
-        // Small ellipse where projection is not defined
-        let b_int = 2.0 * self.r_min / (2.356194490192345 + 3.0328465566001492);
-        let a_int = b_int / e;
         let int_ellipse = Translate {
-            off: center_ellipse,
-            def: Ellipse { a: a_int, b: b_int },
+            off: self.apex,
+            def: Ellipse {
+                a: self.int.x,
+                b: self.int.y,
+            },
         };
-
-        // The top edges
-        let gamma = PI * self.c - HALF_PI;
-        let (s_gam, c_gam) = gamma.sin_cos();
-
-        let b = Vector2::new(c_gam * self.r_max, self.y0 + y_mean + s_gam * self.r_max);
-        let c = Vector2::new(-c_gam * self.r_max, self.y0 + y_mean + s_gam * self.r_max);
-
-        let tri = Triangle {
-            p0: center_ellipse,
-            p1: self.to_clip(&b),
-            p2: self.to_clip(&c),
+        let wedge = Translate {
+            off: self.apex,
+            def: Wedge { beta: self.beta },
         };
 
-        Diff::new(Diff::new(ext_ellipse, int_ellipse), tri).sdf(xy)
+        Diff::new(Diff::new(ext_ellipse, int_ellipse), wedge).sdf(&xy)
     }
 }
@@ -1,7 +1,9 @@
+pub mod azp;
 pub mod cod;
 pub mod full;
 pub mod hpx;
 pub mod par;
+pub mod sfl;
 
 pub mod basic;
 
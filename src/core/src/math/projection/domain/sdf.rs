@@ -49,7 +49,7 @@ where
     }
 }
 
-use super::{basic::disk::Disk, cod::Cod, full::FullScreen, hpx::Hpx, par::Par};
+use super::{azp::Azp, basic::disk::Disk, cod::Cod, full::FullScreen, hpx::Hpx, par::Par, sfl::Sfl};
 
 // List of all the footprints
 // found in Aladin Lite
@@ -57,7 +57,9 @@ use super::{basic::disk::Disk, cod::Cod, full::FullScreen, hpx::Hpx, par::Par};
 pub enum ProjDefType {
     Disk,
     Par,
+    Sfl,
     Cod,
     FullScreen,
     Hpx,
+    Azp,
 }
@@ -0,0 +1,17 @@
+use crate::math::projection::coo_space::XYClip;
+use crate::math::HALF_PI;
+
+pub struct Sfl;
+
+use super::sdf::ProjDef;
+impl ProjDef for Sfl {
+    fn sdf(&self, xy: &XYClip<f64>) -> f64 {
+        // The frontier is given by |x| = cos(y * PI/2)
+        // The distance to it is approximated by the first order taylor expansion
+        // of f(x, y) = |x| - cos(y * PI/2), i.e. f / |grad(f)|
+        let (s, c) = (xy.y * HALF_PI).sin_cos();
+        let d = (xy.x.abs() - c) / (1.0 + (HALF_PI * s) * (HALF_PI * s)).sqrt();
+
+        d.max(xy.y.abs() - 1.0)
+    }
+}
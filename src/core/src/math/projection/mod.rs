@@ -12,17 +12,21 @@ use crate::domain::sdf::ProjDefType;
 
 use coo_space::XYZWModel;
 //use crate::num_traits::FloatConst;
-use crate::math::PI;
+use crate::math::{PI, TWICE_PI};
 
 use cgmath::Vector2;
 
+pub mod azp;
+pub mod cod;
 pub mod coo_space;
 pub mod domain;
+pub mod zpn;
 
 use crate::math::angle::ToAngle;
 
-use domain::{basic, full::FullScreen};
+use domain::{basic, full::FullScreen, hpx::Hpx, par::Par, sfl::Sfl};
 use crate::math::angle::Angle;
+use crate::Abort;
 /* S <-> NDC space conversion methods */
 pub fn screen_to_ndc_space(
     pos_screen_space: &XYScreen<f64>,
@@ -100,6 +104,8 @@ pub fn screen_to_clip_space(
 }
 
 use al_api::coo_system::CooSystem;
+use al_api::projection::ProjectionCfg;
+use wasm_bindgen::JsValue;
 
 use crate::coo_space::{XYClip, XYZWWorld};
 
@@ -115,45 +121,164 @@ pub enum ProjectionType {
     Zea(mapproj::zenithal::zea::Zea),
     /* FEYE,     Fish-eyes                  */
     //Feye(mapproj::zenithal::feye::Feye),
-    /* AIR,                                 */
-    //Air(mapproj::zenithal::air::Air),
-    //AZP: {fov: 180},
-    //Azp(mapproj::zenithal::azp::Azp),
-    /* ARC,                                 */
-    //Arc(mapproj::zenithal::arc::Arc),
+    /* AIR,      Airy                       */
+    Air(mapproj::zenithal::air::Air),
+    /* AZP,      Zenithal perspective       */
+    Azp(azp::Azp),
+    /* ARC,      Zenithal equidistant       */
+    Arc(mapproj::zenithal::arc::Arc),
     /* NCP,                                 */
     //Ncp(mapproj::zenithal::ncp::Ncp),
+    /* ZPN,      Zenithal polynomial        */
+    Zpn(zpn::Zpn),
 
     // Pseudo-cylindrical projections
     /* AIT,      Aitoff                     */
     Ait(mapproj::pseudocyl::ait::Ait),
     // MOL,      Mollweide                  */
     Mol(mapproj::pseudocyl::mol::Mol),
-    // PAR,                                 */
-    //Par(mapproj::pseudocyl::par::Par),
-    // SFL,                                 */
-    //Sfl(mapproj::pseudocyl::sfl::Sfl),
+    // PAR,      Parabolic                  */
+    Par(mapproj::pseudocyl::par::Par),
+    // SFL,      Sanson-Flamsteed           */
+    Sfl(mapproj::pseudocyl::sfl::Sfl),
 
     // Cylindrical projections
     // MER,      Mercator                   */
     Mer(mapproj::cylindrical::mer::Mer),
-    // CAR,                                 */
-    //Car(mapproj::cylindrical::car::Car),
-    // CEA,                                 */
-    //Cea(mapproj::cylindrical::cea::Cea),
+    // CAR,      Plate carree               */
+    Car(mapproj::cylindrical::car::Car),
+    // CEA,      Cylindrical equal area     */
+    Cea(mapproj::cylindrical::cea::Cea),
     // CYP,                                 */
     //Cyp(mapproj::cylindrical::cyp::Cyp),
 
     // Conic projections
-    // COD,                                 */
-    //Cod(mapproj::conic::cod::Cod),
+    // COD,      Conic equidistant          */
+    Cod(cod::Cod),
 
     // HEALPix hybrid projection
-    //Hpx(mapproj::hybrid::hpx::Hpx),
+    Hpx(mapproj::hybrid::hpx::Hpx),
 }
 
 use crate::math::lonlat::LonLat;
 impl ProjectionType {
+    /// Build a projection from its configuration
+    pub fn from_cfg(cfg: &ProjectionCfg) -> Result<Self, JsValue> {
+        let ProjectionCfg {
+            name,
+            standard_parallels,
+            pv,
+            mu,
+        } = cfg;
+
+        // Check that the parameters given are handled by the projection
+        let params = [
+            ("standardParallels", standard_parallels.is_some(), "COD"),
+            ("pv", pv.is_some(), "ZPN"),
+            ("mu", mu.is_some(), "AZP"),
+        ];
+        if let Some((param, _, proj)) = params
+            .iter()
+            .find(|(_, given, proj)| *given && proj != name)
+        {
+            return Err(JsValue::from_str(&format!(
+                "{} is only a parameter of the {} projection",
+                param, proj
+            )));
+        }
+
+        let proj = match name.as_str() {
+            // Zenithal
+            "TAN" => ProjectionType::Tan(mapproj::zenithal::tan::Tan::new()), /* Gnomonic projection      */
+            "STG" => ProjectionType::Stg(mapproj::zenithal::stg::Stg::new()), /* Stereographic projection */
+            "SIN" => ProjectionType::Sin(mapproj::zenithal::sin::Sin::new()), /* Orthographic		         */
+            "ZEA" => ProjectionType::Zea(mapproj::zenithal::zea::Zea::new()), /* Equal-area 		         */
+            //"FEYE" => ProjectionType::Feye(mapproj::zenithal::feye::Feye::new()),
+            "AIR" => ProjectionType::Air(mapproj::zenithal::air::Air::new()), /* Airy                     */
+            "AZP" => {
+                let azp = if let Some(mu) = mu {
+                    azp::Azp::from_param(*mu).ok_or_else(|| {
+                        JsValue::from_str("AZP mu must be a positive distance")
+                    })?
+                } else {
+                    azp::Azp::new()
+                };
+
+                ProjectionType::Azp(azp)
+            }
+            "ARC" => ProjectionType::Arc(mapproj::zenithal::arc::Arc::new()), /* Zenithal equidistant     */
+            //"NCP" => ProjectionType::Ncp(mapproj::zenithal::ncp::Ncp::new()),
+            "ZPN" => {
+                let zpn = if let Some(pv) = pv {
+                    zpn::Zpn::from_params(pv.clone()).ok_or_else(|| {
+                        JsValue::from_str(&format!(
+                            "ZPN coefficients {:?} do not define a valid projection. \
+                            Between 2 and {} coefficients are accepted, the first one being 0 \
+                            and the polynomial increasing from 0",
+                            pv,
+                            zpn::MAX_NUM_COEFFS
+                        ))
+                    })?
+                } else {
+                    zpn::Zpn::new()
+                };
+
+                ProjectionType::Zpn(zpn)
+            }
+            // Cylindrical
+            "MER" => ProjectionType::Mer(mapproj::cylindrical::mer::Mer::new()),
+            "CAR" => ProjectionType::Car(mapproj::cylindrical::car::Car::new()),
+            "CEA" => ProjectionType::Cea(mapproj::cylindrical::cea::Cea::new()),
+            //"CYP" => ProjectionType::Cyp(mapproj::cylindrical::cyp::Cyp::new()),
+            // Pseudo-cylindrical
+            "AIT" => ProjectionType::Ait(mapproj::pseudocyl::ait::Ait::new()),
+            "PAR" => ProjectionType::Par(mapproj::pseudocyl::par::Par::new()),
+            "SFL" => ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl::new()),
+            "MOL" => {
+                let mut mol_proj = mapproj::pseudocyl::mol::Mol::new();
+                mol_proj.set_n_iter(10);
+                mol_proj.set_epsilon(1e-12);
+
+                ProjectionType::Mol(mol_proj)
+            }
+            // Conic
+            "COD" => {
+                let cod = match standard_parallels.as_deref() {
+                    None => Some(cod::Cod::new()),
+                    Some(&[theta]) => cod::Cod::from_standard_parallels(
+                        theta.to_radians(),
+                        theta.to_radians(),
+                    ),
+                    Some(&[theta1, theta2]) => cod::Cod::from_standard_parallels(
+                        theta1.to_radians(),
+                        theta2.to_radians(),
+                    ),
+                    Some(_) => {
+                        return Err(JsValue::from_str(
+                            "One or two COD standard parallels are expected",
+                        ))
+                    }
+                }
+                .ok_or_else(|| {
+                    JsValue::from_str(
+                        "COD standard parallels must be latitudes whose mean is not 0",
+                    )
+                })?;
+
+                ProjectionType::Cod(cod)
+            }
+            // Hybrid
+            "HPX" => ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx::new()),
+            _ => {
+                return Err(JsValue::from_str(
+                    "Not a valid projection name. AIT, ZEA, SIN, STG, TAN, MOL, MER, AIR, AZP, ARC, ZPN, CAR, CEA, SFL, PAR, COD and HPX are accepted",
+                ))
+            }
+        };
+
+        Ok(proj)
+    }
+
     pub fn north_pole_celestial_space(&self, camera: &CameraViewPort) -> LonLatT<f64> {
         // This is always defined
         let np_world = self.north_pole_world_space();
@@ -235,7 +360,7 @@ impl ProjectionType {
         camera: &CameraViewPort,
     ) -> Option<XYNDC<f64>> {
         let view_coosys = camera.get_coo_system();
-        let c = CooSystem::ICRS.to(view_coosys);
+        let c = CooSystem::ICRS.to(view_coosys, camera.get_icrs_to_horizontal());
 
         let m2w = camera.get_m2w();
         let pos_world_space = m2w * c * celestial_pos;
@@ -306,7 +431,7 @@ impl ProjectionType {
         }
     }*/
 
-    pub const fn bounds_size_ratio(&self) -> f64 {
+    pub fn bounds_size_ratio(&self) -> f64 {
         match self {
             // Zenithal projections
             /* TAN,      Gnomonic projection        */
@@ -319,41 +444,48 @@ impl ProjectionType {
             ProjectionType::Zea(_) => 1.0,
             /* FEYE,     Fish-eyes                  */
             //ProjectionType::Feye(_) => 1.0,
-            /* AIR,                                 */
-            //ProjectionType::Air(_) => 1.0,
-            //AZP: {fov: 180},
-            //Azp(mapproj::zenithal::azp::Azp),
-            /* ARC,                                 */
-            //ProjectionType::Arc(_) => 1.0,
+            /* AIR,      Airy                       */
+            ProjectionType::Air(_) => 1.0,
+            /* AZP,      Zenithal perspective       */
+            ProjectionType::Azp(_) => 1.0,
+            /* ARC,      Zenithal equidistant       */
+            ProjectionType::Arc(_) => 1.0,
             /* NCP,                                 */
             //ProjectionType::Ncp(_) => 1.0,
+            /* ZPN,      Zenithal polynomial        */
+            ProjectionType::Zpn(_) => 1.0,
 
             // Pseudo-cylindrical projections
             /* AIT,      Aitoff                     */
             ProjectionType::Ait(_) => 2.0,
             // MOL,      Mollweide                  */
             ProjectionType::Mol(_) => 2.0,
-            // PAR,                                 */
-            //ProjectionType::Par(_) => 2.0,
-            // SFL,                                 */
-            //ProjectionType::Sfl(_) => 2.0,
+            // PAR,      Parabolic                  */
+            ProjectionType::Par(_) => 2.0,
+            // SFL,      Sanson-Flamsteed           */
+            ProjectionType::Sfl(_) => 2.0,
 
             // Cylindrical projections
             // MER,      Mercator                   */
             ProjectionType::Mer(_) => 1.0,
-            // CAR,                                 */
-            //ProjectionType::Car(_) => 1.0,
-            // CEA,                                 */
-            //ProjectionType::Cea(_) => 1.0,
+            // CAR,      Plate carree               */
+            ProjectionType::Car(_) => 2.0,
+            // CEA,      Cylindrical equal area     */
+            ProjectionType::Cea(cea) => {
+                let bounds = cea.bounds();
+                let y_bounds = bounds.y_bounds().as_ref().unwrap_abort();
+
+                TWICE_PI / (y_bounds.end() - y_bounds.start())
+            }
             // CYP,                                 */
             //ProjectionType::Cyp(_) => 1.0,
 
             // Conic projections
-            // COD,                                 */
-            //ProjectionType::Cod(_) => 1.0,
+            // COD,      Conic equidistant          */
+            ProjectionType::Cod(cod) => cod.bounds_size_ratio(),
 
             // HEALPix hybrid projection
-            //ProjectionType::Hpx(_) => 2.0,
+            ProjectionType::Hpx(_) => 2.0,
         }
     }
 
@@ -370,41 +502,43 @@ impl ProjectionType {
             ProjectionType::Zea(_) => 360.0_f64.to_radians().to_angle(),
             /* FEYE,     Fish-eyes                  */
             //ProjectionType::Feye(_) => 190.0,
-            /* AIR,                                 */
-            //ProjectionType::Air(_) => 360.0,
-            //AZP: {fov: 180},
-            //Azp(mapproj::zenithal::azp::Azp),
-            /* ARC,                                 */
-            //ProjectionType::Arc(_) => 360.0,
+            /* AIR,      Airy                       */
+            ProjectionType::Air(_) => 360.0_f64.to_radians().to_angle(),
+            /* AZP,      Zenithal perspective       */
+            ProjectionType::Azp(azp) => azp.aperture_start().to_angle(),
+            /* ARC,      Zenithal equidistant       */
+            ProjectionType::Arc(_) => 360.0_f64.to_radians().to_angle(),
             /* NCP,                                 */
             //ProjectionType::Ncp(_) => 180.0,
+            /* ZPN,      Zenithal polynomial        */
+            ProjectionType::Zpn(zpn) => (2.0 * zpn.ang_dist_max()).to_angle(),
 
             // Pseudo-cylindrical projections
             /* AIT,      Aitoff                     */
             ProjectionType::Ait(_) => 360.0_f64.to_radians().to_angle(),
             // MOL,      Mollweide                  */
             ProjectionType::Mol(_) => 360.0_f64.to_radians().to_angle(),
-            // PAR,                                 */
-            //ProjectionType::Par(_) => 360.0,
-            // SFL,                                 */
-            //ProjectionType::Sfl(_) => 360.0,
+            // PAR,      Parabolic                  */
+            ProjectionType::Par(_) => 360.0_f64.to_radians().to_angle(),
+            // SFL,      Sanson-Flamsteed           */
+            ProjectionType::Sfl(_) => 360.0_f64.to_radians().to_angle(),
 
             // Cylindrical projections
             // MER,      Mercator                   */
             ProjectionType::Mer(_) => 360.0_f64.to_radians().to_angle(),
-            // CAR,                                 */
-            //ProjectionType::Car(_) => 360.0,
-            // CEA,                                 */
-            //ProjectionType::Cea(_) => 360.0,
+            // CAR,      Plate carree               */
+            ProjectionType::Car(_) => 360.0_f64.to_radians().to_angle(),
+            // CEA,      Cylindrical equal area     */
+            ProjectionType::Cea(_) => 360.0_f64.to_radians().to_angle(),
             // CYP,                                 */
             //ProjectionType::Cyp(_) => 360.0,
 
             // Conic projections
-            // COD,                                 */
-            //ProjectionType::Cod(_) => 330.0,
+            // COD,      Conic equidistant          */
+            ProjectionType::Cod(_) => 330.0_f64.to_radians().to_angle(),
 
             // HEALPix hybrid projection
-            //ProjectionType::Hpx(_) => 360.0,
+            ProjectionType::Hpx(_) => 360.0_f64.to_radians().to_angle(),
         }
     }
 
@@ -436,23 +570,28 @@ impl ProjectionType {
                 const DISK: ProjDefType = ProjDefType::Disk(basic::disk::Disk { radius: 1.0 });
                 &DISK
             }*/
-            /* AIR,                                 */
-            /*ProjectionType::Air(_) => {
-                const DISK: ProjDefType = ProjDefType::FullScreen(FullScreen);
-                &DISK
-            }*/
-            //AZP: {fov: 180},
-            //Azp(mapproj::zenithal::azp::Azp),
-            /* ARC,                                 */
-            /*ProjectionType::Arc(_) => {
+            /* AIR,      Airy                       */
+            ProjectionType::Air(_) => {
+                const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
+                &FULL_SCREEN
+            }
+            /* AZP,      Zenithal perspective       */
+            ProjectionType::Azp(azp) => azp.get_area(),
+            /* ARC,      Zenithal equidistant       */
+            ProjectionType::Arc(_) => {
                 const DISK: ProjDefType = ProjDefType::Disk(basic::disk::Disk { radius: 1.0 });
                 &DISK
-            }*/
+            }
             /* NCP,                                 */
             /*ProjectionType::Ncp(_) => {
                 const DISK: ProjDefType = ProjDefType::Disk(basic::disk::Disk { radius: 1.0 });
                 &DISK
             }*/
+            /* ZPN,      Zenithal polynomial        */
+            ProjectionType::Zpn(_) => {
+                const DISK: ProjDefType = ProjDefType::Disk(basic::disk::Disk { radius: 1.0 });
+                &DISK
+            }
 
             // Pseudo-cylindrical projections
             /* AIT,      Aitoff                     */
@@ -465,49 +604,47 @@ impl ProjectionType {
                 const ELLIPSE: ProjDefType = ProjDefType::Disk(basic::disk::Disk { radius: 1.0 });
                 &ELLIPSE
             }
-            // PAR,                                 */
-            /*ProjectionType::Par(_) => {
-                const PAR: ProjDefType = ProjDefType::Par(Par);
-                &PAR
-            }*/
-            // SFL,                                 */
-            /*ProjectionType::Sfl(_) => {
+            // PAR,      Parabolic                  */
+            ProjectionType::Par(_) => {
                 const PAR: ProjDefType = ProjDefType::Par(Par);
                 &PAR
-            }*/
+            }
+            // SFL,      Sanson-Flamsteed           */
+            ProjectionType::Sfl(_) => {
+                const SFL: ProjDefType = ProjDefType::Sfl(Sfl);
+                &SFL
+            }
 
             // Cylindrical projections
             // MER,      Mercator                   */
             ProjectionType::Mer(_) => {
                 const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
                 &FULL_SCREEN
-            } // CAR,                                 */
-              /*ProjectionType::Car(_) => {
-                  const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
-                  &FULL_SCREEN
-              }*/
-              // CEA,                                 */
-              /*ProjectionType::Cea(_) => {
-                  const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
-                  &FULL_SCREEN
-              }*/
-              // CYP,                                 */
-              /*ProjectionType::Cyp(_) => {
-                  const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
-                  &FULL_SCREEN
-              }*/
-
-              // Conic projections
-              // COD,                                 */
-              /*ProjectionType::Cod(_) => {
-                  const CONIC: ProjDefType = ProjDefType::Cod(Cod::new());
-                  &CONIC
-              }*/
-              // HEALPix hybrid projection
-              /*ProjectionType::Hpx(_) => {
-                  const HPX_DEF_REG: ProjDefType = ProjDefType::Hpx(Hpx);
-                  &HPX_DEF_REG
-              }*/
+            }
+            // CAR,      Plate carree               */
+            ProjectionType::Car(_) => {
+                const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
+                &FULL_SCREEN
+            }
+            // CEA,      Cylindrical equal area     */
+            ProjectionType::Cea(_) => {
+                const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
+                &FULL_SCREEN
+            }
+            // CYP,                                 */
+            /*ProjectionType::Cyp(_) => {
+                const FULL_SCREEN: ProjDefType = ProjDefType::FullScreen(FullScreen);
+                &FULL_SCREEN
+            }*/
+
+            // Conic projections
+            // COD,      Conic equidistant          */
+            ProjectionType::Cod(cod) => cod.get_area(),
+            // HEALPix hybrid projection
+            ProjectionType::Hpx(_) => {
+                const HPX_DEF_REG: ProjDefType = ProjDefType::Hpx(Hpx);
+                &HPX_DEF_REG
+            }
         }
     }
 }
@@ -525,47 +662,38 @@ impl Projection for ProjectionType {
             ProjectionType::Sin(sin) => sin.clip_to_world_space(xy),
             /* ZEA,	     Equal-area 		        */
             ProjectionType::Zea(zea) => zea.clip_to_world_space(xy),
-            /* FEYE,     Fish-eyes                  */
-            //ProjectionType::Feye(feye) => feye.clip_to_world_space(xy),
-            /* AIR,                                 */
-            //ProjectionType::Air(air) => air.clip_to_world_space(xy),
-            //AZP: {fov: 180},
-            //Azp(mapproj::zenithal::azp::Azp),
-            /* ARC,                                 */
-            //ProjectionType::Arc(arc) => arc.clip_to_world_space(xy),
-            /* NCP,                                 */
-            //ProjectionType::Ncp(ncp) => ncp.clip_to_world_space(xy),
+            /* AIR,      Airy                       */
+            ProjectionType::Air(air) => air.clip_to_world_space(xy),
+            /* AZP,      Zenithal perspective       */
+            ProjectionType::Azp(azp) => azp.clip_to_world_space(xy),
+            /* ARC,      Zenithal equidistant       */
+            ProjectionType::Arc(arc) => arc.clip_to_world_space(xy),
+            /* ZPN,      Zenithal polynomial        */
+            ProjectionType::Zpn(zpn) => zpn.clip_to_world_space(xy),
 
             // Pseudo-cylindrical projections
             /* AIT,      Aitoff                     */
             ProjectionType::Ait(ait) => ait.clip_to_world_space(xy),
-            // MOL,      Mollweide                  */
+            /* MOL,      Mollweide                  */
             ProjectionType::Mol(mol) => mol.clip_to_world_space(xy),
-            // PAR,                                 */
-            //ProjectionType::Par(par) => par.clip_to_world_space(xy),
-            // SFL,                                 */
-            //ProjectionType::Sfl(sfl) => sfl.clip_to_world_space(xy),
+            /* PAR,      Parabolic                  */
+            ProjectionType::Par(par) => par.clip_to_world_space(xy),
+            /* SFL,      Sanson-Flamsteed           */
+            ProjectionType::Sfl(sfl) => sfl.clip_to_world_space(xy),
 
             // Cylindrical projections
-            // MER,      Mercator                   */
+            /* MER,      Mercator                   */
             ProjectionType::Mer(mer) => mer.clip_to_world_space(xy),
-            // CAR,                                 */
-            //ProjectionType::Car(car) => car.clip_to_world_space(xy),
-            // CEA,                                 */
-            //ProjectionType::Cea(cea) => cea.clip_to_world_space(xy),
-            // CYP,                                 */
-            //ProjectionType::Cyp(cyp) => cyp.clip_to_world_space(xy),
+            /* CAR,      Plate carree               */
+            ProjectionType::Car(car) => car.clip_to_world_space(xy),
+            /* CEA,      Cylindrical equal area     */
+            ProjectionType::Cea(cea) => cea.clip_to_world_space(xy),
 
             // Conic projections
-            // COD,                                 */
-            /*ProjectionType::Cod(cod) => cod.clip_to_world_space(xy).map(|xyzw| {
-                let rot = Rotation::from_sky_position(
-                    &LonLatT::new(0.0_f64.to_angle(), (HALF_PI * 0.5).to_angle()).vector(),
-                );
-                rot.inv_rotate(&xyzw)
-            }),*/
+            /* COD,      Conic equidistant          */
+            ProjectionType::Cod(cod) => cod.clip_to_world_space(xy),
             // HEALPix hybrid projection
-            //ProjectionType::Hpx(hpx) => hpx.clip_to_world_space(xy),
+            ProjectionType::Hpx(hpx) => hpx.clip_to_world_space(xy),
         }
     }
 
@@ -581,80 +709,109 @@ impl Projection for ProjectionType {
             ProjectionType::Sin(sin) => sin.world_to_clip_space(xyzw),
             /* ZEA,	     Equal-area 		        */
             ProjectionType::Zea(zea) => zea.world_to_clip_space(xyzw),
-            /* FEYE,     Fish-eyes                  */
-            //ProjectionType::Feye(feye) => feye.world_to_clip_space(xyzw),
-            /* AIR,                                 */
-            //ProjectionType::Air(air) => air.world_to_clip_space(xyzw),
-            //AZP: {fov: 180},
-            //Azp(mapproj::zenithal::azp::Azp),
-            /* ARC,                                 */
-            //ProjectionType::Arc(arc) => arc.world_to_clip_space(xyzw),
-            /* NCP,                                 */
-            //ProjectionType::Ncp(ncp) => ncp.world_to_clip_space(xyzw),
+            /* AIR,      Airy                       */
+            ProjectionType::Air(air) => air.world_to_clip_space(xyzw),
+            /* AZP,      Zenithal perspective       */
+            ProjectionType::Azp(azp) => azp.world_to_clip_space(xyzw),
+            /* ARC,      Zenithal equidistant       */
+            ProjectionType::Arc(arc) => arc.world_to_clip_space(xyzw),
+            /* ZPN,      Zenithal polynomial        */
+            ProjectionType::Zpn(zpn) => zpn.world_to_clip_space(xyzw),
 
             // Pseudo-cylindrical projections
             /* AIT,      Aitoff                     */
             ProjectionType::Ait(ait) => ait.world_to_clip_space(xyzw),
-            // MOL,      Mollweide                  */
+            /* MOL,      Mollweide                  */
             ProjectionType::Mol(mol) => mol.world_to_clip_space(xyzw),
-            // PAR,                                 */
-            //ProjectionType::Par(par) => par.world_to_clip_space(xyzw),
-            // SFL,                                 */
-            //ProjectionType::Sfl(sfl) => sfl.world_to_clip_space(xyzw),
+            /* PAR,      Parabolic                  */
+            ProjectionType::Par(par) => par.world_to_clip_space(xyzw),
+            /* SFL,      Sanson-Flamsteed           */
+            ProjectionType::Sfl(sfl) => sfl.world_to_clip_space(xyzw),
 
             // Cylindrical projections
-            // MER,      Mercator                   */
+            /* MER,      Mercator                   */
             ProjectionType::Mer(mer) => mer.world_to_clip_space(xyzw),
-            // CAR,                                 */
-            //ProjectionType::Car(car) => car.world_to_clip_space(xyzw),
-            // CEA,                                 */
-            //ProjectionType::Cea(cea) => cea.world_to_clip_space(xyzw),
-            // CYP,                                 */
-            //ProjectionType::Cyp(cyp) => cyp.world_to_clip_space(xyzw),
+            /* CAR,      Plate carree               */
+            ProjectionType::Car(car) => car.world_to_clip_space(xyzw),
+            /* CEA,      Cylindrical equal area     */
+            ProjectionType::Cea(cea) => cea.world_to_clip_space(xyzw),
+
             // Conic projections
-            // COD,                                 */
-            /*ProjectionType::Cod(cod) => {
-                // The Cod projection is centered on (0, 45 deg)
-                let rot = Rotation::from_sky_position(
-                    &LonLatT::new(0.0_f64.to_angle(), (HALF_PI * 0.5).to_angle()).vector(),
-                );
-                cod.world_to_clip_space(&rot.rotate(&xyzw))
-            }*/
+            /* COD,      Conic equidistant          */
+            ProjectionType::Cod(cod) => cod.world_to_clip_space(xyzw),
             // HEALPix hybrid projection
-            //ProjectionType::Hpx(hpx) => hpx.world_to_clip_space(xyzw),
+            ProjectionType::Hpx(hpx) => hpx.world_to_clip_space(xyzw),
         }
     }
 }
 
-use al_core::shader::UniformType;
-use al_core::WebGlContext;
-use web_sys::WebGlUniformLocation;
-impl UniformType for ProjectionType {
-    fn uniform(gl: &WebGlContext, location: Option<&WebGlUniformLocation>, value: &Self) {
-        match value {
-            // Zenithal projections
+use al_core::shader::{SendUniforms, ShaderBound};
+impl SendUniforms for ProjectionType {
+    fn attach_uniforms<'a>(&self, shader: &'a ShaderBound<'a>) -> &'a ShaderBound<'a> {
+        let id: i32 = match self {
             /* TAN,      Gnomonic projection        */
-            ProjectionType::Tan(_) => gl.uniform1i(location, 0),
+            ProjectionType::Tan(_) => 0,
             /* STG,	     Stereographic projection   */
-            ProjectionType::Stg(_) => gl.uniform1i(location, 1),
+            ProjectionType::Stg(_) => 1,
             /* SIN,	     Orthographic		        */
-            ProjectionType::Sin(_) => gl.uniform1i(location, 2),
+            ProjectionType::Sin(_) => 2,
             /* ZEA,	     Equal-area 		        */
-            ProjectionType::Zea(_) => gl.uniform1i(location, 3),
-
-            // Pseudo-cylindrical projections
+            ProjectionType::Zea(_) => 3,
             /* AIT,      Aitoff                     */
-            ProjectionType::Ait(_) => gl.uniform1i(location, 4),
-            // MOL,      Mollweide                  */
-            ProjectionType::Mol(_) => gl.uniform1i(location, 5),
+            ProjectionType::Ait(_) => 4,
+            /* MOL,      Mollweide                  */
+            ProjectionType::Mol(_) => 5,
+            /* MER,      Mercator                   */
+            ProjectionType::Mer(_) => 6,
+            /* ARC,      Zenithal equidistant       */
+            ProjectionType::Arc(_) => 7,
+            /* AIR,      Airy                       */
+            ProjectionType::Air(_) => 8,
+            /* ZPN,      Zenithal polynomial        */
+            ProjectionType::Zpn(_) => 9,
+            /* CAR,      Plate carree               */
+            ProjectionType::Car(_) => 10,
+            /* CEA,      Cylindrical equal area     */
+            ProjectionType::Cea(_) => 11,
+            /* SFL,      Sanson-Flamsteed           */
+            ProjectionType::Sfl(_) => 12,
+            /* PAR,      Parabolic                  */
+            ProjectionType::Par(_) => 13,
+            /* COD,      Conic equidistant          */
+            ProjectionType::Cod(_) => 14,
+            // HEALPix hybrid projection
+            ProjectionType::Hpx(_) => 15,
+            /* AZP,      Zenithal perspective       */
+            ProjectionType::Azp(_) => 16,
+        };
 
-            // Cylindrical projections
-            // MER,      Mercator                   */
-            ProjectionType::Mer(_) => gl.uniform1i(location, 6),
+        // Projections parametrized on the GPU side
+        let params: Vec<f32> = match self {
+            ProjectionType::Air(air) => vec![air_cte_b(air.rho_b()) as f32],
+            ProjectionType::Azp(azp) => azp.gpu_params(),
+            ProjectionType::Zpn(zpn) => zpn.gpu_params(),
+            ProjectionType::Cod(cod) => cod.gpu_params(),
+            _ => vec![],
+        };
+
+        shader.attach_uniform("u_proj", &id);
+        // WebGL does not accept empty uniform arrays
+        if !params.is_empty() {
+            shader.attach_uniform("u_proj_params", &params.as_slice());
         }
+
+        shader
     }
 }
 
+// Constant of the Airy projection depending on the angle (rho_b) for which the error is minimized
+fn air_cte_b(rho_b: f64) -> f64 {
+    let xb = rho_b.cos();
+    let xb_p_1 = xb + 1.0;
+
+    xb_p_1 * (0.5 * xb_p_1).ln() / (1.0 - xb)
+}
+
 use cgmath::Vector4;
 
 use mapproj::CanonicalProjection;
@@ -695,6 +852,21 @@ use self::coo_space::XYNDC;
 
 use super::lonlat::LonLatT;
 
+/// Half size of the region of the projection plane mapped to the clipping space
+///
+/// The position in the projection plane (in radians) of the canonical projection
+/// is the position in the clipping space times this size.
+pub fn proj_plane_half_size<P: CanonicalProjection>(proj: &P) -> Vector2<f64> {
+    let proj_bounds = proj.bounds();
+    let x_proj_bounds = proj_bounds.x_bounds().as_ref().unwrap_or(&(-PI..=PI));
+    let y_proj_bounds = proj_bounds.y_bounds().as_ref().unwrap_or(&(-PI..=PI));
+
+    Vector2::new(
+        (x_proj_bounds.end() - x_proj_bounds.start()) * 0.5,
+        (y_proj_bounds.end() - y_proj_bounds.start()) * 0.5,
+    )
+}
+
 impl<'a, P> Projection for &'a P
 where
     P: CanonicalProjection,
@@ -825,66 +997,90 @@ mod tests {
             "./../img/zea.jpg",
             ProjectionType::Zea(mapproj::zenithal::zea::Zea),
         );
-        /*generate_projection_map(
-            "./../img/feye.png",
-            ProjectionType::Feye(mapproj::zenithal::feye::Feye),
-        );
         generate_projection_map(
-            "./../img/arc.png",
+            "./../img/arc.jpg",
             ProjectionType::Arc(mapproj::zenithal::arc::Arc),
         );
         generate_projection_map(
-            "./../img/ncp.png",
-            ProjectionType::Ncp(mapproj::zenithal::ncp::Ncp),
-        );
-        generate_projection_map(
-            "./../img/air.png",
+            "./../img/air.jpg",
             ProjectionType::Air(mapproj::zenithal::air::Air::new()),
-        );*/
+        );
+        generate_projection_map("./../img/zpn.jpg", ProjectionType::Zpn(zpn::Zpn::new()));
+        generate_projection_map("./../img/azp.jpg", ProjectionType::Azp(azp::Azp::new()));
 
         // Cylindrical
         generate_projection_map(
             "./../img/mer.jpg",
             ProjectionType::Mer(mapproj::cylindrical::mer::Mer),
         );
-        /*generate_projection_map(
-            "./../img/car.png",
+        generate_projection_map(
+            "./../img/car.jpg",
             ProjectionType::Car(mapproj::cylindrical::car::Car),
         );
         generate_projection_map(
-            "./../img/cea.png",
+            "./../img/cea.jpg",
             ProjectionType::Cea(mapproj::cylindrical::cea::Cea::new()),
         );
-        generate_projection_map(
-            "./../img/cyp.png",
-            ProjectionType::Cyp(mapproj::cylindrical::cyp::Cyp::new()),
-        );*/
         // Pseudo-cylindrical
         generate_projection_map(
             "./../img/ait.jpg",
             ProjectionType::Ait(mapproj::pseudocyl::ait::Ait),
         );
-        /*generate_projection_map(
-            "./../img/car.png",
+        generate_projection_map(
+            "./../img/par.jpg",
             ProjectionType::Par(mapproj::pseudocyl::par::Par),
         );
         generate_projection_map(
-            "./../img/cea.png",
+            "./../img/sfl.jpg",
             ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl),
-        );*/
+        );
         generate_projection_map(
             "./../img/mol.jpg",
             ProjectionType::Mol(mapproj::pseudocyl::mol::Mol::new()),
         );
         // Conic
-        /*generate_projection_map(
-            "./../img/cod.png",
-            ProjectionType::Cod(mapproj::conic::cod::Cod::new()),
-        );
+        generate_projection_map("./../img/cod.jpg", ProjectionType::Cod(cod::Cod::new()));
         // Hybrid
         generate_projection_map(
-            "./../img/hpx.png",
+            "./../img/hpx.jpg",
             ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx),
-        );*/
+        );
+    }
+
+    #[test]
+    fn rejected_projection_params() {
+        use super::{azp::Azp, cod::Cod, zpn::Zpn};
+        use crate::math::HALF_PI;
+
+        // Parallels out of the latitude range
+        assert!(Cod::from_standard_parallels(0.5, HALF_PI + 0.1).is_none());
+        assert!(Cod::from_standard_parallels(-HALF_PI - 0.1, 0.5).is_none());
+        assert!(Cod::from_standard_parallels(f64::NAN, 0.5).is_none());
+        // The cone degenerates into a cylinder
+        assert!(Cod::from_standard_parallels(-0.5, 0.5).is_none());
+        assert!(Cod::from_standard_parallels(0.0, 0.0).is_none());
+        // The cone degenerates into a plane
+        assert!(Cod::from_standard_parallels(HALF_PI, HALF_PI).is_none());
+        assert!(Cod::from_standard_parallels(-HALF_PI, -HALF_PI).is_none());
+        assert!(Cod::from_standard_parallels(0.3, 0.4).is_some());
+        assert!(Cod::from_standard_parallels(-HALF_PI, -0.2).is_some());
+
+        // The point of projection must be on the opposite side of the center
+        assert!(Azp::from_param(-0.5).is_none());
+        assert!(Azp::from_param(f64::NAN).is_none());
+        assert!(Azp::from_param(f64::INFINITY).is_none());
+        assert!(Azp::from_param(0.0).is_some());
+        assert!(Azp::from_param(1.0).is_some());
+
+        // Not enough or too many coefficients
+        assert!(Zpn::from_params(vec![]).is_none());
+        assert!(Zpn::from_params(vec![0.0]).is_none());
+        assert!(Zpn::from_params(vec![0.0; super::zpn::MAX_NUM_COEFFS + 1]).is_none());
+        // The center of projection must be mapped to the origin
+        assert!(Zpn::from_params(vec![0.1, 1.0]).is_none());
+        assert!(Zpn::from_params(vec![0.0, f64::NAN]).is_none());
+        // Decreasing from the center
+        assert!(Zpn::from_params(vec![0.0, -1.0]).is_none());
+        assert!(Zpn::from_params(vec![0.0, 1.0]).is_some());
     }
 }
@@ -0,0 +1,102 @@
+//! Zenithal polynomial (ZPN) projection
+//!
+//! The angular distance to the center of projection is mapped to the projection plane
+//! through a polynomial whose coefficients are given by the PV2_i WCS keywords.
+use mapproj::{CanonicalProjection, ProjXY};
+
+use super::Projection;
+use crate::coo_space::{XYClip, XYZWWorld};
+
+/// Maximum number of polynomial coefficients handled by the shaders
+pub const MAX_NUM_COEFFS: usize = 20;
+
+// A zenithal equidistant projection slightly compressed towards the south pole
+const DEFAULT_COEFFS: [f64; 4] = [0.0, 1.0, 0.0, -0.05];
+
+pub struct Zpn {
+    proj: mapproj::zenithal::zpn::Zpn,
+    coeffs: Vec<f64>,
+    // Angular distance from the center where the polynomial stops being monotonic
+    ang_dist_max: f64,
+    // Radius of the projection domain
+    euc_dist_max: f64,
+}
+
+impl Default for Zpn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Zpn {
+    pub fn new() -> Self {
+        // The default coefficients are checked by the unit tests
+        Self::from_params(DEFAULT_COEFFS.to_vec()).unwrap()
+    }
+
+    /// Define a ZPN projection from its polynomial coefficients
+    ///
+    /// Returns `None` if the polynomial does not define a valid projection.
+    /// The constant coefficient must be null so that the center of projection
+    /// is mapped to the origin of the projection plane.
+    pub fn from_params(coeffs: Vec<f64>) -> Option<Self> {
+        if coeffs.len() < 2
+            || coeffs.len() > MAX_NUM_COEFFS
+            || coeffs[0] != 0.0
+            || coeffs.iter().any(|c| !c.is_finite())
+        {
+            return None;
+        }
+
+        let proj = mapproj::zenithal::zpn::Zpn::from_params(coeffs.clone())?;
+
+        let euc_dist_max = *proj.bounds().x_bounds().as_ref()?.end();
+        if euc_dist_max <= 0.0 {
+            return None;
+        }
+        let ang_dist_max = proj.unproj(&ProjXY::new(euc_dist_max, 0.0))?.x().acos();
+
+        Some(Self {
+            proj,
+            coeffs,
+            ang_dist_max,
+            euc_dist_max,
+        })
+    }
+
+    pub fn coeffs(&self) -> &[f64] {
+        &self.coeffs
+    }
+
+    pub fn canonical(&self) -> &mapproj::zenithal::zpn::Zpn {
+        &self.proj
+    }
+
+    pub fn ang_dist_max(&self) -> f64 {
+        self.ang_dist_max
+    }
+
+    /// Parameters sent to the shaders: the angular and euclidean radii of the domain,
+    /// the number of coefficients followed by the coefficients
+    pub fn gpu_params(&self) -> Vec<f32> {
+        [
+            self.ang_dist_max,
+            self.euc_dist_max,
+            self.coeffs.len() as f64,
+        ]
+        .iter()
+        .chain(self.coeffs.iter())
+        .map(|v| *v as f32)
+        .collect()
+    }
+}
+
+impl Projection for Zpn {
+    fn clip_to_world_space(&self, xy_clip: &XYClip<f64>) -> Option<XYZWWorld<f64>> {
+        (&self.proj).clip_to_world_space(xy_clip)
+    }
+
+    fn world_to_clip_space(&self, pos_world_space: &XYZWWorld<f64>) -> Option<XYClip<f64>> {
+        (&self.proj).world_to_clip_space(pos_world_space)
+    }
+}
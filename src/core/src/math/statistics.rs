@@ -0,0 +1,146 @@
+//! Statistics on pixel values and sampling of great-circle segments
+use std::cmp::Ordering;
+
+use al_api::stats::{Histogram, Statistics};
+use cgmath::{InnerSpace, Vector3};
+
+/// Compute the statistics of a set of pixel values
+///
+/// NaN values are discarded. The values are sorted in place.
+///
+/// # Arguments
+///
+/// * `values` - The pixel values
+/// * `num_bins` - The number of bins of the histogram, spanning from the minimum to the maximum value
+pub fn compute(values: &mut Vec<f64>, num_bins: usize) -> Statistics {
+    values.retain(|v| !v.is_nan());
+    if values.is_empty() {
+        return Statistics::default();
+    }
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let count = values.len();
+    let min = values[0];
+    let max = values[count - 1];
+
+    let sum = values.iter().sum::<f64>();
+    let mean = sum / (count as f64);
+    let median = 0.5 * (values[(count - 1) / 2] + values[count / 2]);
+    let std = (values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (count as f64)).sqrt();
+
+    Statistics {
+        count,
+        sum,
+        mean,
+        median,
+        std,
+        min,
+        max,
+        histogram: histogram(values, min, max, num_bins),
+    }
+}
+
+fn histogram(values: &[f64], min: f64, max: f64, num_bins: usize) -> Histogram {
+    let num_bins = num_bins.max(1);
+    let width = (max - min) / (num_bins as f64);
+
+    let edges = (0..=num_bins)
+        .map(|i| if i == num_bins { max } else { min + (i as f64) * width })
+        .collect();
+
+    let mut counts = vec![0; num_bins];
+    for v in values {
+        // The maximum value falls in the last bin
+        let bin = if width > 0.0 {
+            (((v - min) / width) as usize).min(num_bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+
+    Histogram { edges, counts }
+}
+
+/// Sample regularly a great-circle segment
+///
+/// Returns the positions along with their angular distance (in radians) from `start`.
+/// The segment must not join antipodal points as its great circle is not defined.
+pub fn sample_great_circle_arc(
+    start: &Vector3<f64>,
+    end: &Vector3<f64>,
+    num_samples: usize,
+) -> Vec<(Vector3<f64>, f64)> {
+    let theta = start.cross(*end).magnitude().atan2(start.dot(*end));
+    let sin_theta = theta.sin();
+
+    (0..num_samples)
+        .map(|i| {
+            let t = if num_samples > 1 {
+                (i as f64) / ((num_samples - 1) as f64)
+            } else {
+                0.0
+            };
+
+            let p = if sin_theta.abs() < 1e-12 {
+                *start
+            } else {
+                // Spherical linear interpolation
+                (start * ((1.0 - t) * theta).sin() + end * (t * theta).sin()) / sin_theta
+            };
+
+            (p, t * theta)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_of_values() {
+        let mut values = vec![4.0, f64::NAN, 1.0, 3.0, 2.0, 10.0];
+        let stats = compute(&mut values, 3);
+
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.sum, 20.0);
+        assert_eq!(stats.mean, 4.0);
+        assert_eq!(stats.median, 3.0);
+        assert!((stats.std - 10.0f64.sqrt()).abs() < 1e-12);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 10.0);
+
+        assert_eq!(stats.histogram.edges, vec![1.0, 4.0, 7.0, 10.0]);
+        assert_eq!(stats.histogram.counts, vec![3, 1, 1]);
+    }
+
+    #[test]
+    fn statistics_of_constant_and_empty_values() {
+        let stats = compute(&mut vec![2.0; 4], 10);
+        assert_eq!(stats.median, 2.0);
+        assert_eq!(stats.std, 0.0);
+        assert_eq!(stats.histogram.counts.iter().sum::<u32>(), 4);
+
+        let stats = compute(&mut vec![f64::NAN], 10);
+        assert_eq!(stats, Statistics::default());
+    }
+
+    #[test]
+    fn great_circle_arc_samples() {
+        let start = Vector3::new(1.0, 0.0, 0.0);
+        let end = Vector3::new(0.0, 1.0, 0.0);
+        let samples = sample_great_circle_arc(&start, &end, 3);
+
+        assert_eq!(samples.len(), 3);
+        let expected = [
+            (start, 0.0),
+            (Vector3::new(0.5f64.sqrt(), 0.5f64.sqrt(), 0.0), std::f64::consts::FRAC_PI_4),
+            (end, std::f64::consts::FRAC_PI_2),
+        ];
+        for ((p, d), (p_e, d_e)) in samples.iter().zip(expected.iter()) {
+            assert!((p - p_e).magnitude() < 1e-12);
+            assert!((d - d_e).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use al_api::blend::BlendCfg;
+use al_api::colormap::CmapLabel;
+use al_api::composite::LayerOperation;
+use al_api::hips::ImageMetadata;
+
+use al_core::colormap::Colormaps;
+use al_core::image::format::RGBA32F;
+use al_core::webgl_ctx::GlWrapper;
+use al_core::{FrameBufferObject, VertexArrayObject, WebGlContext};
+
+use crate::shader::{ShaderId, ShaderManager};
+
+use cgmath::Vector2;
+use wasm_bindgen::JsValue;
+use web_sys::WebGl2RenderingContext;
+
+// The operands are rendered as their physical values
+fn operand_meta(meta: &ImageMetadata) -> ImageMetadata {
+    let mut meta = meta.clone();
+
+    // Color HiPS are read from their first channel
+    meta.color.cmap_name = CmapLabel::from("grayscale");
+    meta.color.raw_output = true;
+    meta.opacity = 1.0;
+    meta.blend_cfg = BlendCfg::default();
+
+    meta
+}
+
+/// A layer computing a per-pixel expression between two layers
+///
+/// The operand layers are first rendered as physical values into offscreen
+/// float buffers that are then combined and colored on the screen.
+pub struct LayerArithmetic {
+    // The layers of the operands A and B
+    operands: [String; 2],
+    operation: LayerOperation,
+
+    // The buffers in which the operands are rendered
+    fbos: [FrameBufferObject; 2],
+
+    gl: WebGlContext,
+}
+
+impl LayerArithmetic {
+    pub fn new(
+        gl: &WebGlContext,
+        operands: [String; 2],
+        operation: LayerOperation,
+        screen_size: &Vector2<f32>,
+    ) -> Result<Self, JsValue> {
+        let (w, h) = (screen_size.x as usize, screen_size.y as usize);
+        let fbos = [
+            FrameBufferObject::new_with_format::<RGBA32F>(gl, w, h)?,
+            FrameBufferObject::new_with_format::<RGBA32F>(gl, w, h)?,
+        ];
+
+        Ok(Self {
+            operands,
+            operation,
+            fbos,
+            gl: gl.clone(),
+        })
+    }
+
+    pub fn set_cfg(&mut self, operands: [String; 2], operation: LayerOperation) {
+        self.operands = operands;
+        self.operation = operation;
+    }
+
+    pub fn get_operands(&self) -> &[String; 2] {
+        &self.operands
+    }
+
+    pub fn has_operand(&self, layer: &str) -> bool {
+        self.operands.iter().any(|operand| operand == layer)
+    }
+
+    /// The config with which an operand layer is rendered from its own one
+    pub fn get_operand_meta(&self, meta: &ImageMetadata) -> ImageMetadata {
+        operand_meta(meta)
+    }
+
+    /// Render the operands into their buffers, `draw_operand` being called with
+    /// the index of the operand to draw
+    ///
+    /// The viewport and the scissor must be restored afterwards
+    pub fn draw_operands(
+        &mut self,
+        screen_size: &Vector2<f32>,
+        mut draw_operand: impl FnMut(usize) -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        // The sky not covered by an operand must stay transparent
+        self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+
+        for (idx, fbo) in self.fbos.iter_mut().enumerate() {
+            fbo.resize(screen_size.x as usize, screen_size.y as usize);
+            fbo.draw_onto(|| draw_operand(idx), None)?;
+        }
+
+        self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+
+        Ok(())
+    }
+
+    /// Compute the expression and render it on the screen
+    pub fn draw(
+        &self,
+        shaders: &mut ShaderManager,
+        meta: &ImageMetadata,
+        colormaps: &Colormaps,
+        screen_vao: &VertexArrayObject,
+    ) -> Result<(), JsValue> {
+        let shader = shaders.get(
+            &self.gl,
+            ShaderId("hips_composite_rgb.vert", "hips_composite_arithmetic.frag"),
+        )?;
+
+        meta.blend_cfg.enable(&self.gl, || {
+            shader
+                .bind(&self.gl)
+                .attach_uniform("tex_a", &self.fbos[0].texture)
+                .attach_uniform("tex_b", &self.fbos[1].texture)
+                .attach_uniform("operation", &self.operation.id())
+                // The cuts, stretch and colormap of the derived layer
+                .attach_uniforms_with_params_from(&meta.color, colormaps)
+                .attach_uniform("opacity", &meta.opacity)
+                .bind_vertex_array_object_ref(screen_vao)
+                .draw_elements_with_i32(
+                    WebGl2RenderingContext::TRIANGLES,
+                    None,
+                    WebGl2RenderingContext::UNSIGNED_SHORT,
+                    0,
+                );
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use al_api::blend::BlendFunc;
+    use al_api::hips::fixtures;
+
+    #[test]
+    fn operands_rendered_as_physical_values() {
+        let mut meta = fixtures::meta();
+        meta.color.min_cut = Some(-2.0);
+        meta.color.max_cut = Some(5.0);
+        meta.color.cmap_name = CmapLabel::from("rdbu");
+        meta.opacity = 0.5;
+        meta.blend_cfg.func = BlendFunc::Max;
+        assert!(!meta.color.raw_output);
+
+        let operand = operand_meta(&meta);
+        assert!(operand.color.raw_output);
+        assert_eq!(operand.color.cmap_name.as_ref(), "grayscale");
+        assert_eq!(operand.opacity, 1.0);
+        assert_eq!(operand.blend_cfg.func, BlendFunc::FuncAdd);
+        // The cuts are kept, the raw output not being scaled by them
+        assert_eq!(operand.color.min_cut, Some(-2.0));
+        assert_eq!(operand.color.max_cut, Some(5.0));
+    }
+}
@@ -0,0 +1,181 @@
+use al_api::comparison::{ComparisonCfg, ComparisonMode};
+
+use crate::time::Time;
+
+use cgmath::Vector2;
+
+/// How a compared layer is rendered in the current frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComparedLayer {
+    Shown,
+    Hidden,
+    /// Its opacity is multiplied by the factor
+    Faded(f32),
+    /// It is only rendered inside the scissor box given as [x, y, width, height]
+    Scissored([i32; 4]),
+}
+
+/// Compares two layers by blinking, cross-fading or swiping between them
+pub struct Comparison {
+    cfg: ComparisonCfg,
+    // The time at which the blinking started
+    start_time: Time,
+}
+
+impl Comparison {
+    pub fn new(cfg: ComparisonCfg, start_time: Time) -> Self {
+        Self { cfg, start_time }
+    }
+
+    pub fn get_layers(&self) -> &[String; 2] {
+        &self.cfg.layers
+    }
+
+    pub fn has_layer(&self, layer: &str) -> bool {
+        self.cfg.layers.iter().any(|l| l == layer)
+    }
+
+    pub fn rename_layer(&mut self, layer: &str, new_layer: &str) {
+        for l in self.cfg.layers.iter_mut() {
+            if l == layer {
+                *l = new_layer.to_string();
+            }
+        }
+    }
+
+    /// How a layer is rendered in the frame drawn at `now`, `layers` being the stack of
+    /// layers in their drawing order. None is returned if it is not compared
+    pub fn get_compared_layer(
+        &self,
+        layer: &str,
+        layers: &[String],
+        screen_size: &Vector2<f32>,
+        now: Time,
+    ) -> Option<ComparedLayer> {
+        let is_a = self.cfg.layers.iter().position(|l| l == layer)? == 0;
+
+        let compared = match self.cfg.mode {
+            ComparisonMode::Blink { period } => {
+                let elapsed = (now - self.start_time).0 / 1000.0;
+                let half_period = (period * 0.5).max(1e-3);
+                let a_shown = ((elapsed / half_period).floor() as u64).is_multiple_of(2);
+
+                if a_shown == is_a {
+                    ComparedLayer::Shown
+                } else {
+                    ComparedLayer::Hidden
+                }
+            }
+            ComparisonMode::CrossFade { ratio } => {
+                let ratio = ratio.clamp(0.0, 1.0);
+                let idx = |l: &str| layers.iter().position(|layer| layer == l);
+                let [a, b] = &self.cfg.layers;
+                // The upper layer is faded over the lower one
+                let b_is_upper = idx(b) > idx(a);
+
+                match (is_a, b_is_upper) {
+                    (false, true) => ComparedLayer::Faded(ratio),
+                    (true, false) => ComparedLayer::Faded(1.0 - ratio),
+                    _ => ComparedLayer::Shown,
+                }
+            }
+            ComparisonMode::Swipe { position } => {
+                let (w, h) = (screen_size.x as i32, screen_size.y as i32);
+                let x = (position.clamp(0.0, 1.0) * screen_size.x) as i32;
+
+                if is_a {
+                    ComparedLayer::Scissored([0, 0, x, h])
+                } else {
+                    ComparedLayer::Scissored([x, 0, w - x, h])
+                }
+            }
+        };
+
+        Some(compared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comparison(mode: ComparisonMode) -> Comparison {
+        Comparison::new(ComparisonCfg {
+            layers: ["a".to_string(), "b".to_string()],
+            mode,
+        }, Time(0.0))
+    }
+
+    #[test]
+    fn cross_fade_fades_the_upper_layer() {
+        let screen_size = Vector2::new(100.0, 50.0);
+        let cmp = comparison(ComparisonMode::CrossFade { ratio: 0.25 });
+
+        let layers = ["a".to_string(), "b".to_string()];
+        assert_eq!(
+            cmp.get_compared_layer("a", &layers, &screen_size, Time(0.0)),
+            Some(ComparedLayer::Shown)
+        );
+        assert_eq!(
+            cmp.get_compared_layer("b", &layers, &screen_size, Time(0.0)),
+            Some(ComparedLayer::Faded(0.25))
+        );
+
+        let layers = ["b".to_string(), "a".to_string()];
+        assert_eq!(
+            cmp.get_compared_layer("a", &layers, &screen_size, Time(0.0)),
+            Some(ComparedLayer::Faded(0.75))
+        );
+        assert_eq!(
+            cmp.get_compared_layer("c", &layers, &screen_size, Time(0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn swipe_splits_the_view() {
+        let screen_size = Vector2::new(100.0, 50.0);
+        let cmp = comparison(ComparisonMode::Swipe { position: 0.3 });
+        let layers = ["a".to_string(), "b".to_string()];
+
+        assert_eq!(
+            cmp.get_compared_layer("a", &layers, &screen_size, Time(0.0)),
+            Some(ComparedLayer::Scissored([0, 0, 30, 50]))
+        );
+        assert_eq!(
+            cmp.get_compared_layer("b", &layers, &screen_size, Time(0.0)),
+            Some(ComparedLayer::Scissored([30, 0, 70, 50]))
+        );
+    }
+
+    #[test]
+    fn blink_alternates_the_layers() {
+        let screen_size = Vector2::new(100.0, 50.0);
+        let cmp = comparison(ComparisonMode::Blink { period: 2.0 });
+        let layers = ["a".to_string(), "b".to_string()];
+
+        // The times are given in milliseconds, the period in seconds
+        for (now, a_shown) in [
+            (0.0, true),
+            (999.0, true),
+            (1000.0, false),
+            (1999.0, false),
+            (2000.0, true),
+            (5500.0, false),
+        ] {
+            let (a, b) = if a_shown {
+                (ComparedLayer::Shown, ComparedLayer::Hidden)
+            } else {
+                (ComparedLayer::Hidden, ComparedLayer::Shown)
+            };
+            assert_eq!(
+                cmp.get_compared_layer("a", &layers, &screen_size, Time(now)),
+                Some(a)
+            );
+            assert_eq!(
+                cmp.get_compared_layer("b", &layers, &screen_size, Time(now)),
+                Some(b)
+            );
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use al_api::blend::BlendCfg;
+use al_api::composite::LuptonCfg;
+use al_api::hips::ImageMetadata;
+
+use al_core::colormap::Colormaps;
+use al_core::image::format::RGBA32F;
+use al_core::webgl_ctx::GlWrapper;
+use al_core::{FrameBufferObject, VertexArrayObject, WebGlContext};
+
+use crate::renderable::arithmetic::LayerArithmetic;
+use crate::shader::{ShaderId, ShaderManager};
+
+use cgmath::Vector2;
+use wasm_bindgen::JsValue;
+use web_sys::WebGl2RenderingContext;
+
+/// A layer rendered from other layers of the stack, called its channels
+pub enum Composite {
+    RGB(RGBComposite),
+    Arithmetic(LayerArithmetic),
+}
+
+impl Composite {
+    pub fn get_channels(&self) -> &[String] {
+        match self {
+            Composite::RGB(composite) => composite.get_channels(),
+            Composite::Arithmetic(arithmetic) => arithmetic.get_operands(),
+        }
+    }
+
+    pub fn has_channel(&self, layer: &str) -> bool {
+        match self {
+            Composite::RGB(composite) => composite.has_channel(layer),
+            Composite::Arithmetic(arithmetic) => arithmetic.has_operand(layer),
+        }
+    }
+
+    /// The config with which a channel layer is rendered from its own one
+    pub fn get_channel_meta(&self, meta: &ImageMetadata) -> ImageMetadata {
+        match self {
+            Composite::RGB(composite) => composite.get_channel_meta(meta),
+            Composite::Arithmetic(arithmetic) => arithmetic.get_operand_meta(meta),
+        }
+    }
+
+    /// Render the channels into their buffers, `metas` being the own configs
+    /// of the channel layers
+    ///
+    /// The viewport and the scissor must be restored afterwards
+    pub fn draw_channels(
+        &mut self,
+        screen_size: &Vector2<f32>,
+        metas: &[Option<&ImageMetadata>],
+        draw_channel: impl FnMut(usize) -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        match self {
+            Composite::RGB(composite) => composite.draw_channels(screen_size, metas, draw_channel),
+            Composite::Arithmetic(arithmetic) => {
+                arithmetic.draw_operands(screen_size, draw_channel)
+            }
+        }
+    }
+
+    pub fn draw(
+        &self,
+        shaders: &mut ShaderManager,
+        meta: &ImageMetadata,
+        colormaps: &Colormaps,
+        screen_vao: &VertexArrayObject,
+    ) -> Result<(), JsValue> {
+        match self {
+            Composite::RGB(composite) => composite.draw(shaders, meta, screen_vao),
+            Composite::Arithmetic(arithmetic) => {
+                arithmetic.draw(shaders, meta, colormaps, screen_vao)
+            }
+        }
+    }
+}
+
+/// The first channel of a composite that is not a HiPS or an image layer
+///
+/// A composite can neither be one of its own channels nor be composed of other
+/// composites, `is_resource_layer` telling whether a layer refers to a HiPS or an image.
+pub fn find_missing_channel<'a>(
+    layer: &str,
+    channels: &'a [String],
+    is_resource_layer: impl Fn(&str) -> bool,
+) -> Option<&'a String> {
+    channels
+        .iter()
+        .find(|channel| *channel == layer || !is_resource_layer(channel))
+}
+
+/// The channel layers to remove along with a composite
+///
+/// The channels still used by another composite, `is_used` telling it, are kept.
+pub fn unused_channels(channels: &[String], is_used: impl Fn(&str) -> bool) -> Vec<&String> {
+    let mut unused: Vec<&String> = vec![];
+    for channel in channels {
+        if !is_used(channel) && !unused.contains(&channel) {
+            unused.push(channel);
+        }
+    }
+
+    unused
+}
+
+// The channels are rendered in grayscale. The channels of a Lupton composite are
+// rendered as physical values, the composite scaling them by their cuts before
+// applying the asinh stretch
+fn channel_meta(meta: &ImageMetadata, lupton: bool) -> ImageMetadata {
+    let mut meta = meta.clone();
+
+    meta.color.cmap_name = "grayscale".into();
+    meta.color.raw_output = lupton;
+    meta.opacity = 1.0;
+    meta.blend_cfg = BlendCfg::default();
+
+    meta
+}
+
+/// A true-color layer composed from three single-band layers
+///
+/// The channel layers are first rendered in grayscale into offscreen float
+/// buffers that are then combined on the screen.
+pub struct RGBComposite {
+    // The layers of the red, green and blue channels
+    channels: [String; 3],
+    weights: [f32; 3],
+    lupton: Option<LuptonCfg>,
+    // The cuts of the channel layers, scaling their physical values in a Lupton composite
+    min_cuts: [f32; 3],
+    max_cuts: [f32; 3],
+
+    // The buffers in which the channels are rendered
+    fbos: [FrameBufferObject; 3],
+
+    gl: WebGlContext,
+}
+
+impl RGBComposite {
+    pub fn new(
+        gl: &WebGlContext,
+        channels: [String; 3],
+        weights: [f32; 3],
+        lupton: Option<LuptonCfg>,
+        screen_size: &Vector2<f32>,
+    ) -> Result<Self, JsValue> {
+        let (w, h) = (screen_size.x as usize, screen_size.y as usize);
+        let fbos = [
+            FrameBufferObject::new_with_format::<RGBA32F>(gl, w, h)?,
+            FrameBufferObject::new_with_format::<RGBA32F>(gl, w, h)?,
+            FrameBufferObject::new_with_format::<RGBA32F>(gl, w, h)?,
+        ];
+
+        Ok(Self {
+            channels,
+            weights,
+            lupton,
+            min_cuts: [0.0; 3],
+            max_cuts: [1.0; 3],
+            fbos,
+            gl: gl.clone(),
+        })
+    }
+
+    pub fn set_cfg(&mut self, channels: [String; 3], weights: [f32; 3], lupton: Option<LuptonCfg>) {
+        self.channels = channels;
+        self.weights = weights;
+        self.lupton = lupton;
+    }
+
+    pub fn get_channels(&self) -> &[String; 3] {
+        &self.channels
+    }
+
+    pub fn has_channel(&self, layer: &str) -> bool {
+        self.channels.iter().any(|channel| channel == layer)
+    }
+
+    /// The config with which a channel layer is rendered from its own one
+    pub fn get_channel_meta(&self, meta: &ImageMetadata) -> ImageMetadata {
+        channel_meta(meta, self.lupton.is_some())
+    }
+
+    /// Render the channels into their buffers, `draw_channel` being called with
+    /// the index of the channel to draw and `metas` being the own configs of the
+    /// channel layers
+    ///
+    /// The viewport and the scissor must be restored afterwards
+    pub fn draw_channels(
+        &mut self,
+        screen_size: &Vector2<f32>,
+        metas: &[Option<&ImageMetadata>],
+        mut draw_channel: impl FnMut(usize) -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        for (idx, meta) in metas.iter().enumerate() {
+            let color = meta.map(|meta| &meta.color);
+            self.min_cuts[idx] = color.and_then(|c| c.min_cut).unwrap_or(0.0);
+            self.max_cuts[idx] = color.and_then(|c| c.max_cut).unwrap_or(1.0);
+        }
+
+        // The sky not covered by a channel must stay transparent
+        self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+
+        for (idx, fbo) in self.fbos.iter_mut().enumerate() {
+            fbo.resize(screen_size.x as usize, screen_size.y as usize);
+            fbo.draw_onto(|| draw_channel(idx), None)?;
+        }
+
+        self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+
+        Ok(())
+    }
+
+    /// Compose the channels on the screen
+    pub fn draw(
+        &self,
+        shaders: &mut ShaderManager,
+        meta: &ImageMetadata,
+        screen_vao: &VertexArrayObject,
+    ) -> Result<(), JsValue> {
+        let shader = shaders.get(
+            &self.gl,
+            ShaderId("hips_composite_rgb.vert", "hips_composite_rgb.frag"),
+        )?;
+
+        meta.blend_cfg.enable(&self.gl, || {
+            shader
+                .bind(&self.gl)
+                .attach_uniform("tex_r", &self.fbos[0].texture)
+                .attach_uniform("tex_g", &self.fbos[1].texture)
+                .attach_uniform("tex_b", &self.fbos[2].texture)
+                .attach_uniform("weights", &self.weights)
+                .attach_uniform("min_cuts", &self.min_cuts)
+                .attach_uniform("max_cuts", &self.max_cuts)
+                .attach_uniform("lupton", &(self.lupton.is_some() as i32))
+                .attach_uniform("lupton_q", &self.lupton.map(|l| l.q).unwrap_or(1.0))
+                // The tonal corrections of the composite
+                .attach_uniforms_from(&meta.color)
+                .attach_uniform("opacity", &meta.opacity)
+                .bind_vertex_array_object_ref(screen_vao)
+                .draw_elements_with_i32(
+                    WebGl2RenderingContext::TRIANGLES,
+                    None,
+                    WebGl2RenderingContext::UNSIGNED_SHORT,
+                    0,
+                );
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use al_api::blend::{BlendFactor, BlendMode};
+    use al_api::hips::{fixtures, TransferFunction};
+
+    #[test]
+    fn channels_are_resource_layers() {
+        let layers = ["i".to_string(), "r".to_string(), "g".to_string()];
+        let is_resource_layer = |layer: &str| layers.iter().any(|l| l == layer);
+
+        let channels = layers.clone();
+        assert_eq!(
+            find_missing_channel("rgb", &channels, is_resource_layer),
+            None
+        );
+        // The same layer can be given to several channels
+        let channels = ["i".to_string(), "i".to_string(), "g".to_string()];
+        assert_eq!(
+            find_missing_channel("rgb", &channels, is_resource_layer),
+            None
+        );
+
+        // Unknown layers or composites
+        let channels = ["i".to_string(), "z".to_string(), "g".to_string()];
+        assert_eq!(
+            find_missing_channel("rgb", &channels, is_resource_layer),
+            Some(&channels[1])
+        );
+        // A layer replaced by the composite
+        let channels = ["i".to_string(), "r".to_string(), "g".to_string()];
+        assert_eq!(
+            find_missing_channel("g", &channels, is_resource_layer),
+            Some(&channels[2])
+        );
+    }
+
+    #[test]
+    fn channels_rendered_in_grayscale() {
+        let mut meta = fixtures::meta();
+        meta.color.stretch = TransferFunction::Asinh;
+        meta.color.cmap_name = "viridis".into();
+        meta.opacity = 0.4;
+        meta.blend_cfg.src_color_factor = BlendFactor::One;
+        meta.blend_cfg.mode = Some(BlendMode::Screen);
+
+        let channel = channel_meta(&meta, false);
+        assert_eq!(channel.color.cmap_name.as_ref(), "grayscale");
+        assert_eq!(channel.color.stretch, TransferFunction::Asinh);
+        // The opacity and the blending are the ones of the composite
+        assert_eq!(channel.opacity, 1.0);
+        assert_eq!(channel.blend_cfg.src_color_factor, BlendFactor::SrcAlpha);
+        assert_eq!(channel.blend_cfg.mode, None);
+        assert_eq!(meta.blend_cfg.mode, Some(BlendMode::Screen));
+
+        assert!(!channel.color.raw_output);
+
+        // The channels of a Lupton composite are scaled by the composite
+        let channel = channel_meta(&meta, true);
+        assert!(channel.color.raw_output);
+        assert_eq!(channel.color.cmap_name.as_ref(), "grayscale");
+    }
+
+    #[test]
+    fn channels_removed_with_their_composite() {
+        let channels = ["i".to_string(), "i".to_string(), "g".to_string()];
+
+        // A layer given to several channels is removed once
+        assert_eq!(
+            unused_channels(&channels, |_| false),
+            vec![&channels[0], &channels[2]]
+        );
+        // The channels shared with another composite are kept
+        assert_eq!(
+            unused_channels(&channels, |channel| channel == "g"),
+            vec![&channels[0]]
+        );
+        assert!(unused_channels(&channels, |_| true).is_empty());
+    }
+}
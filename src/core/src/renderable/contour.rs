@@ -0,0 +1,672 @@
+//! Contours of the pixel values of a layer
+//!
+//! The contours are traced with the marching squares algorithm on grids of values, i.e. the
+//! pixels of a FITS image or of the FITS tiles of a HiPS. Their lines are kept in ICRS and
+//! projected each time the view is drawn.
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+
+use al_api::color::ColorRGBA;
+use al_api::contour::{ContourLevels, ContourStyle};
+use al_api::coo_system::CooSystem;
+use al_core::WebGlContext;
+use cgmath::{InnerSpace, Matrix4, SquareMatrix, Vector3, Vector4, Zero};
+use healpix::compass_point::MainWind;
+use wasm_bindgen::JsValue;
+
+use crate::camera::CameraViewPort;
+use crate::coo_space::CooSpace;
+use crate::coosys;
+use crate::healpix::cell::HEALPixCell;
+use crate::math::angle::ToAngle;
+use crate::math::lonlat::{LonLat, LonLatT};
+use crate::renderable::hips::sampling::TileValues;
+use crate::renderable::line::{self, PathVertices, RasterizedLineRenderer, Style};
+use crate::renderable::Renderer;
+use crate::shader::ShaderManager;
+use crate::ProjectionType;
+
+// Segments longer than this distance in NDC are projected as great circle arcs
+const MAX_NDC_SEGMENT_LENGTH: f64 = 0.1;
+
+/// Physical values on a regular grid, NaN for the blank pixels
+pub struct ValueGrid {
+    pub values: Vec<f32>,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// A grid of values with the function giving the ICRS position of a point of the grid
+pub type LocatedGrid<'a> = (
+    ValueGrid,
+    Box<dyn Fn(f64, f64) -> Option<Vector4<f64>> + 'a>,
+);
+
+/// Levels of the contours computed from the values of a layer
+///
+/// NaN values are discarded. The levels are sorted in increasing order.
+pub fn levels(values: &mut Vec<f32>, cfg: &ContourLevels) -> Vec<f64> {
+    values.retain(|v| v.is_finite());
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let mut levels = match cfg {
+        ContourLevels::Values { values } => values.clone(),
+        ContourLevels::Linear { num } => match (values.first(), values.last()) {
+            (Some(&min), Some(&max)) if min < max => {
+                let (min, max) = (min as f64, max as f64);
+
+                (1..=*num)
+                    .map(|i| min + (max - min) * (i as f64) / ((num + 1) as f64))
+                    .collect()
+            }
+            _ => vec![],
+        },
+        ContourLevels::Log { num } => {
+            let min = values.iter().find(|v| **v > 0.0);
+            match (min, values.last()) {
+                (Some(&min), Some(&max)) if min < max => {
+                    let (min, max) = ((min as f64).ln(), (max as f64).ln());
+
+                    (1..=*num)
+                        .map(|i| (min + (max - min) * (i as f64) / ((num + 1) as f64)).exp())
+                        .collect()
+                }
+                _ => vec![],
+            }
+        }
+        ContourLevels::Sigma { sigmas } => {
+            if values.is_empty() {
+                vec![]
+            } else {
+                let background = median(values);
+                let mut deviations = values
+                    .iter()
+                    .map(|v| (v - background).abs())
+                    .collect::<Vec<_>>();
+                deviations.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                // Standard deviation of a normal distribution having that median absolute deviation
+                let std = 1.4826 * (median(&deviations) as f64);
+
+                sigmas.iter().map(|k| background as f64 + k * std).collect()
+            }
+        }
+    };
+
+    levels.retain(|level| level.is_finite());
+    levels.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    levels.dedup();
+
+    levels
+}
+
+// Median of sorted values
+fn median(sorted: &[f32]) -> f32 {
+    let n = sorted.len();
+    0.5 * (sorted[(n - 1) / 2] + sorted[n / 2])
+}
+
+// An edge of the grid, given by its first vertex and whether it goes along the x axis
+type Edge = (usize, usize, bool);
+
+/// Lines of a grid of values at a level with the marching squares algorithm
+///
+/// The values are given row by row. The vertices of the lines are given in the coordinates of
+/// the grid, the value at index `y * width + x` being located at `(x, y)`. Cells having a NaN
+/// corner are not crossed by the lines. Closed lines end with their first vertex.
+pub fn marching_squares(
+    values: &[f32],
+    width: usize,
+    height: usize,
+    level: f32,
+) -> Vec<Vec<[f64; 2]>> {
+    let value = |x: usize, y: usize| values[y * width + x];
+
+    let mut segments: Vec<(Edge, Edge)> = vec![];
+    for y in 0..height.saturating_sub(1) {
+        for x in 0..width.saturating_sub(1) {
+            // Bottom left, bottom right, top right and top left corners
+            let corners = [
+                value(x, y),
+                value(x + 1, y),
+                value(x + 1, y + 1),
+                value(x, y + 1),
+            ];
+            if corners.iter().any(|v| v.is_nan()) {
+                continue;
+            }
+
+            let case = corners
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| **v >= level)
+                .fold(0, |case, (i, _)| case | (1 << i));
+            let (bottom, right, top, left) = (
+                (x, y, true),
+                (x + 1, y, false),
+                (x, y + 1, true),
+                (x, y, false),
+            );
+
+            match case {
+                1 | 14 => segments.push((left, bottom)),
+                2 | 13 => segments.push((bottom, right)),
+                3 | 12 => segments.push((left, right)),
+                4 | 11 => segments.push((right, top)),
+                6 | 9 => segments.push((bottom, top)),
+                7 | 8 => segments.push((left, top)),
+                5 | 10 => {
+                    // Saddle, the mean of the corners tells whether the corners above the level
+                    // are connected through the center of the cell
+                    let center_above = corners.iter().sum::<f32>() * 0.25 >= level;
+                    if (case == 5) == center_above {
+                        // The bottom right and top left corners are isolated
+                        segments.push((bottom, right));
+                        segments.push((top, left));
+                    } else {
+                        // The bottom left and top right corners are isolated
+                        segments.push((left, bottom));
+                        segments.push((right, top));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // Where the line crosses an edge
+    let point = |(x, y, along_x): Edge| -> [f64; 2] {
+        let (v0, v1) = if along_x {
+            (value(x, y), value(x + 1, y))
+        } else {
+            (value(x, y), value(x, y + 1))
+        };
+        let t = ((level - v0) / (v1 - v0)) as f64;
+
+        if along_x {
+            [x as f64 + t, y as f64]
+        } else {
+            [x as f64, y as f64 + t]
+        }
+    };
+
+    // Join the segments sharing an edge
+    let mut segments_of_edge: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (i, (a, b)) in segments.iter().enumerate() {
+        segments_of_edge.entry(*a).or_default().push(i);
+        segments_of_edge.entry(*b).or_default().push(i);
+    }
+
+    let mut joined = vec![false; segments.len()];
+    let mut lines = vec![];
+    for start in 0..segments.len() {
+        if joined[start] {
+            continue;
+        }
+        joined[start] = true;
+
+        let (a, b) = segments[start];
+        let mut line = VecDeque::from(vec![a, b]);
+        // Extend the line from both of its ends
+        for &forward in &[true, false] {
+            loop {
+                let end = if forward {
+                    line[line.len() - 1]
+                } else {
+                    line[0]
+                };
+                let next = segments_of_edge[&end].iter().copied().find(|i| !joined[*i]);
+
+                if let Some(i) = next {
+                    joined[i] = true;
+
+                    let (a, b) = segments[i];
+                    let other = if a == end { b } else { a };
+                    if forward {
+                        line.push_back(other);
+                    } else {
+                        line.push_front(other);
+                    }
+                } else {
+                    break;
+                }
+            }
+        }
+
+        lines.push(line.into_iter().map(point).collect());
+    }
+
+    lines
+}
+
+/// Trace the contours of several grids of values
+///
+/// The levels are computed from the values of all the grids.
+/// Returns the levels along with the lines in ICRS.
+pub fn trace(grids: &[LocatedGrid], cfg: &ContourLevels) -> (Vec<f64>, Vec<Vec<Vector4<f64>>>) {
+    let mut values = grids
+        .iter()
+        .flat_map(|(grid, _)| grid.values.iter().copied())
+        .collect::<Vec<_>>();
+    let levels = levels(&mut values, cfg);
+
+    let mut lines = vec![];
+    for (grid, to_icrs) in grids {
+        for level in &levels {
+            for vertices in marching_squares(&grid.values, grid.width, grid.height, *level as f32) {
+                // Lines are split where they cannot be located on the sky
+                let mut line = vec![];
+                for [x, y] in vertices {
+                    if let Some(v) = to_icrs(x, y) {
+                        line.push(v);
+                    } else if line.len() > 1 {
+                        lines.push(std::mem::take(&mut line));
+                    } else {
+                        line.clear();
+                    }
+                }
+
+                if line.len() > 1 {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+
+    (levels, lines)
+}
+
+/// Grid of the values of a HiPS tile bordered by the first column and row of its neighbour
+/// tiles, along with the positions of its pixels in the frame of the HiPS
+///
+/// The cells between two tiles are marched with one of them so that the lines of adjacent
+/// tiles are joined. The border pixels of the tiles not received are blank.
+fn bordered_tile(
+    cell: &HEALPixCell,
+    tiles: &HashMap<HEALPixCell, Vec<f32>>,
+) -> (ValueGrid, Vec<Option<Vector4<f64>>>) {
+    let values = &tiles[cell];
+    let n = (values.len() as f64).sqrt() as usize;
+    // The depth of the pixels of the tile
+    let depth = cell.depth() + n.trailing_zeros() as u8;
+    let to_vector = |(lon, lat): (f64, f64)| -> Vector4<f64> {
+        LonLatT::new(lon.to_angle(), lat.to_angle()).vector()
+    };
+
+    // FITS tiles are stored upside down, see `TileReader::read`
+    let pixel_center = |x: usize, y: usize| {
+        let (dx, dy) = (
+            (n as f64 - 0.5 - y as f64) / n as f64,
+            (x as f64 + 0.5) / n as f64,
+        );
+        healpix::nested::sph_coo(cell.depth(), cell.idx(), dx, dy)
+    };
+
+    let width = n + 1;
+    let mut grid = ValueGrid {
+        values: Vec::with_capacity(width * width),
+        width,
+        height: width,
+    };
+    let mut positions = Vec::with_capacity(width * width);
+    for y in 0..width {
+        for x in 0..width {
+            if x < n && y < n {
+                grid.values.push(values[y * n + x]);
+                positions.push(Some(to_vector(pixel_center(x, y))));
+                continue;
+            }
+
+            // The grid goes along the north west and south west sides of the tile
+            let wind = match (x == n, y == n) {
+                (true, true) => MainWind::W,
+                (true, false) => MainWind::NW,
+                _ => MainWind::SW,
+            };
+            let (lon, lat) = pixel_center(x.min(n - 1), y.min(n - 1));
+            let neighbour = HEALPixCell(depth, healpix::nested::hash(depth, lon, lat))
+                .neighbor(wind)
+                .map(|pixel| pixel.center());
+
+            let value = neighbour.and_then(|(lon, lat)| {
+                let (idx, dx, dy) = healpix::nested::hash_with_dxdy(cell.depth(), lon, lat);
+                let tile = tiles
+                    .get(&HEALPixCell(cell.depth(), idx))
+                    .filter(|tile| tile.len() == values.len())?;
+                let (i, j) = (
+                    ((dx * n as f64) as usize).min(n - 1),
+                    ((dy * n as f64) as usize).min(n - 1),
+                );
+
+                Some(tile[(n - 1 - i) * n + j])
+            });
+
+            grid.values.push(value.unwrap_or(f32::NAN));
+            positions.push(neighbour.filter(|_| value.is_some()).map(to_vector));
+        }
+    }
+
+    (grid, positions)
+}
+
+// Position of a point of a grid interpolated from the positions of its pixels
+fn interpolate(
+    positions: &[Option<Vector4<f64>>],
+    width: usize,
+    x: f64,
+    y: f64,
+) -> Option<Vector4<f64>> {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as usize, y0 as usize);
+
+    let mut pos = Vector3::zero();
+    for (i, j, w) in [
+        (x0, y0, (1.0 - tx) * (1.0 - ty)),
+        (x0 + 1, y0, tx * (1.0 - ty)),
+        (x0, y0 + 1, (1.0 - tx) * ty),
+        (x0 + 1, y0 + 1, tx * ty),
+    ] {
+        if w > 0.0 {
+            pos += positions.get(j * width + i).copied().flatten()?.truncate() * w;
+        }
+    }
+
+    Some(pos.normalize().extend(1.0))
+}
+
+/// Grids of the tiles of a HiPS located in ICRS, `frame` being the frame of the HiPS
+fn tile_grids(
+    tiles: &HashMap<HEALPixCell, Vec<f32>>,
+    frame: CooSystem,
+) -> Vec<LocatedGrid<'static>> {
+    tiles
+        .keys()
+        .map(|cell| {
+            let (grid, positions) = bordered_tile(cell, tiles);
+            // The positions of the pixels in ICRS
+            let positions = positions
+                .into_iter()
+                .map(|pos| {
+                    // The frame of a HiPS is never the horizontal one
+                    let icrs2hor = Matrix4::identity();
+                    pos.map(|pos| coosys::apply_coo_system(frame, CooSystem::ICRS, &icrs2hor, &pos))
+                })
+                .collect::<Vec<_>>();
+            let width = grid.width;
+
+            let to_icrs = move |x: f64, y: f64| interpolate(&positions, width, x, y);
+
+            (
+                grid,
+                Box::new(to_icrs) as Box<dyn Fn(f64, f64) -> Option<Vector4<f64>>>,
+            )
+        })
+        .collect()
+}
+
+/// Contours of a HiPS waiting for its tiles
+pub struct PendingContours {
+    pub tiles: TileValues,
+    pub uuid: String,
+    pub levels: ContourLevels,
+    pub style: ContourStyle,
+    // The frame of the HiPS
+    pub frame: CooSystem,
+    // The function called with the levels once the contours are traced
+    pub resolve: js_sys::Function,
+}
+
+impl PendingContours {
+    pub fn is_complete(&self) -> bool {
+        self.tiles.is_complete()
+    }
+
+    /// Trace the contours on the tiles received and give the levels to the javascript
+    pub fn trace(self, renderer: &mut ContourRenderer) -> Result<(), JsValue> {
+        let PendingContours {
+            tiles,
+            uuid,
+            levels,
+            style,
+            frame,
+            resolve,
+        } = self;
+
+        let grids = tile_grids(&tiles.into_tiles(), frame);
+        let (levels, lines) = trace(&grids, &levels);
+        renderer.push(uuid, lines, style);
+
+        resolve.call1(&JsValue::NULL, &serde_wasm_bindgen::to_value(&levels)?)?;
+
+        Ok(())
+    }
+}
+
+struct Contours {
+    uuid: String,
+    // The vertices of the lines in ICRS
+    lines: Vec<Vec<Vector4<f64>>>,
+    style: ContourStyle,
+}
+
+pub struct ContourRenderer {
+    contours: Vec<Contours>,
+    line_renderer: RasterizedLineRenderer,
+}
+
+impl ContourRenderer {
+    pub fn new(gl: &WebGlContext) -> Result<Self, JsValue> {
+        let line_renderer = RasterizedLineRenderer::new(gl)?;
+
+        Ok(Self {
+            contours: vec![],
+            line_renderer,
+        })
+    }
+
+    /// Add the lines of contours, replacing the ones having the same uuid
+    pub fn push(&mut self, uuid: String, lines: Vec<Vec<Vector4<f64>>>, style: ContourStyle) {
+        self.remove(&uuid);
+        self.contours.push(Contours { uuid, lines, style });
+    }
+
+    pub fn remove(&mut self, uuid: &str) -> Option<()> {
+        let idx = self.contours.iter().position(|c| c.uuid == uuid)?;
+        self.contours.remove(idx);
+
+        Some(())
+    }
+
+    pub fn set_style(&mut self, uuid: &str, style: ContourStyle) -> Option<()> {
+        let contours = self.contours.iter_mut().find(|c| c.uuid == uuid)?;
+        contours.style = style;
+
+        Some(())
+    }
+
+    pub fn draw(
+        &mut self,
+        camera: &CameraViewPort,
+        projection: &ProjectionType,
+        shaders: &mut ShaderManager,
+    ) -> Result<(), JsValue> {
+        if self.contours.iter().all(|c| !c.style.show) {
+            return Ok(());
+        }
+
+        let icrs2view =
+            CooSystem::ICRS.to(camera.get_coo_system(), camera.get_icrs_to_horizontal());
+        let to_ndc = |v: &Vector4<f64>| {
+            projection
+                .model_to_normalized_device_space(&(icrs2view * v), camera)
+                .map(|p| [p.x, p.y])
+        };
+
+        self.line_renderer.begin();
+        for Contours { lines, style, .. } in self.contours.iter().filter(|c| c.style.show) {
+            let paths = lines
+                .iter()
+                .flat_map(|line| line.iter().zip(line.iter().skip(1)))
+                .filter_map(|(v1, v2)| {
+                    let (p1, p2) = (to_ndc(v1), to_ndc(v2));
+                    let vertices = match (p1, p2) {
+                        // Segments outside of the view
+                        (Some([x1, y1]), Some([x2, y2]))
+                            if (x1.abs() > 1.0 && x2.abs() > 1.0 && x1 * x2 > 0.0)
+                                || (y1.abs() > 1.0 && y2.abs() > 1.0 && y1 * y2 > 0.0) =>
+                        {
+                            return None;
+                        }
+                        (Some([x1, y1]), Some([x2, y2]))
+                            if (x2 - x1).hypot(y2 - y1) < MAX_NDC_SEGMENT_LENGTH =>
+                        {
+                            vec![[x1 as f32, y1 as f32], [x2 as f32, y2 as f32]]
+                        }
+                        // Long segments may cross the border of the projection
+                        _ => {
+                            let (v1, v2) = ((icrs2view * v1).lonlat(), (icrs2view * v2).lonlat());
+
+                            line::great_circle_arc::project(
+                                v1.lon().to_radians(),
+                                v1.lat().to_radians(),
+                                v2.lon().to_radians(),
+                                v2.lat().to_radians(),
+                                camera,
+                                projection,
+                            )
+                            .into_iter()
+                            .map(|v| [v.x as f32, v.y as f32])
+                            .collect()
+                        }
+                    };
+
+                    Some(PathVertices { vertices })
+                });
+
+            let color = ColorRGBA {
+                r: style.color.r,
+                g: style.color.g,
+                b: style.color.b,
+                a: style.opacity,
+            };
+            self.line_renderer.add_stroke_paths(
+                paths,
+                style.thickness,
+                &color,
+                &Style::None,
+                CooSpace::NDC,
+            );
+        }
+        self.line_renderer.end();
+
+        self.line_renderer.draw(shaders, camera, projection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_line_around_a_peak() {
+        let size = 7;
+        let values = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f32 - 3.0, (i / size) as f32 - 3.0);
+                -(x * x + y * y)
+            })
+            .collect::<Vec<_>>();
+
+        let lines = marching_squares(&values, size, size, -4.5);
+        assert_eq!(lines.len(), 1);
+
+        let line = &lines[0];
+        assert_eq!(line.first(), line.last());
+        for [x, y] in line {
+            let r = (x - 3.0).hypot(y - 3.0);
+            assert!(r > 1.5 && r < 2.5, "vertex at {} from the peak", r);
+        }
+    }
+
+    #[test]
+    fn open_line_across_a_ramp() {
+        let (width, height) = (4, 5);
+        let values = (0..width * height)
+            .map(|i| (i % width) as f32)
+            .collect::<Vec<_>>();
+
+        let lines = marching_squares(&values, width, height, 1.5);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].len(), height);
+        assert!(lines[0].iter().all(|[x, _]| (x - 1.5).abs() < 1e-9));
+
+        // No line crosses the cells having a blank corner
+        let mut values = values;
+        values[2 * width + 2] = f32::NAN;
+        let lines = marching_squares(&values, width, height, 1.5);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() == 2));
+    }
+
+    #[test]
+    fn lines_joined_across_tiles() {
+        // The sine of the latitude in the tiles of depth 3 having 4x4 pixels
+        let n = 4;
+        let tiles = (0..768)
+            .map(|idx| {
+                let values = (0..n * n)
+                    .map(|i| {
+                        let (x, y) = (i % n, i / n);
+                        let (dx, dy) = ((n - y) as f64 - 0.5, x as f64 + 0.5);
+                        let (_, lat) =
+                            healpix::nested::sph_coo(3, idx, dx / n as f64, dy / n as f64);
+                        lat.sin() as f32
+                    })
+                    .collect::<Vec<_>>();
+
+                (HEALPixCell(3, idx), values)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let grids = tile_grids(&tiles, CooSystem::ICRS);
+        let (_, lines) = trace(&grids, &ContourLevels::Values { values: vec![0.3] });
+        assert!(lines.len() > 1);
+
+        // The lines ending on the border of a tile go on in the neighbour tile
+        let ends = lines
+            .iter()
+            .flat_map(|line| [line[0], line[line.len() - 1]])
+            .collect::<Vec<_>>();
+        for (i, end) in ends.iter().enumerate() {
+            assert!((end.y - 0.3).abs() < 1e-2);
+            assert!(ends
+                .iter()
+                .enumerate()
+                .any(|(j, other)| i != j && (end - other).magnitude() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn levels_of_the_methods() {
+        let mut values = vec![1.0, f32::NAN, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(
+            levels(&mut values, &ContourLevels::Linear { num: 3 }),
+            vec![2.0, 3.0, 4.0]
+        );
+
+        let mut values = vec![-1.0, 1.0, 10.0, 100.0];
+        let log = levels(&mut values, &ContourLevels::Log { num: 1 });
+        assert_eq!(log.len(), 1);
+        assert!((log[0] - 10.0).abs() < 1e-9);
+
+        let mut values = vec![0.0, 1.0, 2.0, 3.0, 4.0];
+        let sigma = levels(
+            &mut values,
+            &ContourLevels::Sigma {
+                sigmas: vec![1.0, 2.0],
+            },
+        );
+        assert!((sigma[0] - (2.0 + 1.4826)).abs() < 1e-5);
+        assert!((sigma[1] - (2.0 + 2.0 * 1.4826)).abs() < 1e-5);
+    }
+}
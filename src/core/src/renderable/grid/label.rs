@@ -13,6 +13,7 @@ use crate::math::TWICE_PI;
 use crate::math::angle::ToAngle;
 use crate::math::angle::AngleFormatter;
 use al_api::angle::Formatter;
+use al_api::coo_system::CooSystem;
 use cgmath::Vector2;
 use core::ops::Range;
 
@@ -77,14 +78,20 @@ impl Label {
             lon += TWICE_PI;
         }
 
+        // In the horizontal frame, the azimuth is displayed
+        let horizontal = camera.get_coo_system() == CooSystem::HORIZONTAL;
+        if horizontal {
+            lon = (TWICE_PI - lon) % TWICE_PI;
+        }
+
         let mut angle = lon.to_angle();
         let fmt = match fmt {
             Formatter::Decimal => {
                 AngleFormatter::Decimal { prec: grid_decimal_prec }
             },
             Formatter::Sexagesimal => {
-                // Sexagesimal formatting for longitudes is HMS
-                AngleFormatter::Sexagesimal { prec: grid_decimal_prec, plus: false, hours: true }
+                // Sexagesimal formatting for longitudes is HMS (DMS for azimuths)
+                AngleFormatter::Sexagesimal { prec: grid_decimal_prec, plus: false, hours: !horizontal }
             }
         };
         angle.set_format(fmt);
@@ -502,8 +502,13 @@ impl HpxTileBuffer for HiPS2DBuffer {
         let camera_frame = camera.get_coo_system();
         let hips_frame = cfg.get_frame();
 
-        let pos: LonLatT<f64> =
-            crate::coosys::apply_coo_system(camera_frame, hips_frame, &pos.vector()).lonlat();
+        let pos: LonLatT<f64> = crate::coosys::apply_coo_system(
+            camera_frame,
+            hips_frame,
+            camera.get_icrs_to_horizontal(),
+            &pos.vector(),
+        )
+        .lonlat();
 
         // Get the array of textures from that survey
         let depth = camera.get_texture_depth().min(cfg.get_max_depth_texture());
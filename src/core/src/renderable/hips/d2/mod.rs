@@ -705,7 +705,7 @@ impl HiPS2D {
         let selected_frame = camera.get_coo_system();
         let hips_cfg = self.buffer.config();
         let hips_frame = hips_cfg.get_frame();
-        let c = selected_frame.to(hips_frame);
+        let c = selected_frame.to(hips_frame, camera.get_icrs_to_horizontal());
 
         let raytracing = camera.is_raytracing(proj);
         let config = self.get_config();
@@ -768,7 +768,7 @@ impl HiPS2D {
                     .attach_uniform("inv_model", &v2w)
                     .attach_uniform("current_time", &utils::get_current_time())
                     .attach_uniform("opacity", opacity)
-                    .attach_uniform("u_proj", proj)
+                    .attach_uniforms_from(proj)
                     .attach_uniforms_from(colormaps)
                     .bind_vertex_array_object_ref(&self.vao)
                     .draw_elements_with_i32(
@@ -552,7 +552,7 @@ impl HiPS3D {
         // Get the coo system transformation matrix
         let selected_frame = camera.get_coo_system();
         let hips_frame = hips_cfg.get_frame();
-        let c = selected_frame.to(hips_frame);
+        let c = selected_frame.to(hips_frame, camera.get_icrs_to_horizontal());
 
         let big_fov = camera.is_raytracing(proj);
         if big_fov {
@@ -611,7 +611,7 @@ impl HiPS3D {
                     .attach_uniforms_from(camera)
                     .attach_uniform("inv_model", &v2w)
                     .attach_uniform("opacity", opacity)
-                    .attach_uniform("u_proj", proj)
+                    .attach_uniforms_from(proj)
                     .attach_uniforms_from(colormaps)
                     .bind_vertex_array_object_ref(&self.vao)
                     .draw_elements_with_i32(
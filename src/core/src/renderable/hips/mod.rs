@@ -3,6 +3,7 @@ pub mod config;
 pub mod d2;
 pub mod d3;
 pub mod raytracing;
+pub mod sampling;
 mod triangulation;
 pub mod uv;
 
@@ -138,6 +139,14 @@ impl HiPS {
         }
     }
 
+    #[inline]
+    pub fn get_moc(&self) -> Option<&HEALPixCoverage> {
+        match self {
+            D2(hips) => hips.get_moc(),
+            D3(hips) => hips.get_moc(),
+        }
+    }
+
     #[inline]
     pub fn get_tile_query(&self, cell: &HEALPixCell) -> query::Tile {
         match self {
@@ -1,4 +1,7 @@
+use crate::math::projection::coo_space::XYClip;
+use crate::math::projection::domain::sdf::{ProjDef, ProjDefType};
 use crate::math::projection::Projection;
+use cgmath::{InnerSpace, Vector2};
 
 use al_core::VecData;
 use al_core::{shader::ShaderBound, VertexArrayObject, WebGlContext};
@@ -9,27 +12,68 @@ pub trait RayTracingProjection {
     fn get_raytracer_vertex_array_object(raytracer: &RayTracer) -> &VertexArrayObject;
 }
 
-fn create_vertices_array(proj: &ProjectionType) -> (Vec<f32>, Vec<u16>) {
+const MOVE_INSIDE_MARGIN: f64 = 1e-3;
+const MAX_MOVE_INSIDE_STEPS: usize = 8;
+
+fn create_vertices_array(proj: &ProjectionType) -> Result<(Vec<f32>, Vec<u16>), JsValue> {
     let Triangulation { vertices, idx } = Triangulation::build(proj.get_area());
 
     let vertices = vertices
         .into_iter()
-        .flat_map(|pos_clip_space| {
+        .map(|pos_clip_space| {
             // Cast all the double into float
             // simple precision because this buffer
             // is sent to the GPU
-            let pos_world_space = proj.clip_to_world_space(&(pos_clip_space * 0.99)).unwrap();
-            [
+            let pos_world_space = proj
+                .clip_to_world_space(&(pos_clip_space * 0.99))
+                .or_else(|| {
+                    // Domains that are not star-shaped around the origin (e.g. COD).
+                    // The signed distance functions being approximate (e.g. around
+                    // the apex of the COD domain), retry with larger margins
+                    (0..MAX_MOVE_INSIDE_STEPS).find_map(|i| {
+                        let margin = MOVE_INSIDE_MARGIN * (1 << i) as f64;
+                        proj.clip_to_world_space(&move_inside(
+                            &pos_clip_space,
+                            proj.get_area(),
+                            margin,
+                        ))
+                    })
+                })
+                .ok_or_else(|| {
+                    JsValue::from_str(&format!(
+                        "The vertex ({}, {}) of the projection domain cannot be unprojected",
+                        pos_clip_space.x, pos_clip_space.y
+                    ))
+                })?;
+
+            Ok([
                 pos_clip_space.x as f32,
                 pos_clip_space.y as f32,
                 pos_world_space.x as f32,
                 pos_world_space.y as f32,
                 pos_world_space.z as f32,
-            ]
+            ])
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, JsValue>>()?
+        .concat();
+
+    Ok((vertices, idx))
+}
+
+// Move a vertex lying on the frontier of a definition domain slightly inside of it
+// following the gradient of its signed distance function
+fn move_inside(pos_clip_space: &XYClip<f64>, proj_def: &ProjDefType, margin: f64) -> XYClip<f64> {
+    const H: f64 = 1e-6;
+
+    let dx = Vector2::new(H, 0.0);
+    let dy = Vector2::new(0.0, H);
+    let grad = Vector2::new(
+        proj_def.sdf(&(pos_clip_space + dx)) - proj_def.sdf(&(pos_clip_space - dx)),
+        proj_def.sdf(&(pos_clip_space + dy)) - proj_def.sdf(&(pos_clip_space - dy)),
+    )
+    .normalize();
 
-    (vertices, idx)
+    pos_clip_space - grad * (proj_def.sdf(pos_clip_space) + margin)
 }
 
 use web_sys::WebGl2RenderingContext;
@@ -100,7 +144,7 @@ use crate::ProjectionType;
 use wasm_bindgen::JsValue;
 impl RayTracer {
     pub fn new(gl: &WebGlContext, proj: &ProjectionType) -> Result<RayTracer, JsValue> {
-        let (vertices, idx) = create_vertices_array(proj);
+        let (vertices, idx) = create_vertices_array(proj)?;
 
         let mut vao = VertexArrayObject::new(gl);
         // layout (location = 0) in vec2 pos_clip_space;
@@ -174,3 +218,95 @@ impl RayTracer {
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertices_inside_projection_domains() {
+        // The vertices of the triangulation must all be deprojectable
+        let projections = [
+            ProjectionType::Tan(mapproj::zenithal::tan::Tan),
+            ProjectionType::Stg(mapproj::zenithal::stg::Stg),
+            ProjectionType::Sin(mapproj::zenithal::sin::Sin),
+            ProjectionType::Zea(mapproj::zenithal::zea::Zea),
+            ProjectionType::Air(mapproj::zenithal::air::Air::new()),
+            ProjectionType::Arc(mapproj::zenithal::arc::Arc),
+            ProjectionType::Zpn(crate::math::projection::zpn::Zpn::new()),
+            ProjectionType::Ait(mapproj::pseudocyl::ait::Ait),
+            ProjectionType::Mol(mapproj::pseudocyl::mol::Mol::new()),
+            ProjectionType::Par(mapproj::pseudocyl::par::Par),
+            ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl),
+            ProjectionType::Mer(mapproj::cylindrical::mer::Mer),
+            ProjectionType::Car(mapproj::cylindrical::car::Car),
+            ProjectionType::Cea(mapproj::cylindrical::cea::Cea::new()),
+            ProjectionType::Cod(crate::math::projection::cod::Cod::new()),
+            ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx),
+        ];
+        let cod = |theta_1: f64, theta_2: f64| {
+            crate::math::projection::cod::Cod::from_standard_parallels(
+                theta_1.to_radians(),
+                theta_2.to_radians(),
+            )
+            .map(ProjectionType::Cod)
+            .unwrap()
+        };
+        let azp = |mu: f64| {
+            crate::math::projection::azp::Azp::from_param(mu)
+                .map(ProjectionType::Azp)
+                .unwrap()
+        };
+        let parameterised = [
+            cod(20.0, 40.0),
+            cod(-30.0, -30.0),
+            cod(60.0, 70.0),
+            cod(-70.0, -10.0),
+            azp(0.5),
+            azp(1.35),
+            azp(3.0),
+        ];
+
+        for proj in projections.iter().chain(parameterised.iter()) {
+            let (vertices, idx) = create_vertices_array(proj).unwrap();
+            assert!(!idx.is_empty());
+            assert!(vertices.iter().all(|v| v.is_finite()));
+        }
+    }
+
+    #[test]
+    fn vertices_for_parameterised_projections() {
+        use crate::math::projection::{azp::Azp, cod::Cod, zpn::Zpn};
+
+        let check = |proj: ProjectionType| {
+            let (vertices, idx) = create_vertices_array(&proj).unwrap();
+            assert!(!idx.is_empty());
+            assert!(vertices.iter().all(|v| v.is_finite()));
+        };
+
+        // Every pair of valid standard parallels
+        let parallels = (-9..=9).map(|i| (i as f64 * 10.0).to_radians());
+        for theta_1 in parallels.clone() {
+            for theta_2 in parallels.clone() {
+                if let Some(cod) = Cod::from_standard_parallels(theta_1, theta_2) {
+                    check(ProjectionType::Cod(cod));
+                }
+            }
+        }
+
+        for mu in (0..=20).map(|i| i as f64 * 0.25) {
+            check(ProjectionType::Azp(Azp::from_param(mu).unwrap()));
+        }
+
+        for coeffs in [
+            vec![0.0, 1.0],
+            vec![0.0, 1.0, 0.0, -0.05],
+            vec![0.0, 1.0, 0.2],
+            vec![0.0, 1.0, -0.2],
+            vec![0.0, 0.5, 0.0, 0.1],
+            vec![0.0, 1.0, 0.0, -0.2, 0.0, 0.01],
+        ] {
+            check(ProjectionType::Zpn(Zpn::from_params(coeffs).unwrap()));
+        }
+    }
+}
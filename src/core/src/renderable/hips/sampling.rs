@@ -0,0 +1,820 @@
+//! Sampling of the tiles of a HiPS at several sky positions
+//!
+//! The tiles are not read from the GPU buffer as they may not be in the view
+//! but are requested again and decoded on the CPU once received.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Cursor;
+
+use al_core::image::format::ChannelType;
+use al_core::image::ImageType;
+use fitsrs::fits::Fits;
+use fitsrs::hdu::data::InMemData;
+use wasm_bindgen::{JsCast, JsValue};
+
+use super::HiPS;
+use crate::camera::CameraViewPort;
+use crate::downloader::query;
+use crate::downloader::request::tile::Tile;
+use crate::healpix::cell::HEALPixCell;
+use crate::math::lonlat::LonLatT;
+use crate::renderable::image::fits::{self, CardValue, ImageData};
+use crate::renderable::CreatorDid;
+
+pub struct TileSampling {
+    hips_cdid: CreatorDid,
+    channel: Option<u32>,
+    // The tile containing each position and the offsets of the position inside it
+    positions: Vec<(HEALPixCell, f64, f64)>,
+    values: Vec<JsValue>,
+    // Tiles not received yet
+    missing: HashSet<HEALPixCell>,
+    // Whether a tile has been received
+    received: bool,
+
+    resolve: js_sys::Function,
+    reject: js_sys::Function,
+}
+
+impl TileSampling {
+    /// Start a sampling of the tiles of a HiPS
+    ///
+    /// # Arguments
+    ///
+    /// * `hips` - The HiPS to sample
+    /// * `positions` - The sky positions given in the frame of the HiPS
+    /// * `depth` - The order of the tiles to sample
+    /// * `resolve` - The function called with the values once all the tiles are received
+    /// * `reject` - The function called if none of the tiles can be retrieved
+    ///
+    /// Returns the sampling with the queries of the tiles to fetch
+    pub fn new(
+        hips: &HiPS,
+        positions: &[LonLatT<f64>],
+        depth: u8,
+        resolve: js_sys::Function,
+        reject: js_sys::Function,
+    ) -> (Self, Vec<query::Tile>) {
+        let positions = positions
+            .iter()
+            .map(|lonlat| {
+                let (idx, dx, dy) = crate::healpix::utils::hash_with_dxdy(depth, lonlat);
+                (HEALPixCell(depth, idx), dx, dy)
+            })
+            .collect::<Vec<_>>();
+
+        let missing = positions
+            .iter()
+            .map(|(cell, _, _)| *cell)
+            .collect::<HashSet<_>>();
+        let queries = missing
+            .iter()
+            .map(|cell| hips.get_tile_query(cell))
+            .collect::<Vec<_>>();
+
+        let sampling = Self {
+            hips_cdid: hips.get_config().get_creator_did().to_string(),
+            channel: queries.first().and_then(|query| query.channel),
+            values: vec![JsValue::NULL; positions.len()],
+            positions,
+            missing,
+            received: false,
+            resolve,
+            reject,
+        };
+
+        (sampling, queries)
+    }
+
+    /// Read the values of the positions located in a received tile
+    ///
+    /// Positions in a tile that could not be retrieved or decoded have a null value.
+    pub fn add_tile(&mut self, tile: &Tile) {
+        if tile.get_hips_cdid() != &self.hips_cdid
+            || tile.channel != self.channel
+            || !self.missing.remove(tile.cell())
+        {
+            return;
+        }
+        self.received = true;
+
+        let image = tile.image.borrow();
+        let reader = if let Some(reader) = image
+            .as_ref()
+            .and_then(|image| TileReader::new(image, tile.format.get_channel()).ok())
+        {
+            reader
+        } else {
+            return;
+        };
+
+        for ((cell, dx, dy), value) in self.positions.iter().zip(self.values.iter_mut()) {
+            if cell == tile.cell() {
+                *value = reader.read(*dx, *dy).unwrap_or(JsValue::NULL);
+            }
+        }
+    }
+
+    /// Give up a tile whose query has been dropped
+    ///
+    /// The positions located in it keep a null value.
+    pub fn drop_tile(&mut self, query: &query::Tile) {
+        if query.hips_cdid == self.hips_cdid && query.channel == self.channel {
+            self.missing.remove(&query.cell);
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Give the values to the javascript
+    ///
+    /// The promise is rejected if none of the tiles has been received.
+    pub fn resolve(self) -> Result<(), JsValue> {
+        if !self.received && !self.positions.is_empty() {
+            self.reject.call1(
+                &JsValue::NULL,
+                &JsValue::from_str("The tiles could not be retrieved"),
+            )?;
+        } else {
+            let values = self.values.into_iter().collect::<js_sys::Array>();
+            self.resolve.call1(&JsValue::NULL, &values)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reprojection of the tiles of a HiPS into a FITS image
+///
+/// The pixels of the image are sampled in the tiles like the positions of a `TileSampling`.
+pub struct FITSCutout {
+    hips_cdid: CreatorDid,
+    channel: Option<u32>,
+    width: usize,
+    height: usize,
+    // The tile containing the center of each pixel, `None` if it cannot be unprojected
+    positions: Vec<Option<(HEALPixCell, f64, f64)>>,
+    // Physical values of the pixels, NaN for the blank ones
+    values: Vec<f64>,
+    // Tiles not received yet
+    missing: HashSet<HEALPixCell>,
+    // BITPIX, BSCALE, BZERO and BLANK of the first tile received
+    encoding: Option<(i32, f64, f64, Option<f64>)>,
+    // The WCS of the image
+    wcs_cards: Vec<(String, CardValue)>,
+    // Whether a tile has been received
+    received: bool,
+
+    resolve: js_sys::Function,
+    reject: js_sys::Function,
+}
+
+impl FITSCutout {
+    /// Start the reprojection of a HiPS
+    ///
+    /// # Arguments
+    ///
+    /// * `hips` - The HiPS to reproject
+    /// * `positions` - The centers of the pixels of the image row by row, in the frame of the HiPS
+    /// * `width` - NAXIS1
+    /// * `depth` - The order of the tiles to sample
+    /// * `wcs_cards` - The header cards of the WCS of the image
+    /// * `resolve` - The function called with the FITS file once all the tiles are received
+    /// * `reject` - The function called if none of the tiles can be retrieved
+    ///
+    /// Returns the cutout with the queries of the tiles to fetch
+    pub fn new(
+        hips: &HiPS,
+        positions: &[Option<LonLatT<f64>>],
+        width: usize,
+        depth: u8,
+        wcs_cards: Vec<(String, CardValue)>,
+        resolve: js_sys::Function,
+        reject: js_sys::Function,
+    ) -> (Self, Vec<query::Tile>) {
+        let positions = positions
+            .iter()
+            .map(|lonlat| {
+                lonlat.map(|lonlat| {
+                    let (idx, dx, dy) = crate::healpix::utils::hash_with_dxdy(depth, &lonlat);
+                    (HEALPixCell(depth, idx), dx, dy)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let missing = positions
+            .iter()
+            .flatten()
+            .map(|(cell, _, _)| *cell)
+            .collect::<HashSet<_>>();
+        let queries = missing
+            .iter()
+            .map(|cell| hips.get_tile_query(cell))
+            .collect::<Vec<_>>();
+
+        let cutout = Self {
+            hips_cdid: hips.get_config().get_creator_did().to_string(),
+            channel: queries.first().and_then(|query| query.channel),
+            width,
+            height: positions.len() / width.max(1),
+            values: vec![f64::NAN; positions.len()],
+            positions,
+            missing,
+            encoding: None,
+            wcs_cards,
+            received: false,
+            resolve,
+            reject,
+        };
+
+        (cutout, queries)
+    }
+
+    /// Read the pixels located in a received tile
+    pub fn add_tile(&mut self, tile: &Tile) {
+        if tile.get_hips_cdid() != &self.hips_cdid
+            || tile.channel != self.channel
+            || !self.missing.remove(tile.cell())
+        {
+            return;
+        }
+        self.received = true;
+
+        let image = tile.image.borrow();
+        if let Some(reader) = image
+            .as_ref()
+            .and_then(|image| TileReader::new(image, tile.format.get_channel()).ok())
+        {
+            self.read(tile.cell(), &reader);
+        }
+    }
+
+    /// Read the pixels located in a tile generated in memory
+    pub fn add_tile_values(&mut self, cell: &HEALPixCell, tile: &[f32], size: usize) {
+        if self.missing.remove(cell) {
+            self.received = true;
+            self.read(cell, &TileReader::from_values(tile, size));
+        }
+    }
+
+    /// Give up a tile whose query has been dropped
+    ///
+    /// The pixels located in it are left blank.
+    pub fn drop_tile(&mut self, query: &query::Tile) {
+        if query.hips_cdid == self.hips_cdid && query.channel == self.channel {
+            self.missing.remove(&query.cell);
+        }
+    }
+
+    fn read(&mut self, tile_cell: &HEALPixCell, reader: &TileReader) {
+        if let TileReader::Fits {
+            bitpix,
+            scale,
+            offset,
+            blank,
+            ..
+        } = reader
+        {
+            self.encoding
+                .get_or_insert((*bitpix, *scale, *offset, *blank));
+        }
+
+        for (position, value) in self.positions.iter().zip(self.values.iter_mut()) {
+            if let Some((cell, dx, dy)) = position {
+                if cell == tile_cell {
+                    *value = reader.read_value(*dx, *dy).unwrap_or(f64::NAN);
+                }
+            }
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Give the FITS file to the javascript
+    ///
+    /// The pixels are stored with the BITPIX, BSCALE, BZERO and BLANK of the tiles.
+    /// The promise is rejected if none of the tiles has been received.
+    pub fn resolve(self) -> Result<(), JsValue> {
+        if !self.received && self.positions.iter().any(Option::is_some) {
+            self.reject.call1(
+                &JsValue::NULL,
+                &JsValue::from_str("The tiles could not be retrieved"),
+            )?;
+            return Ok(());
+        }
+
+        let (bitpix, scale, offset, blank) = self.encoding.unwrap_or((-32, 1.0, 0.0, None));
+
+        // Stored value of a physical one, blank pixels being given the BLANK value
+        let encode = |default_blank: f64, min: f64, max: f64| {
+            let blank = blank.unwrap_or(default_blank);
+            let values = self
+                .values
+                .iter()
+                .map(|value| {
+                    if value.is_nan() {
+                        blank
+                    } else {
+                        ((value - offset) / scale).round().clamp(min, max)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            (values, blank)
+        };
+        let (data, blank) = match bitpix {
+            8 => {
+                let (values, blank) = encode(0.0, u8::MIN as f64, u8::MAX as f64);
+                (
+                    ImageData::U8(values.iter().map(|v| *v as u8).collect()),
+                    Some(blank),
+                )
+            }
+            16 => {
+                let (values, blank) = encode(i16::MIN as f64, i16::MIN as f64, i16::MAX as f64);
+                (
+                    ImageData::I16(values.iter().map(|v| *v as i16).collect()),
+                    Some(blank),
+                )
+            }
+            32 => {
+                let (values, blank) = encode(i32::MIN as f64, i32::MIN as f64, i32::MAX as f64);
+                (
+                    ImageData::I32(values.iter().map(|v| *v as i32).collect()),
+                    Some(blank),
+                )
+            }
+            64 => {
+                let (values, blank) = encode(i64::MIN as f64, i64::MIN as f64, i64::MAX as f64);
+                (
+                    ImageData::I64(values.iter().map(|v| *v as i64).collect()),
+                    Some(blank),
+                )
+            }
+            -64 => (ImageData::F64(self.values), None),
+            _ => (
+                ImageData::F32(self.values.iter().map(|v| *v as f32).collect()),
+                None,
+            ),
+        };
+
+        let mut cards = vec![];
+        if let Some(blank) = blank {
+            cards.push(("BSCALE".to_string(), CardValue::Float(scale)));
+            cards.push(("BZERO".to_string(), CardValue::Float(offset)));
+            cards.push(("BLANK".to_string(), CardValue::Integer(blank as i64)));
+        }
+        cards.extend(self.wcs_cards);
+
+        let bytes = fits::write_image(self.width, self.height, &data, cards);
+        let file = js_sys::Uint8Array::from(&bytes[..]);
+        self.resolve.call1(&JsValue::NULL, &file)?;
+
+        Ok(())
+    }
+}
+
+/// Physical values of all the pixels of some FITS tiles of a HiPS
+///
+/// The tiles are kept in the FITS layout, NaN being given to the blank pixels.
+pub struct TileValues {
+    hips_cdid: CreatorDid,
+    channel: Option<u32>,
+    tiles: HashMap<HEALPixCell, Vec<f32>>,
+    // Tiles not received yet
+    missing: HashSet<HEALPixCell>,
+}
+
+impl TileValues {
+    /// Start gathering the values of tiles of a HiPS
+    ///
+    /// Returns the gathering with the queries of the tiles to fetch
+    pub fn new(hips: &HiPS, cells: &[HEALPixCell]) -> (Self, Vec<query::Tile>) {
+        let missing = cells.iter().copied().collect::<HashSet<_>>();
+        let queries = missing
+            .iter()
+            .map(|cell| hips.get_tile_query(cell))
+            .collect::<Vec<_>>();
+
+        let tile_values = Self {
+            hips_cdid: hips.get_config().get_creator_did().to_string(),
+            channel: queries.first().and_then(|query| query.channel),
+            tiles: HashMap::new(),
+            missing,
+        };
+
+        (tile_values, queries)
+    }
+
+    /// Read the values of a received tile
+    ///
+    /// Tiles that could not be retrieved or decoded are left out.
+    pub fn add_tile(&mut self, tile: &Tile) {
+        if tile.get_hips_cdid() != &self.hips_cdid
+            || tile.channel != self.channel
+            || !self.missing.remove(tile.cell())
+        {
+            return;
+        }
+
+        let image = tile.image.borrow();
+        if let Some(values) = image
+            .as_ref()
+            .and_then(|image| TileReader::new(image, tile.format.get_channel()).ok())
+            .and_then(|reader| reader.values())
+        {
+            self.tiles.insert(*tile.cell(), values);
+        }
+    }
+
+    /// Add the values of a tile generated in memory
+    pub fn add_tile_values(&mut self, cell: &HEALPixCell, tile: Vec<f32>) {
+        if self.missing.remove(cell) {
+            self.tiles.insert(*cell, tile);
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    pub fn into_tiles(self) -> HashMap<HEALPixCell, Vec<f32>> {
+        self.tiles
+    }
+}
+
+// Side of the grid of pixels sampled in a tile
+const NUM_SAMPLES_PER_TILE_SIDE: usize = 32;
+// Maximum number of tiles whose samples are kept
+const MAX_NUM_SAMPLED_TILES: usize = 1024;
+
+type SampledTile = (CreatorDid, HEALPixCell, Option<u32>);
+
+/// Pixel values sampled regularly in the FITS tiles received
+///
+/// They are used to compute the cuts from the tiles in the view.
+#[derive(Default)]
+pub struct TilePixelSamples {
+    values: HashMap<SampledTile, Vec<f32>>,
+    // Tiles in their order of insertion, the oldest being forgotten first
+    tiles: VecDeque<SampledTile>,
+}
+
+impl TilePixelSamples {
+    /// Sample a received tile
+    ///
+    /// Returns whether new values have been added
+    pub fn add_tile(&mut self, tile: &Tile) -> bool {
+        let key = (tile.get_hips_cdid().to_string(), *tile.cell(), tile.channel);
+        if self.values.contains_key(&key) {
+            return false;
+        }
+
+        let values = match &*tile.image.borrow() {
+            Some(image @ ImageType::FitsImage { .. }) => {
+                if let Ok(reader) = TileReader::new(image, tile.format.get_channel()) {
+                    reader.grid_values(NUM_SAMPLES_PER_TILE_SIDE)
+                } else {
+                    return false;
+                }
+            }
+            _ => return false,
+        };
+
+        self.insert(key, values);
+
+        true
+    }
+
+    /// Sample a FITS tile generated in memory, e.g. from a HEALPix map
+    ///
+    /// Returns whether new values have been added
+    pub fn add_tile_values(
+        &mut self,
+        hips_cdid: &str,
+        cell: &HEALPixCell,
+        tile: &[f32],
+        size: usize,
+    ) -> bool {
+        let key = (hips_cdid.to_string(), *cell, None);
+        if self.values.contains_key(&key) {
+            return false;
+        }
+
+        let step = (size / NUM_SAMPLES_PER_TILE_SIDE).max(1);
+        let values = (0..size)
+            .step_by(step)
+            .flat_map(|y| (0..size).step_by(step).map(move |x| y * size + x))
+            .filter_map(|idx| tile.get(idx).copied())
+            .filter(|value| !value.is_nan())
+            .collect();
+
+        self.insert(key, values);
+
+        true
+    }
+
+    fn insert(&mut self, key: SampledTile, values: Vec<f32>) {
+        if self.tiles.len() >= MAX_NUM_SAMPLED_TILES {
+            if let Some(oldest) = self.tiles.pop_front() {
+                self.values.remove(&oldest);
+            }
+        }
+        self.tiles.push_back(key.clone());
+        self.values.insert(key, values);
+    }
+
+    /// Values sampled in the tiles of a HiPS covering the view
+    ///
+    /// When a tile has not been received, the samples of its nearest parent are used.
+    pub fn get_in_view(&self, hips: &HiPS, camera: &CameraViewPort) -> Vec<f32> {
+        let cfg = hips.get_config();
+        let depth_tile = (camera.get_texture_depth() + cfg.delta_depth())
+            .clamp(cfg.get_min_depth_tile(), cfg.get_max_depth_tile());
+        let channel = match hips {
+            HiPS::D2(_) => None,
+            HiPS::D3(hips) => Some(hips.get_slice() as u32),
+        };
+        let cdid = cfg.get_creator_did().to_string();
+
+        let mut tiles = HashSet::new();
+        for mut cell in camera.get_hpx_cells(depth_tile, cfg.get_frame()) {
+            loop {
+                let key = (cdid.clone(), cell, channel);
+                if self.values.contains_key(&key) {
+                    tiles.insert(key);
+                    break;
+                }
+
+                if cell.depth() <= cfg.get_min_depth_tile() {
+                    break;
+                }
+                cell = cell.parent();
+            }
+        }
+
+        tiles
+            .iter()
+            .flat_map(|key| self.values[key].iter().cloned())
+            .collect()
+    }
+}
+
+enum TileReader {
+    Fits {
+        data: Vec<f64>,
+        size: usize,
+        bitpix: i32,
+        scale: f64,
+        offset: f64,
+        blank: Option<f64>,
+    },
+    Color {
+        ctx: web_sys::CanvasRenderingContext2d,
+        size: usize,
+        num_channels: usize,
+    },
+}
+
+impl TileReader {
+    fn new(image: &ImageType, channel: ChannelType) -> Result<Self, JsValue> {
+        match image {
+            ImageType::FitsImage { raw_bytes } => {
+                let raw_bytes = raw_bytes.to_vec();
+                let mut reader = Cursor::new(raw_bytes.as_slice());
+                let Fits { hdu } = Fits::from_reader(&mut reader)
+                    .map_err(|_| JsValue::from_str("Parsing fits error"))?;
+
+                let header = hdu.get_header();
+                let size = header
+                    .get_xtension()
+                    .get_naxisn(1)
+                    .map(|size| *size as usize)
+                    .ok_or_else(|| JsValue::from_str("NAXIS1 not found in the fits"))?;
+                let scale = header
+                    .get_parsed::<f64>(b"BSCALE  ")
+                    .and_then(Result::ok)
+                    .unwrap_or(1.0);
+                let offset = header
+                    .get_parsed::<f64>(b"BZERO   ")
+                    .and_then(Result::ok)
+                    .unwrap_or(0.0);
+                let blank = header.get_parsed::<f64>(b"BLANK   ").and_then(Result::ok);
+
+                let (data, bitpix) = match hdu.get_data() {
+                    InMemData::U8(data) => (data.iter().map(|v| *v as f64).collect(), 8),
+                    InMemData::I16(data) => (data.iter().map(|v| *v as f64).collect(), 16),
+                    InMemData::I32(data) => (data.iter().map(|v| *v as f64).collect(), 32),
+                    InMemData::I64(data) => (data.iter().map(|v| *v as f64).collect(), 64),
+                    InMemData::F32(data) => (data.iter().map(|v| *v as f64).collect(), -32),
+                    InMemData::F64(data) => (data.to_vec(), -64),
+                };
+
+                Ok(TileReader::Fits {
+                    data,
+                    size,
+                    bitpix,
+                    scale,
+                    offset,
+                    blank,
+                })
+            }
+            ImageType::HTMLImageRgb8u { image } => Self::from_html_image(image.get_image(), 3),
+            ImageType::HTMLImageRgba8u { image } => Self::from_html_image(image.get_image(), 4),
+            _ => Err(JsValue::from_str(&format!(
+                "Sampling not implemented for {:?} tiles",
+                channel
+            ))),
+        }
+    }
+
+    // A tile of floats generated in memory, in the layout of the FITS tiles
+    fn from_values(tile: &[f32], size: usize) -> Self {
+        TileReader::Fits {
+            data: tile.iter().map(|v| *v as f64).collect(),
+            size,
+            bitpix: -32,
+            scale: 1.0,
+            offset: 0.0,
+            blank: None,
+        }
+    }
+
+    fn from_html_image(
+        image: &web_sys::HtmlImageElement,
+        num_channels: usize,
+    ) -> Result<Self, JsValue> {
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| JsValue::from_str("No document found"))?;
+        let canvas = document
+            .create_element("canvas")?
+            .dyn_into::<web_sys::HtmlCanvasElement>()?;
+        canvas.set_width(image.width());
+        canvas.set_height(image.height());
+
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("Cannot get a 2d context"))?
+            .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+        ctx.draw_image_with_html_image_element(image, 0.0, 0.0)?;
+
+        Ok(TileReader::Color {
+            ctx,
+            size: image.width() as usize,
+            num_channels,
+        })
+    }
+
+    // Physical values of a regular grid of pixels, blank pixels excepted
+    fn grid_values(&self, num_per_side: usize) -> Vec<f32> {
+        match self {
+            TileReader::Fits {
+                data,
+                size,
+                scale,
+                offset,
+                blank,
+                ..
+            } => {
+                let step = (size / num_per_side).max(1);
+
+                (0..*size)
+                    .step_by(step)
+                    .flat_map(|y| (0..*size).step_by(step).map(move |x| y * size + x))
+                    .filter_map(|idx| data.get(idx))
+                    .filter(|value| !value.is_nan() && Some(**value) != *blank)
+                    .map(|value| (value * scale + offset) as f32)
+                    .collect()
+            }
+            TileReader::Color { .. } => vec![],
+        }
+    }
+
+    // Physical value of a FITS pixel, `None` if it is blank
+    fn read_value(&self, dx: f64, dy: f64) -> Option<f64> {
+        match self {
+            TileReader::Fits {
+                data,
+                size,
+                scale,
+                offset,
+                blank,
+                ..
+            } => {
+                let x = ((dy * (*size as f64)) as usize).min(size - 1);
+                // Fits tiles are stored upside down
+                let y = size - 1 - ((dx * (*size as f64)) as usize).min(size - 1);
+
+                let value = data[y * size + x];
+                if value.is_nan() || Some(value) == *blank {
+                    None
+                } else {
+                    Some(value * scale + offset)
+                }
+            }
+            TileReader::Color { .. } => None,
+        }
+    }
+
+    // Physical values of all the FITS pixels, NaN for the blank ones
+    fn values(&self) -> Option<Vec<f32>> {
+        match self {
+            TileReader::Fits {
+                data,
+                scale,
+                offset,
+                blank,
+                ..
+            } => Some(
+                data.iter()
+                    .map(|value| {
+                        if value.is_nan() || Some(*value) == *blank {
+                            f32::NAN
+                        } else {
+                            (value * scale + offset) as f32
+                        }
+                    })
+                    .collect(),
+            ),
+            TileReader::Color { .. } => None,
+        }
+    }
+
+    // Same pixel as the one read from the GPU buffer (see `HpxTileBuffer::read_pixel`)
+    fn read(&self, dx: f64, dy: f64) -> Result<JsValue, JsValue> {
+        match self {
+            TileReader::Fits { .. } => Ok(self
+                .read_value(dx, dy)
+                .map(JsValue::from_f64)
+                .unwrap_or(JsValue::NULL)),
+            TileReader::Color {
+                ctx,
+                size,
+                num_channels,
+            } => {
+                let x = ((dy * (*size as f64)) as usize).min(size - 1);
+                let y = ((dx * (*size as f64)) as usize).min(size - 1);
+
+                let pixel = ctx.get_image_data(x as f64, y as f64, 1.0, 1.0)?.data();
+                Ok(serde_wasm_bindgen::to_value(&pixel[..*num_channels])?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 4x4 tile of 16 bits integers
+    fn tile() -> TileReader {
+        TileReader::Fits {
+            data: (0..16).map(|v| v as f64).collect(),
+            size: 4,
+            bitpix: 16,
+            scale: 2.0,
+            offset: -10.0,
+            blank: Some(5.0),
+        }
+    }
+
+    #[test]
+    fn read_tile_pixels() {
+        let tile = tile();
+
+        // dy gives the column and dx the row from the last one
+        assert_eq!(tile.read_value(0.9, 0.1), Some(-10.0));
+        assert_eq!(tile.read_value(0.1, 0.1), Some(14.0));
+        assert_eq!(tile.read_value(0.1, 0.9), Some(20.0));
+        assert_eq!(tile.read_value(0.6, 0.6), Some(2.0));
+        // The upper borders of the cell are in the last pixels
+        assert_eq!(tile.read_value(1.0, 1.0), Some(-4.0));
+        assert_eq!(tile.read_value(0.0, 0.0), Some(14.0));
+    }
+
+    #[test]
+    fn blank_tile_pixels() {
+        let tile = tile();
+
+        // The raw value is compared to BLANK
+        assert_eq!(tile.read_value(0.6, 0.3), None);
+        let values = tile.values().unwrap();
+        assert!(values[5].is_nan());
+        assert_eq!(values.iter().filter(|v| v.is_nan()).count(), 1);
+        assert_eq!(values[6], 2.0);
+        assert!(!tile.grid_values(4).contains(&0.0));
+        assert_eq!(tile.grid_values(4).len(), 15);
+        // One pixel out of two in each direction
+        assert_eq!(tile.grid_values(2), vec![-10.0, -6.0, 6.0, 10.0]);
+
+        // NaN values of the float tiles
+        let tile = TileReader::from_values(&[1.0, f32::NAN, 3.0, 4.0], 2);
+        assert_eq!(tile.read_value(0.9, 0.9), None);
+        assert_eq!(tile.read_value(0.9, 0.1), Some(1.0));
+        assert_eq!(tile.grid_values(2), vec![1.0, 3.0, 4.0]);
+    }
+}
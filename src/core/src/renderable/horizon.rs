@@ -0,0 +1,251 @@
+use al_api::color::ColorRGBA;
+use al_api::coo_system::CooSystem;
+use al_api::horizon::HorizonCfg;
+use al_core::WebGlContext;
+use cgmath::Vector4;
+use wasm_bindgen::JsValue;
+use web_sys::HtmlElement;
+
+use crate::camera::CameraViewPort;
+use crate::coo_space::CooSpace;
+use crate::math::angle::ToAngle;
+use crate::math::horizontal::{self, Observer};
+use crate::math::lonlat::{radec_to_xyzw, LonLat};
+use crate::math::TWICE_PI;
+use crate::renderable::grid::label::Label;
+use crate::renderable::line::{self, PathVertices, RasterizedLineRenderer, Style};
+use crate::renderable::text::TextRenderManager;
+use crate::renderable::Renderer;
+use crate::shader::ShaderManager;
+use crate::Abort;
+use crate::ProjectionType;
+
+// Number of great circle arcs approximating an altitude circle
+const NUM_ARCS: usize = 180;
+
+// Cardinal points with their longitude in the horizontal frame (i.e. 360° - azimuth)
+const CARDINAL_POINTS: [(&str, f64); 4] = [
+    ("N", 0.0),
+    ("E", 270.0),
+    ("S", 180.0),
+    ("W", 90.0),
+];
+
+/// Horizon of an observer drawn onto the sky
+///
+/// It consists of the apparent horizon line, the cardinal points and
+/// iso-airmass lines. These are computed in the horizontal frame and
+/// rotated to the frame of the view.
+pub struct Horizon {
+    // Properties
+    pub enabled: bool,
+    pub show_labels: bool,
+    color: ColorRGBA,
+    thickness: f32,
+    // Airmasses for which an iso-line is drawn
+    airmass: Vec<f64>,
+
+    observer: Observer,
+
+    line_renderer: RasterizedLineRenderer,
+    text_renderer: TextRenderManager,
+    labels: Vec<Label>,
+}
+
+impl Horizon {
+    pub fn new(gl: &WebGlContext, aladin_div: &HtmlElement) -> Result<Self, JsValue> {
+        let line_renderer = RasterizedLineRenderer::new(gl)?;
+        let mut text_renderer = TextRenderManager::new(aladin_div)?;
+
+        let color = ColorRGBA {
+            r: 1.0,
+            g: 0.5,
+            b: 0.0,
+            a: 1.0,
+        };
+        text_renderer.set_color(&color);
+
+        let observer = Observer {
+            lon: 0.0,
+            lat: 0.0,
+            height: 0.0,
+        };
+
+        Ok(Self {
+            enabled: false,
+            show_labels: true,
+            color,
+            thickness: 2.0,
+            airmass: vec![1.5, 2.0, 3.0],
+            observer,
+            line_renderer,
+            text_renderer,
+            labels: vec![],
+        })
+    }
+
+    pub fn set_cfg(&mut self, new_cfg: HorizonCfg) -> Result<(), JsValue> {
+        let HorizonCfg {
+            color,
+            thickness,
+            opacity,
+            show_labels,
+            label_size,
+            enabled,
+            airmass,
+        } = new_cfg;
+
+        if let Some(color) = color {
+            self.color = ColorRGBA {
+                r: color.r,
+                g: color.g,
+                b: color.b,
+                a: self.color.a,
+            };
+            self.text_renderer.set_color(&self.color);
+        }
+
+        if let Some(opacity) = opacity {
+            self.color.a = opacity;
+            self.text_renderer.set_color(&self.color);
+        }
+
+        if let Some(thickness) = thickness {
+            self.thickness = thickness;
+        }
+
+        if let Some(show_labels) = show_labels {
+            self.show_labels = show_labels;
+        }
+
+        if let Some(label_size) = label_size {
+            self.text_renderer.set_font_size(label_size as u32);
+        }
+
+        if let Some(enabled) = enabled {
+            self.enabled = enabled;
+        }
+
+        if let Some(airmass) = airmass {
+            if let Some(x) = airmass.iter().find(|x| x.is_nan() || **x < 1.0) {
+                return Err(JsValue::from_str(&format!(
+                    "Airmass must be greater or equal to 1, got {}",
+                    x
+                )));
+            }
+
+            self.airmass = airmass;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_observer(&mut self, observer: Observer) {
+        self.observer = observer;
+    }
+
+    pub fn draw_labels(&mut self) -> Result<(), JsValue> {
+        if self.enabled && self.show_labels {
+            self.text_renderer.begin();
+            for Label {
+                content,
+                position,
+                rot,
+            } in &self.labels
+            {
+                let position = position.cast::<f32>().unwrap_abort();
+                self.text_renderer
+                    .add_label(content, &position, cgmath::Rad(*rot as f32))?;
+            }
+            self.text_renderer.end();
+        }
+
+        Ok(())
+    }
+
+    pub fn draw(
+        &mut self,
+        camera: &CameraViewPort,
+        projection: &ProjectionType,
+        shaders: &mut ShaderManager,
+    ) -> Result<(), JsValue> {
+        if self.enabled {
+            let hor2view =
+                CooSystem::HORIZONTAL.to(camera.get_coo_system(), camera.get_icrs_to_horizontal());
+            let to_view = |lon: f64, alt: f64| -> Vector4<f64> {
+                hor2view * radec_to_xyzw(lon.to_angle(), alt.to_angle())
+            };
+
+            let horizon_alt = self.observer.horizon_altitude();
+
+            self.labels = CARDINAL_POINTS
+                .iter()
+                .filter_map(|(content, lon)| {
+                    let position = projection
+                        .model_to_screen_space(&to_view(lon.to_radians(), horizon_alt), camera)?;
+
+                    Some(Label {
+                        position,
+                        content: content.to_string(),
+                        rot: 0.0,
+                    })
+                })
+                .collect();
+
+            let airmass_alts = self
+                .airmass
+                .iter()
+                .filter_map(|x| horizontal::airmass_to_altitude(*x))
+                .collect::<Vec<_>>();
+
+            // The iso-airmass lines are fainter than the horizon line
+            let airmass_color = ColorRGBA {
+                a: self.color.a * 0.5,
+                ..self.color
+            };
+
+            self.line_renderer.begin();
+            for (alt, color) in std::iter::once((horizon_alt, self.color)).chain(
+                airmass_alts
+                    .into_iter()
+                    .map(|alt| (alt, airmass_color)),
+            ) {
+                let vertices = (0..=NUM_ARCS)
+                    .map(|i| to_view(TWICE_PI * (i as f64) / (NUM_ARCS as f64), alt).lonlat())
+                    .collect::<Vec<_>>();
+
+                let paths = vertices
+                    .iter()
+                    .zip(vertices.iter().skip(1))
+                    .map(|(v1, v2)| {
+                        let vertices = line::great_circle_arc::project(
+                            v1.lon().to_radians(),
+                            v1.lat().to_radians(),
+                            v2.lon().to_radians(),
+                            v2.lat().to_radians(),
+                            camera,
+                            projection,
+                        )
+                        .into_iter()
+                        .map(|v| [v.x as f32, v.y as f32])
+                        .collect::<Vec<_>>();
+
+                        PathVertices { vertices }
+                    });
+
+                self.line_renderer.add_stroke_paths(
+                    paths,
+                    self.thickness,
+                    &color,
+                    &Style::None,
+                    CooSpace::NDC,
+                );
+            }
+            self.line_renderer.end();
+
+            self.line_renderer.draw(shaders, camera, projection)?;
+        }
+
+        Ok(())
+    }
+}
@@ -23,3 +23,267 @@ where
 
     min_val..max_val
 }
+
+use al_api::hips::CutsAlgorithm;
+
+/// Compute the cuts of a set of pixel values
+///
+/// NaN values are discarded. Returns `None` if no value remains.
+pub fn compute(values: &mut Vec<f32>, algorithm: &CutsAlgorithm) -> Option<Range<f32>> {
+    values.retain(|v| !v.is_nan());
+    if values.is_empty() {
+        return None;
+    }
+
+    let cuts = match *algorithm {
+        CutsAlgorithm::MinMax => min_max(values),
+        CutsAlgorithm::Percentile { low, high } => percentile(values, low, high),
+        CutsAlgorithm::ZScale { contrast } => zscale(values, contrast),
+        CutsAlgorithm::SigmaClip {
+            sigma,
+            max_iterations,
+        } => sigma_clip(values, sigma, max_iterations),
+    };
+
+    Some(cuts)
+}
+
+fn sort(values: &mut [f32]) {
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+}
+
+pub fn min_max(values: &[f32]) -> Range<f32> {
+    values
+        .iter()
+        .fold(f32::INFINITY..f32::NEG_INFINITY, |cuts, &v| {
+            cuts.start.min(v)..cuts.end.max(v)
+        })
+}
+
+/// Values at the `low` and `high` percentiles (between 0 and 100)
+pub fn percentile(values: &mut [f32], mut low: f32, mut high: f32) -> Range<f32> {
+    if low > high {
+        std::mem::swap(&mut low, &mut high);
+    }
+    sort(values);
+
+    let idx = |p: f32| (((p.clamp(0.0, 100.0) * 0.01) * (values.len() as f32)) as usize).min(values.len() - 1);
+    values[idx(low)]..values[idx(high)]
+}
+
+// Maximum number of values considered by zscale, as in IRAF
+const ZSCALE_NUM_SAMPLES: usize = 1000;
+// Rejection threshold of the fit in standard deviations
+const ZSCALE_KREJ: f32 = 2.5;
+// Minimum fraction of the values kept by the fit
+const ZSCALE_MAX_REJECT: f32 = 0.5;
+const ZSCALE_MAX_ITERATIONS: usize = 5;
+
+/// IRAF zscale algorithm
+///
+/// A line is fitted to the sorted values, rejecting iteratively the outliers. The cuts are given
+/// around the median by the slope of the line divided by `contrast`.
+pub fn zscale(values: &mut [f32], contrast: f32) -> Range<f32> {
+    let step = (values.len() / ZSCALE_NUM_SAMPLES).max(1);
+    let mut samples = values
+        .iter()
+        .step_by(step)
+        .map(|&v| v as f64)
+        .collect::<Vec<_>>();
+    samples.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let n = samples.len();
+    let (zmin, zmax) = (samples[0], samples[n - 1]);
+    let median = 0.5 * (samples[(n - 1) / 2] + samples[n / 2]);
+
+    let min_num_pixels = ((n as f32 * ZSCALE_MAX_REJECT) as usize).max(5);
+    // Width of the neighbourhood of a rejected value that is also rejected
+    let num_grow = ((n as f32 * 0.01) as usize).max(1);
+
+    let mut rejected = vec![false; n];
+    let mut num_good = n;
+    let mut last_num_good = n + 1;
+    let mut slope = 0.0;
+
+    for _ in 0..ZSCALE_MAX_ITERATIONS {
+        if num_good >= last_num_good || num_good < min_num_pixels {
+            break;
+        }
+
+        // Least squares fit of the kept values
+        let (mut sx, mut sy, mut sxx, mut sxy) = (0.0, 0.0, 0.0, 0.0);
+        for (i, (&y, _)) in samples
+            .iter()
+            .zip(rejected.iter())
+            .enumerate()
+            .filter(|(_, (_, &r))| !r)
+        {
+            let x = i as f64;
+            sx += x;
+            sy += y;
+            sxx += x * x;
+            sxy += x * y;
+        }
+        let k = num_good as f64;
+        let det = k * sxx - sx * sx;
+        if det == 0.0 {
+            break;
+        }
+        slope = (k * sxy - sx * sy) / det;
+        let intercept = (sy - slope * sx) / k;
+
+        // Reject the values too far from the line
+        let residuals = samples
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| y - (intercept + slope * (i as f64)))
+            .collect::<Vec<_>>();
+        let (sum, sum2) = residuals
+            .iter()
+            .zip(rejected.iter())
+            .filter(|(_, &r)| !r)
+            .fold((0.0, 0.0), |(s, s2), (&r, _)| (s + r, s2 + r * r));
+        let mean = sum / k;
+        let threshold = (ZSCALE_KREJ as f64) * (sum2 / k - mean * mean).max(0.0).sqrt();
+
+        let mut grown = rejected.clone();
+        for (i, r) in residuals.iter().enumerate() {
+            if r.abs() > threshold {
+                let start = i.saturating_sub(num_grow / 2);
+                let end = (i + num_grow - num_grow / 2).min(n);
+                grown[start..end].iter_mut().for_each(|g| *g = true);
+            }
+        }
+        rejected = grown;
+
+        last_num_good = num_good;
+        num_good = rejected.iter().filter(|&&r| !r).count();
+    }
+
+    if num_good >= min_num_pixels && contrast > 0.0 {
+        let slope = slope / (contrast as f64);
+        let center = ((n - 1) / 2) as f64;
+
+        let z1 = zmin.max(median - (center - 1.0) * slope);
+        let z2 = zmax.min(median + ((n as f64) - center) * slope);
+
+        (z1 as f32)..(z2 as f32)
+    } else {
+        (zmin as f32)..(zmax as f32)
+    }
+}
+
+/// Extremal values remaining after clipping iteratively the values farther than
+/// `sigma` standard deviations from their median
+pub fn sigma_clip(values: &mut [f32], sigma: f32, max_iterations: u32) -> Range<f32> {
+    sort(values);
+
+    // The values kept are a contiguous range of the sorted values
+    let mut kept = &values[..];
+    for _ in 0..max_iterations {
+        let n = kept.len();
+        let median = 0.5 * (kept[(n - 1) / 2] + kept[n / 2]);
+        let mean = kept.iter().sum::<f32>() / (n as f32);
+        let std = (kept.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / (n as f32)).sqrt();
+
+        let (low, high) = (median - sigma * std, median + sigma * std);
+        let start = kept.partition_point(|&v| v < low);
+        let end = kept.partition_point(|&v| v <= high);
+        if (start == 0 && end == n) || start >= end {
+            break;
+        }
+
+        kept = &kept[start..end];
+    }
+
+    kept[0]..kept[kept.len() - 1]
+}
+
+/// Lookup table of the histogram equalization between the cuts
+///
+/// The cumulative distribution of the values is sampled at `size` regularly spaced
+/// values from `cuts.start` to `cuts.end`. Values outside the cuts are discarded.
+/// Returns `None` if no value lies within the cuts.
+pub fn equalization(values: &mut Vec<f32>, cuts: &Range<f32>, size: usize) -> Option<Vec<f32>> {
+    values.retain(|v| (cuts.start..=cuts.end).contains(v));
+    if values.is_empty() || size < 2 {
+        return None;
+    }
+    sort(values);
+
+    let n = values.len() as f32;
+    let table = (0..size)
+        .map(|i| {
+            let x = cuts.start + (cuts.end - cuts.start) * (i as f32) / ((size - 1) as f32);
+            (values.partition_point(|&v| v <= x) as f32) / n
+        })
+        .collect();
+
+    Some(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_max_and_percentile_cuts() {
+        let mut values = (0..=100).map(|v| v as f32).rev().collect::<Vec<_>>();
+        values.push(f32::NAN);
+
+        assert_eq!(compute(&mut values.clone(), &CutsAlgorithm::MinMax), Some(0.0..100.0));
+        assert_eq!(
+            compute(&mut values, &CutsAlgorithm::Percentile { low: 99.0, high: 1.0 }),
+            Some(1.0..99.0)
+        );
+        assert_eq!(compute(&mut vec![f32::NAN], &CutsAlgorithm::MinMax), None);
+    }
+
+    #[test]
+    fn zscale_cuts() {
+        // A linear ramp is fitted exactly, the cuts only depend on the contrast
+        let mut values = (0..1000).map(|v| v as f32).collect::<Vec<_>>();
+        let cuts = zscale(&mut values.clone(), 1.0);
+        assert!((cuts.start - 1.5).abs() < 1e-3 && (cuts.end - 999.0).abs() < 1e-3);
+
+        let cuts = zscale(&mut values, 4.0);
+        assert!((cuts.start - 375.0).abs() < 1e-3 && (cuts.end - 624.75).abs() < 1e-3);
+
+        // Bright outliers do not stretch the cuts
+        let mut values = (0..1000)
+            .map(|v| if v % 100 == 0 { 1e6 } else { (v % 10) as f32 })
+            .collect::<Vec<_>>();
+        let cuts = zscale(&mut values, 0.25);
+        assert!(cuts.start >= 0.0 && cuts.end < 1e3);
+    }
+
+    #[test]
+    fn sigma_clip_cuts() {
+        let mut values = (0..100).map(|v| (v % 10) as f32).collect::<Vec<_>>();
+        values.extend([-1e3, 1e4]);
+
+        assert_eq!(
+            compute(
+                &mut values,
+                &CutsAlgorithm::SigmaClip {
+                    sigma: 3.0,
+                    max_iterations: 5
+                }
+            ),
+            Some(0.0..9.0)
+        );
+    }
+
+    #[test]
+    fn equalization_table() {
+        // Half of the values are 0, the others are evenly spread
+        let mut values = vec![0.0; 100];
+        values.extend((1..=100).map(|v| v as f32));
+        values.push(1e3);
+
+        let table = equalization(&mut values, &(0.0..100.0), 3).unwrap();
+        assert_eq!(table, vec![0.5, 0.75, 1.0]);
+
+        assert_eq!(equalization(&mut vec![-1.0, 1e3], &(0.0..100.0), 3), None);
+    }
+}
@@ -0,0 +1,320 @@
+//! Writing of FITS images and reading of FITS headers
+use std::collections::HashMap;
+
+/// Value of a header card
+pub enum CardValue {
+    Logical(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+/// Pixels of an image, the BITPIX being given by their type
+pub enum ImageData {
+    U8(Vec<u8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+}
+
+impl ImageData {
+    fn bitpix(&self) -> i64 {
+        match self {
+            ImageData::U8(_) => 8,
+            ImageData::I16(_) => 16,
+            ImageData::I32(_) => 32,
+            ImageData::I64(_) => 64,
+            ImageData::F32(_) => -32,
+            ImageData::F64(_) => -64,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            ImageData::U8(data) => data.len(),
+            ImageData::I16(data) => data.len(),
+            ImageData::I32(data) => data.len(),
+            ImageData::I64(data) => data.len(),
+            ImageData::F32(data) => data.len(),
+            ImageData::F64(data) => data.len(),
+        }
+    }
+
+    // Big-endian bytes
+    fn to_be_bytes(&self) -> Vec<u8> {
+        match self {
+            ImageData::U8(data) => data.clone(),
+            ImageData::I16(data) => data.iter().flat_map(|v| v.to_be_bytes()).collect(),
+            ImageData::I32(data) => data.iter().flat_map(|v| v.to_be_bytes()).collect(),
+            ImageData::I64(data) => data.iter().flat_map(|v| v.to_be_bytes()).collect(),
+            ImageData::F32(data) => data.iter().flat_map(|v| v.to_be_bytes()).collect(),
+            ImageData::F64(data) => data.iter().flat_map(|v| v.to_be_bytes()).collect(),
+        }
+    }
+}
+
+/// Serialize a 2D image in a FITS file
+///
+/// # Arguments
+///
+/// * `width` - NAXIS1
+/// * `height` - NAXIS2
+/// * `data` - The pixels, row by row in the FITS order
+/// * `cards` - The header cards written after the mandatory ones (e.g. BSCALE, BLANK or the WCS)
+pub fn write_image(
+    width: usize,
+    height: usize,
+    data: &ImageData,
+    cards: Vec<(String, CardValue)>,
+) -> Vec<u8> {
+    debug_assert_eq!(data.len(), width * height);
+
+    let mut bytes = vec![];
+    for (key, value) in [
+        ("SIMPLE", CardValue::Logical(true)),
+        ("BITPIX", CardValue::Integer(data.bitpix())),
+        ("NAXIS", CardValue::Integer(2)),
+        ("NAXIS1", CardValue::Integer(width as i64)),
+        ("NAXIS2", CardValue::Integer(height as i64)),
+    ]
+    .iter()
+    .map(|(key, value)| (*key, value))
+    .chain(cards.iter().map(|(key, value)| (key.as_str(), value)))
+    {
+        bytes.extend(card(key, value));
+    }
+    bytes.extend(format!("{:<80}", "END").bytes());
+    bytes.resize(bytes.len().div_ceil(2880) * 2880, b' ');
+
+    bytes.extend(data.to_be_bytes());
+    bytes.resize(bytes.len().div_ceil(2880) * 2880, 0);
+
+    bytes
+}
+
+/// The header cards of a WCS, the dimensions of the image excepted
+pub fn wcs_cards(params: &wcs::WCSParams) -> Vec<(String, CardValue)> {
+    let keywords = match serde_json::to_value(params) {
+        Ok(serde_json::Value::Object(keywords)) => keywords,
+        _ => return vec![],
+    };
+
+    keywords
+        .into_iter()
+        .filter(|(key, _)| !key.starts_with("NAXIS"))
+        .filter_map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::Bool(v) => CardValue::Logical(v),
+                serde_json::Value::Number(v) => {
+                    if let Some(v) = v.as_i64() {
+                        CardValue::Integer(v)
+                    } else {
+                        CardValue::Float(v.as_f64()?)
+                    }
+                }
+                serde_json::Value::String(v) => CardValue::String(v),
+                // Keywords not defined
+                _ => return None,
+            };
+
+            Some((key, value))
+        })
+        .collect()
+}
+
+// A card of 80 characters, the value being right-justified in columns 11-30 except for strings
+fn card(key: &str, value: &CardValue) -> Vec<u8> {
+    let value = match value {
+        CardValue::Logical(v) => format!("{:>20}", if *v { "T" } else { "F" }),
+        CardValue::Integer(v) => format!("{:>20}", v),
+        CardValue::Float(v) => {
+            // The decimal point is mandatory
+            let v = format!("{:E}", v);
+            let v = if v.contains('.') {
+                v
+            } else {
+                v.replacen('E', ".0E", 1)
+            };
+
+            format!("{:>20}", v)
+        }
+        // Quotes are escaped by doubling them
+        CardValue::String(v) => format!("'{:<8}'", v.replace('\'', "''")),
+    };
+
+    let mut card = format!("{:<8}= {}", key, value);
+    card.truncate(80);
+
+    format!("{:<80}", card).into_bytes()
+}
+
+/// Read the header of a HDU starting at the beginning of `bytes`
+///
+/// Returns the cards having a value with the size of the header in bytes, padding included.
+/// The commentary cards (e.g. COMMENT or HISTORY) and the values that cannot be parsed are skipped.
+pub fn read_header(bytes: &[u8]) -> Result<(HashMap<String, CardValue>, usize), String> {
+    let mut cards = HashMap::new();
+    for (idx, card) in bytes.chunks_exact(80).enumerate() {
+        let card = std::str::from_utf8(card)
+            .ok()
+            .filter(|card| card.is_ascii())
+            .ok_or_else(|| "The header must only contain ASCII characters".to_string())?;
+        let key = card[..8].trim_end();
+        if key == "END" {
+            let size = ((idx + 1) * 80).div_ceil(2880) * 2880;
+            return Ok((cards, size));
+        }
+
+        if &card[8..10] == "= " {
+            if let Some(value) = parse_value(&card[10..]) {
+                cards.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    Err("The END card of the header has not been found".to_string())
+}
+
+// The value of a card, i.e. the characters in columns 11-80 before the comment
+fn parse_value(value: &str) -> Option<CardValue> {
+    let value = value.trim_start();
+    if let Some(string) = value.strip_prefix('\'') {
+        // Quotes are escaped by doubling them
+        let mut chars = string.chars().peekable();
+        let mut v = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                } else {
+                    return Some(CardValue::String(v.trim_end().to_string()));
+                }
+            }
+            v.push(c);
+        }
+
+        None
+    } else {
+        let value = value.split('/').next()?.trim();
+        match value {
+            "T" => Some(CardValue::Logical(true)),
+            "F" => Some(CardValue::Logical(false)),
+            _ => value
+                .parse::<i64>()
+                .map(CardValue::Integer)
+                .or_else(|_| value.replace('D', "E").parse::<f64>().map(CardValue::Float))
+                .ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fitsrs::fits::Fits;
+    use fitsrs::hdu::data::InMemData;
+    use std::io::Cursor;
+
+    #[test]
+    fn written_image_can_be_parsed() {
+        let values = [1.0, f32::NAN, -2.5, 4.0e10, 0.0, 3.0];
+        let bytes = write_image(
+            3,
+            2,
+            &ImageData::F32(values.to_vec()),
+            vec![
+                ("CRVAL1".to_string(), CardValue::Float(83.63)),
+                (
+                    "CTYPE1".to_string(),
+                    CardValue::String("RA---TAN".to_string()),
+                ),
+            ],
+        );
+        assert_eq!(bytes.len() % 2880, 0);
+
+        let mut reader = Cursor::new(&bytes[..]);
+        let Fits { hdu } = Fits::from_reader(&mut reader).unwrap();
+
+        let xtension = hdu.get_header().get_xtension();
+        assert_eq!(xtension.get_naxisn(1), Some(&3));
+        assert_eq!(xtension.get_naxisn(2), Some(&2));
+
+        match hdu.get_data() {
+            InMemData::F32(data) => {
+                for (v, expected) in data.iter().zip(values.iter()) {
+                    assert!(v == expected || (v.is_nan() && expected.is_nan()));
+                }
+            }
+            _ => panic!("the image must be read as floats"),
+        }
+    }
+
+    #[test]
+    fn wcs_cards_skip_the_dimensions_and_undefined_keywords() {
+        let params: wcs::WCSParams = serde_json::from_value(serde_json::json!({
+            "NAXIS": 2,
+            "NAXIS1": 100,
+            "NAXIS2": 50,
+            "CTYPE1": "RA---TAN",
+            "CTYPE2": "DEC--TAN",
+            "CRPIX1": 50.5,
+            "CRVAL1": 83.63,
+        }))
+        .unwrap();
+
+        let cards = wcs_cards(&params);
+        assert!(cards.iter().all(|(key, _)| !key.starts_with("NAXIS")));
+        assert!(cards.iter().any(|(key, value)| key == "CTYPE1"
+            && matches!(value, CardValue::String(v) if v == "RA---TAN")));
+        assert!(cards
+            .iter()
+            .any(|(key, value)| key == "CRPIX1"
+                && matches!(value, CardValue::Float(v) if *v == 50.5)));
+        assert!(!cards.iter().any(|(key, _)| key == "CD1_1"));
+    }
+
+    #[test]
+    fn written_header_can_be_read() {
+        let bytes = write_image(
+            3,
+            2,
+            &ImageData::I16(vec![0; 6]),
+            vec![
+                ("BZERO".to_string(), CardValue::Float(32768.0)),
+                (
+                    "OBJECT".to_string(),
+                    CardValue::String("M1 'Crab'".to_string()),
+                ),
+            ],
+        );
+
+        let (cards, size) = read_header(&bytes).unwrap();
+        assert_eq!(size, 2880);
+        assert!(matches!(
+            cards.get("SIMPLE"),
+            Some(CardValue::Logical(true))
+        ));
+        assert!(matches!(cards.get("BITPIX"), Some(CardValue::Integer(16))));
+        assert!(matches!(cards.get("NAXIS1"), Some(CardValue::Integer(3))));
+        assert!(matches!(cards.get("BZERO"), Some(CardValue::Float(v)) if *v == 32768.0));
+        assert!(matches!(cards.get("OBJECT"), Some(CardValue::String(v)) if v == "M1 'Crab'"));
+
+        let card = |card: &str| format!("{:<80}", card);
+        let bytes = [
+            card("TFORM1  = 'E       '           / no repeat count"),
+            card("CDELT1  = -1.5D-3"),
+            card("COMMENT = not a value"),
+            card("END"),
+        ]
+        .concat();
+        let (cards, size) = read_header(bytes.as_bytes()).unwrap();
+        assert_eq!(size, 2880);
+        assert!(matches!(cards.get("TFORM1"), Some(CardValue::String(v)) if v == "E"));
+        assert!(matches!(cards.get("CDELT1"), Some(CardValue::Float(v)) if *v == -1.5e-3));
+        assert!(!cards.contains_key("COMMENT"));
+
+        assert!(read_header(&bytes.as_bytes()[..160]).is_err());
+    }
+}
@@ -198,6 +198,7 @@ pub fn vertices(
                     let xyzw = crate::coosys::apply_coo_system(
                         CooSystem::ICRS,
                         camera.get_coo_system(),
+                        camera.get_icrs_to_horizontal(),
                         &Vector4::new(xyz.y(), xyz.z(), xyz.x(), 1.0),
                     );
 
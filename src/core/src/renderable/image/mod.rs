@@ -1,4 +1,5 @@
 pub mod cuts;
+pub mod fits;
 pub mod grid;
 pub mod subdivide_texture;
 
@@ -9,7 +10,7 @@ use std::marker::Unpin;
 use std::vec;
 
 use al_api::coo_system::CooSystem;
-use cgmath::Vector4;
+use cgmath::{Matrix4, SquareMatrix, Vector4};
 use futures::stream::TryStreamExt;
 use futures::AsyncRead;
 
@@ -22,6 +23,7 @@ use wcs::{ImgXY, WCS};
 
 use al_api::fov::CenteredFoV;
 use al_api::hips::ImageMetadata;
+use al_api::image::{ImagePixel, PixelValue};
 
 use al_core::image::format::*;
 use al_core::webgl_ctx::GlWrapper;
@@ -30,7 +32,14 @@ use al_core::WebGlContext;
 use al_core::{Texture2D, VertexArrayObject};
 
 use crate::camera::CameraViewPort;
+use crate::healpix::cell::MAX_HPX_DEPTH;
+use crate::healpix::coverage::HEALPixCoverage;
+use crate::healpix::hipsgen::{self, GeneratedHiPS};
+use crate::math::angle::ToAngle;
+use crate::math::PI;
+use crate::math::lonlat::{LonLat, LonLatT};
 use crate::math::sph_geom::region::Region;
+use crate::renderable::contour::{LocatedGrid, ValueGrid};
 use crate::Colormaps;
 use crate::ProjectionType;
 use crate::ShaderManager;
@@ -38,6 +47,13 @@ use crate::ShaderManager;
 use std::ops::Range;
 type PixelItem<F> = <<F as ImageFormat>::P as Pixel>::Item;
 
+// Number of points sampled along each border of an image for computing its footprint
+const NUM_VERTICES_PER_SIDE: usize = 8;
+// Maximum number of tiles at the max order of a HiPS generated from an image
+const MAX_NUM_HIPS_TILES: u64 = 256;
+// Maximum number of values per side of the grids whose contours are traced
+const MAX_CONTOUR_GRID_SIZE: usize = 2048;
+
 pub struct Image {
     /// A reference to the GL context
     gl: WebGlContext,
@@ -55,6 +71,9 @@ pub struct Image {
     scale: f32,
     offset: f32,
     cuts: Range<f32>,
+    /// Physical values of pixels sampled regularly in the image, blank pixels excepted,
+    /// along with their HEALPix index at the deepest order in ICRS
+    pixel_samples: Vec<(u64, f32)>,
     /// The center of the fits
     centered_fov: CenteredFoV,
 
@@ -71,6 +90,8 @@ pub struct Image {
     reg: Region,
     // The coo system in which the polygonal region has been defined
     coo_sys: CooSystem,
+    // The horizontal frame for which the region has been defined
+    icrs2hor: Matrix4<f64>,
 }
 use al_core::pixel::Pixel;
 use fitsrs::hdu::header::extension;
@@ -86,8 +107,6 @@ impl Image {
         scale: Option<f32>,
         offset: Option<f32>,
         blank: Option<f32>,
-        // Coo sys of the view
-        coo_sys: CooSystem,
     ) -> Result<Self, JsValue>
     where
         F: ImageFormat,
@@ -107,7 +126,7 @@ impl Image {
         let offset = offset.unwrap_or(0.0);
         let scale = scale.unwrap_or(1.0);
 
-        let (textures, cuts) = if width <= max_tex_size as u64 && height <= max_tex_size as u64
+        let (textures, sub_pixels) = if width <= max_tex_size as u64 && height <= max_tex_size as u64
         {
             max_tex_size_x = width as usize;
             max_tex_size_y = height as usize;
@@ -157,50 +176,39 @@ impl Image {
                     Some(data),
                 )?;
 
-                let cuts = match F::CHANNEL_TYPE {
+                // Sample the pixels along with their position in the image
+                let sub_pixels = match F::CHANNEL_TYPE {
                     ChannelType::R32F | ChannelType::R64F => {
                         let pixels = std::slice::from_raw_parts(data.as_ptr() as *const f32, data.len() / 4);
 
-                        let mut sub_pixels = pixels.iter()
+                        pixels.iter()
+                            .enumerate()
                             .step_by(100)
-                            .filter(|pixel| (*pixel).is_finite())
-                            .cloned()
-                            .collect::<Vec<_>>();
-
-                        cuts::first_and_last_percent(&mut sub_pixels, 1, 99)
+                            .filter(|(_, pixel)| (*pixel).is_finite())
+                            .map(|(i, pixel)| (i as u64 % width, i as u64 / width, *pixel))
+                            .collect::<Vec<_>>()
                     }
                     ChannelType::R8UI | ChannelType::R16I | ChannelType::R32I => {
-                        // BLANK is only valid for those channels/BITPIX (> 0)
-                        if let Some(blank) = blank {
-                            let mut sub_pixels = data.iter()
-                                .step_by(100)
-                                .filter_map(|pixel| {
-                                    let pixel = <PixelItem::<F> as Cast<f32>>::cast(*pixel);
-
-                                    if pixel != blank {
-                                        Some(pixel)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect::<Vec<_>>();
-
-                            cuts::first_and_last_percent(&mut sub_pixels, 1, 99)
-                        } else {
-                            // No blank value => we consider all the values
-                            let mut sub_pixels = data.iter()
-                                .step_by(100)
-                                .map(|pixel| <PixelItem::<F> as Cast<f32>>::cast(*pixel))
-                                .collect::<Vec<_>>();
-
-                            cuts::first_and_last_percent(&mut sub_pixels, 1, 99)
-                        }
+                        data.iter()
+                            .enumerate()
+                            .step_by(100)
+                            .filter_map(|(i, pixel)| {
+                                let pixel = <PixelItem::<F> as Cast<f32>>::cast(*pixel);
+
+                                // BLANK is only valid for those channels/BITPIX (> 0)
+                                if Some(pixel) != blank {
+                                    Some((i as u64 % width, i as u64 / width, pixel))
+                                } else {
+                                    None
+                                }
+                            })
+                            .collect::<Vec<_>>()
                     }
                     // RGB(A) images
-                    _ => 0.0..1.0
+                    _ => vec![]
                 };
 
-                (vec![texture], cuts)
+                (vec![texture], sub_pixels)
             }
         } else {
             subdivide_texture::crop_image::<F, R>(
@@ -218,10 +226,25 @@ impl Image {
             tex.generate_mipmap();
         }
 
-        let start = cuts.start * scale + offset;
-        let end = cuts.end * scale + offset;
+        let cuts = if F::CHANNEL_TYPE.is_colored() || sub_pixels.is_empty() {
+            0.0..1.0
+        } else {
+            let mut values = sub_pixels.iter().map(|(_, _, v)| *v).collect::<Vec<_>>();
+            let cuts = cuts::first_and_last_percent(&mut values, 1, 99);
+
+            (cuts.start * scale + offset)..(cuts.end * scale + offset)
+        };
+
+        // Locate the sampled pixels on the sky for computing the cuts of the view
+        let pixel_samples = sub_pixels
+            .into_iter()
+            .filter_map(|(x, y, v)| {
+                let lonlat = wcs.unproj_lonlat(&ImgXY::new(x as f64 + 0.5, y as f64 + 0.5))?;
+                let hash = healpix::nested::hash(MAX_HPX_DEPTH, lonlat.lon(), lonlat.lat());
 
-        let cuts = start..end;
+                Some((hash, v * scale + offset))
+            })
+            .collect();
 
         let num_indices = vec![];
         let indices = vec![];
@@ -261,36 +284,9 @@ impl Image {
         let center = wcs
             .unproj_lonlat(&ImgXY::new(width as f64 / 2.0, height as f64 / 2.0))
             .ok_or(JsValue::from_str("(w / 2, h / 2) px cannot be unprojected"))?;
-        let center_xyz = center.to_xyz();
-        let inside = crate::coosys::apply_coo_system(
-            CooSystem::ICRS,
-            coo_sys,
-            &Vector4::new(center_xyz.y(), center_xyz.z(), center_xyz.x(), 1.0),
-        );
-
-        let vertices = [
-            wcs.unproj_lonlat(&ImgXY::new(0.0, 0.0))
-                .ok_or(JsValue::from_str("(0, 0) does not lie in the sky"))?,
-            wcs.unproj_lonlat(&ImgXY::new(width as f64 - 1.0, 0.0))
-                .ok_or(JsValue::from_str("(w - 1, 0) does not lie in the sky"))?,
-            wcs.unproj_lonlat(&ImgXY::new(width as f64 - 1.0, height as f64 - 1.0))
-                .ok_or(JsValue::from_str("(w - 1, h - 1) does not lie in the sky"))?,
-            wcs.unproj_lonlat(&ImgXY::new(0.0, height as f64 - 1.0))
-                .ok_or(JsValue::from_str("(0, h - 1) does not lie in the sky"))?,
-        ]
-        .iter()
-        .map(|lonlat| {
-            let xyz = lonlat.to_xyz();
-
-            crate::coosys::apply_coo_system(
-                CooSystem::ICRS,
-                coo_sys,
-                &Vector4::new(xyz.y(), xyz.z(), xyz.x(), 1.0),
-            )
-        })
-        .collect::<Vec<_>>();
-
-        let reg = Region::from_vertices(&vertices, &inside);
+        // The region is defined in the frame of the view once drawn
+        let (coo_sys, icrs2hor) = (CooSystem::ICRS, Matrix4::identity());
+        let reg = sky_region(&wcs, coo_sys, &icrs2hor)?;
 
         // ra and dec must be given in ICRS coo system, which is the case because wcs returns
         // only ICRS coo
@@ -326,6 +322,7 @@ impl Image {
             channel: F::CHANNEL_TYPE,
             textures,
             cuts,
+            pixel_samples,
             max_tex_size_x,
             max_tex_size_y,
             // Indices of textures that must be drawn
@@ -334,6 +331,7 @@ impl Image {
             reg,
             // The coo system in which the polygonal region has been defined
             coo_sys,
+            icrs2hor,
         })
     }
 
@@ -344,7 +342,6 @@ impl Image {
     pub async fn from_fits_hdu_async<'a, R>(
         gl: &WebGlContext,
         hdu: &mut AsyncHDU<'a, BufReader<R>, extension::image::Image>,
-        coo_sys: CooSystem,
     ) -> Result<Self, JsValue>
     where
         R: AsyncRead + Unpin + Debug + 'a,
@@ -379,7 +376,6 @@ impl Image {
                     scale.map(|v| v as f32),
                     offset.map(|v| v as f32),
                     blank.map(|v| v as f32),
-                    coo_sys,
                 )
                 .await
             }
@@ -393,7 +389,6 @@ impl Image {
                     scale.map(|v| v as f32),
                     offset.map(|v| v as f32),
                     blank.map(|v| v as f32),
-                    coo_sys,
                 )
                 .await
             }
@@ -407,7 +402,6 @@ impl Image {
                     scale.map(|v| v as f32),
                     offset.map(|v| v as f32),
                     blank.map(|v| v as f32),
-                    coo_sys,
                 )
                 .await
             }
@@ -426,7 +420,6 @@ impl Image {
                     scale.map(|v| v as f32),
                     offset.map(|v| v as f32),
                     blank.map(|v| v as f32),
-                    coo_sys,
                 )
                 .await
             }
@@ -440,7 +433,6 @@ impl Image {
                     scale.map(|v| v as f32),
                     offset.map(|v| v as f32),
                     blank.map(|v| v as f32),
-                    coo_sys,
                 )
                 .await
             }
@@ -459,7 +451,6 @@ impl Image {
                     scale.map(|v| v as f32),
                     offset.map(|v| v as f32),
                     blank.map(|v| v as f32),
-                    coo_sys,
                 )
                 .await
             }
@@ -540,52 +531,16 @@ impl Image {
         camera: &CameraViewPort,
         projection: &ProjectionType,
     ) -> Result<(), JsValue> {
-        if self.coo_sys != camera.get_coo_system() {
+        if self.coo_sys != camera.get_coo_system()
+            || self.icrs2hor != *camera.get_icrs_to_horizontal()
+        {
             self.coo_sys = camera.get_coo_system();
-
-            let (width, height) = self.wcs.img_dimensions();
+            self.icrs2hor = *camera.get_icrs_to_horizontal();
 
             // the camera coo system is not sync with the one in which the region
             // has been defined
             // let's redefine the region
-            let center = self
-                .wcs
-                .unproj_lonlat(&ImgXY::new(width as f64 / 2.0, height as f64 / 2.0))
-                .ok_or(JsValue::from_str("(w / 2, h / 2) px cannot be unprojected"))?;
-            let center_xyz = center.to_xyz();
-            let inside = crate::coosys::apply_coo_system(
-                CooSystem::ICRS,
-                self.coo_sys,
-                &Vector4::new(center_xyz.y(), center_xyz.z(), center_xyz.x(), 1.0),
-            );
-
-            let vertices = [
-                self.wcs
-                    .unproj_lonlat(&ImgXY::new(0.0, 0.0))
-                    .ok_or(JsValue::from_str("(0, 0) does not lie in the sky"))?,
-                self.wcs
-                    .unproj_lonlat(&ImgXY::new(width as f64 - 1.0, 0.0))
-                    .ok_or(JsValue::from_str("(w - 1, 0) does not lie in the sky"))?,
-                self.wcs
-                    .unproj_lonlat(&ImgXY::new(width as f64 - 1.0, height as f64 - 1.0))
-                    .ok_or(JsValue::from_str("(w - 1, h - 1) does not lie in the sky"))?,
-                self.wcs
-                    .unproj_lonlat(&ImgXY::new(0.0, height as f64 - 1.0))
-                    .ok_or(JsValue::from_str("(0, h - 1) does not lie in the sky"))?,
-            ]
-            .iter()
-            .map(|lonlat| {
-                let xyz = lonlat.to_xyz();
-
-                crate::coosys::apply_coo_system(
-                    CooSystem::ICRS,
-                    self.coo_sys,
-                    &Vector4::new(xyz.y(), xyz.z(), xyz.x(), 1.0),
-                )
-            })
-            .collect::<Vec<_>>();
-
-            self.reg = Region::from_vertices(&vertices, &inside);
+            self.reg = sky_region(&self.wcs, self.coo_sys, &self.icrs2hor)?;
         }
 
         self.recompute_vertices(camera, projection)?;
@@ -684,8 +639,542 @@ impl Image {
         Ok(())
     }
 
+    /// Read the pixel of the image located at a sky position
+    ///
+    /// # Arguments
+    ///
+    /// * `pos` - The sky position given in the frame of the view
+    /// * `camera` - The camera
+    ///
+    /// Returns `None` if the position lies outside the image
+    pub fn read_pixel(
+        &self,
+        pos: &LonLatT<f64>,
+        camera: &CameraViewPort,
+    ) -> Result<Option<ImagePixel>, JsValue> {
+        let (x, y) = if let Some(xy) = image_pixel(
+            &self.wcs,
+            pos,
+            camera.get_coo_system(),
+            camera.get_icrs_to_horizontal(),
+        ) {
+            xy
+        } else {
+            return Ok(None);
+        };
+
+        // Find the texture chunk containing the pixel (see subdivide_texture.rs)
+        let (height, max_tex_size) = (
+            self.wcs.img_dimensions().1,
+            (self.max_tex_size_x as u64, self.max_tex_size_y as u64),
+        );
+        let (idx_tex, dx, dy) = texture_chunk(x, y, height, max_tex_size);
+
+        let texture = self
+            .textures
+            .get(idx_tex)
+            .ok_or_else(|| JsValue::from_str("Texture chunk not found"))?;
+        let raw = texture.read_pixel(dx, dy)?;
+
+        let value = if let Some(raw) = raw.as_f64() {
+            physical_value(raw, self.channel, self.blank, self.scale, self.offset)
+                .map(PixelValue::Physical)
+        } else {
+            // RGB(A) images
+            Some(PixelValue::Color(serde_wasm_bindgen::from_value(raw)?))
+        };
+
+        Ok(Some(ImagePixel { value, x, y }))
+    }
+
+    /// Generate a HiPS in memory by reprojecting the image with its WCS
+    ///
+    /// # Arguments
+    ///
+    /// * `max_order` - The max order of the HiPS, the one reaching the resolution of the image by default
+    /// * `creator_did` - The identifier of the HiPS
+    /// * `title` - The title of the HiPS
+    pub fn to_hips(
+        &self,
+        max_order: Option<u8>,
+        creator_did: String,
+        title: String,
+    ) -> Result<GeneratedHiPS, JsValue> {
+        if self.channel.is_colored() {
+            return Err(JsValue::from_str(
+                "Only the FITS images of one channel can be converted to a HiPS",
+            ));
+        }
+
+        // The first order whose pixels are not larger than the ones of the image
+        let native_order = {
+            let depth = ((PI / 3.0).sqrt() / self.get_pixel_scale()).log2().ceil();
+            (depth.max(0.0) as u8).saturating_sub(hipsgen::TILE_DEPTH)
+        };
+        let max_order = max_order
+            .unwrap_or(native_order)
+            .min(MAX_HPX_DEPTH - hipsgen::TILE_DEPTH);
+
+        // The footprint of the image, sampled along its borders for the distorted ones
+        let (width, height) = self.wcs.img_dimensions();
+        let (w, h) = (width as f64, height as f64);
+        let border =
+            (0..NUM_VERTICES_PER_SIDE).map(|i| (i as f64) / (NUM_VERTICES_PER_SIDE as f64));
+        let vertices = border
+            .clone()
+            .map(|t| (t * w, 0.0))
+            .chain(border.clone().map(|t| (w, t * h)))
+            .chain(border.clone().map(|t| ((1.0 - t) * w, h)))
+            .chain(border.map(|t| (0.0, (1.0 - t) * h)))
+            .map(|(x, y)| {
+                self.wcs
+                    .unproj_lonlat(&ImgXY::new(x, y))
+                    .map(|lonlat| {
+                        LonLatT::new(lonlat.lon().to_angle(), lonlat.lat().to_angle())
+                    })
+                    .ok_or_else(|| {
+                        JsValue::from_str("The border of the image does not lie in the sky")
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let moc = HEALPixCoverage::from_polygon(&vertices, max_order);
+
+        let num_tiles = moc.0.range_sum() >> (2 * (MAX_HPX_DEPTH - max_order));
+        if num_tiles > MAX_NUM_HIPS_TILES {
+            return Err(JsValue::from_str(&format!(
+                "The HiPS of order {} would have {} tiles, the maximum being {}. Choose a lower max order.",
+                max_order, num_tiles, MAX_NUM_HIPS_TILES
+            )));
+        }
+
+        let value_at = self.read_back()?;
+        let sample = |lon: f64, lat: f64| -> f32 {
+            self.wcs
+                .proj_lonlat(&wcs::LonLat::new(lon, lat))
+                .map(|xy| (xy.x().floor(), xy.y().floor()))
+                .filter(|&(x, y)| x >= 0.0 && y >= 0.0 && x < w && y < h)
+                .map(|(x, y)| value_at(x as u64, y as u64))
+                .unwrap_or(f32::NAN)
+        };
+
+        Ok(GeneratedHiPS::new(
+            moc,
+            sample,
+            creator_did,
+            title,
+            self.centered_fov.clone(),
+        ))
+    }
+
+    /// The physical values of the image on a grid for tracing its contours
+    ///
+    /// Big images are subsampled. Returns the grid with the function giving the ICRS position
+    /// of a point of the grid.
+    pub fn get_contour_grid(&self) -> Result<LocatedGrid<'_>, JsValue> {
+        if self.channel.is_colored() {
+            return Err(JsValue::from_str(
+                "Contours can only be traced on the FITS images of one channel",
+            ));
+        }
+
+        let value_at = self.read_back()?;
+
+        let (width, height) = self.wcs.img_dimensions();
+        let (width, height) = (width as usize, height as usize);
+        let step = width.max(height).div_ceil(MAX_CONTOUR_GRID_SIZE);
+        let (grid_width, grid_height) = (width.div_ceil(step), height.div_ceil(step));
+
+        let values = (0..grid_height)
+            .flat_map(|y| (0..grid_width).map(move |x| (x * step, y * step)))
+            .map(|(x, y)| value_at(x as u64, y as u64))
+            .collect();
+        let grid = ValueGrid {
+            values,
+            width: grid_width,
+            height: grid_height,
+        };
+
+        let step = step as f64;
+        let to_icrs = move |x: f64, y: f64| -> Option<Vector4<f64>> {
+            let lonlat = self
+                .wcs
+                .unproj_lonlat(&ImgXY::new(x * step + 0.5, y * step + 0.5))?;
+
+            Some(LonLatT::from(lonlat).vector())
+        };
+
+        Ok((grid, Box::new(to_icrs)))
+    }
+
+    // Read the pixels back from the textures
+    //
+    // Returns the function giving the physical value of a pixel, NaN for the blank ones
+    fn read_back(&self) -> Result<impl Fn(u64, u64) -> f32 + '_, JsValue> {
+        let chunks = self
+            .textures
+            .iter()
+            .map(|texture| texture.read_values())
+            .collect::<Result<Vec<_>, _>>()?;
+        let (max_tex_size_x, max_tex_size_y) =
+            (self.max_tex_size_x as u64, self.max_tex_size_y as u64);
+        let (_, height) = self.wcs.img_dimensions();
+        let num_texture_y = height / max_tex_size_y + 1;
+
+        Ok(move |x: u64, y: u64| {
+            // Same texture chunk as the one read by `read_pixel`
+            let idx_tex = (y / max_tex_size_y + (x / max_tex_size_x) * num_texture_y) as usize;
+            let idx = (y % max_tex_size_y) * max_tex_size_x + x % max_tex_size_x;
+
+            match chunks.get(idx_tex).map(|chunk| chunk[idx as usize]) {
+                // BLANK is only valid for the integer BITPIX
+                Some(raw) if !raw.is_nan() && self.blank != Some(raw) => {
+                    raw * self.scale + self.offset
+                }
+                _ => f32::NAN,
+            }
+        })
+    }
+
     #[inline]
     pub fn get_centered_fov(&self) -> &CenteredFoV {
         &self.centered_fov
     }
+
+    /// Values of the pixels sampled in the view
+    pub fn get_pixel_samples_in_view<'a>(
+        &'a self,
+        camera: &'a CameraViewPort,
+    ) -> impl Iterator<Item = f32> + 'a {
+        let view_cov = camera.get_cov(CooSystem::ICRS);
+
+        self.pixel_samples
+            .iter()
+            .filter(move |(hash, _)| view_cov.contains_val(hash))
+            .map(|(_, v)| *v)
+    }
+
+    /// Angular size of a pixel in radians
+    #[inline]
+    pub fn get_pixel_scale(&self) -> f64 {
+        let (width, _) = self.wcs.img_dimensions();
+        (self.centered_fov.fov / (width as f64)).to_radians()
+    }
+}
+
+/// Polygonal region of the sky covered by an image, in the frame of the view
+fn sky_region(wcs: &WCS, coo_sys: CooSystem, icrs2hor: &Matrix4<f64>) -> Result<Region, JsValue> {
+    let (width, height) = wcs.img_dimensions();
+
+    // The wcs works with ICRS positions
+    let to_view = |lonlat: &wcs::LonLat| {
+        let xyz = lonlat.to_xyz();
+
+        crate::coosys::apply_coo_system(
+            CooSystem::ICRS,
+            coo_sys,
+            icrs2hor,
+            &Vector4::new(xyz.y(), xyz.z(), xyz.x(), 1.0),
+        )
+    };
+
+    let center = wcs
+        .unproj_lonlat(&ImgXY::new(width as f64 / 2.0, height as f64 / 2.0))
+        .ok_or(JsValue::from_str("(w / 2, h / 2) px cannot be unprojected"))?;
+    let inside = to_view(&center);
+
+    let vertices = [
+        wcs.unproj_lonlat(&ImgXY::new(0.0, 0.0))
+            .ok_or(JsValue::from_str("(0, 0) does not lie in the sky"))?,
+        wcs.unproj_lonlat(&ImgXY::new(width as f64 - 1.0, 0.0))
+            .ok_or(JsValue::from_str("(w - 1, 0) does not lie in the sky"))?,
+        wcs.unproj_lonlat(&ImgXY::new(width as f64 - 1.0, height as f64 - 1.0))
+            .ok_or(JsValue::from_str("(w - 1, h - 1) does not lie in the sky"))?,
+        wcs.unproj_lonlat(&ImgXY::new(0.0, height as f64 - 1.0))
+            .ok_or(JsValue::from_str("(0, h - 1) does not lie in the sky"))?,
+    ]
+    .iter()
+    .map(to_view)
+    .collect::<Vec<_>>();
+
+    Ok(Region::from_vertices(&vertices, &inside))
+}
+
+// Pixel of an image located at a sky position given in `frame`
+//
+// The pixels are drawn with their edges at the integer image coordinates of the wcs,
+// returns `None` outside the image
+fn image_pixel(
+    wcs: &WCS,
+    pos: &LonLatT<f64>,
+    frame: CooSystem,
+    icrs2hor: &Matrix4<f64>,
+) -> Option<(u64, u64)> {
+    // The wcs works with ICRS positions
+    let pos: LonLatT<f64> =
+        crate::coosys::apply_coo_system(frame, CooSystem::ICRS, icrs2hor, &pos.vector()).lonlat();
+
+    let (lon, lat) = (pos.lon().to_radians(), pos.lat().to_radians());
+    // Undefined positions, e.g. outside the projection, cannot be projected
+    if !lon.is_finite() || !lat.is_finite() {
+        return None;
+    }
+    let xy = wcs.proj_lonlat(&wcs::LonLat::new(lon, lat))?;
+
+    let (x, y) = (xy.x().floor(), xy.y().floor());
+    let (width, height) = wcs.img_dimensions();
+    // Also discards the NaN coordinates
+    if (0.0..width as f64).contains(&x) && (0.0..height as f64).contains(&y) {
+        Some((x as u64, y as u64))
+    } else {
+        None
+    }
+}
+
+// Index of the texture chunk containing a pixel along with the position of the pixel in it,
+// the image being subdivided column by column (see subdivide_texture.rs)
+fn texture_chunk(x: u64, y: u64, height: u64, max_tex_size: (u64, u64)) -> (usize, i32, i32) {
+    let (max_tex_size_x, max_tex_size_y) = max_tex_size;
+    let num_texture_y = height / max_tex_size_y + 1;
+    let idx_tex = (y / max_tex_size_y + (x / max_tex_size_x) * num_texture_y) as usize;
+
+    (
+        idx_tex,
+        (x % max_tex_size_x) as i32,
+        (y % max_tex_size_y) as i32,
+    )
+}
+
+// Physical value of a raw pixel value, `None` for the blank pixels
+fn physical_value(
+    raw: f64,
+    channel: ChannelType,
+    blank: Option<f32>,
+    scale: f32,
+    offset: f32,
+) -> Option<f64> {
+    let is_blank = match channel {
+        ChannelType::R32F | ChannelType::R64F => raw.is_nan(),
+        // BLANK is only valid for the integer BITPIX
+        _ => blank == Some(raw as f32),
+    };
+
+    if is_blank {
+        None
+    } else {
+        Some(raw * (scale as f64) + (offset as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::lonlat::radec_to_xyzw;
+    use crate::math::projection::Projection;
+    use cgmath::Vector2;
+
+    // A 100x80 pixels image centered on the Orion nebula
+    fn image_wcs() -> WCS {
+        let params: wcs::WCSParams = serde_json::from_value(serde_json::json!({
+            "NAXIS": 2,
+            "NAXIS1": 100,
+            "NAXIS2": 80,
+            "CTYPE1": "RA---TAN",
+            "CTYPE2": "DEC--TAN",
+            "CRPIX1": 50.0,
+            "CRPIX2": 40.0,
+            "CRVAL1": 83.82,
+            "CRVAL2": -5.39,
+            "CDELT1": -0.01,
+            "CDELT2": 0.01,
+        }))
+        .unwrap();
+
+        WCS::new(&params).unwrap()
+    }
+
+    fn lonlat(lon: f64, lat: f64) -> LonLatT<f64> {
+        LonLatT::new(lon.to_angle(), lat.to_angle())
+    }
+
+    #[test]
+    fn read_pixels_in_world_space() {
+        let wcs = image_wcs();
+        let icrs2hor = Matrix4::identity();
+
+        for (x, y) in [(0, 0), (99, 0), (0, 79), (99, 79), (49, 39), (13, 57)] {
+            // Center of the pixel
+            let pos = wcs
+                .unproj_lonlat(&ImgXY::new(x as f64 + 0.5, y as f64 + 0.5))
+                .unwrap();
+            let icrs = lonlat(pos.lon(), pos.lat());
+            assert_eq!(
+                image_pixel(&wcs, &icrs, CooSystem::ICRS, &icrs2hor),
+                Some((x, y))
+            );
+
+            // The same position given in the galactic frame
+            let gal = crate::coosys::apply_coo_system(
+                CooSystem::ICRS,
+                CooSystem::GAL,
+                &icrs2hor,
+                &icrs.vector(),
+            )
+            .lonlat();
+            assert_eq!(
+                image_pixel(&wcs, &gal, CooSystem::GAL, &icrs2hor),
+                Some((x, y))
+            );
+        }
+    }
+
+    #[test]
+    fn read_pixels_in_screen_space() {
+        // The image is a cutout of the view, e.g. given by getFITSCutout with the WCS of the view
+        let proj = ProjectionType::Tan(mapproj::zenithal::tan::Tan);
+        let center = radec_to_xyzw(
+            83.82_f64.to_radians().to_angle(),
+            (-5.39_f64).to_radians().to_angle(),
+        );
+        let w2m: Matrix4<f64> =
+            (&crate::math::rotation::Rotation::from_sky_position(&center)).into();
+        let size = Vector2::new(300.0, 200.0);
+        let clip_scale = Vector2::new(0.05, 0.05 * size.y / size.x);
+        let params =
+            crate::camera::wcs::view_wcs(&proj, &w2m, CooSystem::ICRS, size, clip_scale).unwrap();
+        let wcs = WCS::new(&params).unwrap();
+
+        for (sx, sy) in [(0.2, 0.7), (150.0, 100.0), (299.4, 199.9), (12.25, 180.4)] {
+            // Screen position from the top left corner of the canvas to the sky,
+            // as done by the camera
+            let ndc = Vector2::new(2.0 * sx / size.x - 1.0, 1.0 - 2.0 * sy / size.y);
+            let clip = Vector2::new(ndc.x * clip_scale.x, ndc.y * clip_scale.y);
+            let pos = (w2m * proj.clip_to_world_space(&clip).unwrap()).lonlat();
+
+            // The FITS pixels of the canvas are centered on the integer coordinates
+            let expected = (
+                (sx + 0.5_f64).floor() as u64,
+                (size.y - sy + 0.5).floor() as u64,
+            );
+            assert_eq!(
+                image_pixel(&wcs, &pos, CooSystem::ICRS, &Matrix4::identity()),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn no_pixel_outside_the_image() {
+        let wcs = image_wcs();
+        let icrs2hor = Matrix4::identity();
+
+        let outside = [
+            // Beyond the left and bottom edges
+            wcs.unproj_lonlat(&ImgXY::new(-0.5, 10.0)).unwrap(),
+            wcs.unproj_lonlat(&ImgXY::new(10.0, -0.5)).unwrap(),
+            // Beyond the right and top edges
+            wcs.unproj_lonlat(&ImgXY::new(100.5, 10.0)).unwrap(),
+            wcs.unproj_lonlat(&ImgXY::new(10.0, 80.5)).unwrap(),
+        ];
+        for pos in outside {
+            assert_eq!(
+                image_pixel(
+                    &wcs,
+                    &lonlat(pos.lon(), pos.lat()),
+                    CooSystem::ICRS,
+                    &icrs2hor
+                ),
+                None
+            );
+        }
+
+        // The opposite side of the sky cannot be projected
+        let antipode = lonlat((83.82_f64 + 180.0).to_radians(), 5.39_f64.to_radians());
+        assert_eq!(
+            image_pixel(&wcs, &antipode, CooSystem::ICRS, &icrs2hor),
+            None
+        );
+        // Undefined positions
+        let undefined = lonlat(f64::NAN, f64::NAN);
+        assert_eq!(
+            image_pixel(&wcs, &undefined, CooSystem::ICRS, &icrs2hor),
+            None
+        );
+    }
+
+    #[test]
+    fn pixels_of_subdivided_textures() {
+        // A 10x7 image cut in 4x4 textures, column by column as in subdivide_texture.rs:
+        // 3 columns of chunks, the last one 2 pixels wide, each made of 2 chunks, the
+        // last one 3 pixels high
+        let (width, height, max_tex_size) = (10_u64, 7_u64, (4_u64, 4_u64));
+
+        assert_eq!(texture_chunk(0, 0, height, max_tex_size), (0, 0, 0));
+        assert_eq!(texture_chunk(3, 3, height, max_tex_size), (0, 3, 3));
+        assert_eq!(texture_chunk(2, 4, height, max_tex_size), (1, 2, 0));
+        assert_eq!(texture_chunk(3, 6, height, max_tex_size), (1, 3, 2));
+        assert_eq!(texture_chunk(4, 0, height, max_tex_size), (2, 0, 0));
+        assert_eq!(texture_chunk(7, 5, height, max_tex_size), (3, 3, 1));
+        assert_eq!(texture_chunk(8, 1, height, max_tex_size), (4, 0, 1));
+        assert_eq!(texture_chunk(9, 6, height, max_tex_size), (5, 1, 2));
+
+        // Size of the part of each chunk covered by the image
+        let mut sizes = [(0, 0); 6];
+        for y in 0..height {
+            for x in 0..width {
+                let (idx_tex, dx, dy) = texture_chunk(x, y, height, max_tex_size);
+                let size = &mut sizes[idx_tex];
+                *size = (size.0.max(dx + 1), size.1.max(dy + 1));
+            }
+        }
+        assert_eq!(sizes, [(4, 4), (4, 3), (4, 4), (4, 3), (2, 4), (2, 3)]);
+    }
+
+    #[test]
+    fn physical_values() {
+        // BSCALE and BZERO
+        assert_eq!(
+            physical_value(100.0, ChannelType::R16I, None, 2.0, 10.0),
+            Some(210.0)
+        );
+        assert_eq!(
+            physical_value(-3.0, ChannelType::R32I, Some(-32768.0), 0.5, 0.0),
+            Some(-1.5)
+        );
+        assert_eq!(
+            physical_value(12.0, ChannelType::R8UI, None, 1.0, -128.0),
+            Some(-116.0)
+        );
+        assert_eq!(
+            physical_value(1.5, ChannelType::R32F, None, 1.0, 0.0),
+            Some(1.5)
+        );
+
+        // BLANK is compared to the raw value of the integer images
+        assert_eq!(
+            physical_value(-32768.0, ChannelType::R16I, Some(-32768.0), 2.0, 10.0),
+            None
+        );
+        assert_eq!(
+            physical_value(0.0, ChannelType::R8UI, Some(0.0), 1.0, 0.0),
+            None
+        );
+        assert_eq!(
+            physical_value(-32768.0, ChannelType::R16I, None, 1.0, 0.0),
+            Some(-32768.0)
+        );
+
+        // The blank pixels of the float images are NaN, BLANK being ignored
+        assert_eq!(
+            physical_value(f64::NAN, ChannelType::R32F, None, 1.0, 0.0),
+            None
+        );
+        assert_eq!(
+            physical_value(f64::NAN, ChannelType::R64F, None, 1.0, 0.0),
+            None
+        );
+        assert_eq!(
+            physical_value(3.0, ChannelType::R32F, Some(3.0), 1.0, 0.0),
+            Some(3.0)
+        );
+    }
 }
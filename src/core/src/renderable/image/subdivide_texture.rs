@@ -3,13 +3,11 @@ use wasm_bindgen::JsValue;
 
 use futures::AsyncReadExt;
 
-use super::cuts;
 use al_core::image::format::ImageFormat;
 use al_core::texture::pixel::Pixel;
 use al_core::webgl_ctx::WebGlRenderingCtx;
 use al_core::Texture2D;
 use al_core::WebGlContext;
-use std::ops::Range;
 
 use al_core::convert::Cast;
 type PixelItem<F> = <<F as ImageFormat>::P as Pixel>::Item;
@@ -21,7 +19,7 @@ pub async fn crop_image<'a, F, R>(
     mut reader: R,
     max_tex_size: u64,
     blank: Option<f32>,
-) -> Result<(Vec<Texture2D>, Range<f32>), JsValue>
+) -> Result<(Vec<Texture2D>, Vec<(u64, u64, f32)>), JsValue>
 where
     F: ImageFormat,
     R: AsyncReadExt + Unpin,
@@ -112,10 +110,11 @@ where
                     (num_pixels_to_read as usize) * F::NUM_CHANNELS,
                 );
 
-                // compute the cuts if the pixel is grayscale
+                // sample the pixel values if the pixel is grayscale
                 if (pixels_written / width) % (step_cut as u64) == 0 {
                     // We are in a good line
                     let xmin = pixels_written % width;
+                    let y = pixels_written / width;
 
                     match F::CHANNEL_TYPE {
                         ChannelType::R32F | ChannelType::R64F => {
@@ -126,7 +125,7 @@ where
                                     let j = (i - xmin) as usize;
 
                                     if pixels[j].is_finite() {
-                                        sub_pixels.push(pixels[j]);
+                                        sub_pixels.push((i, y, pixels[j]));
                                     }
                                 }
                             }
@@ -140,7 +139,7 @@ where
                                         let pixel = <PixelItem::<F> as Cast<f32>>::cast(data[j]);
 
                                         if pixel != blank {
-                                            sub_pixels.push(pixel);
+                                            sub_pixels.push((i, y, pixel));
                                         }
                                     }
                                 }
@@ -150,7 +149,7 @@ where
                                         let j = (i - xmin) as usize;
     
                                         let pixel = <PixelItem::<F> as Cast<f32>>::cast(data[j]);
-                                        sub_pixels.push(pixel);                                        
+                                        sub_pixels.push((i, y, pixel));
                                     }
                                 }
                             }
@@ -181,11 +180,5 @@ where
         }
     }
 
-    let cuts = if F::CHANNEL_TYPE.is_colored() {
-        cuts::first_and_last_percent(&mut sub_pixels, 1, 99)
-    } else {
-        0.0..1.0
-    };
-
-    Ok((tex_chunks, cuts))
+    Ok((tex_chunks, sub_pixels))
 }
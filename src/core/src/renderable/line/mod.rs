@@ -390,7 +390,9 @@ impl RasterizedLineRenderer {
                     )?
                     .bind(&self.gl)
                     .attach_uniform("u_color", &meta.color)
-                    .attach_uniform("u_width", &meta.thickness)
+                    .attach_uniform("u_width", &camera.get_width())
+                    .attach_uniform("u_height", &camera.get_height())
+                    .attach_uniform("u_thickness", &meta.thickness)
                     .bind_vertex_array_object_ref(&self.instanced_line_vaos[idx])
                     .draw_elements_instanced_with_i32(
                         WebGl2RenderingContext::TRIANGLES,
@@ -399,7 +401,8 @@ impl RasterizedLineRenderer {
                     );
                 }
                 CooSpace::LonLat => {
-                    let icrs2view = CooSystem::ICRS.to(camera.get_coo_system());
+                    let icrs2view = CooSystem::ICRS
+                        .to(camera.get_coo_system(), camera.get_icrs_to_horizontal());
                     let view2world = camera.get_m2w();
                     let icrs2world = view2world * icrs2view;
 
@@ -414,7 +417,7 @@ impl RasterizedLineRenderer {
                     .attach_uniform("u_2world", &icrs2world)
                     .attach_uniform("u_color", &meta.color)
                     .attach_uniform("u_width", &meta.thickness)
-                    .attach_uniform("u_proj", proj)
+                    .attach_uniforms_from(proj)
                     .bind_vertex_array_object_ref(&self.instanced_line_vaos[idx])
                     .draw_elements_instanced_with_i32(
                         WebGl2RenderingContext::TRIANGLES,
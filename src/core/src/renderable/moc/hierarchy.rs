@@ -1,6 +1,9 @@
 use super::MOC;
 use crate::{camera::CameraViewPort, HEALPixCoverage};
+use crate::shader::ShaderManager;
+use crate::ProjectionType;
 use al_api::moc::MOCOptions;
+use wasm_bindgen::JsValue;
 
 pub struct MOCHierarchy {
     full_res_depth: u8,
@@ -71,6 +74,18 @@ impl MOCHierarchy {
         &mut self.mocs[d]
     }
 
+    /// Draw the MOC filled in white at the resolution it is displayed
+    pub fn draw_mask(
+        &mut self,
+        camera: &mut CameraViewPort,
+        proj: &ProjectionType,
+        shaders: &mut ShaderManager,
+    ) -> Result<(), JsValue> {
+        let gl = self.gl.clone();
+        self.select_moc_from_view(camera)
+            .draw_mask(gl, camera, proj, shaders)
+    }
+
     pub fn get_full_moc(&self) -> &HEALPixCoverage {
         &self.mocs.last().unwrap().moc
     }
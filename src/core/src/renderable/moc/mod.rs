@@ -31,6 +31,8 @@ pub struct MOC {
     pub max_order: u8,
 
     inner: [Option<MOCIntern>; 3],
+    // Filled in white to clip layers, created on its first use
+    mask: Option<MOCIntern>,
 
     pub moc: HEALPixCoverage,
 }
@@ -79,6 +81,7 @@ impl MOC {
 
         Self {
             inner,
+            mask: None,
             max_order,
             sky_fraction,
             moc,
@@ -148,6 +151,30 @@ impl MOC {
 
         Ok(())
     }
+
+    pub(super) fn draw_mask(
+        &mut self,
+        gl: WebGlContext,
+        camera: &mut CameraViewPort,
+        proj: &ProjectionType,
+        shaders: &mut ShaderManager,
+    ) -> Result<(), JsValue> {
+        let mask = self.mask.get_or_insert_with(|| {
+            MOCIntern::new(
+                gl,
+                RenderModeType::Filled {
+                    color: ColorRGBA {
+                        r: 1.0,
+                        g: 1.0,
+                        b: 1.0,
+                        a: 1.0,
+                    },
+                },
+            )
+        });
+
+        mask.draw(&self.moc, camera, proj, shaders)
+    }
 }
 
 struct MOCIntern {
@@ -326,7 +353,8 @@ impl MOCIntern {
 
                 let num_instances = buf.len() / 4;
 
-                let icrs2view = CooSystem::ICRS.to(camera.get_coo_system());
+                let icrs2view =
+                    CooSystem::ICRS.to(camera.get_coo_system(), camera.get_icrs_to_horizontal());
                 let view2world = camera.get_m2w();
                 let icrs2world = view2world * icrs2view;
 
@@ -343,7 +371,7 @@ impl MOCIntern {
                 .attach_uniform("u_width", &(camera.get_width()))
                 .attach_uniform("u_height", &(camera.get_height()))
                 .attach_uniform("u_thickness", &thickness)
-                .attach_uniform("u_proj", proj)
+                .attach_uniforms_from(proj)
                 .bind_vertex_array_object_ref(&self.vao)
                 .draw_elements_instanced_with_i32(
                     WebGl2RenderingContext::TRIANGLES,
@@ -364,7 +392,8 @@ impl MOCIntern {
 
                 let num_instances = buf.len() / 4;
 
-                let icrs2view = CooSystem::ICRS.to(camera.get_coo_system());
+                let icrs2view =
+                    CooSystem::ICRS.to(camera.get_coo_system(), camera.get_icrs_to_horizontal());
                 let view2world = camera.get_m2w();
                 let icrs2world = view2world * icrs2view;
 
@@ -381,7 +410,7 @@ impl MOCIntern {
                 .attach_uniform("u_width", &(camera.get_width()))
                 .attach_uniform("u_height", &(camera.get_height()))
                 .attach_uniform("u_thickness", &thickness)
-                .attach_uniform("u_proj", proj)
+                .attach_uniforms_from(proj)
                 .bind_vertex_array_object_ref(&self.vao)
                 .draw_elements_instanced_with_i32(
                     WebGl2RenderingContext::TRIANGLES,
@@ -433,7 +462,8 @@ impl MOCIntern {
                     )
                     .update_element_array(WebGl2RenderingContext::DYNAMIC_DRAW, VecData(&indices));
 
-                let icrs2view = CooSystem::ICRS.to(camera.get_coo_system());
+                let icrs2view =
+                    CooSystem::ICRS.to(camera.get_coo_system(), camera.get_icrs_to_horizontal());
                 let view2world = camera.get_m2w();
                 let icrs2world = view2world * icrs2view;
                 
@@ -444,7 +474,7 @@ impl MOCIntern {
                     .attach_uniforms_from(camera)
                     .attach_uniform("u_2world", &icrs2world)
                     .attach_uniform("u_color", &color)
-                    .attach_uniform("u_proj", proj)
+                    .attach_uniforms_from(proj)
                     .bind_vertex_array_object_ref(&self.vao)
                     .draw_elements_with_i32(
                         WebGl2RenderingContext::TRIANGLES,
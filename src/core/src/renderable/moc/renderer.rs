@@ -88,6 +88,32 @@ impl MOCRenderer {
         }
     }
 
+    /// Replace the coverage of a MOC, keeping its options
+    pub fn set_hpx_coverage(&mut self, moc_uuid: &str, moc: HEALPixCoverage) -> bool {
+        if let Some(idx) = self.cfgs.iter().position(|cfg| cfg.get_uuid() == moc_uuid) {
+            self.mocs[idx] = MOCHierarchy::from_full_res_moc(self.gl.clone(), moc, &self.cfgs[idx]);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Draw a MOC filled in white whether it is shown or not, e.g. to clip a layer
+    pub fn draw_mask(
+        &mut self,
+        moc_uuid: &str,
+        camera: &mut CameraViewPort,
+        proj: &ProjectionType,
+        shaders: &mut ShaderManager,
+    ) -> Result<(), JsValue> {
+        if let Some(idx) = self.cfgs.iter().position(|cfg| cfg.get_uuid() == moc_uuid) {
+            self.mocs[idx].draw_mask(camera, proj, shaders)?;
+        }
+
+        Ok(())
+    }
+
     pub fn remove(
         &mut self,
         moc_uuid: &str,
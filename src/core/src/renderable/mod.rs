@@ -1,10 +1,16 @@
+pub mod arithmetic;
 pub mod catalog;
+pub mod comparison;
+pub mod composite;
+pub mod contour;
 pub mod final_pass;
 pub mod grid;
+pub mod horizon;
 pub mod hips;
 pub mod image;
 pub mod line;
 pub mod moc;
+pub mod offscreen;
 pub mod shape;
 pub mod text;
 pub mod utils;
@@ -17,9 +23,14 @@ use al_core::image::format::ChannelType;
 pub use catalog::Manager;
 
 use al_api::color::ColorRGB;
+use al_api::comparison::ComparisonCfg;
+use al_api::composite::{LayerArithmeticCfg, RGBCompositeCfg};
+use al_api::coo_system::CooSystem;
 use al_api::hips::HiPSCfg;
 use al_api::hips::ImageMetadata;
+use al_api::hips::{TransferFunction, EQUALIZATION_TABLE_SIZE};
 use al_api::image::ImageParams;
+use al_api::moc::MOCClipCfg;
 
 use al_core::colormap::Colormaps;
 
@@ -31,6 +42,7 @@ use crate::camera::CameraViewPort;
 use crate::renderable::hips::config::HiPSConfig;
 use crate::shader::ShaderId;
 use crate::shader::ShaderManager;
+use crate::time::Time;
 use crate::Abort;
 use crate::ProjectionType;
 
@@ -38,9 +50,12 @@ use crate::ProjectionType;
 // to not be too much skewed
 
 use hips::raytracing::RayTracer;
+use hips::sampling::TilePixelSamples;
 
 use std::collections::HashMap;
 
+use cgmath::Vector2;
+
 use hips::d2::HiPS2D;
 use hips::d3::HiPS3D;
 
@@ -55,6 +70,11 @@ pub trait Renderer {
 pub(crate) type Id = String; // ID of an image, can be an url or a uuidv4
 pub(crate) type CreatorDid = String;
 
+use arithmetic::LayerArithmetic;
+use comparison::{ComparedLayer, Comparison};
+use offscreen::OffscreenLayer;
+use moc::MOCRenderer;
+use composite::{Composite, RGBComposite};
 use hips::HiPS;
 type LayerId = String;
 pub struct Layers {
@@ -63,16 +83,22 @@ pub struct Layers {
 
     images: HashMap<Id, Vec<Image>>, // an url can contain multiple images i.e. a fits file can contain
     // multiple image extensions
+    // The RGB composite and derived layers. They do not refer to any resource in `ids`
+    composites: HashMap<LayerId, Composite>,
     // The meta data associated with a layer
     meta: HashMap<LayerId, ImageMetadata>,
     // Hashmap between FITS image urls/HiPS creatorDid and layers
     ids: HashMap<LayerId, String>,
     // Layers given in a specific order to draw
     layers: Vec<LayerId>,
+    // Two layers blinked, cross-faded or swiped
+    comparison: Option<Comparison>,
 
     raytracer: RayTracer,
     // A vao that takes all the screen
     screen_vao: VertexArrayObject,
+    // Renders the layers clipped to a MOC or blended with a shader blend mode
+    offscreen: OffscreenLayer,
 
     background_color: ColorRGB,
 
@@ -100,6 +126,52 @@ fn get_backgroundcolor_shader<'a>(
         .map_err(|e| e.into())
 }
 
+// Restrict the drawing to a box given as [x, y, width, height]
+fn set_scissor(gl: &WebGlContext, scissor: Option<[i32; 4]>) {
+    if let Some([x, y, w, h]) = scissor {
+        gl.scissor(x, y, w, h);
+    }
+}
+
+// The MOC clipping a layer, a layer not being clipped by a MOC that is not loaded
+fn loaded_clip(meta: &ImageMetadata, is_loaded: impl Fn(&str) -> bool) -> Option<&MOCClipCfg> {
+    meta.clip.as_ref().filter(|clip| is_loaded(&clip.uuid))
+}
+
+// Draw the HiPS or the images a layer refers to
+#[allow(clippy::too_many_arguments)]
+fn draw_resource(
+    hipses: &mut HashMap<CreatorDid, HiPS>,
+    images: &mut HashMap<Id, Vec<Image>>,
+    id: &str,
+    draw_opt: &ImageMetadata,
+    camera: &mut CameraViewPort,
+    shaders: &mut ShaderManager,
+    colormaps: &Colormaps,
+    raytracer: &RayTracer,
+    projection: &ProjectionType,
+) -> Result<(), JsValue> {
+    // 1. Update the hips if necessary
+    if let Some(hips) = hipses.get_mut(id) {
+        match hips {
+            HiPS::D2(hips) => {
+                hips.update(camera, projection);
+                hips.draw(shaders, colormaps, camera, raytracer, draw_opt, projection)?;
+            }
+            HiPS::D3(hips) => {
+                hips.draw(shaders, colormaps, camera, draw_opt, projection)?;
+            }
+        }
+    } else if let Some(images) = images.get_mut(id) {
+        // 2. Draw it if its opacity is not null
+        for image in images {
+            image.draw(shaders, colormaps, draw_opt, camera, projection)?;
+        }
+    }
+
+    Ok(())
+}
+
 pub struct ImageLayer {
     /// Layer name
     pub layer: String,
@@ -127,9 +199,11 @@ impl Layers {
         let hipses = HashMap::new();
 
         let images = HashMap::new();
+        let composites = HashMap::new();
         let meta = HashMap::new();
         let ids = HashMap::new();
         let layers = Vec::new();
+        let comparison = None;
 
         // - The raytracer is a mesh covering the view. Each pixel of this mesh
         //   is unprojected to get its (ra, dec). Then we query ang2pix to get
@@ -157,19 +231,25 @@ impl Layers {
             // Unbind the buffer
             .unbind();
 
+        // Its buffers are resized to the screen when drawing
+        let offscreen = OffscreenLayer::new(&gl, &Vector2::new(1.0, 1.0))?;
+
         let background_color = DEFAULT_BACKGROUND_COLOR;
         Ok(Layers {
             hipses,
             images,
+            composites,
 
             meta,
             ids,
             layers,
+            comparison,
 
             raytracer,
 
             background_color,
             screen_vao,
+            offscreen,
 
             gl,
         })
@@ -212,6 +292,7 @@ impl Layers {
         shaders: &mut ShaderManager,
         colormaps: &Colormaps,
         projection: &ProjectionType,
+        mocs: &mut MOCRenderer,
     ) -> Result<(), JsValue> {
         let raytracer = &self.raytracer;
         let raytracing = camera.is_raytracing(projection);
@@ -223,13 +304,17 @@ impl Layers {
 
         for (idx, layer) in self.layers.iter().enumerate() {
             let meta = self.meta.get(layer).unwrap_abort();
-            let cdid = self.ids.get(layer).unwrap_abort();
+            // Channels are only drawn through their composite
+            let is_channel = self.composites.values().any(|c| c.has_channel(layer));
 
-            if let Some(hips) = self.hipses.get(cdid) {
+            if let Some(hips) = self.ids.get(layer).and_then(|cdid| self.hipses.get(cdid)) {
                 // Check if a HiPS is fully opaque so that we cannot see the background
                 // In that case, no need to draw a background because a HiPS will fully cover it
                 let full_covering_hips = (hips.get_config().get_format().get_channel() == ChannelType::RGB8U || hips.is_allsky()) && meta.opacity == 1.0;
-                if full_covering_hips {
+                // The layers below a clipped, blended or compared HiPS can still be seen
+                let offscreen = meta.clip.is_some() || meta.blend_cfg.mode.is_some();
+                let compared = self.comparison.as_ref().is_some_and(|c| c.has_layer(layer));
+                if full_covering_hips && !is_channel && !offscreen && !compared {
                     idx_start_layer = idx as i32;
                 }
             }
@@ -260,34 +345,146 @@ impl Layers {
             idx_start_layer = 0;
         }
 
+        let now = Time::now();
         let layers_to_render = &self.layers[(idx_start_layer as usize)..];
         for layer in layers_to_render {
             let draw_opt = self.meta.get(layer).expect("Meta should be found");
-            if draw_opt.visible() {
-                // 1. Update the hips if necessary
-                let id = self.ids.get(layer).expect("Url should be found");
-                if let Some(hips) = self.hipses.get_mut(id) {
-                    match hips {
-                        HiPS::D2(hips) => {
-                            hips.update(camera, projection);
-                            hips.draw(shaders, colormaps, camera, raytracer, draw_opt, projection)?;
-                        }
-                        HiPS::D3(hips) => {
-                            hips.draw(shaders, colormaps, camera, draw_opt, projection)?;
-                        }
-                    }
-                } else if let Some(images) = self.images.get_mut(id) {
-                    // 2. Draw it if its opacity is not null
-                    for image in images {
-                        image.draw(shaders, colormaps, draw_opt, camera, projection)?;
+            if !draw_opt.visible() || self.composites.values().any(|c| c.has_channel(layer)) {
+                continue;
+            }
+
+            // The compared layers are shown depending on the comparison mode
+            let compared = self.comparison.as_ref().and_then(|c| {
+                c.get_compared_layer(layer, &self.layers, &camera.get_screen_size(), now)
+            });
+            let faded_meta;
+            let draw_opt = match compared {
+                Some(ComparedLayer::Hidden) => continue,
+                Some(ComparedLayer::Faded(factor)) => {
+                    faded_meta = ImageMetadata {
+                        opacity: draw_opt.opacity * factor,
+                        ..draw_opt.clone()
+                    };
+                    &faded_meta
+                }
+                _ => draw_opt,
+            };
+            let scissor = if let Some(ComparedLayer::Scissored(scissor)) = compared {
+                Some(scissor)
+            } else {
+                None
+            };
+
+            if let Some(composite) = self.composites.get_mut(layer) {
+                // A channel whose layer has been removed is left blank
+                let (ids, meta) = (&self.ids, &self.meta);
+                let metas = composite
+                    .get_channels()
+                    .iter()
+                    .map(|channel| meta.get(channel))
+                    .collect::<Vec<_>>();
+                let channels = composite
+                    .get_channels()
+                    .iter()
+                    .zip(&metas)
+                    .map(|(channel, meta)| {
+                        ids.get(channel)
+                            .zip(*meta)
+                            .map(|(id, meta)| (id.clone(), composite.get_channel_meta(meta)))
+                    })
+                    .collect::<Vec<_>>();
+
+                let (hipses, images) = (&mut self.hipses, &mut self.images);
+                let screen_size = camera.get_screen_size();
+                composite.draw_channels(&screen_size, &metas, |idx| {
+                    if let Some((id, meta)) = &channels[idx] {
+                        draw_resource(
+                            hipses, images, id, meta, camera, shaders, colormaps, raytracer,
+                            projection,
+                        )
+                    } else {
+                        Ok(())
                     }
+                })?;
+                camera.reset_viewport();
+
+                set_scissor(&self.gl, scissor);
+                composite.draw(shaders, draw_opt, colormaps, &self.screen_vao)?;
+            } else {
+                let id = self.ids.get(layer).expect("Url should be found");
+                // A layer is not clipped by a MOC that is not loaded
+                let clip = loaded_clip(draw_opt, |uuid| mocs.get_hpx_coverage(uuid).is_some());
+                let blended = draw_opt.blend_cfg.mode.is_some();
+
+                if clip.is_some() || blended {
+                    let meta = OffscreenLayer::get_layer_meta(draw_opt);
+
+                    let (hipses, images) = (&mut self.hipses, &mut self.images);
+                    let screen_size = camera.get_screen_size();
+                    self.offscreen
+                        .draw_offscreen(&screen_size, clip.is_some(), blended, |idx| {
+                            if idx == 0 {
+                                draw_resource(
+                                    hipses, images, id, &meta, camera, shaders, colormaps,
+                                    raytracer, projection,
+                                )
+                            } else if let Some(clip) = clip {
+                                mocs.draw_mask(&clip.uuid, camera, projection, shaders)
+                            } else {
+                                Ok(())
+                            }
+                        })?;
+                    camera.reset_viewport();
+
+                    set_scissor(&self.gl, scissor);
+                    self.offscreen
+                        .draw(shaders, draw_opt, clip, &self.screen_vao)?;
+                } else {
+                    set_scissor(&self.gl, scissor);
+                    draw_resource(
+                        &mut self.hipses,
+                        &mut self.images,
+                        id,
+                        draw_opt,
+                        camera,
+                        shaders,
+                        colormaps,
+                        raytracer,
+                        projection,
+                    )?;
                 }
             }
+
+            if scissor.is_some() {
+                camera.reset_viewport();
+            }
         }
 
         Ok(())
     }
 
+    /// Blink, cross-fade or swipe between two layers. None stops the comparison
+    pub fn set_comparison(&mut self, cfg: Option<ComparisonCfg>) -> Result<(), JsValue> {
+        if let Some(cfg) = &cfg {
+            for layer in &cfg.layers {
+                if !self.layers.iter().any(|l| l == layer) {
+                    return Err(JsValue::from_str(&format!(
+                        "Layer {:?} not found, so cannot be compared.",
+                        layer
+                    )));
+                }
+            }
+
+            if cfg.layers[0] == cfg.layers[1] {
+                return Err(JsValue::from_str("A layer cannot be compared with itself."));
+            }
+        }
+
+        self.comparison = cfg.map(|cfg| Comparison::new(cfg, Time::now()));
+
+        Ok(())
+    }
+
     pub fn remove_layer(
         &mut self,
         layer: &str,
@@ -301,6 +498,35 @@ impl Layers {
         ));
         // Color configs, and urls are indexed by layer
         self.meta.remove(layer).ok_or(err_layer_not_found.clone())?;
+
+        // The comparison stops with one of its layers
+        if self.comparison.as_ref().is_some_and(|c| c.has_layer(layer)) {
+            self.comparison = None;
+        }
+
+        // A composite refers to no resource, its channel layers are removed along with it
+        // unless another composite still uses them
+        if let Some(composite) = self.composites.remove(layer) {
+            let composites = &self.composites;
+            let channels = composite::unused_channels(composite.get_channels(), |channel| {
+                composites.values().any(|c| c.has_channel(channel))
+            });
+            for channel in channels {
+                if self.layers.iter().any(|l| l == channel) {
+                    self.remove_layer(channel, camera, proj, tile_fetcher)?;
+                }
+            }
+
+            let id_layer = self
+                .layers
+                .iter()
+                .position(|l| layer == l)
+                .ok_or(err_layer_not_found)?;
+            self.layers.remove(id_layer);
+
+            return Ok(id_layer);
+        }
+
         let id = self.ids.remove(layer).ok_or(err_layer_not_found.clone())?;
         // layer from layers does also need to be removed
         let id_layer = self
@@ -331,10 +557,13 @@ impl Layers {
 
                 // remove the local files access from the tile fetcher
                 tile_fetcher.delete_hips_local_files(hips.get_config().get_creator_did());
+                tile_fetcher.delete_in_memory_hips(hips.get_config().get_creator_did());
 
                 Ok(id_layer)
             } else if let Some(_) = self.images.remove(&id) {
                 // A FITS image has been found and removed
+                camera.unregister_view_frame(CooSystem::ICRS, proj);
+
                 Ok(id_layer)
             } else {
                 Err(JsValue::from_str(&format!(
@@ -361,11 +590,18 @@ impl Layers {
         self.layers[id_layer] = new_layer.to_string();
 
         let meta = self.meta.remove(layer).ok_or(err_layer_not_found.clone())?;
-        let id = self.ids.remove(layer).ok_or(err_layer_not_found)?;
+        if let Some(comparison) = self.comparison.as_mut() {
+            comparison.rename_layer(layer, new_layer);
+        }
+        if let Some(composite) = self.composites.remove(layer) {
+            self.composites.insert(new_layer.to_string(), composite);
+        } else {
+            let id = self.ids.remove(layer).ok_or(err_layer_not_found)?;
+            self.ids.insert(new_layer.to_string(), id);
+        }
 
         // Add the new
         self.meta.insert(new_layer.to_string(), meta);
-        self.ids.insert(new_layer.to_string(), id);
 
         Ok(())
     }
@@ -527,6 +763,8 @@ impl Layers {
             }*/
 
             self.images.insert(id.clone(), images);
+            // The view coverage in ICRS locates the pixels of the image in view
+            camera.register_view_frame(CooSystem::ICRS, proj);
         }
 
         self.ids.insert(layer.clone(), id.clone());
@@ -538,6 +776,107 @@ impl Layers {
         Ok(img.as_slice())
     }
 
+    pub fn add_rgb_composite(
+        &mut self,
+        cfg: RGBCompositeCfg,
+        camera: &mut CameraViewPort,
+        proj: &ProjectionType,
+        tile_fetcher: &mut TileFetcherQueue,
+    ) -> Result<(), JsValue> {
+        let RGBCompositeCfg {
+            layer,
+            channels,
+            weights,
+            lupton,
+            meta,
+        } = cfg;
+
+        // Composites are not in `ids`
+        if let Some(channel) =
+            composite::find_missing_channel(&layer, &channels, |l| self.ids.contains_key(l))
+        {
+            return Err(JsValue::from_str(&format!(
+                "Channel layer {:?} not found",
+                channel
+            )));
+        }
+
+        if let Some(Composite::RGB(composite)) = self.composites.get_mut(&layer) {
+            // Update the composite keeping its place in the stack
+            composite.set_cfg(channels, weights, lupton);
+        } else {
+            let layer_already_found = self.layers.iter().any(|l| l == &layer);
+
+            let idx = if layer_already_found {
+                self.remove_layer(&layer, camera, proj, tile_fetcher)?
+            } else {
+                self.layers.len()
+            };
+            self.layers.insert(idx, layer.clone());
+
+            let composite = RGBComposite::new(
+                &self.gl,
+                channels,
+                weights,
+                lupton,
+                &camera.get_screen_size(),
+            )?;
+            self.composites
+                .insert(layer.clone(), Composite::RGB(composite));
+        }
+
+        self.meta.insert(layer, meta);
+
+        Ok(())
+    }
+
+    pub fn add_layer_arithmetic(
+        &mut self,
+        cfg: LayerArithmeticCfg,
+        camera: &mut CameraViewPort,
+        proj: &ProjectionType,
+        tile_fetcher: &mut TileFetcherQueue,
+    ) -> Result<(), JsValue> {
+        let LayerArithmeticCfg {
+            layer,
+            operands,
+            operation,
+            meta,
+        } = cfg;
+
+        if let Some(operand) =
+            composite::find_missing_channel(&layer, &operands, |l| self.ids.contains_key(l))
+        {
+            return Err(JsValue::from_str(&format!(
+                "Operand layer {:?} not found",
+                operand
+            )));
+        }
+
+        if let Some(Composite::Arithmetic(arithmetic)) = self.composites.get_mut(&layer) {
+            // Update the derived layer keeping its place in the stack
+            arithmetic.set_cfg(operands, operation);
+        } else {
+            let layer_already_found = self.layers.iter().any(|l| l == &layer);
+
+            let idx = if layer_already_found {
+                self.remove_layer(&layer, camera, proj, tile_fetcher)?
+            } else {
+                self.layers.len()
+            };
+            self.layers.insert(idx, layer.clone());
+
+            let arithmetic =
+                LayerArithmetic::new(&self.gl, operands, operation, &camera.get_screen_size())?;
+            self.composites
+                .insert(layer.clone(), Composite::Arithmetic(arithmetic));
+        }
+
+        self.meta.insert(layer, meta);
+
+        Ok(())
+    }
+
     pub fn get_layer_cfg(&self, layer: &str) -> Result<ImageMetadata, JsValue> {
         self.meta
             .get(layer)
@@ -548,8 +887,13 @@ impl Layers {
     pub fn set_layer_cfg(
         &mut self,
         layer: String,
-        meta: ImageMetadata,
+        mut meta: ImageMetadata,
     ) -> Result<(), JsValue> {
+        // The histogram equalization is computed by the core, keep it until it is updated
+        if let Some(prev_meta) = self.meta.get_mut(&layer) {
+            meta.color.equalization = std::mem::take(&mut prev_meta.color.equalization);
+        }
+
         // Expect the image hips to be found in the hash map
         self.meta.insert(layer.clone(), meta).ok_or_else(|| {
             JsValue::from(js_sys::Error::new(&format!("{:?} layer not found", layer)))
@@ -558,6 +902,67 @@ impl Layers {
         Ok(())
     }
 
+    /// Update the cuts and the histogram equalization of the layers
+    /// computing them from the pixels in the view
+    ///
+    /// Returns whether the color config of a layer has changed
+    pub fn update_auto_cuts(
+        &mut self,
+        camera: &CameraViewPort,
+        tile_samples: &TilePixelSamples,
+    ) -> bool {
+        let mut color_changed = false;
+
+        for (layer, meta) in self.meta.iter_mut() {
+            let equalized = meta.color.stretch == TransferFunction::Histogram;
+            if meta.color.auto_cuts.is_none() && !equalized {
+                continue;
+            }
+
+            let id = if let Some(id) = self.ids.get(layer) {
+                id
+            } else {
+                continue;
+            };
+            let mut values = if let Some(hips) = self.hipses.get(id) {
+                tile_samples.get_in_view(hips, camera)
+            } else if let Some(images) = self.images.get(id) {
+                images
+                    .iter()
+                    .flat_map(|image| image.get_pixel_samples_in_view(camera))
+                    .collect()
+            } else {
+                continue;
+            };
+
+            if let Some(algorithm) = meta.color.auto_cuts.as_ref() {
+                if let Some(cuts) = image::cuts::compute(&mut values, algorithm) {
+                    if meta.color.min_cut != Some(cuts.start) || meta.color.max_cut != Some(cuts.end) {
+                        meta.color.min_cut = Some(cuts.start);
+                        meta.color.max_cut = Some(cuts.end);
+
+                        color_changed = true;
+                    }
+                }
+            }
+
+            if equalized {
+                let cuts = meta.color.min_cut.unwrap_or(0.0)..meta.color.max_cut.unwrap_or(1.0);
+                if let Some(table) =
+                    image::cuts::equalization(&mut values, &cuts, EQUALIZATION_TABLE_SIZE)
+                {
+                    if meta.color.equalization != table {
+                        meta.color.equalization = table;
+
+                        color_changed = true;
+                    }
+                }
+            }
+        }
+
+        color_changed
+    }
+
     // Accessors
     // HiPSes getters
     pub fn get_hips_from_layer(&self, layer: &str) -> Option<&HiPS> {
@@ -602,3 +1007,38 @@ impl Layers {
         images.map(|images| images.as_slice())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use al_api::hips::fixtures;
+
+    fn meta(clip: serde_json::Value) -> ImageMetadata {
+        let mut meta = fixtures::meta_json();
+        meta["clip"] = clip;
+
+        serde_json::from_value(meta).unwrap()
+    }
+
+    #[test]
+    fn layers_clipped_by_loaded_mocs() {
+        let is_loaded = |uuid: &str| uuid == "moc";
+
+        let clipped = meta(serde_json::json!({ "uuid": "moc", "outside": true }));
+        assert_eq!(
+            loaded_clip(&clipped, is_loaded),
+            Some(&MOCClipCfg {
+                uuid: "moc".to_string(),
+                outside: true,
+                clipped_opacity: 0.0,
+            })
+        );
+
+        // The MOC has not been loaded or has been removed
+        let not_loaded = meta(serde_json::json!({ "uuid": "other" }));
+        assert_eq!(loaded_clip(&not_loaded, is_loaded), None);
+
+        let not_clipped = meta(serde_json::Value::Null);
+        assert_eq!(loaded_clip(&not_clipped, is_loaded), None);
+    }
+}
@@ -0,0 +1,171 @@
+use al_api::blend::BlendCfg;
+use al_api::hips::ImageMetadata;
+use al_api::moc::MOCClipCfg;
+
+use al_core::webgl_ctx::GlWrapper;
+use al_core::{FrameBufferObject, VertexArrayObject, WebGlContext};
+
+use crate::shader::{ShaderId, ShaderManager};
+
+use cgmath::Vector2;
+use wasm_bindgen::JsValue;
+use web_sys::WebGl2RenderingContext;
+
+// The clip mode of the shader and the opacity factor of the clipped part
+fn clip_uniforms(clip: Option<&MOCClipCfg>) -> (i32, f32) {
+    match clip {
+        None => (0, 0.0),
+        Some(MOCClipCfg {
+            outside: false,
+            clipped_opacity,
+            ..
+        }) => (1, *clipped_opacity),
+        Some(MOCClipCfg {
+            outside: true,
+            clipped_opacity,
+            ..
+        }) => (2, *clipped_opacity),
+    }
+}
+
+/// Renders the layers clipped to a MOC or blended with a shader blend mode
+///
+/// The layer and the MOC filled in white are first rendered into offscreen
+/// buffers that are then combined on the screen with the layers below. The
+/// buffers are shared by all these layers as they are drawn one after the other.
+pub struct OffscreenLayer {
+    // The buffers in which the layer and the mask are rendered
+    fbos: [FrameBufferObject; 2],
+    // A copy of the layers already drawn on the screen
+    dst: FrameBufferObject,
+
+    gl: WebGlContext,
+}
+
+impl OffscreenLayer {
+    pub fn new(gl: &WebGlContext, screen_size: &Vector2<f32>) -> Result<Self, JsValue> {
+        let (w, h) = (screen_size.x as usize, screen_size.y as usize);
+        let fbos = [
+            FrameBufferObject::new(gl, w, h)?,
+            FrameBufferObject::new(gl, w, h)?,
+        ];
+        let dst = FrameBufferObject::new(gl, w, h)?;
+
+        Ok(Self {
+            fbos,
+            dst,
+            gl: gl.clone(),
+        })
+    }
+
+    /// The config with which a layer is rendered offscreen
+    pub fn get_layer_meta(meta: &ImageMetadata) -> ImageMetadata {
+        let mut meta = meta.clone();
+
+        meta.opacity = 1.0;
+        meta.blend_cfg = BlendCfg::default();
+        meta.clip = None;
+
+        meta
+    }
+
+    /// Render the layer and the mask into their buffers, `draw` being called
+    /// with 0 to draw the layer and 1 to draw the mask. A layer blended with
+    /// a shader blend mode copies what has already been drawn on the screen first.
+    ///
+    /// The viewport and the scissor must be restored afterwards
+    pub fn draw_offscreen(
+        &mut self,
+        screen_size: &Vector2<f32>,
+        clipped: bool,
+        blended: bool,
+        mut draw: impl FnMut(usize) -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        let (w, h) = (screen_size.x as usize, screen_size.y as usize);
+
+        if blended {
+            self.dst.resize(w, h);
+            self.dst
+                .texture
+                .bind()
+                .copy_tex_sub_image_2d(0, 0, 0, 0, w as i32, h as i32);
+        }
+
+        // The sky not covered by the layer or the MOC must stay transparent
+        self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+
+        let num_passes = if clipped { 2 } else { 1 };
+        for (idx, fbo) in self.fbos.iter_mut().take(num_passes).enumerate() {
+            fbo.resize(w, h);
+            fbo.draw_onto(|| draw(idx), None)?;
+        }
+
+        self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+
+        Ok(())
+    }
+
+    /// Draw the layer on the screen
+    pub fn draw(
+        &self,
+        shaders: &mut ShaderManager,
+        meta: &ImageMetadata,
+        clip: Option<&MOCClipCfg>,
+        screen_vao: &VertexArrayObject,
+    ) -> Result<(), JsValue> {
+        let shader = shaders.get(
+            &self.gl,
+            ShaderId("hips_composite_rgb.vert", "hips_composite_layer.frag"),
+        )?;
+
+        let (clip_mode, clipped_opacity) = clip_uniforms(clip);
+        let blend_mode = meta.blend_cfg.mode.map(|m| m.id()).unwrap_or(-1);
+
+        meta.blend_cfg.enable(&self.gl, || {
+            shader
+                .bind(&self.gl)
+                .attach_uniform("tex_layer", &self.fbos[0].texture)
+                .attach_uniform("tex_mask", &self.fbos[1].texture)
+                .attach_uniform("tex_dst", &self.dst.texture)
+                .attach_uniform("clip", &clip_mode)
+                .attach_uniform("clipped_opacity", &clipped_opacity)
+                .attach_uniform("blend_mode", &blend_mode)
+                .attach_uniform("opacity", &meta.opacity)
+                .bind_vertex_array_object_ref(screen_vao)
+                .draw_elements_with_i32(
+                    WebGl2RenderingContext::TRIANGLES,
+                    None,
+                    WebGl2RenderingContext::UNSIGNED_SHORT,
+                    0,
+                );
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use al_api::hips::fixtures;
+
+    #[test]
+    fn layers_rendered_offscreen_without_clip() {
+        let mut meta = fixtures::meta();
+        meta.opacity = 0.3;
+        meta.clip = Some(MOCClipCfg {
+            uuid: "moc".to_string(),
+            outside: false,
+            clipped_opacity: 0.5,
+        });
+
+        // The clip and the opacity are applied when drawing the buffers on the screen
+        let layer = OffscreenLayer::get_layer_meta(&meta);
+        assert_eq!(layer.clip, None);
+        assert_eq!(layer.opacity, 1.0);
+        assert_eq!(meta.clip.as_ref().map(|c| c.clipped_opacity), Some(0.5));
+
+        meta.clip = None;
+        assert_eq!(OffscreenLayer::get_layer_meta(&meta).clip, None);
+    }
+}
@@ -185,7 +185,8 @@ impl PolylineRenderer {
         );*/
 
         // draw the instanced lines
-        let icrs2view = CooSystem::ICRS.to(camera.get_coo_system());
+        let icrs2view =
+            CooSystem::ICRS.to(camera.get_coo_system(), camera.get_icrs_to_horizontal());
         let view2world = camera.get_m2w();
         let icrs2world = view2world * icrs2view;
 
@@ -195,7 +196,7 @@ impl PolylineRenderer {
             .attach_uniform("u_2world", &icrs2world)
             .attach_uniform("u_color", &self.color)
             .attach_uniform("u_width", &self.thickness)
-            .attach_uniform("u_proj", proj)
+            .attach_uniforms_from(proj)
             .bind_vertex_array_object_ref(&self.vao)
             .draw_elements_instanced_with_i32(
                 WebGl2RenderingContext::TRIANGLES,
@@ -5,21 +5,82 @@ use crate::Abort;
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::rc::Rc;
+use al_api::coo_system::CooSystem;
 use al_api::moc::MOCOptions;
+use std::ops::Range;
 
 const MAX_NUM_TILE_FETCHING: usize = 8;
 const MAX_QUERY_QUEUE_LENGTH: usize = 100;
 
+use crate::healpix::hipsgen::GeneratedHiPS;
+use crate::healpix::map::HEALPixMap;
 use crate::renderable::hips::HiPS;
 
 pub struct TileFetcherQueue {
     // A stack of queries to fetch
     queries: VecDeque<query::Tile>,
     base_tile_queries: Vec<query::Tile>,
+    // Base tile queries that could not be sent and whose tiles will never be received
+    dropped_base_tile_queries: Vec<query::Tile>,
     tiles_fetched_time: Time,
     num_tiles_fetched: usize,
 
     hips_local_files: HashMap<CreatorDid, HiPSLocalFiles>,
+    // HiPS whose tiles are generated in memory
+    in_memory_hipses: HashMap<CreatorDid, InMemoryHiPS>,
+}
+
+/// A HiPS whose tiles are generated in memory instead of being fetched
+pub enum InMemoryHiPS {
+    /// A HEALPix map stored in a FITS binary table
+    Map(HEALPixMap),
+    /// A HiPS generated from a FITS image
+    Generated(GeneratedHiPS),
+}
+
+impl InMemoryHiPS {
+    pub fn get_frame(&self) -> CooSystem {
+        match self {
+            InMemoryHiPS::Map(map) => map.get_frame(),
+            InMemoryHiPS::Generated(_) => CooSystem::ICRS,
+        }
+    }
+
+    pub fn get_max_order(&self) -> u8 {
+        match self {
+            InMemoryHiPS::Map(map) => map.get_max_order(),
+            InMemoryHiPS::Generated(hips) => hips.get_max_order(),
+        }
+    }
+
+    pub fn get_tile_size(&self) -> i32 {
+        match self {
+            InMemoryHiPS::Map(map) => map.get_tile_size(),
+            InMemoryHiPS::Generated(hips) => hips.get_tile_size(),
+        }
+    }
+
+    pub fn get_sky_fraction(&self) -> f32 {
+        match self {
+            InMemoryHiPS::Map(map) => map.get_sky_fraction(),
+            InMemoryHiPS::Generated(hips) => hips.get_sky_fraction(),
+        }
+    }
+
+    pub fn get_cuts(&self) -> Range<f32> {
+        match self {
+            InMemoryHiPS::Map(map) => map.get_cuts(),
+            InMemoryHiPS::Generated(hips) => hips.get_cuts(),
+        }
+    }
+
+    /// The pixels of a tile, stored upside down as in the FITS tiles of a HiPS
+    pub fn get_tile(&self, cell: &HEALPixCell) -> Vec<f32> {
+        match self {
+            InMemoryHiPS::Map(map) => map.get_tile(cell),
+            InMemoryHiPS::Generated(hips) => hips.get_tile(cell),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -87,9 +148,11 @@ impl TileFetcherQueue {
         Self {
             queries,
             base_tile_queries,
+            dropped_base_tile_queries: Vec::new(),
             tiles_fetched_time,
             num_tiles_fetched,
             hips_local_files: HashMap::new(),
+            in_memory_hipses: HashMap::new(),
         }
     }
 
@@ -101,6 +164,18 @@ impl TileFetcherQueue {
         self.hips_local_files.remove(id);
     }
 
+    pub fn insert_in_memory_hips(&mut self, id: CreatorDid, hips: InMemoryHiPS) {
+        self.in_memory_hipses.insert(id, hips);
+    }
+
+    pub fn delete_in_memory_hips(&mut self, id: &str) {
+        self.in_memory_hipses.remove(id);
+    }
+
+    pub fn get_in_memory_hips(&self, id: &str) -> Option<&InMemoryHiPS> {
+        self.in_memory_hipses.get(id)
+    }
+
     pub fn clear(&mut self) {
         self.queries.clear();
     }
@@ -121,6 +196,11 @@ impl TileFetcherQueue {
         self.base_tile_queries.push(query);
     }
 
+    /// The base tile queries dropped since the last call
+    pub fn take_dropped_base_tiles(&mut self) -> Vec<query::Tile> {
+        std::mem::take(&mut self.dropped_base_tile_queries)
+    }
+
     pub fn notify(&mut self, downloader: Rc<RefCell<Downloader>>, dt: Option<DeltaTime>) {
         // notify all the x ms
         let now = Time::now();
@@ -163,8 +243,17 @@ impl TileFetcherQueue {
     fn fetch(&mut self, downloader: Rc<RefCell<Downloader>>) {
         // Fetch the base tiles with higher priority
         while let Some(query) = self.base_tile_queries.pop() {
-            if let Ok(query) = self.check_in_file_list(query) {
-                downloader.borrow_mut().fetch(query);
+            let fetched = if let Ok(query) = self.check_in_file_list(query.clone()) {
+                let mut downloader = downloader.borrow_mut();
+                let id = query.id.clone();
+                // A tile already requested will be received
+                downloader.fetch(query) || downloader.is_queried(&id)
+            } else {
+                false
+            };
+
+            if !fetched {
+                self.dropped_base_tile_queries.push(query);
             }
         }
 
@@ -20,6 +20,8 @@ uniform float max_value;
 uniform int H;
 
 uniform float reversed;
+// Output the physical values instead of colors
+uniform int raw_output;
 
 #include ./../colormaps/colormap.glsl;
 #include ./../hips/transfer_funcs.glsl;
@@ -27,6 +29,9 @@ uniform float reversed;
 
 vec4 apply_colormap_to_grayscale(float x) {
     float alpha = x * scale + offset;
+    if (raw_output == 1) {
+        return mix(vec4(alpha, 0.0, 0.0, 1.0), vec4(0.0), float(isinf(x)));
+    }
     alpha = transfer_func(H, alpha, min_value, max_value);
 
     // apply reversed
@@ -5,6 +5,8 @@ uniform float min_value;
 uniform float max_value;
 uniform int H;
 uniform float reversed;
+// Output the physical values instead of colors
+uniform int raw_output;
 uniform float size_tile_uv;
 uniform int tex_storing_fits;
 
@@ -41,6 +43,9 @@ vec4 get_color_from_texture(vec3 UV) {
 
 vec4 apply_colormap_to_grayscale(float x) {
     float alpha = x * scale + offset;
+    if (raw_output == 1) {
+        return mix(vec4(alpha, 0.0, 0.0, 1.0), vec4(0.0), float(isinf(x) || isnan(x)));
+    }
     alpha = transfer_func(H, alpha, min_value, max_value);
 
     // apply reversed
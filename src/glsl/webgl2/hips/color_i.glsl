@@ -7,6 +7,8 @@ uniform float min_value;
 uniform float max_value;
 uniform int H;
 uniform float reversed;
+// Output the physical values instead of colors
+uniform int raw_output;
 
 uniform float size_tile_uv;
 
@@ -31,6 +33,9 @@ vec4 get_colormap_from_grayscale_texture(vec3 UV) {
 
     float x = float(get_pixels(uv).r);
     float alpha = x * scale + offset;
+    if (raw_output == 1) {
+        return mix(vec4(alpha, 0.0, 0.0, 1.0), vec4(0.0), float(x == blank));
+    }
     alpha = transfer_func(H, alpha, min_value, max_value);
 
     // apply reversed
@@ -0,0 +1,54 @@
+#version 300 es
+precision highp float;
+precision highp sampler2D;
+precision mediump int;
+
+in vec2 frag_uv;
+out vec4 out_frag_color;
+
+// The operands rendered as physical values
+uniform sampler2D tex_a;
+uniform sampler2D tex_b;
+
+// 0: A - B, 1: A / B, 2: A x mask
+uniform int operation;
+uniform float opacity;
+
+uniform float min_value;
+uniform float max_value;
+uniform int H;
+uniform float reversed;
+
+#include ../../colormaps/colormap.glsl;
+#include ../transfer_funcs.glsl;
+#include ../tonal_corrections.glsl;
+
+void main() {
+    vec4 a = texture(tex_a, frag_uv);
+    vec4 b = texture(tex_b, frag_uv);
+
+    // Transparent where an operand does not cover the sky
+    if (a.a == 0.0 || b.a == 0.0) {
+        discard;
+    }
+
+    float x;
+    if (operation == 0) {
+        x = a.r - b.r;
+    } else if (operation == 1) {
+        x = a.r / b.r;
+    } else {
+        x = a.r * b.r;
+    }
+
+    // Divisions by zero and pixels out of the mask are left transparent
+    if (isinf(x) || isnan(x) || (operation == 2 && b.r == 0.0)) {
+        discard;
+    }
+
+    float alpha = transfer_func(H, x, min_value, max_value);
+    alpha = mix(alpha, 1.0 - alpha, reversed);
+
+    out_frag_color = apply_tonal(colormap_f(alpha));
+    out_frag_color.a = out_frag_color.a * opacity;
+}
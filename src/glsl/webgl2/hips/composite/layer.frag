@@ -0,0 +1,53 @@
+#version 300 es
+precision highp float;
+precision mediump int;
+
+in vec2 frag_uv;
+out vec4 out_frag_color;
+
+// The layer, the MOC filled in white and the layers below it
+uniform sampler2D tex_layer;
+uniform sampler2D tex_mask;
+uniform sampler2D tex_dst;
+
+// 0: no clip, 1: the inside of the MOC is kept, 2: its outside
+uniform int clip;
+uniform float clipped_opacity;
+// -1: no blend mode, 0: screen, 1: multiply, 2: overlay, 3: lighten, 4: darken
+uniform int blend_mode;
+uniform float opacity;
+
+vec3 blend(vec3 src, vec3 dst) {
+    if (blend_mode == 0) {
+        return 1.0 - (1.0 - src) * (1.0 - dst);
+    } else if (blend_mode == 1) {
+        return src * dst;
+    } else if (blend_mode == 2) {
+        vec3 dark = 2.0 * src * dst;
+        vec3 bright = 1.0 - 2.0 * (1.0 - src) * (1.0 - dst);
+        return mix(dark, bright, step(0.5, dst));
+    } else if (blend_mode == 3) {
+        return max(src, dst);
+    } else if (blend_mode == 4) {
+        return min(src, dst);
+    }
+
+    return src;
+}
+
+void main() {
+    vec4 color = texture(tex_layer, frag_uv);
+
+    if (clip > 0) {
+        float inside = texture(tex_mask, frag_uv).a;
+        float kept = mix(inside, 1.0 - inside, float(clip == 2));
+        color.a *= mix(clipped_opacity, 1.0, kept);
+    }
+
+    if (blend_mode >= 0) {
+        color.rgb = blend(color.rgb, texture(tex_dst, frag_uv).rgb);
+    }
+
+    out_frag_color = color;
+    out_frag_color.a = color.a * opacity;
+}
@@ -0,0 +1,57 @@
+#version 300 es
+precision highp float;
+precision highp sampler2D;
+precision mediump int;
+
+in vec2 frag_uv;
+out vec4 out_frag_color;
+
+// The channels rendered in grayscale, as physical values for a Lupton composite
+uniform sampler2D tex_r;
+uniform sampler2D tex_g;
+uniform sampler2D tex_b;
+
+uniform vec3 weights;
+// The cuts of the channel layers
+uniform vec3 min_cuts;
+uniform vec3 max_cuts;
+// 0: the channels are simply weighted, 1: Lupton et al. (2004) asinh composite
+uniform int lupton;
+uniform float lupton_q;
+uniform float opacity;
+
+#include ../tonal_corrections.glsl;
+
+// Scale the channels by a common asinh stretch of their mean intensity
+// so that the hue is preserved
+vec3 lupton_f(vec3 rgb) {
+    float i = (rgb.r + rgb.g + rgb.b) / 3.0;
+    if (i <= 0.0) {
+        return vec3(0.0);
+    }
+
+    rgb *= asinh(lupton_q * i) / (asinh(lupton_q) * i);
+
+    // Saturated pixels keep their hue
+    float m = max(rgb.r, max(rgb.g, rgb.b));
+    return rgb / max(m, 1.0);
+}
+
+void main() {
+    vec4 r = texture(tex_r, frag_uv);
+    vec4 g = texture(tex_g, frag_uv);
+    vec4 b = texture(tex_b, frag_uv);
+
+    vec3 rgb = vec3(r.r, g.r, b.r);
+    if (lupton == 1) {
+        // Linearly scaled, the values above the max cuts are kept
+        rgb = lupton_f((rgb - min_cuts) / (max_cuts - min_cuts) * weights);
+    } else {
+        rgb *= weights;
+    }
+
+    // Transparent where no channel covers the sky
+    float alpha = max(r.a, max(g.a, b.a));
+    out_frag_color = apply_tonal(vec4(clamp(rgb, 0.0, 1.0), alpha));
+    out_frag_color.a = out_frag_color.a * opacity;
+}
@@ -1,3 +1,10 @@
+// Parameter of the stretch: the exponent of pow_f,
+// the softening parameter of asinh_f and sinh_f
+uniform float stretch_param;
+// Cumulative distribution of the pixels in view sampled
+// regularly between min_value and max_value
+uniform float equalization[32];
+
 float linear_f(float x, float min_value, float max_value) {
     return clamp((x - min_value)/(max_value - min_value), 0.0, 1.0);
 }
@@ -15,7 +22,7 @@ float log_f(float x, float min_value, float max_value) {
 
 float asinh_f(float x, float min_value, float max_value) {
     float d = linear_f(x, min_value, max_value);
-    return asinh(10.0*d)/3.0;
+    return asinh(d/stretch_param)/asinh(1.0/stretch_param);
 }
 
 float pow2_f(float x, float min_value, float max_value) {
@@ -23,6 +30,23 @@ float pow2_f(float x, float min_value, float max_value) {
     return d*d;
 }
 
+float sinh_f(float x, float min_value, float max_value) {
+    float d = linear_f(x, min_value, max_value);
+    return sinh(d/stretch_param)/sinh(1.0/stretch_param);
+}
+
+float pow_f(float x, float min_value, float max_value) {
+    float d = linear_f(x, min_value, max_value);
+    return pow(d, stretch_param);
+}
+
+float histogram_f(float x, float min_value, float max_value) {
+    float d = linear_f(x, min_value, max_value) * 31.0;
+    int i = int(floor(d));
+    int j = min(i + 1, 31);
+    return mix(equalization[i], equalization[j], d - float(i));
+}
+
 float transfer_func(int H, float x, float min_value, float max_value) {
     if (H == 0) {
         return linear_f(x, min_value, max_value);
@@ -32,7 +56,13 @@ float transfer_func(int H, float x, float min_value, float max_value) {
         return log_f(x, min_value, max_value);
     } else if (H == 3) {
         return asinh_f(x, min_value, max_value);
+    } else if (H == 5) {
+        return sinh_f(x, min_value, max_value);
+    } else if (H == 6) {
+        return pow_f(x, min_value, max_value);
+    } else if (H == 7) {
+        return histogram_f(x, min_value, max_value);
     } else {
         return pow2_f(x, min_value, max_value);
     }
-}
\ No newline at end of file
+}
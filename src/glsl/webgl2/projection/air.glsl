@@ -0,0 +1,12 @@
+vec2 w2c_air(vec3 p) {
+    // u_proj_params[0] = cte_b, depending on the angle for which the error is minimized
+    float cte_b = u_proj_params[0];
+    // The antipode of the center of projection is sent to infinity
+    float x = max(p.z, -1.0 + 1e-6);
+    float u = x - 1.0;
+    // ln((1 + x) / 2) / (x - 1), expanded close to the center of projection
+    float ln_o_u = u > -1e-3 ? 0.5 - u * (0.125 - u / 24.0) : log(0.5 * (1.0 + x)) / u;
+    float r_o_sr = ln_o_u - cte_b / (1.0 + x);
+
+    return vec2(-p.x, p.y) * r_o_sr / PI;
+}
\ No newline at end of file
@@ -0,0 +1,12 @@
+vec2 w2c_arc(vec3 p) {
+    // Whole sphere, r <= PI (equidistant)
+    float r = min(length(p.xy), 1.0);
+    if (r == 0.0) {
+        // Center of projection or its antipode
+        return p.z > 0.0 ? vec2(0.0) : vec2(1.0, 0.0);
+    }
+
+    // Angular distance to the center of projection
+    float a = p.z > 0.0 ? asin(r) : acos(p.z);
+    return vec2(-p.x, p.y) * (a / (r * PI));
+}
\ No newline at end of file
@@ -0,0 +1,17 @@
+// u_proj_params[0] = mu, the distance of the point of projection
+// u_proj_params[1] = half length of the projection plane
+// u_proj_params[2] = radius of the limb in the clipping space
+vec2 w2c_azp(vec3 p) {
+    float mu = u_proj_params[0];
+    vec2 q = vec2(-p.x, p.y);
+
+    if (mu > 1.0 && p.z < -1.0 / mu) {
+        // Beyond the limb, positions are projected onto it
+        float r = length(q);
+        return (r > 0.0 ? q / r : vec2(1.0, 0.0)) * u_proj_params[2];
+    }
+
+    // Positions sent to infinity are kept at a finite distance
+    float w = max(mu + p.z, 1e-2);
+    return q * (mu + 1.0) / (w * u_proj_params[1]);
+}
\ No newline at end of file
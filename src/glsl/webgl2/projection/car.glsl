@@ -0,0 +1,3 @@
+vec2 w2c_car(vec3 p) {
+    return vec2(atan(-p.x, p.z) / PI, atan(p.y, length(p.xz)) * 2.0 / PI);
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+vec2 w2c_cea(vec3 p) {
+    // The y axis is scaled by lambda which does not change the clip position
+    return vec2(atan(-p.x, p.z) / PI, p.y);
+}
\ No newline at end of file
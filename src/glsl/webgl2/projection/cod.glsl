@@ -0,0 +1,33 @@
+// u_proj_params[0] = c, the ratio between the cone angle and the longitude
+// u_proj_params[1] = y0
+// u_proj_params[2] = theta_a + y0
+// u_proj_params[3], u_proj_params[4] = sin(theta_a), cos(theta_a)
+// u_proj_params[5] = r_max, the x bounds being [-r_max, r_max]
+// u_proj_params[6], u_proj_params[7] = the y bounds
+// u_proj_params[8] = -1 for southern cones (rotated by 180°), 1 otherwise
+vec2 w2c_cod(vec3 p) {
+    float c = u_proj_params[0];
+    float y0 = u_proj_params[1];
+    float ta_plus_y0 = u_proj_params[2];
+    float s = u_proj_params[3];
+    float cs = u_proj_params[4];
+    float r_max = u_proj_params[5];
+    float y_min = u_proj_params[6];
+    float y_max = u_proj_params[7];
+    float hemisphere = u_proj_params[8];
+
+    // Bring the center of the view to the latitude theta_a
+    p = vec3(p.x, p.y * cs + p.z * s, -p.y * s + p.z * cs);
+    p.xy *= hemisphere;
+
+    float lon = atan(-p.x, p.z);
+    float lat = atan(p.y, length(p.xz));
+
+    float r = ta_plus_y0 - lat;
+    float x = r * sin(c * lon);
+    float y = y0 - r * cos(c * lon);
+
+    float y_len = y_max - y_min;
+    float y_mean = (y_max + y_min) * 0.5;
+    return vec2(x / r_max, ((y - y_min + y_mean) / y_len - 0.5) * 2.0) * hemisphere;
+}
\ No newline at end of file
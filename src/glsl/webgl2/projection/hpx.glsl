@@ -0,0 +1,35 @@
+const float HPX_TRANSITION_Z = 2.0 / 3.0;
+const float HPX_PI_OVER_FOUR = 0.78539816339;
+
+vec2 w2c_hpx(vec3 p) {
+    // Axis convention of the HEALPix library
+    vec3 q = vec3(p.z, -p.x, p.y);
+
+    vec2 xy;
+    if (abs(q.z) > HPX_TRANSITION_Z) {
+        // Polar caps
+        bool x_neg = q.x < 0.0;
+        bool y_neg = q.y < 0.0;
+        float offset = (y_neg ? -4.0 : 0.0) + 1.0 + (x_neg != y_neg ? 2.0 : 0.0);
+
+        float x02 = atan(abs(q.y), abs(q.x)) / HPX_PI_OVER_FOUR;
+        float x_pm1 = x_neg != y_neg ? 1.0 - x02 : x02 - 1.0;
+
+        float d2 = dot(q.xy, q.xy);
+        // 1 - |z| computed from d2 close to the poles for precision
+        float one_min_z = d2 < 1e-1
+            ? d2 * (0.5 + d2 * (0.125 + d2 * (0.0625 + d2 * (0.0390625 + d2 * 0.02734375))))
+            : 1.0 - abs(q.z);
+        float sqrt_3_one_min_z = sqrt(3.0 * one_min_z);
+
+        xy = vec2(
+            (x_pm1 * sqrt_3_one_min_z + offset) * HPX_PI_OVER_FOUR,
+            sign(q.z) * (2.0 - sqrt_3_one_min_z) * HPX_PI_OVER_FOUR
+        );
+    } else {
+        // Equatorial region
+        xy = vec2(atan(q.y, q.x), q.z * 1.5 * HPX_PI_OVER_FOUR);
+    }
+
+    return xy / vec2(PI, 0.5 * PI);
+}
\ No newline at end of file
@@ -0,0 +1,6 @@
+vec2 w2c_par(vec3 p) {
+    float lat = atan(p.y, length(p.xz));
+    float x = atan(-p.x, p.z) * (2.0 * cos(lat * 2.0 / 3.0) - 1.0);
+    // y bounds are +-sin(PI/6) = +-0.5
+    return vec2(x / PI, 2.0 * sin(lat / 3.0));
+}
\ No newline at end of file
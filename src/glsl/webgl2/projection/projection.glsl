@@ -2,6 +2,9 @@
 const float PI = 3.141592653589793;
 const float SQRT_2 = 1.41421356237309504880168872420969808;
 
+// Parameters of the projections (AIR, AZP, ZPN and COD)
+uniform float u_proj_params[24];
+
 #include ./sin.glsl;
 #include ./ait.glsl;
 #include ./mol.glsl;
@@ -9,6 +12,16 @@ const float SQRT_2 = 1.41421356237309504880168872420969808;
 #include ./stg.glsl;
 #include ./zea.glsl;
 #include ./mer.glsl;
+#include ./arc.glsl;
+#include ./air.glsl;
+#include ./zpn.glsl;
+#include ./car.glsl;
+#include ./cea.glsl;
+#include ./sfl.glsl;
+#include ./par.glsl;
+#include ./cod.glsl;
+#include ./hpx.glsl;
+#include ./azp.glsl;
 
 vec3 lonlat2xyz(vec2 lonlat) {
     float t = lonlat.x;
@@ -44,9 +57,39 @@ vec2 proj(vec3 p) {
     } else if (u_proj == 5) {
         // MOL,      Mollweide                  */
         return w2c_mol(p);
-    } else {
+    } else if (u_proj == 6) {
         // Cylindrical projections
         // MER,      Mercator                   */
         return w2c_mer(p);
+    } else if (u_proj == 7) {
+        /* ARC,      Zenithal equidistant       */
+        return w2c_arc(p);
+    } else if (u_proj == 8) {
+        /* AIR,      Airy                       */
+        return w2c_air(p);
+    } else if (u_proj == 9) {
+        /* ZPN,      Zenithal polynomial        */
+        return w2c_zpn(p);
+    } else if (u_proj == 10) {
+        // CAR,      Plate carree               */
+        return w2c_car(p);
+    } else if (u_proj == 11) {
+        // CEA,      Cylindrical equal area     */
+        return w2c_cea(p);
+    } else if (u_proj == 12) {
+        // SFL,      Sanson-Flamsteed           */
+        return w2c_sfl(p);
+    } else if (u_proj == 13) {
+        // PAR,      Parabolic                  */
+        return w2c_par(p);
+    } else if (u_proj == 14) {
+        // COD,      Conic equidistant          */
+        return w2c_cod(p);
+    } else if (u_proj == 15) {
+        // HEALPix hybrid projection
+        return w2c_hpx(p);
+    } else {
+        /* AZP,      Zenithal perspective       */
+        return w2c_azp(p);
     }
 }
\ No newline at end of file
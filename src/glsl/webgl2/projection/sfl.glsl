@@ -0,0 +1,4 @@
+vec2 w2c_sfl(vec3 p) {
+    float r = length(p.xz); // = cos(lat)
+    return vec2(atan(-p.x, p.z) * r / PI, atan(p.y, r) * 2.0 / PI);
+}
\ No newline at end of file
@@ -0,0 +1,28 @@
+// u_proj_params[0] = angular radius of the domain
+// u_proj_params[1] = euclidean radius of the domain
+// u_proj_params[2] = number of coefficients
+// u_proj_params[3..] = coefficients of the polynomial
+float zpn_polynomial(float a) {
+    int n = int(u_proj_params[2]);
+
+    // Horner scheme
+    float r = 0.0;
+    for (int i = n - 1; i >= 0; i--) {
+        r = r * a + u_proj_params[3 + i];
+    }
+    return r;
+}
+
+vec2 w2c_zpn(vec3 p) {
+    float r = min(length(p.xy), 1.0);
+    if (r == 0.0 && p.z > 0.0) {
+        return vec2(0.0);
+    }
+
+    // Angular distance to the center of projection.
+    // Positions out of the domain are projected onto its frontier
+    float a = min(p.z > 0.0 ? asin(r) : acos(p.z), u_proj_params[0]);
+    vec2 dir = r > 0.0 ? vec2(-p.x, p.y) / r : vec2(1.0, 0.0);
+
+    return dir * zpn_polynomial(a) / u_proj_params[1];
+}
\ No newline at end of file
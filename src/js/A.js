@@ -42,6 +42,10 @@ import { Coo } from "./libs/astro/coo.js";
 import { URLBuilder } from "./URLBuilder.js";
 import { Footprint } from './Footprint.js';
 import { Aladin } from "./Aladin.js";
+import { RGBComposite } from "./RGBComposite.js";
+import { LayerArithmetic } from "./LayerArithmetic.js";
+import { HEALPixMap } from "./HEALPixMap.js";
+import { GeneratedHiPS } from "./GeneratedHiPS.js";
 import { ActionButton } from "./gui/Widgets/ActionButton.js";
 import { Box } from "./gui/Widgets/Box.js";
 import { AladinUtils } from "./AladinUtils.js";
@@ -187,6 +191,66 @@ A.image = function (url, options) {
     return Aladin.createImageFITS(url, options, options.successCallback, options.errorCallback);
 }
 
+/**
+ * Creates a true-color layer composed from three single-band HiPS or images
+ *
+ * @function
+ * @name A.rgbComposite
+ * @memberof A
+ * @param {HiPS|Image} red - The layer of the red channel
+ * @param {HiPS|Image} green - The layer of the green channel
+ * @param {HiPS|Image} blue - The layer of the blue channel
+ * @param {RGBCompositeOptions} [options] - The options of the composite
+ * @returns {RGBComposite} - A composite image layer object
+ */
+A.rgbComposite = function (red, green, blue, options) {
+    return new RGBComposite(red, green, blue, options);
+}
+
+/**
+ * Creates a layer computing a per-pixel expression between two HiPS or images
+ *
+ * @function
+ * @name A.layerArithmetic
+ * @memberof A
+ * @param {HiPS|Image} a - The layer of the operand A
+ * @param {HiPS|Image} b - The layer of the operand B
+ * @param {string} operation - 'difference' for A - B, 'ratio' for A / B or 'mask' for A × B
+ * @param {LayerArithmeticOptions} [options] - The options of the derived layer
+ * @returns {LayerArithmetic} - A derived image layer object
+ */
+A.layerArithmetic = function (a, b, operation, options) {
+    return new LayerArithmetic(a, b, operation, options);
+}
+
+/**
+ * Creates a layer from a HEALPix map stored in a FITS binary table (RING or NESTED)
+ *
+ * @function
+ * @name A.HEALPixMap
+ * @memberof A
+ * @param {string|File|Blob} url - The url of the FITS file or the file itself
+ * @param {HEALPixMapOptions} [options] - The options of the map
+ * @returns {HEALPixMap} - A HEALPix map image layer object
+ */
+A.HEALPixMap = function (url, options) {
+    return new HEALPixMap(url, options);
+}
+
+/**
+ * Creates a HiPS generated in the browser from a FITS image layer
+ *
+ * @function
+ * @name A.generatedHiPS
+ * @memberof A
+ * @param {Image} image - A FITS image layer
+ * @param {GeneratedHiPSOptions} [options] - The options of the HiPS
+ * @returns {GeneratedHiPS} - A HiPS image layer object
+ */
+A.generatedHiPS = function (image, options) {
+    return new GeneratedHiPS(image, options);
+}
+
 /**
  * Creates a celestial source object with the given coordinates.
  *
@@ -425,7 +489,8 @@ A.footprintsFromSTCS = function (stcs, options) {
  * @memberof A
  * @name MOCFromURL
  *
- * @param {string} url - The url to the MOC (e.g. stored as FITS file)
+ * @param {string} url - The url to the MOC (e.g. stored as FITS file). A space-time MOC is displayed through its sky coverage
+ * at all times, see {@link MOC#setTimeRange} to restrict it to a time range
  * @param {MOCOptions} [options] - Display options for the MOC
  * @param {function} [successCallback] - Callback function when the MOC loads
  * @param {function} [errorCallback] - Callback function when the MOC fails loading
@@ -445,7 +510,8 @@ A.MOCFromURL = function (url, options, successCallback, errorCallback) {
  * @memberof A
  * @name MOCFromJSON
  *
- * @param {Object} jsonMOC - The MOC stores as a JSON-like dictionary
+ * @param {Object} jsonMOC - The MOC stores as a JSON-like dictionary, or as an array of <code>{t: ..., s: ...}</code>
+ * dictionaries for a space-time MOC
  * @param {MOCOptions} [options] - Display options for the MOC
  * @param {function} [successCallback] - Callback function when the MOC loads
  * @param {function} [errorCallback] - Callback function when the MOC fails loading
@@ -518,6 +584,71 @@ A.MOCFromPolygon= function (polygon, options, successCallback, errorCallback) {
     return moc;
 };
 
+/**
+ * Creates a new MOC (Multi-Order-Coverage) from a region of the sky
+ *
+ * @function
+ * @memberof A
+ * @name MOCFromRegion
+ *
+ * @param {Object} region - The region, positions and angles being given in degrees in ICRS. Its <code>type</code> is one of:
+ * <ul>
+ * <li><code>cone</code> with <code>ra</code>, <code>dec</code> and <code>radius</code></li>
+ * <li><code>ellipse</code> with <code>ra</code>, <code>dec</code>, the semi-axes <code>a</code> and <code>b</code> and the position angle <code>pa</code></li>
+ * <li><code>box</code> with <code>ra</code>, <code>dec</code>, <code>width</code>, <code>height</code> and the position angle <code>angle</code></li>
+ * <li><code>zone</code> delimited by <code>raMin</code>, <code>decMin</code>, <code>raMax</code> and <code>decMax</code>, crossing the 0 meridian if <code>raMin > raMax</code></li>
+ * <li><code>ring</code> with <code>ra</code>, <code>dec</code>, <code>innerRadius</code> and <code>outerRadius</code></li>
+ * <li><code>polygon</code> with its <code>vertices</code> as <code>[ra, dec]</code> pairs and optionally a position <code>inside</code> it.
+ * Its smallest area is taken otherwise</li>
+ * <li><code>stcs</code> with an <code>stcs</code> string, e.g. "Circle ICRS 10 20 0.5"</li>
+ * </ul>
+ * @param {MOCOptions} [options] - Display options for the MOC
+ * @param {number} [options.order] - The order of the MOC. By default, 9 orders deeper than the tiles in the view minus one
+ * @param {boolean} [options.complement=false] - Cover the outside of the region instead of its inside
+ * @param {function} [successCallback] - Callback function when the MOC loads
+ * @param {function} [errorCallback] - Callback function when the MOC fails loading
+ * @returns {MOC} Returns a new MOC object
+ *
+ * @example
+ * var moc = A.MOCFromRegion({type: 'ellipse', ra: 83.8, dec: -5.4, a: 1, b: 0.5, pa: 30}, {order: 12, fill: true});
+ * aladin.addMOC(moc);
+ */
+A.MOCFromRegion = function (region, options, successCallback, errorCallback) {
+    options = options || {};
+
+    var moc = new MOC(options);
+    moc.parse({region, order: options.order, complement: options.complement}, successCallback, errorCallback);
+
+    return moc;
+};
+
+/**
+ * Creates a new MOC (Multi-Order-Coverage) from a set operation between other MOCs
+ *
+ * @function
+ * @memberof A
+ * @name MOCFromOperation
+ *
+ * @param {string} operation - The set operation: <code>union</code>, <code>intersection</code>, <code>difference</code>,
+ * <code>symmetricDifference</code> or <code>complement</code>. The difference removes all the other MOCs from the first one
+ * and the complement takes only one MOC
+ * @param {MOC[]} mocs - The MOCs to combine. They must be added to the same aladin instance as the new MOC
+ * @param {MOCOptions} [options] - Display options for the MOC
+ * @param {function} [successCallback] - Callback function when the MOC loads
+ * @param {function} [errorCallback] - Callback function when the MOC fails loading
+ * @returns {MOC} Returns a new MOC object
+ *
+ * @example
+ * var overlap = A.MOCFromOperation('intersection', [sdssMOC, panstarrsMOC], {color: 'yellow', fill: true});
+ * aladin.addMOC(overlap);
+ */
+A.MOCFromOperation = function (operation, mocs, options, successCallback, errorCallback) {
+    var moc = new MOC(options);
+    moc.parse({operation, mocs}, successCallback, errorCallback);
+
+    return moc;
+};
+
 /**
  * Represents a catalog with configurable options for display and interaction.
  *
@@ -39,7 +39,6 @@ import { CooFrameEnum } from "./CooFrameEnum.js";
 import { MeasurementTable } from "./MeasurementTable.js";
 import { HiPS } from "./HiPS.js";
 import { Coo } from "./libs/astro/coo.js";
-import { CooConversion } from "./CooConversion.js";
 import { HiPSCache } from "./HiPSCache.js";
 import { HiPSList } from "./DefaultHiPSList.js";
 
@@ -48,6 +47,12 @@ import { ProjectionEnum } from "./ProjectionEnum.js";
 import { ALEvent } from "./events/ALEvent.js";
 import { Color } from "./Color.js";
 import { Image } from "./Image.js";
+import { RGBComposite } from "./RGBComposite.js";
+import { LayerArithmetic } from "./LayerArithmetic.js";
+import { HEALPixMap } from "./HEALPixMap.js";
+import { GeneratedHiPS } from "./GeneratedHiPS.js";
+import { Contours } from "./Contours.js";
+import { LayerComparison } from "./LayerComparison.js";
 import { DefaultActionsForContextMenu } from "./DefaultActionsForContextMenu.js";
 import { SAMPConnector } from "./vo/samp.js";
 import { Reticle } from "./Reticle.js";
@@ -927,17 +932,33 @@ export let Aladin = (function () {
      * <br>"MER" (Mercator projection)
      * <br>"AIT" (Hammer-Aitoff projection)
      * <br>"MOL" (Mollweide projection)
+     * <br>"ARC" (Zenital equidistant projection)
+     * <br>"AIR" (Airy projection)
+     * <br>"ZPN" (Zenital polynomial projection)
+     * <br>"CAR" (Plate carrée projection)
+     * <br>"CEA" (Cylindrical equal area projection)
+     * <br>"SFL" (Sanson-Flamsteed projection)
+     * <br>"PAR" (Parabolic projection)
+     * <br>"COD" (Conic equidistant projection)
+     * <br>"AZP" (Zenital perspective projection)
+     * <br>"HPX" (HEALPix projection)
+     * @param {Object} [params] Parameters of the projection. Only the ones of the projection chosen are accepted.
+     * @param {number[]} [params.standardParallels] COD only. The one or two standard parallels of the cone in degrees. Default is [45]
+     * @param {number[]} [params.pv] ZPN only. The polynomial coefficients (PV2_i WCS keywords), the first one being 0. Default is [0, 1, 0, -0.05]
+     * @param {number} [params.mu] AZP only. The distance of the point of projection from the center of the sphere, in sphere radius units. Default is 1.35
      *
      * @example
      * // Set the projection to 'orthographic'
      * let aladin = A.aladin('#aladin-lite-div');
      * aladin.setProjection('SIN');
+     * // Conic projection with standard parallels at -20° and -40°
+     * aladin.setProjection('COD', {standardParallels: [-20, -40]});
      */
-    Aladin.prototype.setProjection = function (projection) {
+    Aladin.prototype.setProjection = function (projection, params) {
         if (!projection) {
             return;
         }
-        this.view.setProjection(projection);
+        this.view.setProjection(projection, params);
 
         ALEvent.PROJECTION_CHANGED.dispatchedTo(this.aladinDiv, {
             projection,
@@ -1136,7 +1157,8 @@ export let Aladin = (function () {
      * @memberof Aladin
      * @param {number} lon - longitude in degrees
      * @param {number} lat - latitude in degrees
-     * @param {string} [frame] - The name of the coordinate frame. Possible values: 'j2000d', 'j2000', 'gal', 'icrs'. The given string is case insensitive.
+     * @param {string} [frame] - The name of the coordinate frame. Possible values: 'j2000d', 'j2000', 'gal', 'icrs', 'fk5', 'fk4', 'ecl', 'sgal', 'hor'. The given string is case insensitive.
+     * If not given, the frame of the view is used.
      *
      * @example
      * // Move to position
@@ -1144,24 +1166,22 @@ export let Aladin = (function () {
      * aladin.gotoPosition(20, 10, "galactic");
      */
     Aladin.prototype.gotoPosition = function (lon, lat, frame) {
-        var radec;
         // convert the frame from string to CooFrameEnum
         if (frame) {
-            frame = CooFrameEnum.fromString(
-                this.options.cooFrame,
-                CooFrameEnum.ICRS
-            );
+            frame = CooFrameEnum.fromString(frame, CooFrameEnum.ICRS);
         }
         // both are CooFrameEnum
         let positionGivenFrame = frame || this.view.cooFrame;
-        // First, convert to ICRS if needed
-        if (positionGivenFrame === CooFrameEnum.GAL) {
-            radec = CooConversion.GalacticToICRS([lon, lat]);
-        } else {
-            radec = [lon, lat];
-        }
+        // First, convert to ICRS
+        const CooSystem = Aladin.wasmLibs.core.CooSystem;
+        const [ra, dec] = this.wasm.convertCooSys(
+            lon,
+            lat,
+            CooSystem[positionGivenFrame.system],
+            CooSystem.ICRS
+        );
 
-        this.gotoRaDec(radec[0], radec[1]);
+        this.gotoRaDec(ra, dec);
     };
 
     var idTimeoutAnim;
@@ -1912,12 +1932,16 @@ export let Aladin = (function () {
      * Add a new HiPS/FITS image layer in the view
      *
      * @memberof Aladin
-     * @param {string|HiPS|Image} urlOrHiPSOrFITS - Can be:
+     * @param {string|HiPS|Image|RGBComposite|LayerArithmetic|HEALPixMap|GeneratedHiPS} urlOrHiPSOrFITS - Can be:
      * <ul>
      * <li>1. An url that refers to a HiPS.</li>
      * <li>2. Or it can be a CDS ID that refers to a HiPS. One can found the list of IDs {@link https://aladin.cds.unistra.fr/hips/list| here}</li>
      * <li>3. A {@link HiPS} HiPS object created from {@link A.HiPS}</li>
      * <li>4. A {@link Image} FITS/jpeg/png image</li>
+     * <li>5. A {@link RGBComposite} true-color composite created from {@link A.rgbComposite}</li>
+     * <li>6. A {@link LayerArithmetic} difference, ratio or masked layer created from {@link A.layerArithmetic}</li>
+     * <li>7. A {@link HEALPixMap} HEALPix map stored in a FITS file created from {@link A.HEALPixMap}</li>
+     * <li>8. A {@link GeneratedHiPS} HiPS generated from a FITS image created from {@link A.generatedHiPS}</li>
      * </ul>
      * @param {string} [layer="overlay"] - A layer name. By default 'overlay' is chosen and it is destined to be plot
      * on top the 'base' layer. If the layer is already present in the view, it will be replaced by the new HiPS/FITS image given here.
@@ -1961,7 +1985,9 @@ export let Aladin = (function () {
         }
 
         // Add it to the hipsList if it is not there yet
-        this.addHiPSToFavorites(imageLayer)
+        if (!(imageLayer instanceof RGBComposite || imageLayer instanceof LayerArithmetic || imageLayer instanceof HEALPixMap || imageLayer instanceof GeneratedHiPS)) {
+            this.addHiPSToFavorites(imageLayer)
+        }
 
         imageLayer.layer = layer;
 
@@ -1981,6 +2007,43 @@ export let Aladin = (function () {
         return survey;
     };
 
+    /**
+     * Compare two image layers by blinking between them, cross-fading them or
+     * swiping a vertical line separating them. Only one comparison can be active at a time.
+     *
+     * @memberof Aladin
+     * @param {string} layerA - The name of the first layer
+     * @param {string} layerB - The name of the second layer
+     * @param {LayerComparisonOptions} [options] - The mode and the parameters of the comparison
+     *
+     * @returns {LayerComparison} - The comparison, that can be updated or stopped
+     */
+    Aladin.prototype.compareLayers = function (layerA, layerB, options) {
+        this.stopLayerComparison();
+
+        let comparison = new LayerComparison(this.view, layerA, layerB, options);
+        this.view.layerComparison = comparison;
+        try {
+            comparison._start();
+        } catch (e) {
+            comparison.stop();
+            throw e;
+        }
+
+        return comparison;
+    };
+
+    /**
+     * Stop the current layer comparison if any
+     *
+     * @memberof Aladin
+     */
+    Aladin.prototype.stopLayerComparison = function () {
+        if (this.view.layerComparison) {
+            this.view.layerComparison.stop();
+        }
+    };
+
     // @api
     Aladin.prototype.increaseZoom = function () {
         this.view.increaseZoom();
@@ -2325,6 +2388,66 @@ export let Aladin = (function () {
         return this.view.getGridOptions();
     };
 
+    /**
+     * Sets the observer defining the horizontal (Alt-Az) frame.
+     *
+     * @memberof Aladin
+     * @param {Object} observer - The observer location and date.
+     * @param {number} observer.lon - Geodetic longitude of the observer in degrees, positive toward the east.
+     * @param {number} observer.lat - Geodetic latitude of the observer in degrees.
+     * @param {number} [observer.height=0] - Height of the observer above the sea level in meters.
+     * @param {Date} [observer.date] - The UTC date of the observation. If not given, the frame follows the current time,
+     * being updated every 10 seconds. Otherwise it stays at that date until {@link Aladin#setObserverTime} is called.
+     *
+     * @example
+     * // Paranal observatory, now, with the view in Alt-Az
+     * aladin.setObserver({lon: -70.404, lat: -24.627, height: 2635});
+     * aladin.setFrame('HOR');
+     */
+    Aladin.prototype.setObserver = function (observer) {
+        this.view.setObserver(observer.lon, observer.lat, observer.height || 0, observer.date);
+    };
+
+    /**
+     * Sets the UTC date of the horizontal (Alt-Az) frame, e.g. to follow the sky during a night
+     * being planned. The observer must have been set with {@link Aladin#setObserver}.
+     *
+     * @memberof Aladin
+     * @param {Date} [date] - The UTC date. If not given, the frame follows the current time.
+     *
+     * @example
+     * // Where the sky will be at midnight UTC
+     * aladin.setObserverTime(new Date('2025-03-21T00:00:00Z'));
+     */
+    Aladin.prototype.setObserverTime = function (date) {
+        this.view.setObserverTime(date);
+    };
+
+    /**
+     * Sets the options of the horizon overlay drawn from the observer location.
+     *
+     * @memberof Aladin
+     * @param {Object} options - Options for the horizon overlay.
+     * @param {string} [options.color] - The color of the horizon line and of the cardinal points.
+     * @param {number} [options.opacity] - The opacity of the horizon overlay.
+     * @param {number} [options.thickness] - The thickness of the lines.
+     * @param {boolean} [options.showLabels] - If true, the cardinal points are drawn.
+     * @param {number} [options.labelSize] - The size of the cardinal point labels in pixels.
+     * @param {number[]} [options.airmass] - The airmasses for which an iso-line is drawn.
+     * @param {boolean} [options.enabled] - If true, the horizon overlay is enabled.
+     */
+    Aladin.prototype.setHorizonOptions = function (options) {
+        if (options.color) {
+            options.color = new Color(options.color);
+            // convert from 0-255 to 0-1
+            options.color.r /= 255;
+            options.color.g /= 255;
+            options.color.b /= 255;
+        }
+
+        this.view.setHorizonOptions(options);
+    };
+
     Aladin.prototype.showCooGrid = function () {
         this.setCooGrid({ enabled: true });
     };
@@ -2431,28 +2554,17 @@ export let Aladin = (function () {
      * Return the current view WCS as a key-value dictionary
      * Can be useful in coordination with getViewDataURL
      *
+     * The header describes the projection, the frame, the center and the rotation of the view
+     * (CTYPE, CRPIX, CRVAL, CD, LONPOLE, LATPOLE, RADESYS and the PV parameters of the projection).
+     * Its pixels are the ones of the canvas, the center of its bottom left pixel being located at (1, 1).
+     *
      * @memberof Aladin
      * @returns {Object} - A JS object describing the WCS of the view.
      */
     Aladin.prototype.getViewWCS = function () {
-        // get general view properties
-        const center = this.wasm.getCenter();
-        const fov = this.getFov();
-        const width = this.view.width;
-        const height = this.view.height;
-
-        // get values common for all
-        let cdelt1 = -fov[0] / width;
-        const cdelt2 = fov[1] / height;
-        const projName = this.getProjectionName();
-
-        if (projName == "FEYE")
-            return "Fish eye projection is not supported by WCS standards.";
-
-        // reversed longitude case
-        if (this.getBaseImageLayer().longitudeReversed) {
-            cdelt1 = -cdelt1;
-        }
+        const WCS = this.view.wasm.getViewWCS();
+        WCS.CUNIT1 = "deg     ";
+        WCS.CUNIT2 = "deg     ";
 
         // solar system object dict from planetary fits standard
         // https://agupubs.onlinelibrary.wiley.com/doi/10.1029/2018EA000388
@@ -2470,89 +2582,22 @@ export let Aladin = (function () {
             satellite: "ST", // not findable in the hips properties?
         };
 
-        // we define a generic LON LAT keyword for unknown body types
-        let cooType1 = "LON--";
-        let cooType2 = "LAT--";
-
-        // just in case it would be equatorial
-        let radesys;
-
         if (this.getBaseImageLayer().isPlanetaryBody()) {
+            // we define a generic LON LAT keyword for unknown body types
+            let cooType1 = "LON-";
+            let cooType2 = "LAT-";
+
             const body = this.getBaseImageLayer().hipsBody;
             if (body in solarSystemObjects) {
-                cooType1 = `${solarSystemObjects[body]}LN-`;
-                cooType2 = `${solarSystemObjects[body]}LT-`;
-            }
-        } else {
-            switch (this.getFrame()) {
-                case "ICRS":
-                case "ICRSd":
-                    cooType1 = "RA---";
-                    cooType2 = "DEC--";
-                    radesys = "ICRS    ";
-                    break;
-                case "GAL":
-                    cooType1 = "GLON-";
-                    cooType2 = "GLAT-";
+                cooType1 = `${solarSystemObjects[body]}LN`;
+                cooType2 = `${solarSystemObjects[body]}LT`;
             }
-        }
-
-        const WCS = {
-            NAXIS: 2,
-            NAXIS1: width,
-            NAXIS2: height,
-            CRPIX1: width / 2 + 0.5,
-            CRPIX2: height / 2 + 0.5,
-            CRVAL1: center[0],
-            CRVAL2: center[1],
-            CTYPE1: cooType1 + projName,
-            CTYPE2: cooType2 + projName,
-            CUNIT1: "deg     ",
-            CUNIT2: "deg     ",
-            CDELT1: cdelt1,
-            CDELT2: cdelt2,
-        };
-
-        // handle the case of equatorial coordinates that need
-        // the radecsys keyword
-        if (radesys == "ICRS    ") WCS.RADESYS = radesys;
-
-        const isProjZenithal = ['TAN', 'SIN', 'STG', 'ZEA'].some((p) => p === projName)
-        if (isProjZenithal) {
-            // zenithal projections
-            // express the 3rd euler angle for zenithal projection
-            let thirdEulerAngle = this.getViewCenter2NorthPoleAngle();
-            WCS.LONPOLE = 180 - thirdEulerAngle
-        } else {
-            // cylindrical or pseudo-cylindrical projections
-            if (WCS.CRVAL2 === 0) {
-                // ref point on the equator not handled (yet)
-                console.warn('TODO: 3rd euler rotation is not handled for ref point located at delta_0 = 0')
-            } else {
-                // ref point not on the equator
-                const npLonlat = this.view.wasm.getNorthPoleCelestialPosition();
-                let dLon = WCS.CRVAL1 - npLonlat[0];
-
-                // dlon angle must lie between -PI and PI
-                // For dlon angle between -PI;-PI/2 or PI/2;PI one must invert LATPOLE
-                if (this.getViewCenter2NorthPoleAngle() < -90 || this.getViewCenter2NorthPoleAngle() > 90) {
-                    // so that the south pole becomes upward to the ref point
-                    WCS.LATPOLE = -90
-                }
-
-                const toRad = Math.PI / 180
-                const toDeg = 1.0 / toRad;
-
-                // Reverse the Eq 9 from the WCS II paper from Mark Calabretta to obtain LONPOLE
-                // function of CRVAL2 and native coordinates of the fiducial ref point, i.e. (phi_0, theta_0) = (0, 0)
-                // for cylindrical projections 
-                WCS.LONPOLE = Math.asin(Math.sin(dLon * toRad) * Math.cos(WCS.CRVAL2 * toRad)) * toDeg;
 
-                if (WCS.CRVAL2 < 0) {
-                    // ref point is located in the south hemisphere
-                    WCS.LONPOLE = -180 - WCS.LONPOLE;
-                }
-            }
+            // keep the projection code, i.e. the last 4 characters
+            WCS.CTYPE1 = cooType1 + WCS.CTYPE1.slice(4);
+            WCS.CTYPE2 = cooType2 + WCS.CTYPE2.slice(4);
+            delete WCS.RADESYS;
+            delete WCS.EQUINOX;
         }
 
         return WCS;
@@ -2625,12 +2670,7 @@ export let Aladin = (function () {
                 frame = CooFrameEnum.fromString(frame, CooFrameEnum.ICRS);
             }
     
-            if (frame.label == CooFrameEnum.SYSTEMS.GAL) {
-                frame = Aladin.wasmLibs.core.CooSystem.GAL;
-            }
-            else {
-                frame = Aladin.wasmLibs.core.CooSystem.ICRS;
-            }
+            frame = Aladin.wasmLibs.core.CooSystem[frame.system];
         }
 
         return this.view.wasm.world2pix(lon, lat, frame);
@@ -2658,6 +2698,63 @@ export let Aladin = (function () {
         return this.wasm.angularDist(ra1, dec1, ra2, dec2);
     };
 
+    /**
+     * Precess equatorial positions from an equinox to another with the IAU 2006 model
+     *
+     * @memberof Aladin
+     * @param {number[]} ra - The right ascensions in degrees
+     * @param {number[]} dec - The declinations in degrees
+     * @param {number} fromEquinox - The equinox of the positions given, in Julian years (e.g. 1950.0)
+     * @param {number} toEquinox - The equinox to precess the positions to, in Julian years
+     * @param {boolean} [nutation=false] - Refer the positions to the true equator and equinox of date
+     *
+     * @returns {number[][]} - The precessed positions [[ra1, dec1], [ra2, dec2], ...] in degrees
+     */
+    Aladin.prototype.precess = function (ra, dec, fromEquinox, toEquinox, nutation) {
+        const radec = this.wasm.precess(ra, dec, fromEquinox, toEquinox, nutation);
+
+        let positions = [];
+        for (let i = 0; i < radec.length; i += 2) {
+            positions.push([radec[i], radec[i + 1]]);
+        }
+
+        return positions;
+    };
+
+    /**
+     * Propagate positions to another epoch with their proper motions, parallaxes and radial velocities
+     *
+     * @memberof Aladin
+     * @param {number[]} ra - The right ascensions in degrees
+     * @param {number[]} dec - The declinations in degrees
+     * @param {Object} options - The astrometric parameters of the positions
+     * @param {number[]} options.pmra - The proper motions in right ascension (multiplied by cos(dec)) in mas/yr
+     * @param {number[]} options.pmdec - The proper motions in declination in mas/yr
+     * @param {number[]} [options.parallax] - The parallaxes in mas
+     * @param {number[]} [options.rv] - The radial velocities in km/s
+     * @param {number} options.fromEpoch - The epoch of the positions given, in Julian years (e.g. 2016.0)
+     * @param {number} options.toEpoch - The epoch to propagate the positions to, in Julian years
+     *
+     * @returns {number[][]} - The propagated positions [[ra1, dec1], [ra2, dec2], ...] in degrees
+     *
+     * @example
+     * // Barnard's star from the Gaia DR3 epoch to 1950
+     * aladin.propagateProperMotion([269.4486], [4.7396], {
+     *     pmra: [-801.551], pmdec: [10362.394], parallax: [546.976], rv: [-110.5],
+     *     fromEpoch: 2016.0, toEpoch: 1950.0
+     * });
+     */
+    Aladin.prototype.propagateProperMotion = function (ra, dec, options) {
+        const radec = this.wasm.propagateProperMotion(ra, dec, options);
+
+        let positions = [];
+        for (let i = 0; i < radec.length; i += 2) {
+            positions.push([radec[i], radec[i + 1]]);
+        }
+
+        return positions;
+    };
+
     /**
      * Gets a set of points along the current Field of View (FoV) corners.
      *
@@ -2698,6 +2795,46 @@ export let Aladin = (function () {
         return points;
     };
 
+    /**
+     * Gets the coverage of the current view as a MOC (Multi-Order Coverage map), e.g. to query
+     * remote services or archive tables with what is looked at. Parts of the view outside of the
+     * projection domain are not covered.
+     *
+     * @memberof Aladin
+     * @param {number} [order] - The order of the MOC. By default, 9 orders deeper than the tiles in the view minus one
+     * @param {MOCOptions} [options] - Display options for the MOC
+     *
+     * @returns {MOC} - A new MOC object of the view at the time of the call. It can be added with {@link Aladin#addMOC}
+     * to be displayed or serialized
+     *
+     * @example
+     * let moc = aladin.getViewMOC(10);
+     * aladin.addMOC(moc);
+     * let json = moc.serialize('json');
+     */
+    Aladin.prototype.getViewMOC = function (order, options) {
+        const json = this.wasm.getViewMOC(order);
+
+        return A.MOCFromJSON(json, options);
+    };
+
+    /**
+     * Gets the tiles of a HiPS layer in the current view as a MOC (Multi-Order Coverage map).
+     * The tiles are given at the order they are displayed and restricted to the MOC of the HiPS when it is known.
+     * Only HiPS in ICRS are supported.
+     *
+     * @memberof Aladin
+     * @param {string} [layer="base"] - The name of the HiPS layer
+     * @param {MOCOptions} [options] - Display options for the MOC
+     *
+     * @returns {MOC} - A new MOC object of the tiles in the view at the time of the call
+     */
+    Aladin.prototype.getLayerViewMOC = function (layer, options) {
+        const json = this.wasm.getLayerViewMOC(layer || "base");
+
+        return A.MOCFromJSON(json, options);
+    };
+
     /**
      * Gets the current Field of View (FoV) size in degrees as a 2-element array.
      *
@@ -2926,6 +3063,93 @@ aladin.displayFITS(
         return this.setOverlayImageLayer(image, layer);
     };
 
+    /**
+     * Display a HEALPix map stored in a FITS binary table (e.g. a Planck map) in the Aladin Lite view.
+     *
+     * The map is rendered as a HiPS whose tiles are generated on the fly from the map.
+     *
+     * @memberof Aladin
+     * @param {string|File|Blob} url - The URL of the FITS file or the file itself.
+     * @param {HEALPixMapOptions} [options] - Options to customize the display
+     * @param {string} [layer="overlay"] - The name of the layer. If not specified, it will add a new overlay layer on top of the base.
+     * @returns {HEALPixMap} - The map layer
+     *
+     * @example
+     * aladin.displayHEALPixMap(
+     *   'https://irsa.ipac.caltech.edu/data/Planck/release_3/all-sky-maps/maps/LFI_SkyMap_030_1024_R3.00_full.fits',
+     *   {colormap: 'rdylbu', column: 'I_STOKES', stretch: 'asinh'}
+     * );
+     */
+    Aladin.prototype.displayHEALPixMap = function (
+        url,
+        options,
+        layer = "overlay"
+    ) {
+        const map = A.HEALPixMap(url, options);
+        this.setOverlayImageLayer(map, layer);
+
+        return map;
+    };
+
+    /**
+     * Generate a HiPS in the browser from a FITS image layer and display it
+     *
+     * The image is reprojected into the tiles of a HiPS so that all the HiPS features can be used on it.
+     * The files of the generated HiPS can be exported with {@link GeneratedHiPS#getFilePaths} and {@link GeneratedHiPS#getFile}.
+     *
+     * @memberof Aladin
+     * @param {Image|string} image - A FITS image layer or the name of its layer.
+     * @param {GeneratedHiPSOptions} [options] - Options of the HiPS
+     * @param {string} [layer="overlay"] - The name of the layer of the HiPS.
+     * @returns {GeneratedHiPS} - The HiPS layer
+     *
+     * @example
+     * const image = aladin.displayFITS('https://fits.gsfc.nasa.gov/samples/FOCx38i0101t_c0f.fits');
+     * const hips = aladin.generateHiPS(image, {name: 'FOC'}, 'FOC HiPS');
+     * // Later, once generated
+     * hips.getFilePaths().forEach((path) => {
+     *     const file = hips.getFile(path);
+     *     // publish the file
+     * });
+     */
+    Aladin.prototype.generateHiPS = function (
+        image,
+        options,
+        layer = "overlay"
+    ) {
+        if (typeof image === "string") {
+            image = this.getOverlayImageLayer(image);
+        }
+
+        const hips = A.generatedHiPS(image, options);
+        this.setOverlayImageLayer(hips, layer);
+
+        return hips;
+    };
+
+    /**
+     * Trace contours on a FITS image or a FITS HiPS layer
+     *
+     * The contours of a HiPS are traced on its tiles in the view, fetched if needed.
+     *
+     * @memberof Aladin
+     * @param {string} [layer="base"] - The name of the layer whose pixels are contoured
+     * @param {ContoursOptions} [options] - Options of the contours
+     * @returns {Promise<Contours>} - The contours, once traced. Their levels are given by their <code>levels</code> property
+     *
+     * @example
+     * // Radio contours of NVSS at 3, 5, 10 and 20 sigmas above the background
+     * aladin.setOverlayImageLayer('CDS/P/NVSS', 'NVSS');
+     * aladin.getOverlayImageLayer('NVSS').setOpacity(0.0);
+     * aladin.addContours('NVSS', {method: 'sigma', color: 'cyan'})
+     *     .then((contours) => console.log(contours.levels));
+     */
+    Aladin.prototype.addContours = function (layer = "base", options) {
+        const contours = new Contours(layer, options);
+
+        return contours._add(this.view);
+    };
+
     /**
      * Display a JPEG image in the Aladin Lite view.
      *
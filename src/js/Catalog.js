@@ -50,6 +50,13 @@ If a function is given, user can return Image, HTMLImageCanvas, HTMLImageElement
 * @property {boolean} [readOnly=false] - Whether the catalog is read-only.
 * @property {string} [raField] - The ID or name of the field holding Right Ascension (RA).
 * @property {string} [decField] - The ID or name of the field holding Declination (dec).
+* @property {number} [epoch] - The epoch of the source positions in Julian years (e.g. 2016.0 for Gaia DR3). Needed to propagate them to <code>targetEpoch</code>
+* @property {number} [targetEpoch] - The epoch in Julian years the source positions are propagated to with their proper motions before being displayed,
+e.g. the epoch of an image they are compared with
+* @property {string} [pmraField="pmra"] - The name of the field holding the proper motion in right ascension (multiplied by cos(dec)) in mas/yr
+* @property {string} [pmdecField="pmdec"] - The name of the field holding the proper motion in declination in mas/yr
+* @property {string} [parallaxField="parallax"] - The name of the field holding the parallax in mas
+* @property {string} [rvField="radial_velocity"] - The name of the field holding the radial velocity in km/s
 * @property {function} [filter] - The filtering function for sources.
 * @property {string} [selectionColor="#00ff00"] - The color to apply to selected sources in the catalog.
 * @property {string} [hoverColor=color] - The color to apply to sources in the catalog when they are hovered.
@@ -102,6 +109,15 @@ export let Catalog = (function () {
         this.raField = options.raField || undefined; // ID or name of the field holding RA
         this.decField = options.decField || undefined; // ID or name of the field holding dec
 
+        // epoch propagation of the source positions
+        this.epoch = options.epoch;
+        this.targetEpoch = options.targetEpoch;
+        this.pmraField = options.pmraField || "pmra";
+        this.pmdecField = options.pmdecField || "pmdec";
+        this.parallaxField = options.parallaxField || "parallax";
+        this.rvField = options.rvField || "radial_velocity";
+        this.positionsOutdated = false;
+
         // allows for filtering of sources
         this.filterFn = options.filter || undefined; // TODO: do the same for catalog
         this.selectionColor = options.selectionColor || "#00ff00";
@@ -584,11 +600,63 @@ export let Catalog = (function () {
             this.dec.push(sources[k].dec);
         }
 
+        this.positionsOutdated = true;
         this.recomputeFootprints = true;
 
         this.reportChange();
     };
 
+    /**
+     * Set the epoch the source positions are propagated to with their proper motions,
+     * e.g. to bring Gaia sources to the epoch of an image taken decades earlier
+     *
+     * @memberof Catalog
+     *
+     * @param {number} [targetEpoch] - The epoch in Julian years. If not given, the positions of the catalog are displayed as they are
+     * @param {number} [epoch] - The epoch of the source positions in Julian years, if not given in the catalog options
+     */
+    Catalog.prototype.setTargetEpoch = function (targetEpoch, epoch) {
+        this.targetEpoch = targetEpoch;
+        if (epoch !== undefined) {
+            this.epoch = epoch;
+        }
+
+        this.positionsOutdated = true;
+        this.reportChange();
+    };
+
+    // Fill the ra and dec columns given to the view with the source positions, propagated to the target epoch
+    Catalog.prototype.updatePositions = function () {
+        this.positionsOutdated = false;
+
+        this.ra = this.sources.map((s) => s.ra);
+        this.dec = this.sources.map((s) => s.dec);
+
+        if (this.epoch === undefined || this.targetEpoch === undefined || this.epoch === this.targetEpoch) {
+            return;
+        }
+
+        // missing astrometric parameters are taken as zero
+        const column = (field) => this.sources.map((s) => {
+            const value = parseFloat(s.data[field]);
+            return isNaN(value) ? 0.0 : value;
+        });
+
+        const radec = this.view.wasm.propagateProperMotion(this.ra, this.dec, {
+            pmra: column(this.pmraField),
+            pmdec: column(this.pmdecField),
+            parallax: column(this.parallaxField),
+            rv: column(this.rvField),
+            fromEpoch: this.epoch,
+            toEpoch: this.targetEpoch,
+        });
+
+        for (let k = 0; k < this.ra.length; k++) {
+            this.ra[k] = radec[2 * k];
+            this.dec[k] = radec[2 * k + 1];
+        }
+    };
+
     Catalog.prototype.computeFootprints = function (sources) {
         let footprints = [];
 
@@ -863,6 +931,7 @@ export let Catalog = (function () {
         this.ra.splice(idx, 1);
         this.dec.splice(idx, 1);
 
+        this.positionsOutdated = true;
         this.recomputeFootprints = true;
 
         this.reportChange();
@@ -921,6 +990,10 @@ export let Catalog = (function () {
             return;
         }
 
+        if (this.positionsOutdated) {
+            this.updatePositions();
+        }
+
         let xy = this.view.wasm.worldToScreenVec(this.ra, this.dec);
 
         let drawSource = (s, idx) => {
@@ -38,8 +38,11 @@
         this.colormap = (options && options.colormap) || "native";
         this.colormap = this.colormap.toLowerCase();
 
-        this.stretch = (options && options.stretch) || "linear";
-        this.stretch = this.stretch.toLowerCase();
+        this.stretch = ColorCfg.parseStretch((options && options.stretch) || "linear");
+        this.stretchParam = null;
+        if (options && Number.isFinite(options.stretchParam)) {
+            this.stretchParam = options.stretchParam;
+        }
         this.reversed = false;
 
         if (options && options.reversed === true) {
@@ -54,11 +57,17 @@
             this.maxCut = options.maxCut;
         }
 
+        this.autoCuts = ColorCfg.parseAutoCuts(options && options.autoCuts);
+
+        this.clip = ColorCfg.parseClip(options && options.clip);
+
         this.additiveBlending = options && options.additive;
         if (this.additiveBlending === undefined)  {
             this.additiveBlending = false;
         }
 
+        this.blendMode = ColorCfg.parseBlendMode(options && options.blendMode);
+
         // A default value for gamma correction
         this.kGamma = (options && options.gamma) || 1.0;
         this.kSaturation = (options && options.saturation) || 0.0;
@@ -81,6 +90,17 @@
             }
         }
 
+        if (this.blendMode === 'min' || this.blendMode === 'max') {
+            // The blend factors are ignored by these equations
+            blend = {
+                srcColorFactor: 'One',
+                dstColorFactor: 'One',
+                func: this.blendMode === 'min' ? 'Min' : 'Max'
+            }
+        } else if (this.blendMode) {
+            blend.mode = this.blendMode;
+        }
+
         // Reset the whole meta object
         return {
             blendCfg: blend,
@@ -93,11 +113,14 @@
                 kContrast: this.kContrast,
 
                 stretch: this.stretch,
+                stretchParam: this.stretchParam,
                 minCut: this.minCut,
                 maxCut: this.maxCut,
+                autoCuts: this.autoCuts,
                 reversed: this.reversed,
                 cmapName: this.colormap,
-            }
+            },
+            clip: this.clip,
         };
     }
     
@@ -105,6 +128,7 @@
         this.setColormap(options.colormap, options)
 
         this.setCuts(options.minCut, options.maxCut)
+        this.setAutoCuts(options.autoCuts)
 
         this.setBrightness(options.brightness)
         this.setSaturation(options.saturation)
@@ -115,6 +139,9 @@
         this.setOpacity(options.opacity)
 
         this.setBlendingConfig(options.additive)
+        this.setBlendMode(options.blendMode)
+
+        this.setClip(options.clip)
     }
 
     // @api
@@ -206,6 +233,35 @@
         return this.additiveBlending;
     };
 
+    ColorCfg.BLEND_MODES = ['min', 'max', 'screen', 'multiply', 'overlay', 'lighten', 'darken'];
+
+    ColorCfg.parseBlendMode = function(blendMode) {
+        if (!blendMode || blendMode.toLowerCase() === 'normal') {
+            return null;
+        }
+
+        const mode = blendMode.toLowerCase();
+        if (!ColorCfg.BLEND_MODES.includes(mode)) {
+            throw blendMode + ' is not a valid blend mode. Valid ones are: normal, ' + ColorCfg.BLEND_MODES.join(', ');
+        }
+
+        return mode;
+    };
+
+    // @api
+    // null goes back to the normal blending
+    ColorCfg.prototype.setBlendMode = function(blendMode) {
+        if (blendMode === undefined) {
+            return;
+        }
+
+        this.blendMode = ColorCfg.parseBlendMode(blendMode);
+    };
+
+    ColorCfg.prototype.getBlendMode = function() {
+        return this.blendMode;
+    };
+
     // @api
     // Optional arguments, 
     ColorCfg.prototype.setColormap = function(colormap, options) {
@@ -214,7 +270,14 @@
 
         /// stretch
         let stretch = (options && options.stretch) || this.stretch || "linear";
-        this.stretch = stretch.toLowerCase()
+        this.stretch = ColorCfg.parseStretch(stretch);
+
+        // The stretch parameter is reset to its default when the stretch changes
+        if (options && options.stretchParam !== undefined) {
+            this.stretchParam = Number.isFinite(options.stretchParam) ? options.stretchParam : null;
+        } else if (options && options.stretch) {
+            this.stretchParam = null;
+        }
 
         /// reversed
         if (options && options.reversed !== undefined) {
@@ -227,6 +290,35 @@
         return this.colormap;
     };
 
+    // Normalize the name of a stretch, throwing if it is unknown
+    ColorCfg.parseStretch = function(stretch) {
+        const stretches = {
+            linear: 'linear',
+            sqrt: 'sqrt',
+            log: 'log',
+            asinh: 'asinh',
+            pow2: 'pow2',
+            square: 'pow2',
+            sinh: 'sinh',
+            pow: 'pow',
+            power: 'pow',
+            histogram: 'histogram',
+            histeq: 'histogram',
+            equalization: 'histogram'
+        };
+
+        const name = stretches[String(stretch).toLowerCase()];
+        if (!name) {
+            throw stretch + ' is not a valid stretch. Valid ones are: linear, sqrt, log, asinh, pow2 (or square), sinh, pow and histogram';
+        }
+
+        return name;
+    };
+
+    ColorCfg.prototype.getStretchParam = function() {
+        return this.stretchParam;
+    };
+
     ColorCfg.prototype.getReversed = function() {
         return this.reversed;
     };
@@ -245,5 +337,87 @@
         return [this.minCut, this.maxCut];
     };
 
+    // Normalize the algorithm computing the cuts from the pixels in view.
+    // It can be given by its name: 'minmax', 'zscale', 'percentile' (1% - 99%) or 'sigmaclip',
+    // or as an object e.g. {method: 'percentile', low: 0.5, high: 99.5}
+    ColorCfg.parseAutoCuts = function(autoCuts) {
+        if (!autoCuts) {
+            return null;
+        }
+
+        if (typeof autoCuts === 'string') {
+            autoCuts = {method: autoCuts};
+        }
+
+        const methods = {
+            minmax: 'minMax',
+            zscale: 'zscale',
+            percentile: 'percentile',
+            sigmaclip: 'sigmaClip'
+        };
+        const method = methods[autoCuts.method.toLowerCase()];
+        if (!method) {
+            throw autoCuts.method + ' is not a valid cuts algorithm. Valid ones are: minmax, zscale, percentile and sigmaclip';
+        }
+
+        if (method === 'percentile') {
+            return {low: 1.0, high: 99.0, ...autoCuts, method};
+        }
+
+        return {...autoCuts, method};
+    };
+
+    // @api
+    // null disables the computation of the cuts from the pixels in view
+    ColorCfg.prototype.setAutoCuts = function(autoCuts) {
+        if (autoCuts === undefined) {
+            return;
+        }
+
+        this.autoCuts = ColorCfg.parseAutoCuts(autoCuts);
+    };
+
+    ColorCfg.prototype.getAutoCuts = function() {
+        return this.autoCuts;
+    };
+
+    ColorCfg.parseClip = function(clip) {
+        if (!clip) {
+            return null;
+        }
+
+        // A MOC object or its uuid
+        if (typeof clip === 'string' || clip.uuid) {
+            clip = {moc: clip};
+        }
+
+        const uuid = typeof clip.moc === 'string' ? clip.moc : clip.moc && clip.moc.uuid;
+        if (!uuid) {
+            throw 'A MOC must be given to clip a layer';
+        }
+
+        const clippedOpacity = +clip.clippedOpacity || 0.0;
+
+        return {
+            uuid,
+            outside: clip.outside === true,
+            clippedOpacity: Math.max(0, Math.min(clippedOpacity, 1)),
+        };
+    };
+
+    // @api
+    // null renders the whole layer again
+    ColorCfg.prototype.setClip = function(clip) {
+        if (clip === undefined) {
+            return;
+        }
+
+        this.clip = ColorCfg.parseClip(clip);
+    };
+
+    ColorCfg.prototype.getClip = function() {
+        return this.clip;
+    };
+
     return ColorCfg;
  })();
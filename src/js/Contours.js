@@ -0,0 +1,170 @@
+// Copyright 2013 - UDS/CNRS
+// The Aladin Lite program is distributed under the terms
+// of the GNU General Public License version 3.
+//
+// This file is part of Aladin Lite.
+//
+//    Aladin Lite is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, version 3 of the License.
+//
+//    Aladin Lite is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    The GNU General Public License is available in COPYING file
+//    along with Aladin Lite.
+//
+
+/******************************************************************************
+ * Aladin Lite project
+ *
+ * File Contours
+ *
+ * Authors: Thomas Boch & Matthieu Baumann [CDS]
+ *
+ *****************************************************************************/
+import { Utils } from "./Utils";
+import { Color } from "./Color.js";
+
+/**
+ * @typedef {Object} ContoursOptions
+ *
+ * @property {number[]} [levels] - The levels of the contours. If not given, they are computed from the pixel values with `method`
+ * @property {string} [method="linear"] - How the levels are computed: <code>linear</code> or <code>log</code> spacing between the
+ * minimum and maximum values, or <code>sigma</code> for levels at several standard deviations above the background
+ * @property {number} [numLevels=5] - The number of levels of the <code>linear</code> and <code>log</code> methods
+ * @property {number[]} [sigmas=[3, 5, 10, 20]] - The levels of the <code>sigma</code> method, in standard deviations above the background.
+ * The background is the median of the pixel values and the standard deviation is estimated from their median absolute deviation
+ * @property {number} [order] - For a HiPS, the order of the tiles on which the contours are traced. The one of the tiles in the view by default
+ * @property {string} [color="#00ff00"] - The color of the lines
+ * @property {number} [opacity=1.0] - The opacity of the lines
+ * @property {number} [lineWidth=1] - The width of the lines in pixels
+ */
+
+export let Contours = (function () {
+    /**
+     * Contour lines traced on a FITS image or a FITS HiPS layer
+     *
+     * The contours are traced with the marching squares algorithm on the pixels of an image or on the
+     * tiles of a HiPS in the view at the time they are added. They stay on the sky afterwards,
+     * e.g. for overlaying radio contours on an optical survey.
+     *
+     * @class
+     * @constructs Contours
+     *
+     * @param {string} layer - The name of the layer whose pixels are contoured
+     * @param {ContoursOptions} [options] - The options of the contours
+     */
+    function Contours(layer, options) {
+        options = options || {};
+
+        this.uuid = Utils.uuidv4();
+        this.layer = layer;
+        this.order = options.order;
+
+        if (options.levels) {
+            this.levelsCfg = {method: "values", values: options.levels};
+        } else if (options.method === "sigma") {
+            this.levelsCfg = {method: "sigma", sigmas: options.sigmas || [3, 5, 10, 20]};
+        } else {
+            this.levelsCfg = {method: options.method || "linear", num: options.numLevels || 5};
+        }
+
+        this.color = options.color || "#00ff00";
+        this.opacity = options.opacity !== undefined ? options.opacity : 1.0;
+        this.lineWidth = options.lineWidth || 1;
+        this.isShowing = true;
+
+        // The levels of the contours, known once they are traced
+        this.levels = undefined;
+        this.view = null;
+    }
+
+    Contours.prototype._getStyle = function () {
+        const color = new Color(this.color);
+
+        return {
+            color: {r: color.r / 255, g: color.g / 255, b: color.b / 255},
+            opacity: this.opacity,
+            thickness: this.lineWidth,
+            show: this.isShowing,
+        };
+    };
+
+    Contours.prototype._add = function (view) {
+        this.view = view;
+
+        return view.wasm
+            .addContours(this.layer, this.uuid, this.levelsCfg, this._getStyle(), this.order)
+            .then((levels) => {
+                this.levels = levels;
+                view.requestRedraw();
+
+                return this;
+            });
+    };
+
+    Contours.prototype._reportChange = function () {
+        if (this.view) {
+            this.view.wasm.setContoursStyle(this.uuid, this._getStyle());
+            this.view.requestRedraw();
+        }
+    };
+
+    /**
+     * Set the style of the contours
+     *
+     * @memberof Contours
+     * @param {Object} options - The <code>color</code>, <code>opacity</code> and <code>lineWidth</code> of the lines
+     */
+    Contours.prototype.setOptions = function (options) {
+        if (options.color) {
+            this.color = options.color;
+        }
+        if (options.opacity !== undefined) {
+            this.opacity = options.opacity;
+        }
+        if (options.lineWidth) {
+            this.lineWidth = options.lineWidth;
+        }
+
+        this._reportChange();
+    };
+
+    /**
+     * Show the contours
+     *
+     * @memberof Contours
+     */
+    Contours.prototype.show = function () {
+        this.isShowing = true;
+        this._reportChange();
+    };
+
+    /**
+     * Hide the contours
+     *
+     * @memberof Contours
+     */
+    Contours.prototype.hide = function () {
+        this.isShowing = false;
+        this._reportChange();
+    };
+
+    /**
+     * Remove the contours from the view
+     *
+     * @memberof Contours
+     */
+    Contours.prototype.remove = function () {
+        if (this.view) {
+            this.view.wasm.removeContours(this.uuid);
+            this.view.requestRedraw();
+            this.view = null;
+        }
+    };
+
+    return Contours;
+})();
@@ -31,13 +31,18 @@
 export let CooFrameEnum = (function() {
 
     // Corresponds to the Rust CooSystem enum possibilities.
-    var systems = {ICRS: 'ICRS', GAL: 'GAL'};
+    var systems = {ICRS: 'ICRS', GAL: 'GAL', FK5: 'FK5J2000', FK4: 'FK4B1950', ECL: 'ECLJ2000', SGAL: 'SUPERGAL', HOR: 'HORIZONTAL'};
     return {
         SYSTEMS: systems,
 
         ICRS: {label: "ICRS", system: systems.ICRS, explain: "International Celestial Reference System"},
         ICRSd: {label: "ICRSd", system: systems.ICRS, explain: "International Celestial Reference System in decimals"},
         GAL:  {label: "GAL", system: systems.GAL, explain: "Galactical"},
+        FK5:  {label: "FK5", system: systems.FK5, explain: "Fifth Fundamental Catalogue, equinox J2000"},
+        FK4:  {label: "FK4", system: systems.FK4, explain: "Fourth Fundamental Catalogue, equinox B1950"},
+        ECL:  {label: "ECL", system: systems.ECL, explain: "Mean ecliptic and equinox of J2000"},
+        SGAL: {label: "SGAL", system: systems.SGAL, explain: "Supergalactic"},
+        HOR:  {label: "HOR", system: systems.HOR, explain: "Horizontal (Alt-Az) of the observer"},
 
         fromString: function(str, defaultValue) {
             if (! str) {
@@ -55,6 +60,21 @@ export let CooFrameEnum = (function() {
             else if (str.indexOf('gal')==0) {
                 return CooFrameEnum.GAL;
             }
+            else if (str.indexOf('fk5')==0) {
+                return CooFrameEnum.FK5;
+            }
+            else if (str.indexOf('fk4')==0 || str.indexOf('b1950')==0) {
+                return CooFrameEnum.FK4;
+            }
+            else if (str.indexOf('ecl')==0) {
+                return CooFrameEnum.ECL;
+            }
+            else if (str.indexOf('sgal')==0 || str.indexOf('supergal')==0) {
+                return CooFrameEnum.SGAL;
+            }
+            else if (str.indexOf('hor')==0 || str.indexOf('altaz')==0) {
+                return CooFrameEnum.HOR;
+            }
             else {
                 return defaultValue ? defaultValue : null;
             }
@@ -0,0 +1,207 @@
+// Copyright 2013 - UDS/CNRS
+// The Aladin Lite program is distributed under the terms
+// of the GNU General Public License version 3.
+//
+// This file is part of Aladin Lite.
+//
+//    Aladin Lite is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, version 3 of the License.
+//
+//    Aladin Lite is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    The GNU General Public License is available in COPYING file
+//    along with Aladin Lite.
+//
+
+/******************************************************************************
+ * Aladin Lite project
+ *
+ * File GeneratedHiPS
+ *
+ * Authors: Thomas Boch & Matthieu Baumann [CDS]
+ *
+ *****************************************************************************/
+import { ColorCfg } from "./ColorCfg.js";
+import { HiPS } from "./HiPS.js";
+import { Utils } from "./Utils";
+import { ALEvent } from "./events/ALEvent.js";
+
+/**
+ * @typedef {Object} GeneratedHiPSOptions
+ *
+ * @property {string} [name] - The title of the HiPS. By default, the one of the image
+ * @property {number} [maxOrder] - The max order of the HiPS. By default, the first order whose pixels are not larger than the ones of the image
+ * @property {string} [colormap="grayscale"] - The colormap applied to the HiPS
+ * @property {string} [stretch="linear"] - The stretch applied to the HiPS. See {@link HiPS#setColormap}
+ * @property {number} [minCut] - The low cut. The 0.5% percentile of the image by default
+ * @property {number} [maxCut] - The high cut. The 99.5% percentile of the image by default
+ * @property {boolean} [reversed=false] - If true, the colormap is reversed
+ * @property {number} [opacity=1.0] - Opacity of the HiPS (value between 0 and 1).
+ * @property {Function} [successCallback] - A callback executed once the HiPS has been generated
+ * @property {Function} [errorCallback] - A callback executed if the HiPS could not be generated
+ */
+
+export let GeneratedHiPS = (function () {
+    /**
+     * A HiPS generated in the browser from a FITS image layer
+     *
+     * The image is reprojected with its WCS into the tiles of a HiPS, from order 0 up to the one
+     * reaching its resolution, as hipsgen does. The HiPS is then displayed like any other
+     * and its files (properties, MOC, Allsky and tiles) can be exported for publishing it.
+     *
+     * @class
+     * @constructs GeneratedHiPS
+     *
+     * @param {Image} image - A FITS image layer already added to the view
+     * @param {GeneratedHiPSOptions} [options] - The options of the HiPS
+     */
+    function GeneratedHiPS(image, options) {
+        // Name of the layer
+        this.layer = null;
+        this.added = false;
+
+        this.image = image;
+        this.name = (options && options.name) || image.name;
+        this.id = this.name;
+        this.maxOrder = options && options.maxOrder;
+        this.creatorDid = 'ivo://aladin-lite/generated-hips?' + Utils.uuidv4();
+
+        // callbacks
+        this.successCallback = options && options.successCallback;
+        this.errorCallback = options && options.errorCallback;
+
+        this.imgFormat = 'fits';
+        this.formats = ['fits'];
+        this.longitudeReversed = false;
+
+        this.colorCfg = new ColorCfg(options);
+        this.options = options || {};
+
+        this.query = Promise.resolve(this);
+    };
+
+    GeneratedHiPS.prototype.setOpacity = HiPS.prototype.setOpacity;
+    GeneratedHiPS.prototype.setBlendingConfig = HiPS.prototype.setBlendingConfig;
+    GeneratedHiPS.prototype.setBlendMode = HiPS.prototype.setBlendMode;
+    GeneratedHiPS.prototype.setClip = HiPS.prototype.setClip;
+    GeneratedHiPS.prototype.setColormap = HiPS.prototype.setColormap;
+    GeneratedHiPS.prototype.setCuts = HiPS.prototype.setCuts;
+    GeneratedHiPS.prototype.getCuts = HiPS.prototype.getCuts;
+    GeneratedHiPS.prototype.setGamma = HiPS.prototype.setGamma;
+    GeneratedHiPS.prototype.setSaturation = HiPS.prototype.setSaturation;
+    GeneratedHiPS.prototype.setBrightness = HiPS.prototype.setBrightness;
+    GeneratedHiPS.prototype.setContrast = HiPS.prototype.setContrast;
+    GeneratedHiPS.prototype.setOptions = HiPS.prototype.setOptions;
+    GeneratedHiPS.prototype.toggle = HiPS.prototype.toggle;
+    GeneratedHiPS.prototype.getColorCfg = HiPS.prototype.getColorCfg;
+    GeneratedHiPS.prototype.getOpacity = HiPS.prototype.getOpacity;
+    GeneratedHiPS.prototype.getAlpha = HiPS.prototype.getOpacity;
+    GeneratedHiPS.prototype.readPixel = HiPS.prototype.readPixel;
+    GeneratedHiPS.prototype.readPixelFromWorld = HiPS.prototype.readPixelFromWorld;
+    GeneratedHiPS.prototype.getFITSCutout = HiPS.prototype.getFITSCutout;
+
+    // A HiPS generated from a FITS image is given in the ICRS frame
+    GeneratedHiPS.prototype.isPlanetaryBody = function () {
+        return false;
+    };
+
+    /**
+     * Get the paths of the files of the HiPS, relative to its root
+     *
+     * @memberof GeneratedHiPS
+     *
+     * @returns {string[]} The paths of the properties, the MOC, the Allsky and the tiles of every order
+     */
+    GeneratedHiPS.prototype.getFilePaths = function () {
+        return this.view.wasm.getGeneratedHiPSFilePaths(this.creatorDid);
+    };
+
+    /**
+     * Get the content of a file of the HiPS
+     *
+     * @memberof GeneratedHiPS
+     *
+     * @param {string} path - The path of the file relative to the root of the HiPS, e.g. 'Norder3/Allsky.fits'
+     * @returns {Uint8Array} The content of the file
+     */
+    GeneratedHiPS.prototype.getFile = function (path) {
+        return this.view.wasm.getGeneratedHiPSFile(this.creatorDid, path);
+    };
+
+    /** PRIVATE METHODS **/
+    GeneratedHiPS.prototype._setView = function (view) {
+        this.view = view;
+    };
+
+    // A generated HiPS is not stored in the HiPS cache
+    GeneratedHiPS.prototype._saveInCache = function () {};
+
+    // Private method for updating the backend with the new meta
+    GeneratedHiPS.prototype._updateMetadata = HiPS.prototype._updateMetadata;
+
+    GeneratedHiPS.prototype._getCfg = function () {
+        return {
+            layer: this.layer,
+            creatorDid: this.creatorDid,
+            name: this.name,
+            image: this.image.layer,
+            maxOrder: this.maxOrder,
+            meta: {
+                ...this.colorCfg.get(),
+                longitudeReversed: this.longitudeReversed,
+                imgFormat: this.imgFormat,
+            }
+        };
+    };
+
+    GeneratedHiPS.prototype._add = function (layer) {
+        this.layer = layer;
+
+        let self = this;
+        // The image must have been parsed before being reprojected
+        const imageAdded = new Promise((resolve) => {
+            if (self.image.added) {
+                resolve();
+                return;
+            }
+
+            const onImageAdded = (e) => {
+                if (e.detail.layer === self.image) {
+                    ALEvent.HIPS_LAYER_ADDED.remove(self.view.aladinDiv, onImageAdded);
+                    resolve();
+                }
+            };
+            ALEvent.HIPS_LAYER_ADDED.listenedBy(self.view.aladinDiv, onImageAdded);
+        });
+
+        return imageAdded
+            .then(() => {
+                const [minCutHiPS, maxCutHiPS] = self.view.wasm.addGeneratedHiPS(self._getCfg());
+                self.added = true;
+
+                // Set the cuts computed from the image unless given by the user
+                let [minCut, maxCut] = self.getCuts();
+                minCut = minCut || minCutHiPS;
+                maxCut = maxCut || maxCutHiPS;
+                self.setCuts(minCut, maxCut);
+
+                if (self.successCallback) {
+                    self.successCallback(self);
+                }
+
+                return self;
+            })
+            .catch((e) => {
+                console.error(`The HiPS could not be generated from ${self.image.id}`);
+                self.view.removeImageLayer(layer);
+
+                return Promise.reject(e);
+            });
+    };
+
+    return GeneratedHiPS;
+})();
@@ -0,0 +1,176 @@
+// Copyright 2013 - UDS/CNRS
+// The Aladin Lite program is distributed under the terms
+// of the GNU General Public License version 3.
+//
+// This file is part of Aladin Lite.
+//
+//    Aladin Lite is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, version 3 of the License.
+//
+//    Aladin Lite is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    The GNU General Public License is available in COPYING file
+//    along with Aladin Lite.
+//
+
+/******************************************************************************
+ * Aladin Lite project
+ *
+ * File HEALPixMap
+ *
+ * Authors: Thomas Boch & Matthieu Baumann [CDS]
+ *
+ *****************************************************************************/
+import { ColorCfg } from "./ColorCfg.js";
+import { HiPS } from "./HiPS.js";
+import { Utils } from "./Utils";
+
+/**
+ * @typedef {Object} HEALPixMapOptions
+ *
+ * @property {string} [name] - A human-readable name for the map
+ * @property {string} [column] - The name (TTYPE) of the column to display. The first column storing values by default
+ * @property {string} [colormap="grayscale"] - The colormap applied to the map
+ * @property {string} [stretch="linear"] - The stretch applied to the map. See {@link HiPS#setColormap}
+ * @property {number} [minCut] - The low cut. The 0.5% percentile of the map by default
+ * @property {number} [maxCut] - The high cut. The 99.5% percentile of the map by default
+ * @property {boolean} [reversed=false] - If true, the colormap is reversed
+ * @property {number} [opacity=1.0] - Opacity of the map (value between 0 and 1).
+ * @property {Function} [successCallback] - A callback executed once the map has been parsed
+ * @property {Function} [errorCallback] - A callback executed if the map could not be loaded
+ */
+
+export let HEALPixMap = (function () {
+    /**
+     * A full-sky or partial HEALPix map stored in a FITS binary table (e.g. the Planck maps)
+     *
+     * The map, given in the RING or NESTED scheme, is rendered as a HiPS whose tiles are
+     * generated in memory from it, without having to run hipsgen first.
+     *
+     * @class
+     * @constructs HEALPixMap
+     *
+     * @param {string|File|Blob} url - The url of the FITS file or the file itself
+     * @param {HEALPixMapOptions} [options] - The options of the map
+     */
+    function HEALPixMap(url, options) {
+        // Name of the layer
+        this.layer = null;
+        this.added = false;
+
+        this.url = url;
+        this.id = (typeof url === 'string') ? url : url.name;
+        this.name = (options && options.name) || this.id;
+        this.column = options && options.column;
+        // The HiPS rendering the map
+        this.creatorDid = 'ivo://aladin-lite/healpix-map?' + Utils.uuidv4();
+
+        // callbacks
+        this.successCallback = options && options.successCallback;
+        this.errorCallback = options && options.errorCallback;
+
+        this.imgFormat = 'fits';
+        this.formats = ['fits'];
+        this.longitudeReversed = false;
+
+        this.colorCfg = new ColorCfg(options);
+        this.options = options || {};
+
+        this.query = Promise.resolve(this);
+    };
+
+    HEALPixMap.prototype.setOpacity = HiPS.prototype.setOpacity;
+    HEALPixMap.prototype.setBlendingConfig = HiPS.prototype.setBlendingConfig;
+    HEALPixMap.prototype.setBlendMode = HiPS.prototype.setBlendMode;
+    HEALPixMap.prototype.setClip = HiPS.prototype.setClip;
+    HEALPixMap.prototype.setColormap = HiPS.prototype.setColormap;
+    HEALPixMap.prototype.setCuts = HiPS.prototype.setCuts;
+    HEALPixMap.prototype.getCuts = HiPS.prototype.getCuts;
+    HEALPixMap.prototype.setGamma = HiPS.prototype.setGamma;
+    HEALPixMap.prototype.setSaturation = HiPS.prototype.setSaturation;
+    HEALPixMap.prototype.setBrightness = HiPS.prototype.setBrightness;
+    HEALPixMap.prototype.setContrast = HiPS.prototype.setContrast;
+    HEALPixMap.prototype.setOptions = HiPS.prototype.setOptions;
+    HEALPixMap.prototype.toggle = HiPS.prototype.toggle;
+    HEALPixMap.prototype.getColorCfg = HiPS.prototype.getColorCfg;
+    HEALPixMap.prototype.getOpacity = HiPS.prototype.getOpacity;
+    HEALPixMap.prototype.getAlpha = HiPS.prototype.getOpacity;
+    HEALPixMap.prototype.readPixel = HiPS.prototype.readPixel;
+    HEALPixMap.prototype.readPixelFromWorld = HiPS.prototype.readPixelFromWorld;
+    HEALPixMap.prototype.getFITSCutout = HiPS.prototype.getFITSCutout;
+
+    // A HEALPix map is given on the celestial sphere
+    HEALPixMap.prototype.isPlanetaryBody = function () {
+        return false;
+    };
+
+    /** PRIVATE METHODS **/
+    HEALPixMap.prototype._setView = function (view) {
+        this.view = view;
+    };
+
+    // A HEALPix map is not stored in the HiPS cache
+    HEALPixMap.prototype._saveInCache = function () {};
+
+    // Private method for updating the backend with the new meta
+    HEALPixMap.prototype._updateMetadata = HiPS.prototype._updateMetadata;
+
+    HEALPixMap.prototype._getCfg = function () {
+        return {
+            layer: this.layer,
+            creatorDid: this.creatorDid,
+            column: this.column,
+            meta: {
+                ...this.colorCfg.get(),
+                longitudeReversed: this.longitudeReversed,
+                imgFormat: this.imgFormat,
+            }
+        };
+    };
+
+    HEALPixMap.prototype._add = function (layer) {
+        this.layer = layer;
+
+        let self = this;
+        let bytes;
+        if (this.url instanceof Blob) {
+            bytes = this.url.arrayBuffer();
+        } else {
+            bytes = Utils.fetch({
+                url: this.url,
+                dataType: 'blob',
+                desc: 'Fetching the HEALPix map: ' + this.name,
+            }).then((blob) => blob.arrayBuffer());
+        }
+
+        return bytes
+            .then((buf) => {
+                const [minCutMap, maxCutMap] = self.view.wasm.addHEALPixMap(self._getCfg(), new Uint8Array(buf));
+                self.added = true;
+
+                // Set the cuts computed from the map unless given by the user
+                let [minCut, maxCut] = self.getCuts();
+                minCut = minCut || minCutMap;
+                maxCut = maxCut || maxCutMap;
+                self.setCuts(minCut, maxCut);
+
+                if (self.successCallback) {
+                    self.successCallback(self);
+                }
+
+                return self;
+            })
+            .catch((e) => {
+                console.error(`HEALPix map located at ${self.id} could not be parsed`);
+                self.view.removeImageLayer(layer);
+
+                return Promise.reject(e);
+            });
+    };
+
+    return HEALPixMap;
+})();
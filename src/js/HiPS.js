@@ -157,10 +157,14 @@ PropertyParser.isPlanetaryBody = function (properties) {
  * @property {boolean} [longitudeReversed=false] - Deprecated since 3.6.1: Set it to True for planetary survey visualization 
  * @property {number} [opacity=1.0] - Opacity of the survey or image (value between 0 and 1).
  * @property {string} [colormap="native"] - The colormap configuration for the survey or image.
- * @property {string} [stretch="linear"] - The stretch configuration for the survey or image.
+ * @property {string} [stretch="linear"] - The stretch configuration for the survey or image. One of 'linear', 'sqrt', 'log', 'asinh', 'pow2' (or 'square'), 'sinh', 'pow' and 'histogram' (equalization of the pixels in view). Unknown names throw an error.
+ * @property {number} [stretchParam] - The exponent of 'pow' (2 by default) or the softening parameter of 'asinh' (0.1 by default) and 'sinh' (1/3 by default).
  * @property {boolean} [reversed=false] - If true, the colormap is reversed; otherwise, it is not reversed.
  * @property {number} [minCut] - The minimum cut value for the color configuration. If not given, 0.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
  * @property {number} [maxCut] - The maximum cut value for the color configuration. If not given, 1.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
+ * @property {string|Object} [autoCuts] - The algorithm computing the cuts from the pixels in view. See {@link HiPS#setAutoCuts}
+ * @property {MOC|string|Object} [clip] - Restricts the rendering to the inside or the outside of a MOC. See {@link HiPS#setClip}
+ * @property {string} [blendMode="normal"] - How its colors are combined with the ones of the layers below. See {@link HiPS#setBlendMode}
  * @property {boolean} [additive=false] - If true, additive blending is applied; otherwise, it is not applied.
  * @property {number} [gamma=1.0] - The gamma correction value for the color configuration.
  * @property {number} [saturation=0.0] - The saturation value for the color configuration.
@@ -596,6 +600,19 @@ export let HiPS = (function () {
         this.setOptions({additive});
     };
 
+    /**
+     * Sets how the colors of the HiPS are combined with the ones of the layers below it
+     *
+     * @memberof HiPS
+     *
+     * @param {string|null} blendMode - 'normal' (or null), 'min' and 'max' keep per channel the minimum or maximum of both colors,
+     * 'screen', 'multiply', 'overlay', 'lighten' and 'darken' are computed like in image editors.
+     * 'min' and 'max' ignore the opacity of the HiPS while the other modes are applied in proportion to it.
+     */
+    HiPS.prototype.setBlendMode = function (blendMode) {
+        this.setOptions({blendMode});
+    };
+
     /**
      * Sets the colormap when rendering the HiPS.
      *
@@ -628,7 +645,8 @@ export let HiPS = (function () {
      * <br>"green"
      * <br>"blue"
      * @param {Object} [options] - Options for the colormap
-     * @param {string} [options.stretch] - Stretching function of the colormap. Possible values are 'linear', 'asinh', 'log', 'sqrt', 'pow'. If no given, will not change it.
+     * @param {string} [options.stretch] - Stretching function of the colormap. Possible values are 'linear', 'sqrt', 'log', 'asinh', 'pow2' (or 'square'), 'sinh', 'pow' and 'histogram' (equalization of the pixels in view). Unknown names throw an error. If no given, will not change it.
+     * @param {number} [options.stretchParam] - The exponent of 'pow' (2 by default) or the softening parameter of 'asinh' (0.1 by default) and 'sinh' (1/3 by default). It is reset to its default when the stretch changes.
      * @param {boolean} [options.reversed=false] - Reverse the colormap axis.
      */
     HiPS.prototype.setColormap = function (colormap, options) {
@@ -657,9 +675,45 @@ export let HiPS = (function () {
      * @returns {number[]} The low and high cut values for the HiPS.
      */
     HiPS.prototype.getCuts = function () {
+        // The cuts computed from the pixels in view are given by the core
+        if (this.colorCfg.getAutoCuts() && this.added) {
+            const color = this.view.wasm.getImageMetadata(this.layer).color;
+            this.colorCfg.setCuts(color.minCut, color.maxCut);
+        }
+
         return this.colorCfg.getCuts();
     };
 
+    /**
+     * Computes the cuts from the pixels in the view. They are updated each time the view stops moving.
+     *
+     * Only FITS tiles and FITS images are considered.
+     *
+     * @memberof HiPS
+     *
+     * @param {string|Object|null} autoCuts - The algorithm: 'minmax', 'zscale', 'percentile' (1% - 99% by default)
+     * or 'sigmaclip'. Its parameters can be given with an object, e.g. <code>{method: 'percentile', low: 0.5, high: 99.5}</code>,
+     * <code>{method: 'zscale', contrast: 0.25}</code> or <code>{method: 'sigmaclip', sigma: 3, maxIterations: 5}</code>.
+     * null goes back to the cuts set by {@link HiPS#setCuts}
+     */
+    HiPS.prototype.setAutoCuts = function (autoCuts) {
+        this.setOptions({autoCuts})
+    };
+
+    /**
+     * Restricts the rendering of the HiPS to the inside or the outside of a MOC.
+     *
+     * @memberof HiPS
+     *
+     * @param {MOC|string|Object|null} clip - The MOC added to the view or its uuid. Options can be given with an object
+     * e.g. <code>{moc: moc, outside: true, clippedOpacity: 0.2}</code>: <code>outside</code> keeps the outside of the MOC instead of its inside,
+     * <code>clippedOpacity</code> (0 by default) dims the clipped part instead of hiding it. null renders the whole HiPS again.
+     * The HiPS is not clipped while the MOC is not in the view.
+     */
+    HiPS.prototype.setClip = function (clip) {
+        this.setOptions({clip})
+    };
+
     /**
      * Sets the gamma correction factor for the HiPS.
      *
@@ -751,10 +805,14 @@ export let HiPS = (function () {
     * @param {Object} options
     * @param {number} [options.opacity=1.0] - Opacity of the survey or image (value between 0 and 1).
     * @param {string} [options.colormap="native"] - The colormap configuration for the survey or image.
-    * @param {string} [options.stretch="linear"] - The stretch configuration for the survey or image.
+    * @param {string} [options.stretch="linear"] - The stretch configuration for the survey or image. See {@link HiPS#setColormap}
+    * @param {number} [options.stretchParam] - The parameter of the stretch. See {@link HiPS#setColormap}
     * @param {boolean} [options.reversed=false] - If true, the colormap is reversed; otherwise, it is not reversed.
     * @param {number} [options.minCut] - The minimum cut value for the color configuration. If not given, 0.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
     * @param {number} [options.maxCut] - The maximum cut value for the color configuration. If not given, 1.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
+    * @param {string|Object|null} [options.autoCuts] - The algorithm computing the cuts from the pixels in view. See {@link HiPS#setAutoCuts}
+    * @param {MOC|string|Object|null} [options.clip] - Restricts the rendering to the inside or the outside of a MOC. See {@link HiPS#setClip}
+    * @param {string|null} [options.blendMode] - How its colors are combined with the ones of the layers below. See {@link HiPS#setBlendMode}
     * @param {boolean} [options.additive=false] - If true, additive blending is applied; otherwise, it is not applied.
     * @param {number} [options.gamma=1.0] - The gamma correction value for the color configuration.
     * @param {number} [options.saturation=0.0] - The saturation value for the color configuration.
@@ -818,16 +876,169 @@ export let HiPS = (function () {
     /**
      * Read a specific screen pixel value
      * 
-     * @todo This has not yet been implemented
      * @memberof HiPS
      * @param {number} x - x axis in screen pixels to probe
      * @param {number} y - y axis in screen pixels to probe
-     * @returns {number} the value of that pixel
+     * @returns {number|number[]} the value of that pixel, an array of rgb(a) components for JPG/PNG tiles
      */
     HiPS.prototype.readPixel = function (x, y) {
         return this.view.wasm.readPixel(x, y, this.layer);
     };
 
+    /**
+     * Read the pixel value at a specific sky position
+     * 
+     * @memberof HiPS
+     * @param {number} lon - longitude in degrees of the position to probe
+     * @param {number} lat - latitude in degrees of the position to probe
+     * @param {string} [frame='icrs'] - frame of the position. See {@link CooFrameEnum}
+     * @returns {number|number[]} the value of that pixel, an array of rgb(a) components for JPG/PNG tiles
+     */
+    HiPS.prototype.readPixelFromWorld = function (lon, lat, frame) {
+        if (frame) {
+            frame = CooFrameEnum.fromString(frame, CooFrameEnum.ICRS);
+            frame = Aladin.wasmLibs.core.CooSystem[frame.system];
+        }
+
+        return this.view.wasm.readPixelFromWorld(lon, lat, this.layer, frame);
+    };
+
+    /**
+     * Read the pixel values at several sky positions
+     * 
+     * The tiles containing the positions are fetched if they have not been received yet,
+     * even if they are not in the view.
+     * 
+     * @memberof HiPS
+     * @param {number[]} lon - longitudes in degrees of the positions to probe
+     * @param {number[]} lat - latitudes in degrees of the positions to probe
+     * @param {Object} [options] - Options
+     * @param {string} [options.frame='icrs'] - frame of the positions. See {@link CooFrameEnum}
+     * @param {number} [options.order] - order of the tiles to sample. The deepest order of the HiPS by default
+     * @returns {Promise<Array>} the values of the pixels as returned by {@link HiPS#readPixel}, null for the positions that could not be read
+     */
+    HiPS.prototype.readPixelsFromWorld = function (lon, lat, options) {
+        options = options || {};
+
+        let frame = options.frame;
+        if (frame) {
+            frame = CooFrameEnum.fromString(frame, CooFrameEnum.ICRS);
+            frame = Aladin.wasmLibs.core.CooSystem[frame.system];
+        }
+
+        return this.view.wasm.readPixelsFromWorld(
+            new Float64Array(lon),
+            new Float64Array(lat),
+            this.layer,
+            frame,
+            options.order
+        );
+    };
+
+    /**
+     * Compute the statistics of the pixels lying in a sky region
+     *
+     * Blank pixels are not taken into account and the mean of the red, green and blue components
+     * is used for JPG/PNG tiles. For big regions, the pixels are sampled at a lower resolution.
+     *
+     * @memberof HiPS
+     * @param {Circle|Polyline|MOC|Object} region - A circle, a closed polyline or a MOC already added to the view.
+     * A plain object can also be given: <code>{type: "circle", ra, dec, radius}</code>,
+     * <code>{type: "box", ra, dec, width, height, angle}</code>, <code>{type: "polygon", vertices: [[ra, dec], ...]}</code>
+     * or <code>{type: "moc", uuid}</code>, all angles being in degrees
+     * @param {Object} [options] - Options
+     * @param {string} [options.frame='icrs'] - frame of a region given as a plain object. See {@link CooFrameEnum}
+     * @param {number} [options.order] - order of the tiles to sample. The deepest order of the HiPS by default
+     * @param {number} [options.bins=100] - number of bins of the histogram
+     * @returns {Promise<Object>} the <code>count</code>, <code>sum</code>, <code>mean</code>, <code>median</code>,
+     * <code>std</code>, <code>min</code> and <code>max</code> of the pixel values along with their
+     * <code>histogram</code> given as its bin <code>edges</code> and <code>counts</code>
+     */
+    HiPS.prototype.getStatistics = function (region, options) {
+        options = options || {};
+
+        let frame = options.frame;
+        if (region.centerRaDec && region.radiusDegrees !== undefined) {
+            // Circle
+            frame = region.frame;
+            region = {type: "circle", ra: region.centerRaDec[0], dec: region.centerRaDec[1], radius: region.radiusDegrees};
+        } else if (region.raDecArray) {
+            // Polyline
+            frame = region.frame;
+            region = {type: "polygon", vertices: region.raDecArray};
+        } else if (region.uuid && !region.type) {
+            // MOC
+            region = {type: "moc", uuid: region.uuid};
+        }
+
+        if (frame) {
+            frame = CooFrameEnum.fromString(frame, CooFrameEnum.ICRS);
+            frame = Aladin.wasmLibs.core.CooSystem[frame.system];
+        }
+
+        return this.view.wasm.getRegionStatistics(region, this.layer, frame, options.order, options.bins);
+    };
+
+    /**
+     * Sample the pixels along a great-circle segment
+     *
+     * @memberof HiPS
+     * @param {number[]} start - [lon, lat] start of the segment in degrees
+     * @param {number[]} end - [lon, lat] end of the segment in degrees
+     * @param {Object} [options] - Options
+     * @param {string} [options.frame='icrs'] - frame of the segment. See {@link CooFrameEnum}
+     * @param {number} [options.order] - order of the tiles to sample. The deepest order of the HiPS by default
+     * @param {number} [options.samples] - number of samples. By default, the segment is sampled at the resolution of the layer
+     * @returns {Promise<Object>} the <code>lon</code> and <code>lat</code> of the samples, their angular distance <code>dist</code>
+     * from the start (all in degrees) and their <code>values</code>, null for blank pixels
+     */
+    HiPS.prototype.getLineProfile = function (start, end, options) {
+        options = options || {};
+
+        let frame = options.frame;
+        if (frame) {
+            frame = CooFrameEnum.fromString(frame, CooFrameEnum.ICRS);
+            frame = Aladin.wasmLibs.core.CooSystem[frame.system];
+        }
+
+        return this.view.wasm.getLineProfile(
+            new Float64Array(start),
+            new Float64Array(end),
+            this.layer,
+            frame,
+            options.order,
+            options.samples
+        );
+    };
+
+    /**
+     * Reproject the tiles into a FITS image
+     *
+     * The tiles covering the image are fetched if they have not been received yet.
+     * The pixels are stored with the BITPIX, BSCALE, BZERO and BLANK of the tiles and the header
+     * contains the WCS of the image. Only FITS tiles can be reprojected.
+     *
+     * @memberof HiPS
+     * @param {Object} [options] - Options
+     * @param {Object} [options.wcs] - WCS of the image given as FITS keywords (NAXIS1, NAXIS2, CTYPE1, CRPIX1, CRVAL1, CD1_1...).
+     * The WCS of the view by default, see {@link Aladin#getViewWCS}
+     * @param {number} [options.order] - order of the tiles to sample. By default, the one whose pixels are as small as the ones of the image
+     * @returns {Promise<Uint8Array>} the bytes of the FITS file
+     *
+     * @example
+     * aladin.getBaseImageLayer().getFITSCutout().then((bytes) => {
+     *     const url = URL.createObjectURL(new Blob([bytes], {type: "application/fits"}));
+     *     window.open(url);
+     * });
+     */
+    HiPS.prototype.getFITSCutout = function (options) {
+        options = options || {};
+
+        const wcs = options.wcs || this.view.aladin.getViewWCS();
+
+        return this.view.wasm.getFITSCutout(this.layer, wcs, options.order);
+    };
+
     HiPS.prototype._setView = function (view) {
         let self = this;
 
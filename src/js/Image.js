@@ -69,10 +69,14 @@ import { HiPS } from "./HiPS.js";
  * @property {Function} [errorCallback] - A callback executed when the FITS could not be loaded
  * @property {number} [opacity=1.0] - Opacity of the survey or image (value between 0 and 1).
  * @property {string} [colormap="native"] - The colormap configuration for the survey or image.
- * @property {string} [stretch="linear"] - The stretch configuration for the survey or image.
+ * @property {string} [stretch="linear"] - The stretch configuration for the survey or image. One of 'linear', 'sqrt', 'log', 'asinh', 'pow2' (or 'square'), 'sinh', 'pow' and 'histogram' (equalization of the pixels in view). Unknown names throw an error.
+ * @property {number} [stretchParam] - The exponent of 'pow' (2 by default) or the softening parameter of 'asinh' (0.1 by default) and 'sinh' (1/3 by default).
  * @property {boolean} [reversed=false] - If true, the colormap is reversed; otherwise, it is not reversed.
  * @property {number} [minCut=0.0] - The minimum cut value for the color configuration. If not given, 0.0 is chosen
  * @property {number} [maxCut=1.0] - The maximum cut value for the color configuration. If not given, 1.0 is chosen
+ * @property {string|Object} [autoCuts] - The algorithm computing the cuts from the pixels in view. See {@link Image#setAutoCuts}
+ * @property {MOC|string|Object} [clip] - Restricts the rendering to the inside or the outside of a MOC. See {@link Image#setClip}
+ * @property {string} [blendMode="normal"] - How its colors are combined with the ones of the layers below. See {@link Image#setBlendMode}
  * @property {boolean} [additive=false] - If true, additive blending is applied; otherwise, it is not applied.
  * @property {number} [gamma=1.0] - The gamma correction value for the color configuration.
  * @property {number} [saturation=0.0] - The saturation value for the color configuration.
@@ -179,7 +183,8 @@ export let Image = (function () {
         * @param {Object} options
         * @param {number} [options.opacity=1.0] - Opacity of the survey or image (value between 0 and 1).
         * @param {string} [options.colormap="native"] - The colormap configuration for the survey or image.
-        * @param {string} [options.stretch="linear"] - The stretch configuration for the survey or image.
+        * @param {string} [options.stretch="linear"] - The stretch configuration for the survey or image. See {@link HiPS#setColormap}
+        * @param {number} [options.stretchParam] - The parameter of the stretch. See {@link HiPS#setColormap}
         * @param {boolean} [options.reversed=false] - If true, the colormap is reversed; otherwise, it is not reversed.
         * @param {number} [options.minCut] - The minimum cut value for the color configuration. If not given, 0.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
         * @param {number} [options.maxCut] - The maximum cut value for the color configuration. If not given, 1.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
@@ -193,6 +198,15 @@ export let Image = (function () {
         // @api
         Image.prototype.setBlendingConfig = HiPS.prototype.setBlendingConfig;
 
+        /**
+         * Sets how the colors of the image are combined with the ones of the layers below it
+         *
+         * @memberof Image
+         * @method
+         * @param {string|null} blendMode - The blend mode, see {@link HiPS#setBlendMode}
+         */
+        Image.prototype.setBlendMode = HiPS.prototype.setBlendMode;
+
         /**
          * Set the colormap of an image
          *
@@ -224,7 +238,8 @@ export let Image = (function () {
         * <br>"green"
         * <br>"blue"
         * @param {Object} [options] - Options for the colormap
-        * @param {string} [options.stretch] - Stretching function of the colormap. Possible values are 'linear', 'asinh', 'log', 'sqrt', 'pow'. If no given, will not change it.
+        * @param {string} [options.stretch] - Stretching function of the colormap. Possible values are 'linear', 'sqrt', 'log', 'asinh', 'pow2' (or 'square'), 'sinh', 'pow' and 'histogram' (equalization of the pixels in view). Unknown names throw an error. If no given, will not change it.
+        * @param {number} [options.stretchParam] - The exponent of 'pow' (2 by default) or the softening parameter of 'asinh' (0.1 by default) and 'sinh' (1/3 by default). It is reset to its default when the stretch changes.
         * @param {boolean} [options.reversed=false] - Reverse the colormap axis.
          */
         Image.prototype.setColormap = HiPS.prototype.setColormap;
@@ -239,6 +254,24 @@ export let Image = (function () {
          */
         Image.prototype.setCuts = HiPS.prototype.setCuts;
 
+        /**
+         * Computes the cuts from the pixels of the image in the view. They are updated each time the view stops moving.
+         *
+         * @memberof Image
+         * @method
+         * @param {string|Object|null} autoCuts - The algorithm, see {@link HiPS#setAutoCuts}
+         */
+        Image.prototype.setAutoCuts = HiPS.prototype.setAutoCuts;
+
+        /**
+         * Restricts the rendering of the image to the inside or the outside of a MOC.
+         *
+         * @memberof Image
+         * @method
+         * @param {MOC|string|Object|null} clip - The MOC or its options, see {@link HiPS#setClip}
+         */
+        Image.prototype.setClip = HiPS.prototype.setClip;
+
         /**
          * Sets the gamma correction factor.
          *
@@ -320,15 +353,71 @@ export let Image = (function () {
         /**
          * Read a specific screen pixel value
          * 
-         * @todo This has not yet been implemented
+         * The value of a FITS pixel is its physical value, i.e. BZERO + BSCALE * stored value,
+         * and is null for BLANK pixels.
+         * 
          * @memberof Image
          * @method
          * @param {number} x - x axis in screen pixels to probe
          * @param {number} y - y axis in screen pixels to probe
-         * @returns {number} the value of that pixel
+         * @returns {{value: number|number[]|null, x: number, y: number}} the value of that pixel and its position in the image, (0, 0) being the first pixel stored
          */
         Image.prototype.readPixel = HiPS.prototype.readPixel;
 
+        /**
+         * Read the pixel value at a specific sky position
+         * 
+         * @memberof Image
+         * @method
+         * @param {number} lon - longitude in degrees of the position to probe
+         * @param {number} lat - latitude in degrees of the position to probe
+         * @param {string} [frame='icrs'] - frame of the position. See {@link CooFrameEnum}
+         * @returns {{value: number|number[]|null, x: number, y: number}} the value of that pixel and its position in the image, (0, 0) being the first pixel stored
+         */
+        Image.prototype.readPixelFromWorld = HiPS.prototype.readPixelFromWorld;
+
+        /**
+         * Read the pixel values at several sky positions
+         * 
+         * @memberof Image
+         * @method
+         * @param {number[]} lon - longitudes in degrees of the positions to probe
+         * @param {number[]} lat - latitudes in degrees of the positions to probe
+         * @param {Object} [options] - Options
+         * @param {string} [options.frame='icrs'] - frame of the positions. See {@link CooFrameEnum}
+         * @returns {Promise<Array>} the pixels as returned by {@link Image#readPixel}, null for the positions outside the image
+         */
+        Image.prototype.readPixelsFromWorld = HiPS.prototype.readPixelsFromWorld;
+
+        /**
+         * Compute the statistics of the pixels lying in a sky region
+         * 
+         * Every pixel of the image lying in the region is taken into account once, blank pixels excepted.
+         * 
+         * @memberof Image
+         * @method
+         * @param {Circle|Polyline|MOC|Object} region - The region. See {@link HiPS#getStatistics}
+         * @param {Object} [options] - Options
+         * @param {string} [options.frame='icrs'] - frame of a region given as a plain object. See {@link CooFrameEnum}
+         * @param {number} [options.bins=100] - number of bins of the histogram
+         * @returns {Promise<Object>} the statistics as returned by {@link HiPS#getStatistics}
+         */
+        Image.prototype.getStatistics = HiPS.prototype.getStatistics;
+
+        /**
+         * Sample the pixels along a great-circle segment
+         * 
+         * @memberof Image
+         * @method
+         * @param {number[]} start - [lon, lat] start of the segment in degrees
+         * @param {number[]} end - [lon, lat] end of the segment in degrees
+         * @param {Object} [options] - Options
+         * @param {string} [options.frame='icrs'] - frame of the segment. See {@link CooFrameEnum}
+         * @param {number} [options.samples] - number of samples. By default, the segment is sampled at the resolution of the image
+         * @returns {Promise<Object>} the profile as returned by {@link HiPS#getLineProfile}
+         */
+        Image.prototype.getLineProfile = HiPS.prototype.getLineProfile;
+
        
         /** PRIVATE METHODS **/
         Image.prototype._setView = function (view) {
@@ -0,0 +1,218 @@
+// Copyright 2013 - UDS/CNRS
+// The Aladin Lite program is distributed under the terms
+// of the GNU General Public License version 3.
+//
+// This file is part of Aladin Lite.
+//
+//    Aladin Lite is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, version 3 of the License.
+//
+//    Aladin Lite is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    The GNU General Public License is available in COPYING file
+//    along with Aladin Lite.
+//
+
+/******************************************************************************
+ * Aladin Lite project
+ *
+ * File LayerArithmetic
+ *
+ * Authors: Thomas Boch & Matthieu Baumann [CDS]
+ *
+ *****************************************************************************/
+import { ColorCfg } from "./ColorCfg.js";
+import { HiPS } from "./HiPS.js";
+
+/**
+ * @typedef {Object} LayerArithmeticOptions
+ *
+ * @property {string} [name] - A human-readable name for the derived layer
+ * @property {string} [colormap="grayscale"] - The colormap applied to the result
+ * @property {string} [stretch="linear"] - The stretch applied to the result. See {@link HiPS#setColormap}
+ * @property {number} [minCut=0.0] - The low cut of the result
+ * @property {number} [maxCut=1.0] - The high cut of the result
+ * @property {boolean} [reversed=false] - If true, the colormap is reversed
+ * @property {number} [opacity=1.0] - Opacity of the derived layer (value between 0 and 1).
+ * @property {boolean} [additive=false] - If true, additive blending is applied; otherwise, it is not applied.
+ * @property {number} [gamma=1.0] - The gamma correction value applied to the result.
+ * @property {number} [saturation=0.0] - The saturation value applied to the result.
+ * @property {number} [brightness=0.0] - The brightness value applied to the result.
+ * @property {number} [contrast=0.0] - The contrast value applied to the result.
+ *
+ * @example
+ *
+ *  let epoch1 = A.HiPS('https://example.org/hips/epoch1', {imgFormat: 'fits'});
+ *  let epoch2 = A.HiPS('https://example.org/hips/epoch2', {imgFormat: 'fits'});
+ *
+ *  aladin.setOverlayImageLayer(A.layerArithmetic(epoch2, epoch1, 'difference', {
+ *      colormap: 'rdbu', minCut: -50, maxCut: 50
+ *  }), 'variability');
+ */
+
+export let LayerArithmetic = (function () {
+    /**
+     * A layer computing a per-pixel expression between two HiPS or images
+     *
+     * The operands are resampled on the screen, so they are matched pixel to pixel when they are HiPS
+     * of the same order and frame. Their physical values, i.e. scaled by BSCALE and BZERO, are combined.
+     * The cuts are not computed from the view and must be set on the derived layer.
+     * Rendering it requires the EXT_float_blend WebGL2 extension.
+     *
+     * @class
+     * @constructs LayerArithmetic
+     *
+     * @param {HiPS|Image} a - The layer of the operand A
+     * @param {HiPS|Image} b - The layer of the operand B
+     * @param {string} operation - 'difference' for A - B, 'ratio' for A / B or 'mask' for A × B.
+     * Where B is 0, the ratio and the masked layer are left transparent.
+     * @param {LayerArithmeticOptions} [options] - The options of the derived layer
+     */
+    function LayerArithmetic(a, b, operation, options) {
+        // Name of the layer
+        this.layer = null;
+        this.added = false;
+
+        this.operands = [a, b];
+        this.operation = LayerArithmetic.parseOperation(operation);
+        this.id = this.operation + ':' + this.operands.map((operand) => operand.id).join(',');
+        this.name = (options && options.name) || (a.name + ' ' + this.operation + ' ' + b.name);
+
+        // Not used by a derived layer but expected by the backend
+        this.imgFormat = 'fits';
+        this.longitudeReversed = false;
+
+        this.colorCfg = new ColorCfg({colormap: 'grayscale', ...options});
+        this.options = options || {};
+
+        let self = this;
+        // The derived layer is ready once its operands are
+        this.query = Promise.all(this.operands.map((operand) => operand.query))
+            .then(() => self);
+    };
+
+    LayerArithmetic.OPERATIONS = ['difference', 'ratio', 'mask'];
+
+    LayerArithmetic.parseOperation = function (operation) {
+        const op = operation && operation.toLowerCase();
+        if (!LayerArithmetic.OPERATIONS.includes(op)) {
+            throw 'Unknown layer operation ' + operation + '. Possible values are ' + LayerArithmetic.OPERATIONS.join(', ');
+        }
+
+        return op;
+    };
+
+    /**
+     * Sets the expression computed between the operands
+     *
+     * @memberof LayerArithmetic
+     * @param {string} operation - 'difference', 'ratio' or 'mask'
+     */
+    LayerArithmetic.prototype.setOperation = function (operation) {
+        this.operation = LayerArithmetic.parseOperation(operation);
+        this._updateLayer();
+    };
+
+    /**
+     * Get the layers of the operands
+     *
+     * @memberof LayerArithmetic
+     * @returns {Array} The HiPS or images of the operands A and B
+     */
+    LayerArithmetic.prototype.getOperands = function () {
+        return this.operands;
+    };
+
+    LayerArithmetic.prototype.setOpacity = HiPS.prototype.setOpacity;
+    LayerArithmetic.prototype.setBlendingConfig = HiPS.prototype.setBlendingConfig;
+    LayerArithmetic.prototype.setColormap = HiPS.prototype.setColormap;
+    LayerArithmetic.prototype.setCuts = HiPS.prototype.setCuts;
+    LayerArithmetic.prototype.getCuts = HiPS.prototype.getCuts;
+    LayerArithmetic.prototype.setGamma = HiPS.prototype.setGamma;
+    LayerArithmetic.prototype.setSaturation = HiPS.prototype.setSaturation;
+    LayerArithmetic.prototype.setBrightness = HiPS.prototype.setBrightness;
+    LayerArithmetic.prototype.setContrast = HiPS.prototype.setContrast;
+    LayerArithmetic.prototype.setOptions = HiPS.prototype.setOptions;
+    LayerArithmetic.prototype.toggle = HiPS.prototype.toggle;
+    LayerArithmetic.prototype.getColorCfg = HiPS.prototype.getColorCfg;
+    LayerArithmetic.prototype.getOpacity = HiPS.prototype.getOpacity;
+    LayerArithmetic.prototype.getAlpha = HiPS.prototype.getOpacity;
+
+    // A derived layer is not meant to be used for planetary data
+    LayerArithmetic.prototype.isPlanetaryBody = function () {
+        return false;
+    };
+
+    /** PRIVATE METHODS **/
+    LayerArithmetic.prototype._setView = function (view) {
+        this.view = view;
+    };
+
+    // A derived layer is not stored in the HiPS cache
+    LayerArithmetic.prototype._saveInCache = function () {};
+
+    // Private method for updating the backend with the new meta
+    LayerArithmetic.prototype._updateMetadata = HiPS.prototype._updateMetadata;
+
+    LayerArithmetic.prototype._getCfg = function () {
+        return {
+            layer: this.layer,
+            operands: this.operands.map((operand) => operand.layer),
+            operation: this.operation,
+            meta: {
+                ...this.colorCfg.get(),
+                longitudeReversed: this.longitudeReversed,
+                imgFormat: this.imgFormat,
+            }
+        };
+    };
+
+    LayerArithmetic.prototype._updateLayer = function () {
+        try {
+            if (this.added) {
+                this.view.wasm.addLayerArithmetic(this._getCfg());
+            }
+        } catch (e) {
+            // Display the error message
+            console.error(e);
+        }
+    };
+
+    LayerArithmetic.prototype._add = function (layer) {
+        this.layer = layer;
+
+        let self = this;
+        // The operands are added to the backend under their own layer
+        // but are only rendered through the derived layer
+        const suffixes = ['a', 'b'];
+        const operandsAdded = this.operands.map((operand, idx) => {
+            operand._setView(self.view);
+            return operand._add(layer + ':' + suffixes[idx]);
+        });
+
+        return Promise.all(operandsAdded)
+            .then(() => {
+                self.view.wasm.addLayerArithmetic(self._getCfg());
+                self.added = true;
+
+                return self;
+            })
+            .catch((e) => {
+                // Do not leave the operands already added in the backend
+                self.operands
+                    .filter((operand) => operand.added)
+                    .forEach((operand) => {
+                        self.view.wasm.removeLayer(operand.layer);
+                        operand.added = false;
+                    });
+
+                return Promise.reject(e);
+            });
+    };
+
+    return LayerArithmetic;
+})();
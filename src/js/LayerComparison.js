@@ -0,0 +1,213 @@
+// Copyright 2013 - UDS/CNRS
+// The Aladin Lite program is distributed under the terms
+// of the GNU General Public License version 3.
+//
+// This file is part of Aladin Lite.
+//
+//    Aladin Lite is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, version 3 of the License.
+//
+//    Aladin Lite is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    The GNU General Public License is available in COPYING file
+//    along with Aladin Lite.
+//
+
+/******************************************************************************
+ * Aladin Lite project
+ *
+ * File LayerComparison
+ *
+ * Authors: Thomas Boch & Matthieu Baumann [CDS]
+ *
+ *****************************************************************************/
+
+/**
+ * @typedef {Object} LayerComparisonOptions
+ *
+ * @property {string} [mode="blink"] - 'blink' shows the layers alternately, 'crossfade' fades the upper layer
+ * and 'swipe' shows A on the left of a movable vertical line and B on its right
+ * @property {number} [period=1] - The period in seconds of a blink cycle, each layer being shown during its half
+ * @property {number} [ratio=0.5] - The cross-fade ratio, 0 showing A and 1 showing B
+ * @property {number} [position=0.5] - The position of the swipe line as a fraction of the width of the view
+ *
+ * @example
+ *
+ *  aladin.setOverlayImageLayer(A.HiPS('CDS/P/DSS2/red'), 'dss');
+ *  aladin.setOverlayImageLayer(A.HiPS('CDS/P/2MASS/color'), '2mass');
+ *
+ *  let comparison = aladin.compareLayers('dss', '2mass', {mode: 'swipe'});
+ *  // later
+ *  comparison.stop();
+ */
+
+export let LayerComparison = (function () {
+    /**
+     * A comparison between two image layers of the view
+     *
+     * @class
+     * @constructs LayerComparison
+     *
+     * @param {View} view - The view in which the layers are
+     * @param {string} layerA - The name of the layer A
+     * @param {string} layerB - The name of the layer B
+     * @param {LayerComparisonOptions} [options] - The options of the comparison
+     */
+    function LayerComparison(view, layerA, layerB, options) {
+        this.view = view;
+        this.layers = [layerA, layerB];
+
+        this.mode = LayerComparison.parseMode(options && options.mode);
+        this.period = (options && options.period) || 1.0;
+        this.ratio = LayerComparison.clamp((options && options.ratio), 0.5);
+        this.position = LayerComparison.clamp((options && options.position), 0.5);
+
+        this.line = null;
+    };
+
+    LayerComparison.MODES = {
+        blink: 'blink',
+        crossfade: 'crossFade',
+        swipe: 'swipe',
+    };
+
+    LayerComparison.parseMode = function (mode) {
+        mode = mode || 'blink';
+
+        const m = LayerComparison.MODES[mode.toLowerCase()];
+        if (!m) {
+            throw mode + ' is not a valid comparison mode. Valid ones are: ' + Object.keys(LayerComparison.MODES).join(', ');
+        }
+
+        return m;
+    };
+
+    LayerComparison.clamp = function (value, defaultValue) {
+        if (!Number.isFinite(value)) {
+            return defaultValue;
+        }
+
+        return Math.max(0, Math.min(value, 1));
+    };
+
+    /**
+     * Sets the period of a blink cycle
+     *
+     * @memberof LayerComparison
+     * @param {number} period - The period in seconds
+     */
+    LayerComparison.prototype.setPeriod = function (period) {
+        this.period = period;
+        this._update();
+    };
+
+    /**
+     * Sets the cross-fade ratio
+     *
+     * @memberof LayerComparison
+     * @param {number} ratio - 0 shows A and 1 shows B
+     */
+    LayerComparison.prototype.setRatio = function (ratio) {
+        this.ratio = LayerComparison.clamp(ratio, this.ratio);
+        this._update();
+    };
+
+    /**
+     * Sets the position of the swipe line
+     *
+     * @memberof LayerComparison
+     * @param {number} position - The fraction of the width of the view on the left of the line
+     */
+    LayerComparison.prototype.setPosition = function (position) {
+        this.position = LayerComparison.clamp(position, this.position);
+        this._update();
+    };
+
+    /**
+     * Stops the comparison, the layers being rendered as before
+     *
+     * @memberof LayerComparison
+     */
+    LayerComparison.prototype.stop = function () {
+        this._removeLine();
+
+        if (this.view.layerComparison === this) {
+            this.view.layerComparison = null;
+            this.view.wasm.setLayerComparison(null);
+        }
+    };
+
+    /** PRIVATE METHODS **/
+    LayerComparison.prototype._getCfg = function () {
+        let mode = {type: this.mode};
+        if (this.mode === 'blink') {
+            mode.period = this.period;
+        } else if (this.mode === 'crossFade') {
+            mode.ratio = this.ratio;
+        } else {
+            mode.position = this.position;
+        }
+
+        return {layers: this.layers, mode};
+    };
+
+    LayerComparison.prototype._start = function () {
+        this.view.wasm.setLayerComparison(this._getCfg());
+
+        if (this.mode === 'swipe') {
+            this._addLine();
+        }
+    };
+
+    LayerComparison.prototype._update = function () {
+        if (this.view.layerComparison !== this) {
+            return;
+        }
+
+        this.view.wasm.setLayerComparison(this._getCfg());
+
+        if (this.line) {
+            this.line.style.left = (this.position * 100) + '%';
+        }
+    };
+
+    LayerComparison.prototype._addLine = function () {
+        let self = this;
+
+        this.line = document.createElement('div');
+        this.line.classList.add('aladin-swipe-line');
+        this.line.style.left = (this.position * 100) + '%';
+        this.view.aladinDiv.appendChild(this.line);
+
+        const onMove = function (e) {
+            const rect = self.view.aladinDiv.getBoundingClientRect();
+            self.setPosition((e.clientX - rect.left) / rect.width);
+        };
+        const onUp = function () {
+            document.removeEventListener('pointermove', onMove);
+            document.removeEventListener('pointerup', onUp);
+        };
+
+        this.line.addEventListener('pointerdown', function (e) {
+            // Do not move the view
+            e.preventDefault();
+            e.stopPropagation();
+
+            document.addEventListener('pointermove', onMove);
+            document.addEventListener('pointerup', onUp);
+        });
+    };
+
+    LayerComparison.prototype._removeLine = function () {
+        if (this.line) {
+            this.line.remove();
+            this.line = null;
+        }
+    };
+
+    return LayerComparison;
+})();
@@ -136,12 +136,23 @@ export let MOC = (function() {
         this.view = view;
         this.mocParams = new Aladin.wasmLibs.core.MOCOptions(this.uuid, this.opacity, this.lineWidth, this.perimeter, this.fill, this.edge, this.isShowing, this.color, this.fillColor);
 
-        this.promiseFetchData
+        this.promiseLoaded = this.promiseFetchData
+            .then((data) => {
+                if (data.operation) {
+                    // set operation between MOCs, waiting for them to be loaded
+                    return Promise.all(data.mocs.map((moc) => moc.promiseLoaded)).then(() => data);
+                }
+
+                return data;
+            })
             .then((data) => {
                 if (data instanceof ArrayBuffer) {
                     // from an url
                     const buf = data;
                     self.view.wasm.addFITSMOC(self.mocParams, new Uint8Array(buf));
+                } else if (data.region) {
+                    // region given with its type
+                    self.view.wasm.addRegionMOC(self.mocParams, data.region, data.order, !!data.complement);
                 } else if(data.ra && data.dec && data.radius) {
                     // circle
                     const c = data;
@@ -150,6 +161,13 @@ export let MOC = (function() {
                     // polygon
                     const p = data;
                     self.view.wasm.addPolyMOC(self.mocParams, p.ra, p.dec);
+                } else if (data.operation) {
+                    // operation between MOCs
+                    if (!data.mocs.every((moc) => moc.ready && moc.view === self.view)) {
+                        throw "the MOCs of the " + data.operation + " must be added to the same aladin instance";
+                    }
+
+                    self.view.wasm.addOperationMOC(self.mocParams, data.operation, data.mocs.map((moc) => moc.uuid));
                 } else {
                     // json moc
                     self.view.wasm.addJSONMoc(self.mocParams, data);
@@ -164,6 +182,8 @@ export let MOC = (function() {
 
                 // Cache the sky fraction
                 self.skyFrac = self.view.wasm.getMOCSkyFraction(this.uuid);
+                // and the times covered by a space-time MOC
+                self.timeRange = self.view.wasm.getMOCTimeRange(this.uuid);
 
                 // Add it to the view
                 self.view.mocs.push(self);
@@ -284,18 +304,92 @@ export let MOC = (function() {
         return this.view.wasm.mocContains(this.uuid, ra, dec);
     };
 
+    /**
+     * Get the times covered by a space-time MOC (ST-MOC)
+     *
+     * @memberof MOC
+     *
+     * @returns {number[]|undefined} The first and last times covered in julian days, undefined for a spatial MOC
+     */
+    MOC.prototype.getTimeRange = function() {
+        return this.timeRange ? [this.timeRange[0], this.timeRange[1]] : undefined;
+    };
+
+    /**
+     * Display the sky areas covered by a space-time MOC (ST-MOC) between two times,
+     * e.g. the fields a survey observed during a given night
+     *
+     * @memberof MOC
+     * @param {number} jdMin - The start of the time range in julian days
+     * @param {number} jdMax - The end of the time range in julian days
+     */
+    MOC.prototype.setTimeRange = function(jdMin, jdMax) {
+        if (!this.ready) {
+            throw this.name + " is not yet ready, either because it has not been downloaded yet or because it has not been added to the aladin instance."
+        }
+
+        this.view.wasm.setMOCTimeRange(this.uuid, jdMin, jdMax);
+        this._reportCoverageChange();
+    };
+
+    /**
+     * Display the sky areas covered by a space-time MOC (ST-MOC) during the times of a time MOC (T-MOC)
+     *
+     * @memberof MOC
+     * @param {string|ArrayBuffer|Object} tmoc - The url of a FITS T-MOC, the content of a FITS T-MOC or a T-MOC as a JSON-like dictionary
+     *
+     * @returns {Promise<number[][]>} A promise resolved with the time ranges of the T-MOC in julian days
+     */
+    MOC.prototype.setTimeMOC = function(tmoc) {
+        if (!this.ready) {
+            throw this.name + " is not yet ready, either because it has not been downloaded yet or because it has not been added to the aladin instance."
+        }
+
+        let promiseData;
+        if (typeof tmoc === 'string' || tmoc instanceof String) {
+            promiseData = Utils.fetch({url: tmoc, method: 'GET', dataType: 'blob'})
+                .then((blob) => blob.arrayBuffer());
+        } else {
+            promiseData = Promise.resolve(tmoc);
+        }
+
+        return promiseData.then((data) => {
+            if (data instanceof ArrayBuffer) {
+                data = new Uint8Array(data);
+            }
+
+            const ranges = this.view.wasm.setMOCTimeMOC(this.uuid, data);
+            this._reportCoverageChange();
+
+            let jdRanges = [];
+            for (let i = 0; i < ranges.length; i += 2) {
+                jdRanges.push([ranges[i], ranges[i + 1]]);
+            }
+
+            return jdRanges;
+        });
+    };
+
+    MOC.prototype._reportCoverageChange = function() {
+        this.skyFrac = this.view.wasm.getMOCSkyFraction(this.uuid);
+        this.view.requestRedraw();
+    };
+
      /**
      * Serialize a MOC into different format
      *
      * @memberof MOC
-     * @param {string} [format='json'] - The output format. Only `json` is currently supported but `fits` could be added.
+     * @param {string} [format='json'] - The output format: <code>json</code> for the Aladin JSON format, <code>ascii</code>
+     * for the IVOA ASCII one (e.g. "3/1-4 8/") or <code>fits</code> for an IVOA MOC 2.0 FITS file
+     *
+     * @returns {string|Uint8Array} The serialized MOC, the bytes of the file for the <code>fits</code> format
      */
     MOC.prototype.serialize = function(format) {
         if (!this.ready) {
             throw this.name + " is not yet ready, either because it has not been downloaded yet or because it has not been added to the aladin instance."
         }
 
-        return this.view.wasm.mocSerialize(this.uuid, format);
+        return this.view.wasm.mocSerialize(this.uuid, format || 'json');
     }
 
     return MOC;
@@ -35,24 +35,25 @@ export let ProjectionEnum = {
    // TODO: fix why the projection disappears at fov = 360.0
    ZEA: {id: 4, fov: 1000, label: "Zenital equal-area"},	/* Equal-area 		         */
    //FEYE: {id: 5, fov: 190, label: "fish eye"},
-   //AIR: {id: 6, fov: 360, label: "airy"},
-   //AZP: {fov: 180},
-   //ARC: {id: 7, fov: 360, label: "zenital equidistant"},
+   AIR: {id: 6, fov: 360, label: "Airy"},
+   AZP: {id: 20, fov: 1000, label: "Zenital perspective"},
+   ARC: {id: 7, fov: 1000, label: "Zenital equidistant"},
    //NCP: {id: 8, fov: 180, label: "north celestial pole"},
+   ZPN: {id: 18, fov: 1000, label: "Zenital polynomial"},
    // Cylindrical
    MER: {id: 9, fov: 360, label: "Mercator"},
-   //CAR: {id: 10, fov: 360, label: "plate carrée"},
-   //CEA: {id: 11, fov: 360, label: "cylindrical equal area"},
+   CAR: {id: 10, fov: 360, label: "Plate carrée"},
+   CEA: {id: 11, fov: 360, label: "Cylindrical equal area"},
    //CYP: {id: 12, fov: 360, label: "cylindrical perspective"},
    // Pseudo-cylindrical
    AIT: {id: 13, fov: 1000, label: "Hammer-Aïtoff"},
-   //PAR: {id: 14, fov: 360, label: "parabolic"},
-   //SFL: {id: 15, fov: 360, label: "sanson-flamsteed"},
+   PAR: {id: 14, fov: 1000, label: "Parabolic"},
+   SFL: {id: 15, fov: 1000, label: "Sanson-Flamsteed"},
    MOL: {id: 16, fov: 1000, label: "Mollweide"},
    // Conic
-   //COD: {id: 17, fov: 360, label: "conic equidistant"},
+   COD: {id: 17, fov: 1000, label: "Conic equidistant"},
    // Hybrid
-   //HPX: {id: 19, fov: 360, label: "healpix"},
+   HPX: {id: 19, fov: 360, label: "HEALPix"},
 };
 
 /*
@@ -64,7 +65,7 @@ export let projectionNames = [
    "ZEA",	  // Equal-area 		         
    "FEYE",
    "AIR",
-   //"AZP",
+   "AZP",
    "ARC",
    "NCP",
    // Cylindrical
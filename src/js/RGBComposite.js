@@ -0,0 +1,225 @@
+// Copyright 2013 - UDS/CNRS
+// The Aladin Lite program is distributed under the terms
+// of the GNU General Public License version 3.
+//
+// This file is part of Aladin Lite.
+//
+//    Aladin Lite is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, version 3 of the License.
+//
+//    Aladin Lite is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    The GNU General Public License is available in COPYING file
+//    along with Aladin Lite.
+//
+
+/******************************************************************************
+ * Aladin Lite project
+ *
+ * File RGBComposite
+ *
+ * Authors: Thomas Boch & Matthieu Baumann [CDS]
+ *
+ *****************************************************************************/
+import { ColorCfg } from "./ColorCfg.js";
+import { HiPS } from "./HiPS.js";
+
+/**
+ * @typedef {Object} RGBCompositeOptions
+ *
+ * @property {string} [name="RGB composite"] - A human-readable name for the composite
+ * @property {number[]} [weights=[1, 1, 1]] - The factors applied to the red, green and blue channels
+ * @property {boolean|Object} [lupton=false] - Compose the channels following Lupton et al. (2004), i.e. scaling them by a common asinh stretch
+ * of their mean intensity so that the hue of bright sources is preserved. It can be an object giving the softening parameter e.g. {q: 8}.
+ * The channels are then rendered with a linear stretch between their cuts.
+ * @property {number} [opacity=1.0] - Opacity of the composite (value between 0 and 1).
+ * @property {boolean} [additive=false] - If true, additive blending is applied; otherwise, it is not applied.
+ * @property {number} [gamma=1.0] - The gamma correction value applied to the composite.
+ * @property {number} [saturation=0.0] - The saturation value applied to the composite.
+ * @property {number} [brightness=0.0] - The brightness value applied to the composite.
+ * @property {number} [contrast=0.0] - The contrast value applied to the composite.
+ *
+ * @example
+ *
+ *  let g = A.HiPS('CDS/P/PanSTARRS/DR1/g', {imgFormat: 'fits', autoCuts: 'zscale'});
+ *  let r = A.HiPS('CDS/P/PanSTARRS/DR1/r', {imgFormat: 'fits', autoCuts: 'zscale'});
+ *  let i = A.HiPS('CDS/P/PanSTARRS/DR1/i', {imgFormat: 'fits', autoCuts: 'zscale'});
+ *
+ *  aladin.setOverlayImageLayer(A.rgbComposite(i, r, g, {lupton: {q: 10}}), 'gri');
+ */
+
+export let RGBComposite = (function () {
+    /**
+     * A true-color layer composed from three single-band HiPS or images
+     *
+     * Each channel is rendered in grayscale with its own cuts, stretch and tonal corrections
+     * that can be changed on the channel objects.
+     *
+     * @class
+     * @constructs RGBComposite
+     *
+     * @param {HiPS|Image} red - The layer of the red channel
+     * @param {HiPS|Image} green - The layer of the green channel
+     * @param {HiPS|Image} blue - The layer of the blue channel
+     * @param {RGBCompositeOptions} [options] - The options of the composite
+     */
+    function RGBComposite(red, green, blue, options) {
+        // Name of the layer
+        this.layer = null;
+        this.added = false;
+
+        this.channels = [red, green, blue];
+        this.id = 'rgb:' + this.channels.map((channel) => channel.id).join(',');
+        this.name = (options && options.name) || "RGB composite";
+
+        this.weights = (options && options.weights) || [1.0, 1.0, 1.0];
+        this.lupton = RGBComposite.parseLupton(options && options.lupton);
+
+        // Not used by a composite but expected by the backend
+        this.imgFormat = 'png';
+        this.longitudeReversed = false;
+
+        this.colorCfg = new ColorCfg(options);
+        this.options = options || {};
+
+        let self = this;
+        // The composite is ready once its channels are
+        this.query = Promise.all(this.channels.map((channel) => channel.query))
+            .then(() => self);
+    };
+
+    RGBComposite.parseLupton = function (lupton) {
+        if (!lupton) {
+            return null;
+        }
+
+        if (lupton === true) {
+            return {};
+        }
+
+        return lupton;
+    };
+
+    /**
+     * Sets the factors applied to the channels
+     *
+     * @memberof RGBComposite
+     * @param {number[]} weights - The factors of the red, green and blue channels
+     */
+    RGBComposite.prototype.setWeights = function (weights) {
+        this.weights = weights;
+        this._updateComposite();
+    };
+
+    /**
+     * Enables or disables the composite following Lupton et al. (2004)
+     *
+     * @memberof RGBComposite
+     * @param {boolean|Object} lupton - false to disable it, true or an object giving the softening parameter e.g. {q: 8}
+     */
+    RGBComposite.prototype.setLupton = function (lupton) {
+        this.lupton = RGBComposite.parseLupton(lupton);
+        this._updateComposite();
+    };
+
+    /**
+     * Get the layers of the red, green and blue channels
+     *
+     * @memberof RGBComposite
+     * @returns {Array} The HiPS or images of the channels
+     */
+    RGBComposite.prototype.getChannels = function () {
+        return this.channels;
+    };
+
+    RGBComposite.prototype.setOpacity = HiPS.prototype.setOpacity;
+    RGBComposite.prototype.setBlendingConfig = HiPS.prototype.setBlendingConfig;
+    RGBComposite.prototype.setGamma = HiPS.prototype.setGamma;
+    RGBComposite.prototype.setSaturation = HiPS.prototype.setSaturation;
+    RGBComposite.prototype.setBrightness = HiPS.prototype.setBrightness;
+    RGBComposite.prototype.setContrast = HiPS.prototype.setContrast;
+    RGBComposite.prototype.setOptions = HiPS.prototype.setOptions;
+    RGBComposite.prototype.toggle = HiPS.prototype.toggle;
+    RGBComposite.prototype.getColorCfg = HiPS.prototype.getColorCfg;
+    RGBComposite.prototype.getOpacity = HiPS.prototype.getOpacity;
+    RGBComposite.prototype.getAlpha = HiPS.prototype.getOpacity;
+
+    // A composite is not meant to be used for planetary data
+    RGBComposite.prototype.isPlanetaryBody = function () {
+        return false;
+    };
+
+    /** PRIVATE METHODS **/
+    RGBComposite.prototype._setView = function (view) {
+        this.view = view;
+    };
+
+    // A composite is not stored in the HiPS cache
+    RGBComposite.prototype._saveInCache = function () {};
+
+    // Private method for updating the backend with the new meta
+    RGBComposite.prototype._updateMetadata = HiPS.prototype._updateMetadata;
+
+    RGBComposite.prototype._getCfg = function () {
+        return {
+            layer: this.layer,
+            channels: this.channels.map((channel) => channel.layer),
+            weights: this.weights,
+            lupton: this.lupton,
+            meta: {
+                ...this.colorCfg.get(),
+                longitudeReversed: this.longitudeReversed,
+                imgFormat: this.imgFormat,
+            }
+        };
+    };
+
+    RGBComposite.prototype._updateComposite = function () {
+        try {
+            if (this.added) {
+                this.view.wasm.addRGBComposite(this._getCfg());
+            }
+        } catch (e) {
+            // Display the error message
+            console.error(e);
+        }
+    };
+
+    RGBComposite.prototype._add = function (layer) {
+        this.layer = layer;
+
+        let self = this;
+        // The channels are added to the backend under their own layer
+        // but are only rendered through the composite
+        const suffixes = ['r', 'g', 'b'];
+        const channelsAdded = this.channels.map((channel, idx) => {
+            channel._setView(self.view);
+            return channel._add(layer + ':' + suffixes[idx]);
+        });
+
+        return Promise.all(channelsAdded)
+            .then(() => {
+                self.view.wasm.addRGBComposite(self._getCfg());
+                self.added = true;
+
+                return self;
+            })
+            .catch((e) => {
+                // Do not leave the channels already added in the backend
+                self.channels
+                    .filter((channel) => channel.added)
+                    .forEach((channel) => {
+                        self.view.wasm.removeLayer(channel.layer);
+                        channel.added = false;
+                    });
+
+                return Promise.reject(e);
+            });
+    };
+
+    return RGBComposite;
+})();
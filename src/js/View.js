@@ -214,6 +214,8 @@ export let View = (function () {
         this.imageLayers = new Map();
 
         this.overlayLayers = [];
+        // The comparison between two layers if any
+        this.layerComparison = null;
         // a dedicated catalog for the popup
         var c = document.createElement('canvas');
         c.width = c.height = 24;
@@ -311,6 +313,7 @@ export let View = (function () {
     View.DRAW_MOCS_WHILE_DRAGGING = true;
 
     View.CALLBACKS_THROTTLE_TIME_MS = 100; // minimum time between two consecutive callback calls
+    View.OBSERVER_TIME_UPDATE_MS = 10000; // period of the updates of the horizontal frame following the current time
 
 
     // (re)create needed canvases
@@ -1428,6 +1431,16 @@ export let View = (function () {
             this.wasm.drawGridLabels();
         }
 
+        // display the cardinal points of the horizon
+        if (this.horizonCfg && this.horizonCfg.enabled && this.horizonCfg.showLabels !== false) {
+            if (!this.catalogCanvasCleared) {
+                ctx.clearRect(0, 0, this.width, this.height);
+                this.catalogCanvasCleared = true;
+            }
+
+            this.wasm.drawHorizonLabels();
+        }
+
         if (this.mode === View.SELECT) {
             if (!this.catalogCanvasCleared) {
                 ctx.clearRect(0, 0, this.width, this.height);
@@ -1613,6 +1626,45 @@ export let View = (function () {
         return this.gridCfg;
     }
 
+    View.prototype.setHorizonOptions = function (options) {
+        this.horizonCfg = {...this.horizonCfg, ...options};
+        this.wasm.setHorizonOptions(this.horizonCfg);
+
+        this.mustClearCatalog = true;
+
+        this.requestRedraw();
+    };
+
+    View.prototype.setObserver = function (lon, lat, height, date) {
+        this.wasm.setObserver(lon, lat, height, (date || new Date()).getTime());
+        this.followObserverTime(!date);
+
+        this.requestRedraw();
+    };
+
+    View.prototype.setObserverTime = function (date) {
+        this.wasm.setObserverTime((date || new Date()).getTime());
+        this.followObserverTime(!date);
+
+        this.requestRedraw();
+    };
+
+    // The horizontal frame is computed for a given date. Without a date given, it is
+    // moved periodically to the current time so that the sky keeps turning
+    View.prototype.followObserverTime = function (follow) {
+        if (this.observerTimer) {
+            clearInterval(this.observerTimer);
+            this.observerTimer = null;
+        }
+
+        if (follow) {
+            this.observerTimer = setInterval(() => {
+                this.wasm.setObserverTime(Date.now());
+                this.requestRedraw();
+            }, View.OBSERVER_TIME_UPDATE_MS);
+        }
+    };
+
     View.prototype.updateZoomState = function (fov) {
         // Get the new zoom values from the backend
         const newFov = fov || this.wasm.getFieldOfView()
@@ -1837,6 +1889,11 @@ export let View = (function () {
             return;
         }
 
+        // A comparison involving the layer ends with it
+        if (this.layerComparison && this.layerComparison.layers.includes(layer)) {
+            this.layerComparison.stop();
+        }
+
         // Update the backend
         if (imageLayer.added) {
             this.wasm.removeLayer(layer);
@@ -1929,20 +1986,25 @@ export let View = (function () {
         this.needRedraw = true;
     };
 
-    View.prototype.setProjection = function (projName) {
+    View.prototype.setProjection = function (projName, params) {
         if (!ProjectionEnum[projName]) {
             console.warn(projName + " is not a valid projection.")
             projName = 'SIN'
+            params = undefined;
         }
 
-        if (this.projection && this.projection.id === ProjectionEnum[projName].id) {
+        // Parameters may change even if the projection stays the same
+        if (!params && this.projection && this.projection.id === ProjectionEnum[projName].id) {
             return;
         }
 
-        this.projection = ProjectionEnum[projName];
-        
         // Change the projection here
-        this.wasm.setProjection(projName);
+        if (params) {
+            this.wasm.setProjectionCfg({name: projName, ...params});
+        } else {
+            this.wasm.setProjection(projName);
+        }
+        this.projection = ProjectionEnum[projName];
         let newProjFov = Math.min(this.fov, this.projection.fov);
         this.setZoom(newProjFov)
 
@@ -1959,7 +2021,7 @@ export let View = (function () {
         this.wasm.setCooSystem(this.cooFrame.system);
 
         // Set the grid label format
-        if (this.cooFrame.label == "ICRS") {
+        if (this.cooFrame.label == "ICRS" || this.cooFrame.label == "FK5" || this.cooFrame.label == "FK4") {
             this.setGridOptions({fmt: "sexagesimal"});
         }
         else {
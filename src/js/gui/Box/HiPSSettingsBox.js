@@ -200,7 +200,7 @@ import { Form } from "../Widgets/Form.js";
                 type: 'select',
                 name: 'stretch',
                 value: 'linear',
-                options: ['sqrt', 'linear', 'asinh', 'pow2', 'log'],
+                options: ['sqrt', 'linear', 'asinh', 'pow2', 'log', 'sinh', 'pow', 'histogram'],
                 change(e) {
                     self.options.layer.setColormap(self.options.layer.getColorCfg().getColormap(), {stretch: e.target.value});
                 },
@@ -44,7 +44,7 @@
             name: 'cooFrame',
             type: 'select',
             value: cooFrame.label,
-            options: [CooFrameEnum.ICRS.label, CooFrameEnum.ICRSd.label, CooFrameEnum.GAL.label],
+            options: [CooFrameEnum.ICRS.label, CooFrameEnum.ICRSd.label, CooFrameEnum.GAL.label, CooFrameEnum.FK5.label, CooFrameEnum.FK4.label, CooFrameEnum.ECL.label, CooFrameEnum.SGAL.label],
             change(e) {
                 aladin.setFrame(e.target.value)
             },
@@ -28,7 +28,6 @@
  * 
  *****************************************************************************/
 
-import { CooConversion } from "../CooConversion.js";
 import { Coo }            from "../libs/astro/coo.js";
 import { CooFrameEnum }   from "../CooFrameEnum.js";
 
@@ -142,14 +141,12 @@ export class Location extends DOMElement {
             let param = e.detail;
 
             if (param.type === 'mouseout') {
-                let radec = aladin.getRaDec();
-                // convert to the view frame
-                let lonlat = radec;
-                if (aladin.getFrame() === "GAL") {
-                    lonlat = CooConversion.ICRSToGalactic(radec)
+                // The center is given in the frame of the view
+                let [lon, lat] = aladin.wasm.getCenter();
+                if (lon < 0) {
+                    lon += 360.0;
                 }
 
-                let [lon, lat] = lonlat;
                 self.field.el.blur()
                 self.update({
                     lon, lat,
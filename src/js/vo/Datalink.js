@@ -171,6 +171,7 @@ export let Datalink = (function() {
                                                                     maxCut: colorCfg.maxCut,
                                                                     colormap: colorCfg.colormap,
                                                                     stretch: colorCfg.stretch,
+                                                                    stretchParam: colorCfg.stretchParam,
                                                                     reversed: colorCfg.reversed
                                                                 })
                                             